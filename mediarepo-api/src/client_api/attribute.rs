@@ -0,0 +1,130 @@
+use super::IPCApi;
+use crate::client_api::connection::ConnectionHandle;
+use crate::client_api::error::ApiResult;
+use crate::types::attributes::{
+    AddAttributeFieldRequest, AttributeFieldResponse, AttributeValueType, FileAttributeResponse,
+    RemoveFileAttributeRequest, SetFileAttributeRequest, UpdateAttributeFieldRequest,
+};
+use crate::types::identifier::FileIdentifier;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct AttributeApi {
+    ctx: ConnectionHandle,
+}
+
+impl IPCApi for AttributeApi {
+    fn namespace() -> &'static str {
+        "attributes"
+    }
+
+    fn connection(&self) -> &ConnectionHandle {
+        &self.ctx
+    }
+}
+
+impl AttributeApi {
+    pub fn new(ctx: ConnectionHandle) -> Self {
+        Self { ctx }
+    }
+
+    /// Returns all custom attribute fields of the repository
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_fields(&self) -> ApiResult<Vec<AttributeFieldResponse>> {
+        self.emit_and_get("all_attribute_fields", (), Some(Duration::from_secs(1)))
+            .await
+    }
+
+    /// Registers a new custom attribute field with the given value type
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_field(
+        &self,
+        name: String,
+        value_type: AttributeValueType,
+    ) -> ApiResult<AttributeFieldResponse> {
+        self.emit_and_get(
+            "add_attribute_field",
+            AddAttributeFieldRequest { name, value_type },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
+    /// Renames a field or changes its type. Changing the type fails
+    /// if values assigned to files aren't valid for the new type
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_field(
+        &self,
+        id: i64,
+        name: Option<String>,
+        value_type: Option<AttributeValueType>,
+    ) -> ApiResult<AttributeFieldResponse> {
+        self.emit_and_get(
+            "update_attribute_field",
+            UpdateAttributeFieldRequest {
+                id,
+                name,
+                value_type,
+            },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Deletes a field together with all of its values
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_field(&self, id: i64) -> ApiResult<()> {
+        self.emit_and_await_reply("delete_attribute_field", id, Some(Duration::from_secs(10)))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the attribute values assigned to a file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_file_attributes(
+        &self,
+        file_id: FileIdentifier,
+    ) -> ApiResult<Vec<FileAttributeResponse>> {
+        self.emit_and_get("get_file_attributes", file_id, Some(Duration::from_secs(1)))
+            .await
+    }
+
+    /// Assigns a value to a file. The value is rejected if it isn't
+    /// valid for the type of the field
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_file_attribute(
+        &self,
+        file_id: FileIdentifier,
+        field_id: i64,
+        value: String,
+    ) -> ApiResult<FileAttributeResponse> {
+        self.emit_and_get(
+            "set_file_attribute",
+            SetFileAttributeRequest {
+                file_id,
+                field_id,
+                value,
+            },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
+    /// Removes the value of a field from a file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_file_attribute(
+        &self,
+        file_id: FileIdentifier,
+        field_id: i64,
+    ) -> ApiResult<()> {
+        self.emit_and_await_reply(
+            "remove_file_attribute",
+            RemoveFileAttributeRequest { file_id, field_id },
+            Some(Duration::from_secs(1)),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::client_api::error::{ApiError, ApiResult};
+use bromine::prelude::*;
+use futures::future::BoxFuture;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+use std::mem;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex};
+use tokio::time::Duration;
+
+pub(crate) type ConnectFn =
+    Box<dyn Fn() -> BoxFuture<'static, ApiResult<PooledContext>> + Send + Sync>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionState {
+    Connected,
+    Reconnecting,
+    Disconnected,
+}
+
+/// Options for reconnecting to the daemon after the connection was lost
+#[derive(Clone, Debug)]
+pub struct ReconnectOptions {
+    /// The number of connection attempts before giving up.
+    /// Requests sent after giving up start a new series of attempts.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// The connection to the daemon shared between all apis of a client.
+/// The underlying context gets replaced when the client reconnects.
+#[derive(Clone)]
+pub struct ConnectionHandle {
+    inner: Arc<Connection>,
+}
+
+struct Connection {
+    ctx: RwLock<PooledContext>,
+    connect: Option<ConnectFn>,
+    options: ReconnectOptions,
+    state: watch::Sender<ConnectionState>,
+    reconnect_lock: Mutex<()>,
+}
+
+impl ConnectionHandle {
+    /// Creates a new handle for the given context.
+    /// The handle only reconnects if a connect function is given
+    pub(crate) fn new(
+        ctx: PooledContext,
+        connect: Option<ConnectFn>,
+        options: ReconnectOptions,
+    ) -> Self {
+        let (state, _) = watch::channel(ConnectionState::Connected);
+
+        Self {
+            inner: Arc::new(Connection {
+                ctx: RwLock::new(ctx),
+                connect,
+                options,
+                state,
+                reconnect_lock: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// Acquires a context of the current connection
+    pub fn acquire(&self) -> PoolGuard<Context> {
+        self.inner.ctx.read().acquire()
+    }
+
+    /// Returns the current state of the connection
+    pub fn state(&self) -> ConnectionState {
+        *self.inner.state.borrow()
+    }
+
+    /// Returns a receiver that gets notified about connection state changes
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionState> {
+        self.inner.state.subscribe()
+    }
+
+    /// Flags the connection as disconnected if the error was caused by a broken connection.
+    /// Timed out replies don't count since reconnecting would abort every other running request
+    pub(crate) fn track_error(&self, error: &ApiError) {
+        if self.inner.connect.is_some() && is_connection_error(error) {
+            tracing::warn!("lost connection to the daemon: {}", error);
+            self.inner.state.send_replace(ConnectionState::Disconnected);
+        }
+    }
+
+    /// Reconnects to the daemon with an exponential backoff if the connection was lost
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn ensure_connected(&self) -> ApiResult<()> {
+        if self.state() == ConnectionState::Connected {
+            return Ok(());
+        }
+        let connect = if let Some(connect) = &self.inner.connect {
+            connect
+        } else {
+            return Ok(());
+        };
+        let _guard = self.inner.reconnect_lock.lock().await;
+
+        if self.state() == ConnectionState::Connected {
+            return Ok(());
+        }
+        self.inner.state.send_replace(ConnectionState::Reconnecting);
+        let options = &self.inner.options;
+        let mut backoff = options.initial_backoff;
+        let mut attempt = 1;
+
+        loop {
+            match connect().await {
+                Ok(ctx) => {
+                    let old_ctx = mem::replace(&mut *self.inner.ctx.write(), ctx);
+                    let _ = (*old_ctx.acquire()).clone().stop().await;
+                    self.inner.state.send_replace(ConnectionState::Connected);
+                    tracing::info!("reconnected to the daemon after {} attempt(s)", attempt);
+
+                    return Ok(());
+                }
+                Err(e) if attempt >= options.max_attempts => {
+                    tracing::error!("failed to reconnect to the daemon: {}", e);
+                    self.inner.state.send_replace(ConnectionState::Disconnected);
+
+                    return Err(e);
+                }
+                Err(e) => {
+                    tracing::debug!("reconnect attempt {} failed: {}", attempt, e);
+                    tokio::time::sleep(backoff).await;
+                    backoff = min(backoff * 2, options.max_backoff);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+fn is_connection_error(error: &ApiError) -> bool {
+    matches!(
+        error,
+        ApiError::IPC(IPCError::IoError(_)) | ApiError::IPC(IPCError::SendError)
+    )
+}
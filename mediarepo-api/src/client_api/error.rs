@@ -9,6 +9,9 @@ pub enum ApiError {
 
     #[error("The servers api version (version {server:?}) is incompatible with the api client {client:?}")]
     VersionMismatch { server: String, client: String },
+
+    #[error("the message of {size} bytes exceeds the daemon's limit of {limit} bytes. Large data has to be transferred with the chunked or streaming apis")]
+    MessageTooLarge { size: u64, limit: u64 },
 }
 
 unsafe impl Send for ApiError {}
@@ -1,26 +1,48 @@
-use crate::client_api::error::ApiResult;
-use crate::client_api::IPCApi;
+use crate::client_api::connection::ConnectionHandle;
+use crate::client_api::error::{ApiError, ApiResult};
+use crate::client_api::{get_capabilities, IPCApi};
 use crate::types::files::{
-    AddFileRequestHeader, FileBasicDataResponse, FileMetadataResponse, FileOSMetadata, FileStatus,
-    GetFileThumbnailOfSizeRequest, GetFileThumbnailsRequest, ReadFileRequest,
-    ThumbnailMetadataResponse, UpdateFileNameRequest, UpdateFileStatusRequest,
+    AddFileRequestHeader, BulkRenameFilesRequest, ChangeFilesStatusRequest,
+    CreateTimelineThumbnailsRequest, DeleteFileRequest, DeleteFilesPreviewResponse,
+    DeleteFilesRequest, ExportFilesEvent, ExportFilesRequest, FileBasicDataResponse,
+    FileDeletionResponse, FileMetadataField, FileMetadataResponse, FileOSMetadata, FileStatus,
+    FileStorageInfoResponse, FilesPageResponse, FindFilesNearRequest, FindSimilarFilesRequest,
+    GetContactSheetRequest, GetFileThumbnailOfSizeRequest, GetFileThumbnailsRequest,
+    GetFilesPageRequest, GetInlineThumbnailsForCdsRequest, GetThumbnailsForCdsRequest,
+    ImportDirectoryEvent, ImportDirectoryOptions, ImportDirectoryRequest, ImportFileEntry,
+    ImportFilesEvent, ImportFilesRequest, InlineThumbnailResponse, PrepareDeleteFilesRequest,
+    ProbeFileRequest, ProbedFileResponse, ReadFileRangeRequest, ReadFileRequest,
+    RenamedFileResponse, SetFileIndexedRequest, SimilarFileResponse, ThumbnailCrop,
+    ThumbnailMetadataResponse, UpdateFileCommentRequest, UpdateFileMetadataRequest,
+    UpdateFileMimeTypeRequest, UpdateFileNameRequest, UpdateFileStatusRequest,
+};
+use crate::types::filtering::{
+    FilterExpression, FindFileIdsResponse, FindFilesRequest, FindFilesResponse,
+    SearchFilesByTextRequest, SortKey, StreamFilesRequest,
 };
-use crate::types::filtering::{FilterExpression, FindFilesRequest, SortKey};
 use crate::types::identifier::FileIdentifier;
 use async_trait::async_trait;
-use bromine::context::{PoolGuard, PooledContext};
+use bromine::error_event::ErrorEventData;
+use bromine::event::EventType;
 use bromine::payload::BytePayload;
 use bromine::prelude::*;
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 use tokio::time::Duration;
 
 pub struct FileApi {
-    ctx: PooledContext,
+    ctx: ConnectionHandle,
+    /// The message size limit reported by the daemon
+    max_message_size: Arc<OnceCell<Option<u64>>>,
 }
 
 impl Clone for FileApi {
     fn clone(&self) -> Self {
         Self {
             ctx: self.ctx.clone(),
+            max_message_size: Arc::clone(&self.max_message_size),
         }
     }
 }
@@ -31,15 +53,36 @@ impl IPCApi for FileApi {
         "files"
     }
 
-    fn ctx(&self) -> PoolGuard<Context> {
-        self.ctx.acquire()
+    fn connection(&self) -> &ConnectionHandle {
+        &self.ctx
     }
 }
 
 impl FileApi {
     /// Creates a new file api client
-    pub fn new(ctx: PooledContext) -> Self {
-        Self { ctx }
+    pub fn new(ctx: ConnectionHandle) -> Self {
+        Self {
+            ctx,
+            max_message_size: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Fails with [ApiError::MessageTooLarge] if data of the given size exceeds
+    /// the message size limit of the daemon. The limit is requested once per client
+    async fn check_message_size(&self, size: u64) -> ApiResult<()> {
+        let limit = self
+            .max_message_size
+            .get_or_try_init(|| async {
+                get_capabilities(&self.ctx)
+                    .await
+                    .map(|c| c.max_message_size)
+            })
+            .await?;
+
+        match *limit {
+            Some(limit) if size > limit => Err(ApiError::MessageTooLarge { size, limit }),
+            _ => Ok(()),
+        }
     }
 
     /// Returns all known files
@@ -49,6 +92,150 @@ impl FileApi {
             .await
     }
 
+    /// Returns a page of all files together with the total number of files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_files_paginated(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> ApiResult<FilesPageResponse> {
+        self.emit_and_get(
+            "all_files_paginated",
+            GetFilesPageRequest { offset, limit },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns all files that were flagged as corrupt on import
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn corrupt_files(&self) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get("corrupt_files", (), Some(Duration::from_secs(30)))
+            .await
+    }
+
+    /// Returns groups of files that share the same content.
+    /// All but one file of a group can be deleted without losing any tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_duplicate_files(&self) -> ApiResult<Vec<Vec<FileBasicDataResponse>>> {
+        self.emit_and_get("find_duplicate_files", (), Some(Duration::from_secs(30)))
+            .await
+    }
+
+    /// Returns the images that look similar to the given file ordered by
+    /// the distance of their perceptual hashes
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_similar_files(
+        &self,
+        id: FileIdentifier,
+        max_distance: u32,
+    ) -> ApiResult<Vec<SimilarFileResponse>> {
+        self.emit_and_get(
+            "find_similar_files",
+            FindSimilarFilesRequest { id, max_distance },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Returns the file that was imported with the given external id
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_file_by_external_id(
+        &self,
+        external_id: String,
+    ) -> ApiResult<Option<FileBasicDataResponse>> {
+        self.emit_and_get(
+            "file_by_external_id",
+            external_id,
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
+    /// Streams the content descriptors of all files in chunks
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn stream_all_hashes(&self) -> ApiResult<impl Stream<Item = ApiResult<Vec<String>>>> {
+        let stream = self
+            .emit_and_stream_replies("stream_all_hashes", (), Duration::from_secs(10))
+            .await?;
+
+        Ok(stream.map(|event| {
+            let event = event?;
+
+            if event.event_type() == EventType::Error {
+                Err(IPCError::from(event.payload::<ErrorEventData>()?).into())
+            } else {
+                Ok(event.payload::<Vec<String>>()?)
+            }
+        }))
+    }
+
+    /// Returns the files with the highest view count
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn most_viewed_files(&self, limit: u64) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get("most_viewed_files", limit, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Returns all files that miss any of the given metadata fields
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn files_missing_metadata(
+        &self,
+        fields: Vec<FileMetadataField>,
+    ) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get(
+            "files_missing_metadata",
+            fields,
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Returns all files whose name contains the given pattern ignoring the casing
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_files_by_name(
+        &self,
+        pattern: String,
+    ) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get("find_files_by_name", pattern, Some(Duration::from_secs(10)))
+            .await
+    }
+
+    /// Returns all files located within the given radius (in km) around the coordinates
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_files_near(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+    ) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get(
+            "find_files_near",
+            FindFilesNearRequest {
+                latitude,
+                longitude,
+                radius_km,
+            },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns all files with a `source:` tag on the given domain or its subdomains.
+    /// Patterns containing `*` are matched against the whole source url instead
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_files_from_source(
+        &self,
+        source_pattern: String,
+    ) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get(
+            "find_files_from_source",
+            source_pattern,
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
     /// Returns a file by identifier
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_file(&self, id: FileIdentifier) -> ApiResult<FileBasicDataResponse> {
@@ -66,23 +253,225 @@ impl FileApi {
             .await
     }
 
+    /// Returns the encoded content descriptors of the files with the given ids.
+    /// Ids without a file are omitted
+    #[tracing::instrument(level = "debug", skip(self, ids))]
+    pub async fn get_cds_for_ids(&self, ids: Vec<i64>) -> ApiResult<HashMap<i64, String>> {
+        self.emit_and_get("get_cds_for_ids", ids, Some(Duration::from_secs(10)))
+            .await
+    }
+
     pub async fn get_file_metadata(&self, id: FileIdentifier) -> ApiResult<FileMetadataResponse> {
         self.emit_and_get("get_file_metadata", id, Some(Duration::from_secs(2)))
             .await
     }
 
+    /// Returns the storage location and hash details of a file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_file_storage_info(
+        &self,
+        id: FileIdentifier,
+    ) -> ApiResult<FileStorageInfoResponse> {
+        self.emit_and_get("get_file_storage_info", id, Some(Duration::from_secs(5)))
+            .await
+    }
+
     /// Searches for a file by a list of tags
+    /// If a maximum is given only that many files are returned
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn find_files(
         &self,
         filters: Vec<FilterExpression>,
         sort_expression: Vec<SortKey>,
-    ) -> ApiResult<Vec<FileBasicDataResponse>> {
+        max_results: Option<u64>,
+    ) -> ApiResult<FindFilesResponse> {
+        self.emit_and_get(
+            "find_files",
+            FindFilesRequest {
+                filters,
+                sort_expression,
+                max_results,
+                ids_only: false,
+                offset: None,
+                limit: None,
+                include_unindexed: false,
+                include_statuses: None,
+            },
+            Some(Duration::from_secs(20)),
+        )
+        .await
+    }
+
+    /// Searches for a page of the files matching the filters.
+    /// The response contains the number of all matching files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_files_paginated(
+        &self,
+        filters: Vec<FilterExpression>,
+        sort_expression: Vec<SortKey>,
+        offset: u64,
+        limit: u64,
+    ) -> ApiResult<FindFilesResponse> {
+        self.emit_and_get(
+            "find_files",
+            FindFilesRequest {
+                filters,
+                sort_expression,
+                max_results: None,
+                ids_only: false,
+                offset: Some(offset),
+                limit: Some(limit),
+                include_unindexed: false,
+                include_statuses: None,
+            },
+            Some(Duration::from_secs(20)),
+        )
+        .await
+    }
+
+    /// Searches for a page of files like [FileApi::find_files_paginated]
+    /// including the files that are excluded from searches
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_all_files_paginated(
+        &self,
+        filters: Vec<FilterExpression>,
+        sort_expression: Vec<SortKey>,
+        offset: u64,
+        limit: u64,
+    ) -> ApiResult<FindFilesResponse> {
+        self.emit_and_get(
+            "find_files",
+            FindFilesRequest {
+                filters,
+                sort_expression,
+                max_results: None,
+                ids_only: false,
+                offset: Some(offset),
+                limit: Some(limit),
+                include_unindexed: true,
+                include_statuses: None,
+            },
+            Some(Duration::from_secs(20)),
+        )
+        .await
+    }
+
+    /// Searches for files with one of the statuses. The results are paginated
+    /// if an offset or limit is given
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_files_with_statuses(
+        &self,
+        filters: Vec<FilterExpression>,
+        sort_expression: Vec<SortKey>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        include_unindexed: bool,
+        statuses: Vec<FileStatus>,
+    ) -> ApiResult<FindFilesResponse> {
         self.emit_and_get(
             "find_files",
             FindFilesRequest {
                 filters,
                 sort_expression,
+                max_results: None,
+                ids_only: false,
+                offset,
+                limit,
+                include_unindexed,
+                include_statuses: Some(statuses),
+            },
+            Some(Duration::from_secs(20)),
+        )
+        .await
+    }
+
+    /// Searches for files and streams the results in pages of the given size.
+    /// Every page contains the number of all matching files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn stream_files(
+        &self,
+        search_id: String,
+        filters: Vec<FilterExpression>,
+        sort_expression: Vec<SortKey>,
+        page_size: u64,
+        max_results: Option<u64>,
+    ) -> ApiResult<impl Stream<Item = ApiResult<FindFilesResponse>>> {
+        let stream = self
+            .emit_and_stream_replies(
+                "stream_files",
+                StreamFilesRequest {
+                    search_id,
+                    filters,
+                    sort_expression,
+                    page_size,
+                    max_results,
+                    include_unindexed: false,
+                    include_statuses: None,
+                },
+                Duration::from_secs(20),
+            )
+            .await?;
+
+        Ok(stream.map(|event| {
+            let event = event?;
+
+            if event.event_type() == EventType::Error {
+                Err(IPCError::from(event.payload::<ErrorEventData>()?).into())
+            } else {
+                Ok(event.payload::<FindFilesResponse>()?)
+            }
+        }))
+    }
+
+    /// Stops a streamed search after the page that is currently loaded.
+    /// Returns if a search with the id was running
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn stop_search(&self, search_id: String) -> ApiResult<bool> {
+        self.emit_and_get("stop_search", search_id, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Searches for a page of the files whose name or comment contains the text
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn search_files_by_text(
+        &self,
+        query: String,
+        sort_expression: Vec<SortKey>,
+        offset: u64,
+        limit: u64,
+    ) -> ApiResult<FindFilesResponse> {
+        self.emit_and_get(
+            "search_files_by_text",
+            SearchFilesByTextRequest {
+                query,
+                sort_expression,
+                offset: Some(offset),
+                limit: Some(limit),
+            },
+            Some(Duration::from_secs(20)),
+        )
+        .await
+    }
+
+    /// Searches for files like [FileApi::find_files] but only returns their ids
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_file_ids(
+        &self,
+        filters: Vec<FilterExpression>,
+        sort_expression: Vec<SortKey>,
+        max_results: Option<u64>,
+    ) -> ApiResult<FindFileIdsResponse> {
+        self.emit_and_get(
+            "find_files",
+            FindFilesRequest {
+                filters,
+                sort_expression,
+                max_results,
+                ids_only: true,
+                offset: None,
+                limit: None,
+                include_unindexed: false,
+                include_statuses: None,
             },
             Some(Duration::from_secs(20)),
         )
@@ -103,16 +492,44 @@ impl FileApi {
         Ok(payload.into_inner())
     }
 
-    /// Adds a file with predefined tags
+    /// Reads up to `length` bytes of the file starting at the offset
+    /// without transferring the rest of the file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn read_file_range(
+        &self,
+        id: FileIdentifier,
+        offset: u64,
+        length: u64,
+    ) -> ApiResult<Vec<u8>> {
+        let payload: BytePayload = self
+            .emit_and_get(
+                "read_file_range",
+                ReadFileRangeRequest { id, offset, length },
+                Some(Duration::from_secs(10)),
+            )
+            .await?;
+
+        Ok(payload.into_inner())
+    }
+
+    /// Adds a file with predefined tags and an optional id
+    /// of the file from an external source.
+    /// New files are stored together with their tags so they are never visible untagged
     #[tracing::instrument(level = "debug", skip(self, bytes))]
     pub async fn add_file(
         &self,
         metadata: FileOSMetadata,
         tags: Vec<String>,
+        external_id: Option<String>,
         bytes: Vec<u8>,
     ) -> ApiResult<FileBasicDataResponse> {
+        self.check_message_size(bytes.len() as u64).await?;
         let payload = TandemPayload::new(
-            AddFileRequestHeader { metadata, tags },
+            AddFileRequestHeader {
+                metadata,
+                tags,
+                external_id,
+            },
             BytePayload::new(bytes),
         );
 
@@ -120,6 +537,107 @@ impl FileApi {
             .await
     }
 
+    /// Imports files from paths on the machine of the daemon and streams the progress.
+    /// The last event of the stream contains the imported files and the failures
+    #[tracing::instrument(level = "debug", skip(self, files))]
+    pub async fn import_files(
+        &self,
+        import_id: String,
+        files: Vec<ImportFileEntry>,
+    ) -> ApiResult<impl Stream<Item = ApiResult<ImportFilesEvent>>> {
+        let stream = self
+            .emit_and_stream_replies(
+                "import_files",
+                ImportFilesRequest { import_id, files },
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        Ok(stream.map(|event| {
+            let event = event?;
+
+            if event.event_type() == EventType::Error {
+                Err(IPCError::from(event.payload::<ErrorEventData>()?).into())
+            } else {
+                Ok(event.payload::<ImportFilesEvent>()?)
+            }
+        }))
+    }
+
+    /// Imports the files of a folder on the machine of the daemon and streams the progress.
+    /// The last event of the stream contains the counts and the failures of the import
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn import_directory(
+        &self,
+        import_id: String,
+        path: String,
+        options: ImportDirectoryOptions,
+    ) -> ApiResult<impl Stream<Item = ApiResult<ImportDirectoryEvent>>> {
+        let stream = self
+            .emit_and_stream_replies(
+                "import_directory",
+                ImportDirectoryRequest {
+                    import_id,
+                    path,
+                    options,
+                },
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        Ok(stream.map(|event| {
+            let event = event?;
+
+            if event.event_type() == EventType::Error {
+                Err(IPCError::from(event.payload::<ErrorEventData>()?).into())
+            } else {
+                Ok(event.payload::<ImportDirectoryEvent>()?)
+            }
+        }))
+    }
+
+    /// Cancels a running import after the file that is currently imported.
+    /// Returns if an import with the id was running
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn cancel_import(&self, import_id: String) -> ApiResult<bool> {
+        self.emit_and_get("cancel_import", import_id, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Writes the files into a folder on the machine of the daemon and streams the progress.
+    /// The last event of the stream contains the paths of the written files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn export_files(
+        &self,
+        ids: Vec<i64>,
+        target_dir: String,
+        write_tag_sidecars: bool,
+        group_by_namespace: Option<String>,
+    ) -> ApiResult<impl Stream<Item = ApiResult<ExportFilesEvent>>> {
+        let stream = self
+            .emit_and_stream_replies(
+                "export_files",
+                ExportFilesRequest {
+                    ids,
+                    target_dir,
+                    write_tag_sidecars,
+                    group_by_namespace,
+                },
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        Ok(stream.map(|event| {
+            let event = event?;
+
+            if event.event_type() == EventType::Error {
+                Err(IPCError::from(event.payload::<ErrorEventData>()?).into())
+            } else {
+                Ok(event.payload::<ExportFilesEvent>()?)
+            }
+        }))
+    }
+
     /// Updates a files name
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn update_file_name(
@@ -135,6 +653,46 @@ impl FileApi {
         .await
     }
 
+    /// Updates the comment of a file. The comment is removed if none is given
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_file_comment(
+        &self,
+        file_id: FileIdentifier,
+        comment: Option<String>,
+    ) -> ApiResult<FileMetadataResponse> {
+        self.emit_and_get(
+            "update_file_comment",
+            UpdateFileCommentRequest { file_id, comment },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
+    /// Updates the name, comment and status of a file at once. Deleted files
+    /// only get another status if `undelete` is set
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_file_metadata(
+        &self,
+        file_id: FileIdentifier,
+        name: Option<String>,
+        comment: Option<String>,
+        status: Option<FileStatus>,
+        undelete: bool,
+    ) -> ApiResult<FileMetadataResponse> {
+        self.emit_and_get(
+            "update_file_metadata",
+            UpdateFileMetadataRequest {
+                file_id,
+                name,
+                comment,
+                status,
+                undelete,
+            },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
     /// Updates the status of a file
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn update_file_status(
@@ -150,17 +708,152 @@ impl FileApi {
         .await
     }
 
-    /// Permanently deletes a file from the disk and database
+    /// Archives the imported files and returns the archived ones
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn delete_file(&self, file_id: FileIdentifier) -> ApiResult<()> {
-        self.emit("delete_file", file_id)
-            .await_reply()
-            .await?;
+    pub async fn archive_files(&self, ids: Vec<i64>) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get(
+            "archive_files",
+            ChangeFilesStatusRequest { ids },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
 
-        Ok(())
+    /// Moves the archived files back to the imported files and returns them
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn unarchive_files(&self, ids: Vec<i64>) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get(
+            "unarchive_files",
+            ChangeFilesStatusRequest { ids },
+            Some(Duration::from_secs(10)),
+        )
+        .await
     }
 
-    /// Returns a list of all thumbnails of the file
+    /// Restores the deleted files as imported files and returns them
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn restore_files(&self, ids: Vec<i64>) -> ApiResult<Vec<FileBasicDataResponse>> {
+        self.emit_and_get(
+            "restore_files",
+            ChangeFilesStatusRequest { ids },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Sets if a file is included in searches
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_file_indexed(
+        &self,
+        file_id: FileIdentifier,
+        indexed: bool,
+    ) -> ApiResult<FileBasicDataResponse> {
+        self.emit_and_get(
+            "set_file_indexed",
+            SetFileIndexedRequest { file_id, indexed },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
+    /// Overrides the mime type of a file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_file_mime_type(
+        &self,
+        file_id: FileIdentifier,
+        mime_type: String,
+    ) -> ApiResult<FileBasicDataResponse> {
+        self.emit_and_get(
+            "update_file_mime_type",
+            UpdateFileMimeTypeRequest { file_id, mime_type },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
+    /// Permanently deletes a file from the disk and database and returns what was removed.
+    /// With `dry_run` the file is kept and only the report is returned
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_file(
+        &self,
+        file_id: FileIdentifier,
+        dry_run: bool,
+    ) -> ApiResult<FileDeletionResponse> {
+        self.emit_and_get(
+            "delete_file",
+            DeleteFileRequest {
+                id: file_id,
+                dry_run,
+            },
+            None,
+        )
+        .await
+    }
+
+    /// Returns the metadata a file on the daemon's file system would be imported with
+    /// without importing it
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn probe_file(&self, path: String) -> ApiResult<ProbedFileResponse> {
+        self.emit_and_get(
+            "probe_file",
+            ProbeFileRequest { path },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns a preview of the files that would be deleted with a token
+    /// that confirms the deletion when passed to [FileApi::delete_files]
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn prepare_delete_files(
+        &self,
+        ids: Vec<i64>,
+    ) -> ApiResult<DeleteFilesPreviewResponse> {
+        self.emit_and_get(
+            "prepare_delete_files",
+            PrepareDeleteFilesRequest { ids },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Permanently deletes the files of a prepared deletion and returns the number of deleted files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_files(&self, token: String) -> ApiResult<u64> {
+        self.emit_and_get("delete_files", DeleteFilesRequest { token }, None)
+            .await
+    }
+
+    /// Renames the files by expanding the pattern for every file and returns the new names.
+    /// With `dry_run` set the names are only returned and the files keep their names
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn bulk_rename_files(
+        &self,
+        ids: Vec<i64>,
+        pattern: String,
+        dry_run: bool,
+    ) -> ApiResult<Vec<RenamedFileResponse>> {
+        self.emit_and_get(
+            "bulk_rename_files",
+            BulkRenameFilesRequest {
+                ids,
+                pattern,
+                dry_run,
+            },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Increments the view count of a file and updates its access time
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn touch_file(&self, file_id: FileIdentifier) -> ApiResult<FileMetadataResponse> {
+        self.emit_and_get("touch_file", file_id, Some(Duration::from_secs(1)))
+            .await
+    }
+
+    /// Returns a list of all thumbnails of the file ordered by their area
+    /// starting with the smallest thumbnail
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_file_thumbnails(
         &self,
@@ -174,13 +867,17 @@ impl FileApi {
         .await
     }
 
-    /// Returns a thumbnail of size that is within the specified range
+    /// Returns a thumbnail of size that is within the specified range.
+    /// The daemon's configured crop is used if no crop is given.
+    /// For videos the timeline thumbnail closest to the position is returned if one is given
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_thumbnail_of_size(
         &self,
         file_id: FileIdentifier,
         min_size: (u32, u32),
         max_size: (u32, u32),
+        crop: Option<ThumbnailCrop>,
+        position: Option<f64>,
     ) -> ApiResult<(ThumbnailMetadataResponse, Vec<u8>)> {
         let payload: TandemPayload<SerdePayload<ThumbnailMetadataResponse>, BytePayload> = self
             .emit_and_get(
@@ -189,6 +886,8 @@ impl FileApi {
                     id: file_id,
                     min_size,
                     max_size,
+                    crop,
+                    position,
                 },
                 Some(Duration::from_secs(2)),
             )
@@ -198,11 +897,109 @@ impl FileApi {
         Ok((metadata.data(), bytes.into_inner()))
     }
 
+    /// Creates `count` thumbnails of evenly spaced frames of a video and returns them
+    /// with their position as a fraction of the video's duration
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn create_timeline_thumbnails(
+        &self,
+        file_id: FileIdentifier,
+        count: u32,
+    ) -> ApiResult<Vec<(f64, ThumbnailMetadataResponse)>> {
+        self.emit_and_get(
+            "create_timeline_thumbnails",
+            CreateTimelineThumbnailsRequest { id: file_id, count },
+            Some(Duration::from_secs(60)),
+        )
+        .await
+    }
+
+    /// Returns the contact sheet of a video with the given number of rows and columns.
+    /// The sheet is created by the daemon if it doesn't exist yet
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_contact_sheet(
+        &self,
+        file_id: FileIdentifier,
+        rows: u32,
+        cols: u32,
+    ) -> ApiResult<(ThumbnailMetadataResponse, Vec<u8>)> {
+        let payload: TandemPayload<SerdePayload<ThumbnailMetadataResponse>, BytePayload> = self
+            .emit_and_get(
+                "get_contact_sheet",
+                GetContactSheetRequest {
+                    id: file_id,
+                    rows,
+                    cols,
+                },
+                Some(Duration::from_secs(60)),
+            )
+            .await?;
+        let (metadata, bytes) = payload.into_inner();
+
+        Ok((metadata.data(), bytes.into_inner()))
+    }
+
+    /// Returns the metadata of a thumbnail within the specified size range for each
+    /// of the given content descriptor ids that has one. Missing thumbnails of images
+    /// and videos are created
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_thumbnails_for_cds(
+        &self,
+        cd_ids: Vec<i64>,
+        min_size: (u32, u32),
+        max_size: (u32, u32),
+        crop: Option<ThumbnailCrop>,
+    ) -> ApiResult<HashMap<i64, ThumbnailMetadataResponse>> {
+        self.emit_and_get(
+            "get_thumbnails_for_cds",
+            GetThumbnailsForCdsRequest {
+                cd_ids,
+                min_size,
+                max_size,
+                crop,
+            },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns the metadata of a thumbnail within the specified size range for each
+    /// of the given content descriptor ids that has one. Missing thumbnails of images
+    /// and videos are created. Thumbnails of up to `max_inline_size` bytes are returned
+    /// together with their contents
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_inline_thumbnails_for_cds(
+        &self,
+        cd_ids: Vec<i64>,
+        min_size: (u32, u32),
+        max_size: (u32, u32),
+        crop: Option<ThumbnailCrop>,
+        max_inline_size: u64,
+    ) -> ApiResult<HashMap<i64, InlineThumbnailResponse>> {
+        self.emit_and_get(
+            "get_inline_thumbnails_for_cds",
+            GetInlineThumbnailsForCdsRequest {
+                cd_ids,
+                min_size,
+                max_size,
+                crop,
+                max_inline_size,
+            },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
     /// Deletes all thumbnails of a file to regenerate them when requested
+    /// and returns the number of bytes freed
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn delete_thumbnails(&self, file_id: FileIdentifier) -> ApiResult<()> {
-        self.emit("delete_thumbnails", file_id).await_reply().await?;
+    pub async fn delete_thumbnails(&self, file_id: FileIdentifier) -> ApiResult<u64> {
+        self.emit_and_get("delete_thumbnails", file_id, None).await
+    }
 
-        Ok(())
+    /// Deletes the thumbnails of all files and returns the number of bytes freed
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn clear_all_thumbnails(&self) -> ApiResult<u64> {
+        self.emit_and_get("clear_all_thumbnails", (), Some(Duration::from_secs(120)))
+            .await
     }
 }
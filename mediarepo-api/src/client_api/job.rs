@@ -1,12 +1,19 @@
+use crate::client_api::connection::ConnectionHandle;
 use crate::client_api::error::ApiResult;
 use crate::client_api::IPCApi;
-use crate::types::jobs::{JobType, RunJobRequest};
-use bromine::context::{Context, PoolGuard, PooledContext};
+use crate::types::jobs::{
+    DatabaseBackupResponse, HashAlgorithm, JobInfoResponse, JobProgressResponse, JobType,
+    MigrateStorageRequest, RunJobRequest,
+};
+use bromine::error_event::ErrorEventData;
+use bromine::event::EventType;
+use bromine::prelude::*;
+use futures::{Stream, StreamExt};
 use std::time::Duration;
 
 #[derive(Clone)]
 pub struct JobApi {
-    ctx: PooledContext,
+    ctx: ConnectionHandle,
 }
 
 impl IPCApi for JobApi {
@@ -14,23 +21,25 @@ impl IPCApi for JobApi {
         "jobs"
     }
 
-    fn ctx(&self) -> PoolGuard<Context> {
-        self.ctx.acquire()
+    fn connection(&self) -> &ConnectionHandle {
+        &self.ctx
     }
 }
 
 impl JobApi {
-    pub fn new(ctx: PooledContext) -> Self {
+    pub fn new(ctx: ConnectionHandle) -> Self {
         Self { ctx }
     }
 
     /// Runs a job of the given type and returns when it has finished
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn run_job(&self, job_type: JobType, sync: bool) -> ApiResult<()> {
-        self.emit("run_job", RunJobRequest { job_type, sync })
-            .await_reply()
-            .with_timeout(Duration::from_secs(3600))
-            .await?;
+        self.emit_and_await_reply(
+            "run_job",
+            RunJobRequest { job_type, sync },
+            Some(Duration::from_secs(3600)),
+        )
+        .await?;
 
         Ok(())
     }
@@ -40,4 +49,89 @@ impl JobApi {
     pub async fn is_job_running(&self, job_type: JobType) -> ApiResult<bool> {
         self.emit_and_get("is_job_running", job_type, None).await
     }
+
+    /// Cancels the current run of a job and returns if the job was running
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn cancel_job(&self, job_type: JobType) -> ApiResult<bool> {
+        self.emit_and_get("cancel_job", job_type, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Pauses a running job at its next checkpoint and returns if the job was running
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn pause_job(&self, job_type: JobType) -> ApiResult<bool> {
+        self.emit_and_get("pause_job", job_type, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Resumes a paused job and returns if the job was paused
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn resume_job(&self, job_type: JobType) -> ApiResult<bool> {
+        self.emit_and_get("resume_job", job_type, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Returns the state and progress of all jobs that have been started
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_jobs(&self) -> ApiResult<Vec<JobInfoResponse>> {
+        self.emit_and_get("list_jobs", (), Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Returns the progress of the current or last run of a job
+    /// or None if the job doesn't report its progress or hasn't run yet
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn job_progress(&self, job_type: JobType) -> ApiResult<Option<JobProgressResponse>> {
+        self.emit_and_get("job_progress", job_type, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Starts generating thumbnails for all files that don't have any
+    /// and streams the progress until all thumbnails have been generated
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn generate_missing_thumbnails(
+        &self,
+    ) -> ApiResult<impl Stream<Item = ApiResult<JobProgressResponse>>> {
+        let stream = self
+            .emit_and_stream_replies("generate_missing_thumbnails", (), Duration::from_secs(10))
+            .await?;
+
+        Ok(stream.map(parse_progress_event))
+    }
+
+    /// Starts storing all files under content descriptors created with the given algorithm
+    /// and streams the progress until all files have been migrated
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn migrate_storage(
+        &self,
+        algorithm: HashAlgorithm,
+    ) -> ApiResult<impl Stream<Item = ApiResult<JobProgressResponse>>> {
+        let stream = self
+            .emit_and_stream_replies(
+                "migrate_storage",
+                MigrateStorageRequest { algorithm },
+                Duration::from_secs(10),
+            )
+            .await?;
+
+        Ok(stream.map(parse_progress_event))
+    }
+
+    /// Creates a backup of the database in the configured backup directory
+    /// and returns the location and size of the backup
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn backup_now(&self) -> ApiResult<DatabaseBackupResponse> {
+        self.emit_and_get("backup_now", (), Some(Duration::from_secs(3600)))
+            .await
+    }
+}
+
+fn parse_progress_event(event: IPCResult<Event>) -> ApiResult<JobProgressResponse> {
+    let event = event?;
+
+    if event.event_type() == EventType::Error {
+        Err(IPCError::from(event.payload::<ErrorEventData>()?).into())
+    } else {
+        Ok(event.payload::<JobProgressResponse>()?)
+    }
 }
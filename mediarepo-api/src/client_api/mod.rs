@@ -1,56 +1,133 @@
+pub mod attribute;
+pub mod connection;
 pub mod error;
 pub mod file;
 pub mod job;
+pub mod preset;
 pub mod protocol;
 pub mod repo;
 pub mod tag;
-pub mod preset;
 
+use crate::client_api::attribute::AttributeApi;
+use crate::client_api::connection::{
+    ConnectFn, ConnectionHandle, ConnectionState, ReconnectOptions,
+};
 use crate::client_api::error::{ApiError, ApiResult};
 use crate::client_api::file::FileApi;
 use crate::client_api::job::JobApi;
+use crate::client_api::preset::PresetApi;
 use crate::client_api::repo::RepoApi;
 use crate::client_api::tag::TagApi;
-use crate::types::misc::{check_apis_compatible, get_api_version, InfoResponse};
+use crate::types::misc::{
+    check_apis_compatible, get_api_version, CapabilitiesResponse, InfoResponse,
+};
 use async_trait::async_trait;
-use bromine::prelude::*;
 use bromine::prelude::emit_metadata::EmitMetadata;
+use bromine::prelude::emit_metadata_with_response_stream::ResponseStream;
+use bromine::prelude::*;
+use tokio::sync::watch;
 use tokio::time::Duration;
-use crate::client_api::preset::PresetApi;
 
 #[async_trait]
 pub trait IPCApi {
     fn namespace() -> &'static str;
-    fn ctx(&self) -> PoolGuard<Context>;
+    fn connection(&self) -> &ConnectionHandle;
+
+    fn ctx(&self) -> PoolGuard<Context> {
+        self.connection().acquire()
+    }
 
     fn emit<T: IntoPayload + Send>(&self, event_name: &str, data: T) -> EmitMetadata<T> {
         let ctx = self.ctx();
         ctx.emit_to(Self::namespace(), event_name, data)
     }
 
+    /// Emits an event without waiting for a reply.
+    /// Reconnects to the daemon first if the connection was lost before
+    async fn emit_event<T: IntoPayload + Send + Sync + 'static>(
+        &self,
+        event_name: &str,
+        data: T,
+    ) -> ApiResult<()> {
+        self.connection().ensure_connected().await?;
+        let result = self
+            .emit(event_name, data)
+            .await
+            .map(|_| ())
+            .map_err(ApiError::from);
+
+        if let Err(e) = &result {
+            self.connection().track_error(e);
+        }
+
+        result
+    }
+
     async fn emit_and_get<T: IntoPayload + Send + Sync + 'static, R: FromPayload + Send>(
         &self,
         event_name: &str,
         data: T,
         timeout: Option<Duration>,
     ) -> ApiResult<R> {
+        let response = self.emit_and_await_reply(event_name, data, timeout).await?;
+
+        Ok(response.payload()?)
+    }
+
+    /// Emits an event and waits for the reply.
+    /// Reconnects to the daemon first if the connection was lost before
+    async fn emit_and_await_reply<T: IntoPayload + Send + Sync + 'static>(
+        &self,
+        event_name: &str,
+        data: T,
+        timeout: Option<Duration>,
+    ) -> ApiResult<Event> {
+        self.connection().ensure_connected().await?;
         let mut meta = self.emit(event_name, data).await_reply();
 
         if let Some(timeout) = timeout {
             meta = meta.with_timeout(timeout);
         }
-        let response = meta.await?;
+        let response = meta.await.map_err(ApiError::from);
 
-        Ok(response.payload()?)
+        if let Err(e) = &response {
+            self.connection().track_error(e);
+        }
+
+        response
+    }
+
+    /// Emits an event and returns the stream of replies.
+    /// Reconnects to the daemon first if the connection was lost before
+    async fn emit_and_stream_replies<T: IntoPayload + Send + Sync + 'static>(
+        &self,
+        event_name: &str,
+        data: T,
+        timeout: Duration,
+    ) -> ApiResult<ResponseStream> {
+        self.connection().ensure_connected().await?;
+        let stream = self
+            .emit(event_name, data)
+            .stream_replies()
+            .with_timeout(timeout)
+            .await
+            .map_err(ApiError::from);
+
+        if let Err(e) = &stream {
+            self.connection().track_error(e);
+        }
+
+        stream
     }
 }
 pub struct ApiClient {
-    ctx: PooledContext,
+    ctx: ConnectionHandle,
     pub file: FileApi,
     pub tag: TagApi,
     pub repo: RepoApi,
     pub job: JobApi,
     pub preset: PresetApi,
+    pub attribute: AttributeApi,
 }
 
 impl Clone for ApiClient {
@@ -62,64 +139,95 @@ impl Clone for ApiClient {
             repo: self.repo.clone(),
             job: self.job.clone(),
             preset: self.preset.clone(),
+            attribute: self.attribute.clone(),
         }
     }
 }
 
 impl ApiClient {
     /// Creates a new client from an existing ipc context
+    /// The client created this way does not reconnect when the connection is lost
     pub fn new(ctx: PooledContext) -> Self {
+        Self::from_connection(ConnectionHandle::new(
+            ctx,
+            None,
+            ReconnectOptions::default(),
+        ))
+    }
+
+    fn from_connection(ctx: ConnectionHandle) -> Self {
         Self {
             file: FileApi::new(ctx.clone()),
             tag: TagApi::new(ctx.clone()),
             repo: RepoApi::new(ctx.clone()),
             job: JobApi::new(ctx.clone()),
             preset: PresetApi::new(ctx.clone()),
+            attribute: AttributeApi::new(ctx.clone()),
             ctx,
         }
     }
 
     /// Connects to the ipc Socket
     #[tracing::instrument(level = "debug")]
-    pub async fn connect<L: AsyncStreamProtocolListener>(
+    pub async fn connect<L: AsyncStreamProtocolListener + 'static>(
         address: L::AddressType,
     ) -> ApiResult<Self> {
-        tracing::debug!("Connecting to {:?}", address);
-        let ctx = IPCBuilder::<L>::new()
-            .address(address)
-            .timeout(Duration::from_secs(10))
-            .build_pooled_client(8)
-            .await?;
-        let client = Self::new(ctx);
-        tracing::debug!("Retrieving info on daemon version...");
-        let info = client.info().await?;
-        let server_api_version = info.api_version();
-
-        if !check_apis_compatible(get_api_version(), server_api_version) {
-            let server_version_string = format!(
-                "{}.{}.{}",
-                server_api_version.0, server_api_version.1, server_api_version.2
-            );
-            let client_version_string = env!("CARGO_PKG_VERSION").to_string();
-            Err(ApiError::VersionMismatch {
-                server: server_version_string,
-                client: client_version_string,
-            })
-        } else {
-            Ok(client)
-        }
+        Self::connect_with_options::<L>(address, ReconnectOptions::default()).await
+    }
+
+    /// Connects to the ipc Socket and reconnects with the given options
+    /// when the connection gets lost
+    #[tracing::instrument(level = "debug")]
+    pub async fn connect_with_options<L: AsyncStreamProtocolListener + 'static>(
+        address: L::AddressType,
+        options: ReconnectOptions,
+    ) -> ApiResult<Self> {
+        let ctx = connect_context::<L>(address.clone()).await?;
+        let connect: ConnectFn = Box::new(move || Box::pin(connect_context::<L>(address.clone())));
+
+        Ok(Self::from_connection(ConnectionHandle::new(
+            ctx,
+            Some(connect),
+            options,
+        )))
+    }
+
+    /// Returns the current state of the connection to the daemon
+    pub fn connection_state(&self) -> ConnectionState {
+        self.ctx.state()
+    }
+
+    /// Returns a receiver that gets notified when the connection state changes
+    pub fn subscribe_connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.ctx.subscribe()
     }
 
     /// Returns information about the connected ipc server
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn info(&self) -> ApiResult<InfoResponse> {
+        self.ctx.ensure_connected().await?;
         let ctx = self.ctx.acquire();
-        let res = ctx.emit("info", ()).await_reply().await?;
+        let res = ctx
+            .emit("info", ())
+            .await_reply()
+            .await
+            .map_err(ApiError::from);
+
+        if let Err(e) = &res {
+            self.ctx.track_error(e);
+        }
+        let res = res?;
         tracing::trace!("Got info event {:?}", res);
 
         Ok(res.payload::<InfoResponse>()?)
     }
 
+    /// Returns the features that are supported by the connected daemon
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn capabilities(&self) -> ApiResult<CapabilitiesResponse> {
+        get_capabilities(&self.ctx).await
+    }
+
     /// Shuts down the daemon that the client is connected to.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn shutdown_daemon(&self) -> ApiResult<()> {
@@ -140,3 +248,58 @@ impl ApiClient {
         Ok(())
     }
 }
+
+/// Requests the features that are supported by the daemon of the connection
+pub(crate) async fn get_capabilities(
+    connection: &ConnectionHandle,
+) -> ApiResult<CapabilitiesResponse> {
+    connection.ensure_connected().await?;
+    let ctx = connection.acquire();
+    let res = ctx
+        .emit("capabilities", ())
+        .await_reply()
+        .with_timeout(Duration::from_secs(5))
+        .await
+        .map_err(ApiError::from);
+
+    if let Err(e) = &res {
+        connection.track_error(e);
+    }
+
+    Ok(res?.payload::<CapabilitiesResponse>()?)
+}
+
+/// Connects to the daemon and checks if its api version is compatible
+#[tracing::instrument(level = "debug")]
+async fn connect_context<L: AsyncStreamProtocolListener>(
+    address: L::AddressType,
+) -> ApiResult<PooledContext> {
+    tracing::debug!("Connecting to {:?}", address);
+    let ctx = IPCBuilder::<L>::new()
+        .address(address)
+        .timeout(Duration::from_secs(10))
+        .build_pooled_client(8)
+        .await?;
+    tracing::debug!("Retrieving info on daemon version...");
+    let info = ctx
+        .acquire()
+        .emit("info", ())
+        .await_reply()
+        .await?
+        .payload::<InfoResponse>()?;
+    let server_api_version = info.api_version();
+
+    if !check_apis_compatible(get_api_version(), server_api_version) {
+        let server_version_string = format!(
+            "{}.{}.{}",
+            server_api_version.0, server_api_version.1, server_api_version.2
+        );
+        let client_version_string = env!("CARGO_PKG_VERSION").to_string();
+        Err(ApiError::VersionMismatch {
+            server: server_version_string,
+            client: client_version_string,
+        })
+    } else {
+        Ok(ctx)
+    }
+}
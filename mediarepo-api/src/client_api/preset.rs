@@ -1,12 +1,12 @@
 use super::IPCApi;
+use crate::client_api::connection::ConnectionHandle;
 use crate::client_api::error::ApiResult;
 use crate::types::filtering::{SortKey, SortingPreset};
-use bromine::prelude::*;
 use std::time::Duration;
 
 #[derive(Clone)]
 pub struct PresetApi {
-    ctx: PooledContext,
+    ctx: ConnectionHandle,
 }
 
 impl IPCApi for PresetApi {
@@ -14,13 +14,13 @@ impl IPCApi for PresetApi {
         "presets"
     }
 
-    fn ctx(&self) -> PoolGuard<Context> {
-        self.ctx.acquire()
+    fn connection(&self) -> &ConnectionHandle {
+        &self.ctx
     }
 }
 
 impl PresetApi {
-    pub fn new(ctx: PooledContext) -> Self {
+    pub fn new(ctx: ConnectionHandle) -> Self {
         Self { ctx }
     }
 
@@ -41,7 +41,8 @@ impl PresetApi {
     /// Deletes a given sorting preset by id
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn delete_sorting_preset(&self, id: i32) -> ApiResult<()> {
-        self.emit("delete_sorting_preset", id).await_reply().await?;
+        self.emit_and_await_reply("delete_sorting_preset", id, None)
+            .await?;
 
         Ok(())
     }
@@ -1,13 +1,21 @@
+use bromine::error_event::ErrorEventData;
+use bromine::event::EventType;
 use bromine::prelude::*;
+use futures::{Stream, StreamExt};
 use tokio::time::Duration;
 
+use crate::client_api::connection::ConnectionHandle;
 use crate::client_api::error::ApiResult;
 use crate::client_api::IPCApi;
-use crate::types::repo::{FrontendState, RepositoryMetadata, SizeMetadata, SizeType};
+use crate::types::repo::{
+    CheckIntegrityEvent, CheckIntegrityRequest, CleanupOrphanedBlobsRequest, DedupStatsResponse,
+    FrontendState, OrphanedBlobResponse, RepositoryMetadata, SizeMetadata, SizeType,
+    StorageResponse,
+};
 
 #[derive(Clone)]
 pub struct RepoApi {
-    ctx: PooledContext,
+    ctx: ConnectionHandle,
 }
 
 impl IPCApi for RepoApi {
@@ -15,13 +23,13 @@ impl IPCApi for RepoApi {
         "repo"
     }
 
-    fn ctx(&self) -> PoolGuard<Context> {
-        self.ctx.acquire()
+    fn connection(&self) -> &ConnectionHandle {
+        &self.ctx
     }
 }
 
 impl RepoApi {
-    pub fn new(ctx: PooledContext) -> Self {
+    pub fn new(ctx: ConnectionHandle) -> Self {
         Self { ctx }
     }
 
@@ -39,6 +47,68 @@ impl RepoApi {
             .await
     }
 
+    /// Returns statistics on how much space is saved by deduplicating file contents
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_dedup_stats(&self) -> ApiResult<DedupStatsResponse> {
+        self.emit_and_get("dedup_stats", (), Some(Duration::from_secs(30)))
+            .await
+    }
+
+    /// Returns the storages used by the repository
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_storages(&self) -> ApiResult<Vec<StorageResponse>> {
+        self.emit_and_get("list_storages", (), Some(Duration::from_secs(1)))
+            .await
+    }
+
+    /// Returns the blobs of the file storage that don't belong to any file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_orphaned_blobs(&self) -> ApiResult<Vec<OrphanedBlobResponse>> {
+        self.emit_and_get("find_orphaned_blobs", (), Some(Duration::from_secs(60)))
+            .await
+    }
+
+    /// Deletes the blobs of the file storage that don't belong to any file and returns them.
+    /// With `dry_run` the blobs are only returned
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn cleanup_orphaned_blobs(
+        &self,
+        dry_run: bool,
+    ) -> ApiResult<Vec<OrphanedBlobResponse>> {
+        self.emit_and_get(
+            "cleanup_orphaned_blobs",
+            CleanupOrphanedBlobsRequest { dry_run },
+            Some(Duration::from_secs(60)),
+        )
+        .await
+    }
+
+    /// Checks the stored files against their content descriptors and streams the progress.
+    /// The last event of the stream contains the contents that are corrupt or missing
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn check_integrity(
+        &self,
+        tag_ids: Vec<i64>,
+    ) -> ApiResult<impl Stream<Item = ApiResult<CheckIntegrityEvent>>> {
+        let stream = self
+            .emit_and_stream_replies(
+                "check_integrity",
+                CheckIntegrityRequest { tag_ids },
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        Ok(stream.map(|event| {
+            let event = event?;
+
+            if event.event_type() == EventType::Error {
+                Err(IPCError::from(event.payload::<ErrorEventData>()?).into())
+            } else {
+                Ok(event.payload::<CheckIntegrityEvent>()?)
+            }
+        }))
+    }
+
     /// Returns the state of the frontend that is stored in the repo
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_frontend_state(&self) -> ApiResult<FrontendState> {
@@ -49,8 +119,6 @@ impl RepoApi {
     /// Sets the state of the frontend
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn set_frontend_state(&self, state: FrontendState) -> ApiResult<()> {
-        self.emit("set_frontend_state", state).await?;
-
-        Ok(())
+        self.emit_event("set_frontend_state", state).await
     }
 }
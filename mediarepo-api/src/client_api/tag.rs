@@ -1,16 +1,20 @@
 use std::collections::HashMap;
+use crate::client_api::connection::ConnectionHandle;
 use crate::client_api::error::ApiResult;
 use crate::client_api::IPCApi;
-use crate::types::files::{GetFileTagsRequest, GetFilesTagsRequest, GetFileTagMapRequest};
+use crate::types::files::{GetFileTagsPageRequest, GetFileTagsRequest, GetFilesTagsRequest, GetFileTagMapRequest};
+use crate::types::filtering::FilterExpression;
 use crate::types::identifier::FileIdentifier;
-use crate::types::tags::{ChangeFileTagsRequest, NamespaceResponse, TagResponse};
+use crate::types::tags::{
+    AddTagAliasRequest, AggregateTagsRequest, AutocompleteTagsRequest, ChangeFileTagsRequest, ConvertTagToNamespacedRequest, FileTagsPageResponse, IdentifyingTagsResponse, MergeNamespacesRequest, MergeTagsRequest, MissingTagsRequest, NamespaceMergeResponse, NamespaceResponse,
+    GetTagCountsRequest, TagAggregateResponse, TagAliasResponse, TagChangeResponse, TagFacetsRequest, TagFilesRequest,
+    TagImplicationRequest, TagResponse,
+};
 use async_trait::async_trait;
-use bromine::context::{PoolGuard, PooledContext};
-use bromine::ipc::context::Context;
 use std::time::Duration;
 
 pub struct TagApi {
-    ctx: PooledContext,
+    ctx: ConnectionHandle,
 }
 
 impl Clone for TagApi {
@@ -27,13 +31,13 @@ impl IPCApi for TagApi {
         "tags"
     }
 
-    fn ctx(&self) -> PoolGuard<Context> {
-        self.ctx.acquire()
+    fn connection(&self) -> &ConnectionHandle {
+        &self.ctx
     }
 }
 
 impl TagApi {
-    pub fn new(ctx: PooledContext) -> Self {
+    pub fn new(ctx: ConnectionHandle) -> Self {
         Self { ctx }
     }
 
@@ -62,6 +66,22 @@ impl TagApi {
         .await
     }
 
+    /// Returns a page of the tags of a file together with the number of all its tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_tags_for_file_paginated(
+        &self,
+        id: FileIdentifier,
+        offset: u64,
+        limit: u64,
+    ) -> ApiResult<FileTagsPageResponse> {
+        self.emit_and_get(
+            "tags_for_file_paginated",
+            GetFileTagsPageRequest { id, offset, limit },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
+
     /// Returns a list of all tags that are assigned to the list of files
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn get_tags_for_files(&self, cds: Vec<String>) -> ApiResult<Vec<TagResponse>> {
@@ -79,13 +99,268 @@ impl TagApi {
         self.emit_and_get("file_tag_map", GetFileTagMapRequest{cds}, Some(Duration::from_secs(10))).await
     }
 
-    /// Creates a new tag and returns the created tag object
+    /// Creates new tags and returns either the created tag object or the reason
+    /// why the tag couldn't be created for each given tag
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn create_tags(&self, tags: Vec<String>) -> ApiResult<Vec<TagResponse>> {
+    pub async fn create_tags(
+        &self,
+        tags: Vec<String>,
+    ) -> ApiResult<Vec<Result<TagResponse, String>>> {
         self.emit_and_get("create_tags", tags, Some(Duration::from_secs(10)))
             .await
     }
 
+    /// Moves all tags of the namespace `from_id` into the namespace `into_id`.
+    /// Tags existing in both namespaces are merged
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn merge_namespaces(
+        &self,
+        from_id: i64,
+        into_id: i64,
+    ) -> ApiResult<NamespaceMergeResponse> {
+        self.emit_and_get(
+            "merge_namespaces",
+            MergeNamespacesRequest { from_id, into_id },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Moves the tag into the given namespace. If the tag already exists in the
+    /// namespace, both tags are merged
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn convert_tag_to_namespaced(
+        &self,
+        tag_id: i64,
+        namespace: String,
+    ) -> ApiResult<TagResponse> {
+        self.emit_and_get(
+            "convert_tag_to_namespaced",
+            ConvertTagToNamespacedRequest { tag_id, namespace },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Returns all tags with the same name as the given tag in any namespace
+    /// including the tag without a namespace
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_tag_variants(&self, tag: String) -> ApiResult<Vec<TagResponse>> {
+        self.emit_and_get("find_tag_variants", tag, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Returns groups of tags that only differ in casing, whitespace or underscores
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_similar_tags(&self) -> ApiResult<Vec<Vec<TagResponse>>> {
+        self.emit_and_get("find_similar_tags", (), Some(Duration::from_secs(10)))
+            .await
+    }
+
+    /// Merges the tags `from_ids` into the tag `into_id` and returns the remaining tag
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn merge_tags(&self, from_ids: Vec<i64>, into_id: i64) -> ApiResult<TagResponse> {
+        self.emit_and_get(
+            "merge_tags",
+            MergeTagsRequest { from_ids, into_id },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Returns all tag aliases
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_all_tag_aliases(&self) -> ApiResult<Vec<TagAliasResponse>> {
+        self.emit_and_get("all_tag_aliases", (), Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Returns all aliases of a tag
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_tag_aliases(&self, tag_id: i64) -> ApiResult<Vec<TagAliasResponse>> {
+        self.emit_and_get("tag_aliases_for_tag", tag_id, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Adds an alias for a tag or changes the tag of an existing alias.
+    /// Files of an existing tag with the name of the alias are assigned to the aliased tag
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_tag_alias(&self, alias: String, tag_id: i64) -> ApiResult<TagAliasResponse> {
+        self.emit_and_get(
+            "add_tag_alias",
+            AddTagAliasRequest { alias, tag_id },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Removes a tag alias
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_tag_alias(&self, alias_id: i64) -> ApiResult<()> {
+        self.emit_and_await_reply("remove_tag_alias", alias_id, Some(Duration::from_secs(5)))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns all tags implied by a tag including the tags implied by those tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_tag_implications(&self, tag_id: i64) -> ApiResult<Vec<TagResponse>> {
+        self.emit_and_get("tag_implications", tag_id, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Adds an implication so that the implied tag is assigned together with the tag.
+    /// Files that already have the tag get the implied tags assigned as well
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_tag_implication(&self, tag_id: i64, implied_tag_id: i64) -> ApiResult<()> {
+        self.emit_and_await_reply(
+            "add_tag_implication",
+            TagImplicationRequest {
+                tag_id,
+                implied_tag_id,
+            },
+            Some(Duration::from_secs(30)),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes an implication and the implied tags of files that aren't implied
+    /// by another tag of the file anymore
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_tag_implication(&self, tag_id: i64, implied_tag_id: i64) -> ApiResult<()> {
+        self.emit_and_await_reply(
+            "remove_tag_implication",
+            TagImplicationRequest {
+                tag_id,
+                implied_tag_id,
+            },
+            Some(Duration::from_secs(30)),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the given tags together with all tags they imply
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn resolve_implications(&self, tag_ids: Vec<i64>) -> ApiResult<Vec<TagResponse>> {
+        self.emit_and_get("resolve_implications", tag_ids, Some(Duration::from_secs(5)))
+            .await
+    }
+
+    /// Returns the tags assigned to any of the given files with the number of files
+    /// they are assigned to. Only tags shared by all files are returned if `intersection` is set
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn aggregate_tags_for_files(
+        &self,
+        file_ids: Vec<i64>,
+        intersection: bool,
+    ) -> ApiResult<TagAggregateResponse> {
+        self.emit_and_get(
+            "aggregate_tags_for_files",
+            AggregateTagsRequest {
+                file_ids,
+                intersection,
+            },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns a small combination of the tags of a file that matches as few files as possible.
+    /// This api is experimental and might change
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn minimal_identifying_tags(
+        &self,
+        id: FileIdentifier,
+    ) -> ApiResult<IdentifyingTagsResponse> {
+        self.emit_and_get(
+            "minimal_identifying_tags",
+            GetFileTagsRequest { id },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns the tags of the reference files that aren't assigned to the given file
+    /// with the number of reference files they are assigned to, most common first
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn missing_tags_for_file(
+        &self,
+        file_id: i64,
+        reference_file_ids: Vec<i64>,
+    ) -> ApiResult<Vec<(TagResponse, u64)>> {
+        self.emit_and_get(
+            "missing_tags_for_file",
+            MissingTagsRequest {
+                file_id,
+                reference_file_ids,
+            },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns the given tags or all tags if none are given
+    /// with the number of files they are assigned to
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_tag_counts(
+        &self,
+        tag_ids: Option<Vec<i64>>,
+    ) -> ApiResult<Vec<(TagResponse, u64)>> {
+        self.emit_and_get(
+            "get_tag_counts",
+            GetTagCountsRequest { tag_ids },
+            Some(Duration::from_secs(10)),
+        )
+        .await
+    }
+
+    /// Returns the tags whose namespace or name starts with the prefix
+    /// with the number of files they are assigned to, most used first
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn autocomplete_tags(
+        &self,
+        prefix: String,
+        limit: u64,
+    ) -> ApiResult<Vec<(TagResponse, u64)>> {
+        self.emit_and_get(
+            "autocomplete_tags",
+            AutocompleteTagsRequest { prefix, limit },
+            Some(Duration::from_secs(5)),
+        )
+        .await
+    }
+
+    /// Assigns the tags to all given files and returns the number of newly created mappings
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_tags_to_files(&self, file_ids: Vec<i64>, tag_ids: Vec<i64>) -> ApiResult<u64> {
+        self.emit_and_get(
+            "add_tags_to_files",
+            TagFilesRequest { file_ids, tag_ids },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
+    /// Removes the tags from all given files and returns the number of removed mappings
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_tags_from_files(
+        &self,
+        file_ids: Vec<i64>,
+        tag_ids: Vec<i64>,
+    ) -> ApiResult<u64> {
+        self.emit_and_get(
+            "remove_tags_from_files",
+            TagFilesRequest { file_ids, tag_ids },
+            Some(Duration::from_secs(30)),
+        )
+        .await
+    }
+
     /// Changes the tags of a file
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn change_file_tags(
@@ -105,4 +380,34 @@ impl TagApi {
         )
         .await
     }
+
+    /// Returns the most used tags among the files matching the filters
+    /// together with the number of matching files they are assigned to
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_tag_facets(
+        &self,
+        filters: Vec<FilterExpression>,
+        limit: u64,
+    ) -> ApiResult<Vec<(TagResponse, u64)>> {
+        self.emit_and_get(
+            "tag_facets",
+            TagFacetsRequest { filters, limit },
+            Some(Duration::from_secs(20)),
+        )
+        .await
+    }
+
+    /// Returns the last tag changes of a file with the newest change first
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_recent_tag_changes_for_file(
+        &self,
+        id: FileIdentifier,
+    ) -> ApiResult<Vec<TagChangeResponse>> {
+        self.emit_and_get(
+            "recent_tag_changes_for_file",
+            GetFileTagsRequest { id },
+            Some(Duration::from_secs(1)),
+        )
+        .await
+    }
 }
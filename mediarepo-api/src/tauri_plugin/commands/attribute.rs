@@ -0,0 +1,91 @@
+use crate::tauri_plugin::commands::ApiAccess;
+use crate::tauri_plugin::error::PluginResult;
+use crate::types::attributes::{AttributeFieldResponse, AttributeValueType, FileAttributeResponse};
+use crate::types::identifier::FileIdentifier;
+
+#[tauri::command]
+pub async fn all_attribute_fields(
+    api_state: ApiAccess<'_>,
+) -> PluginResult<Vec<AttributeFieldResponse>> {
+    let api = api_state.api().await?;
+    let fields = api.attribute.all_fields().await?;
+
+    Ok(fields)
+}
+
+#[tauri::command]
+pub async fn add_attribute_field(
+    api_state: ApiAccess<'_>,
+    name: String,
+    value_type: AttributeValueType,
+) -> PluginResult<AttributeFieldResponse> {
+    let api = api_state.api().await?;
+    let field = api.attribute.add_field(name, value_type).await?;
+
+    Ok(field)
+}
+
+#[tauri::command]
+pub async fn update_attribute_field(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    name: Option<String>,
+    value_type: Option<AttributeValueType>,
+) -> PluginResult<AttributeFieldResponse> {
+    let api = api_state.api().await?;
+    let field = api.attribute.update_field(id, name, value_type).await?;
+
+    Ok(field)
+}
+
+#[tauri::command]
+pub async fn delete_attribute_field(api_state: ApiAccess<'_>, id: i64) -> PluginResult<()> {
+    let api = api_state.api().await?;
+    api.attribute.delete_field(id).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_file_attributes(
+    api_state: ApiAccess<'_>,
+    id: i64,
+) -> PluginResult<Vec<FileAttributeResponse>> {
+    let api = api_state.api().await?;
+    let attributes = api
+        .attribute
+        .get_file_attributes(FileIdentifier::ID(id))
+        .await?;
+
+    Ok(attributes)
+}
+
+#[tauri::command]
+pub async fn set_file_attribute(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    field_id: i64,
+    value: String,
+) -> PluginResult<FileAttributeResponse> {
+    let api = api_state.api().await?;
+    let attribute = api
+        .attribute
+        .set_file_attribute(FileIdentifier::ID(id), field_id, value)
+        .await?;
+
+    Ok(attribute)
+}
+
+#[tauri::command]
+pub async fn remove_file_attribute(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    field_id: i64,
+) -> PluginResult<()> {
+    let api = api_state.api().await?;
+    api.attribute
+        .remove_file_attribute(FileIdentifier::ID(id), field_id)
+        .await?;
+
+    Ok(())
+}
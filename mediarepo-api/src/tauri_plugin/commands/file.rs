@@ -1,17 +1,48 @@
-use crate::tauri_plugin::commands::{ApiAccess, BufferAccess};
-use crate::tauri_plugin::error::PluginResult;
-use crate::tauri_plugin::utils::system_time_to_naive_date_time;
+use crate::tauri_plugin::commands::{ApiAccess, AppAccess, BufferAccess};
+use crate::tauri_plugin::error::{PluginError, PluginResult};
+use crate::tauri_plugin::utils::{system_time_to_naive_date_time, thumbnail_size_range};
 use crate::types::files::{
-    FileBasicDataResponse, FileMetadataResponse, FileOSMetadata, FileStatus,
-    ThumbnailMetadataResponse,
+    DeleteFilesPreviewResponse, ExportFilesEvent, FileBasicDataResponse, FileDeletionResponse,
+    FileMetadataField, FileMetadataResponse, FileOSMetadata, FileStatus, FileStorageInfoResponse,
+    ImportDirectoryEvent, ImportDirectoryOptions, ImportDirectoryResponse, ImportFileEntry,
+    ImportFilesEvent, ImportFilesResponse, InlineThumbnailResponse, ProbedFileResponse,
+    RenamedFileResponse, SimilarFileResponse, ThumbnailCrop, ThumbnailMetadataResponse,
 };
-use crate::types::filtering::{FilterExpression, SortKey};
+use crate::types::filtering::{FilterExpression, FindFileIdsResponse, FindFilesResponse, SortKey};
 use crate::types::identifier::FileIdentifier;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
+use tauri::{AppHandle, Manager, Runtime};
 use tokio::fs;
-use tokio::fs::DirEntry;
+
+/// The event the progress of daemon side imports is sent to the frontend with
+static IMPORT_PROGRESS_EVENT: &str = "import_progress";
+
+/// The event the progress of daemon side folder imports is sent to the frontend with
+static DIRECTORY_IMPORT_PROGRESS_EVENT: &str = "directory_import_progress";
+
+/// The event the progress of daemon side exports is sent to the frontend with
+static EXPORT_PROGRESS_EVENT: &str = "export_progress";
+
+/// The event the pages of streamed searches are sent to the frontend with
+static FILES_PAGE_EVENT: &str = "files_page";
+
+/// Names of files created by operating systems that should never be imported
+const SYSTEM_FILE_NAMES: &[&str] = &["thumbs.db", "ehthumbs.db", "desktop.ini", ".ds_store"];
+
+/// The page size used when only an offset is given as the daemon stores limits as signed integers
+const UNLIMITED_PAGE_SIZE: u64 = i64::MAX as u64;
+
+/// A page of the results of a streamed search
+#[derive(Clone, Serialize, Debug)]
+pub struct FilesPageEvent {
+    pub search_id: String,
+    pub files: Vec<FileBasicDataResponse>,
+    pub total: Option<u64>,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AddFileOptions {
@@ -19,14 +50,159 @@ pub struct AddFileOptions {
     pub delete_after_import: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResolvedFiles {
+    pub files: Vec<FileOSMetadata>,
+    pub skipped_count: usize,
+    /// The paths of symlinks that weren't resolved
+    pub skipped_symlinks: Vec<String>,
+}
+
+/// How symlinks are treated when resolving paths to files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymlinkHandling {
+    /// Resolves symlinks to files and directories.
+    /// Files and directories that were already visited are skipped
+    Follow,
+    /// Skips all symlinks
+    #[default]
+    Skip,
+    /// Imports symlinks to files like regular files without descending into symlinked directories
+    ImportAsIs,
+}
+
+/// Identifies a file independent of the path it is reached by
+#[cfg(unix)]
+type FileKey = (u64, u64);
+
+#[cfg(not(unix))]
+type FileKey = PathBuf;
+
 #[tauri::command]
-pub async fn get_all_files(api_state: ApiAccess<'_>) -> PluginResult<Vec<FileBasicDataResponse>> {
+pub async fn get_all_files(
+    api_state: ApiAccess<'_>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
     let api = api_state.api().await?;
-    let all_files = api.file.all_files().await?;
+    let all_files = if offset.is_some() || limit.is_some() {
+        api.file
+            .all_files_paginated(offset.unwrap_or(0), limit.unwrap_or(UNLIMITED_PAGE_SIZE))
+            .await?
+            .files
+    } else {
+        api.file.all_files().await?
+    };
 
     Ok(all_files)
 }
 
+#[tauri::command]
+pub async fn get_corrupt_files(
+    api_state: ApiAccess<'_>,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api.file.corrupt_files().await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn find_duplicate_files(
+    api_state: ApiAccess<'_>,
+) -> PluginResult<Vec<Vec<FileBasicDataResponse>>> {
+    let api = api_state.api().await?;
+    let groups = api.file.find_duplicate_files().await?;
+
+    Ok(groups)
+}
+
+#[tauri::command]
+pub async fn find_similar_files(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    max_distance: u32,
+) -> PluginResult<Vec<SimilarFileResponse>> {
+    let api = api_state.api().await?;
+    let files = api
+        .file
+        .find_similar_files(FileIdentifier::ID(id), max_distance)
+        .await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn get_file_by_external_id(
+    api_state: ApiAccess<'_>,
+    external_id: String,
+) -> PluginResult<Option<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let file = api.file.get_file_by_external_id(external_id).await?;
+
+    Ok(file)
+}
+
+#[tauri::command]
+pub async fn find_files_by_name(
+    api_state: ApiAccess<'_>,
+    pattern: String,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api.file.find_files_by_name(pattern).await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn find_files_near(
+    api_state: ApiAccess<'_>,
+    latitude: f64,
+    longitude: f64,
+    radius_km: f64,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api
+        .file
+        .find_files_near(latitude, longitude, radius_km)
+        .await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn find_files_from_source(
+    api_state: ApiAccess<'_>,
+    source_pattern: String,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api.file.find_files_from_source(source_pattern).await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn get_most_viewed_files(
+    api_state: ApiAccess<'_>,
+    limit: u64,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api.file.most_viewed_files(limit).await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn get_files_missing_metadata(
+    api_state: ApiAccess<'_>,
+    fields: Vec<FileMetadataField>,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api.file.files_missing_metadata(fields).await?;
+
+    Ok(files)
+}
+
 #[tauri::command]
 pub async fn get_files(
     api_state: ApiAccess<'_>,
@@ -44,6 +220,7 @@ pub async fn add_local_file(
     api_state: ApiAccess<'_>,
     metadata: FileOSMetadata,
     options: AddFileOptions,
+    external_id: Option<String>,
 ) -> PluginResult<FileBasicDataResponse> {
     let api = api_state.api().await?;
     let path = PathBuf::from(&metadata.path);
@@ -63,7 +240,10 @@ pub async fn add_local_file(
     }
 
     let file_content = fs::read(&path).await?;
-    let file = api.file.add_file(metadata, tags, file_content).await?;
+    let file = api
+        .file
+        .add_file(metadata, tags, external_id, file_content)
+        .await?;
     if options.delete_after_import {
         fs::remove_file(path).await?;
 
@@ -75,16 +255,247 @@ pub async fn add_local_file(
     Ok(file)
 }
 
+/// Imports files from paths on the machine of the daemon. The progress
+/// is emitted to the frontend after each file
+#[tauri::command]
+pub async fn import_files<R: Runtime>(
+    app: AppHandle<R>,
+    api_state: ApiAccess<'_>,
+    import_id: String,
+    files: Vec<ImportFileEntry>,
+) -> PluginResult<ImportFilesResponse> {
+    let api = api_state.api().await?;
+    let mut stream = Box::pin(api.file.import_files(import_id, files).await?);
+
+    while let Some(event) = stream.next().await {
+        match event? {
+            ImportFilesEvent::Progress(progress) => {
+                if let Err(e) = app.emit_all(IMPORT_PROGRESS_EVENT, progress) {
+                    tracing::error!("failed to emit import progress: {}", e);
+                }
+            }
+            ImportFilesEvent::Finished(response) => return Ok(response),
+        }
+    }
+
+    Err(PluginError::from("the import ended without a result"))
+}
+
+/// Imports the files of a folder on the machine of the daemon. The progress
+/// is emitted to the frontend after each path
+#[tauri::command]
+pub async fn import_directory<R: Runtime>(
+    app: AppHandle<R>,
+    api_state: ApiAccess<'_>,
+    import_id: String,
+    path: String,
+    options: ImportDirectoryOptions,
+) -> PluginResult<ImportDirectoryResponse> {
+    let api = api_state.api().await?;
+    let mut stream = Box::pin(api.file.import_directory(import_id, path, options).await?);
+
+    while let Some(event) = stream.next().await {
+        match event? {
+            ImportDirectoryEvent::Progress(progress) => {
+                if let Err(e) = app.emit_all(DIRECTORY_IMPORT_PROGRESS_EVENT, progress) {
+                    tracing::error!("failed to emit directory import progress: {}", e);
+                }
+            }
+            ImportDirectoryEvent::Finished(response) => return Ok(response),
+        }
+    }
+
+    Err(PluginError::from("the import ended without a result"))
+}
+
+/// Writes files into a folder on the machine of the daemon. The progress
+/// is emitted to the frontend after each file
+#[tauri::command]
+pub async fn export_files<R: Runtime>(
+    app: AppHandle<R>,
+    api_state: ApiAccess<'_>,
+    ids: Vec<i64>,
+    target_dir: String,
+    write_tag_sidecars: bool,
+    group_by_namespace: Option<String>,
+) -> PluginResult<Vec<String>> {
+    let api = api_state.api().await?;
+    let mut stream = Box::pin(
+        api.file
+            .export_files(ids, target_dir, write_tag_sidecars, group_by_namespace)
+            .await?,
+    );
+
+    while let Some(event) = stream.next().await {
+        match event? {
+            ExportFilesEvent::Progress(progress) => {
+                if let Err(e) = app.emit_all(EXPORT_PROGRESS_EVENT, progress) {
+                    tracing::error!("failed to emit export progress: {}", e);
+                }
+            }
+            ExportFilesEvent::Finished(paths) => return Ok(paths),
+        }
+    }
+
+    Err(PluginError::from("the export ended without a result"))
+}
+
+#[tauri::command]
+pub async fn cancel_import(api_state: ApiAccess<'_>, import_id: String) -> PluginResult<bool> {
+    let api = api_state.api().await?;
+    let cancelled = api.file.cancel_import(import_id).await?;
+
+    Ok(cancelled)
+}
+
 #[tauri::command]
 pub async fn find_files(
     filters: Vec<FilterExpression>,
     sort_by: Vec<SortKey>,
+    max_results: Option<u64>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    include_unindexed: Option<bool>,
     api_state: ApiAccess<'_>,
-) -> PluginResult<Vec<FileBasicDataResponse>> {
+) -> PluginResult<FindFilesResponse> {
     let api = api_state.api().await?;
-    let files = api.file.find_files(filters, sort_by).await?;
+    let response = if include_unindexed.unwrap_or(false) {
+        let limit = limit.or(max_results).unwrap_or(UNLIMITED_PAGE_SIZE);
+        api.file
+            .find_all_files_paginated(filters, sort_by, offset.unwrap_or(0), limit)
+            .await?
+    } else if offset.is_some() || limit.is_some() {
+        let limit = limit.or(max_results).unwrap_or(UNLIMITED_PAGE_SIZE);
+        api.file
+            .find_files_paginated(filters, sort_by, offset.unwrap_or(0), limit)
+            .await?
+    } else {
+        api.file.find_files(filters, sort_by, max_results).await?
+    };
 
-    Ok(files)
+    Ok(response)
+}
+
+/// Searches for files with one of the statuses
+#[tauri::command]
+pub async fn find_files_with_statuses(
+    filters: Vec<FilterExpression>,
+    sort_by: Vec<SortKey>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    include_unindexed: Option<bool>,
+    statuses: Vec<FileStatus>,
+    api_state: ApiAccess<'_>,
+) -> PluginResult<FindFilesResponse> {
+    let api = api_state.api().await?;
+    let response = api
+        .file
+        .find_files_with_statuses(
+            filters,
+            sort_by,
+            offset,
+            limit,
+            include_unindexed.unwrap_or(false),
+            statuses,
+        )
+        .await?;
+
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn search_files_by_text(
+    query: String,
+    sort_by: Vec<SortKey>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    api_state: ApiAccess<'_>,
+) -> PluginResult<FindFilesResponse> {
+    let api = api_state.api().await?;
+    let response = api
+        .file
+        .search_files_by_text(
+            query,
+            sort_by,
+            offset.unwrap_or(0),
+            limit.unwrap_or(UNLIMITED_PAGE_SIZE),
+        )
+        .await?;
+
+    Ok(response)
+}
+
+/// Searches for files and emits the results to the frontend page by page.
+/// Returns the number of files that were sent
+#[tauri::command]
+pub async fn stream_files<R: Runtime>(
+    app: AppHandle<R>,
+    api_state: ApiAccess<'_>,
+    search_id: String,
+    filters: Vec<FilterExpression>,
+    sort_by: Vec<SortKey>,
+    page_size: u64,
+    max_results: Option<u64>,
+) -> PluginResult<u64> {
+    let api = api_state.api().await?;
+    let mut stream = Box::pin(
+        api.file
+            .stream_files(search_id.clone(), filters, sort_by, page_size, max_results)
+            .await?,
+    );
+    let mut sent = 0;
+
+    while let Some(page) = stream.next().await {
+        let page = page?;
+        sent += page.files.len() as u64;
+        let event = FilesPageEvent {
+            search_id: search_id.clone(),
+            files: page.files,
+            total: page.total,
+        };
+
+        if let Err(e) = app.emit_all(FILES_PAGE_EVENT, event) {
+            tracing::error!("failed to emit files page: {}", e);
+        }
+    }
+
+    Ok(sent)
+}
+
+#[tauri::command]
+pub async fn stop_search(api_state: ApiAccess<'_>, search_id: String) -> PluginResult<bool> {
+    let api = api_state.api().await?;
+    let stopped = api.file.stop_search(search_id).await?;
+
+    Ok(stopped)
+}
+
+#[tauri::command]
+pub async fn find_file_ids(
+    filters: Vec<FilterExpression>,
+    sort_by: Vec<SortKey>,
+    max_results: Option<u64>,
+    api_state: ApiAccess<'_>,
+) -> PluginResult<FindFileIdsResponse> {
+    let api = api_state.api().await?;
+    let response = api
+        .file
+        .find_file_ids(filters, sort_by, max_results)
+        .await?;
+
+    Ok(response)
+}
+
+/// Returns the encoded content descriptors of the files with the given ids
+#[tauri::command]
+pub async fn get_cds_for_ids(
+    api_state: ApiAccess<'_>,
+    ids: Vec<i64>,
+) -> PluginResult<HashMap<i64, String>> {
+    let api = api_state.api().await?;
+    let cds = api.file.get_cds_for_ids(ids).await?;
+
+    Ok(cds)
 }
 
 #[tauri::command]
@@ -98,6 +509,51 @@ pub async fn get_file_thumbnails(
     Ok(thumbs)
 }
 
+/// Returns the metadata of the best matching thumbnail for the given
+/// size for each content descriptor id
+#[tauri::command]
+pub async fn get_thumbnail_metadata_for_cds(
+    api_state: ApiAccess<'_>,
+    app_state: AppAccess<'_>,
+    cd_ids: Vec<i64>,
+    height: u32,
+    width: u32,
+    crop: Option<ThumbnailCrop>,
+) -> PluginResult<HashMap<i64, ThumbnailMetadataResponse>> {
+    let api = api_state.api().await?;
+    let tolerance = app_state.thumbnail_size_tolerance().await;
+    let (min_size, max_size) = thumbnail_size_range(height, width, tolerance);
+    let thumbs = api
+        .file
+        .get_thumbnails_for_cds(cd_ids, min_size, max_size, crop)
+        .await?;
+
+    Ok(thumbs)
+}
+
+/// Returns the metadata of the best matching thumbnail for the given size for each
+/// content descriptor id together with the contents of thumbnails up to `max_inline_size` bytes
+#[tauri::command]
+pub async fn get_inline_thumbnails_for_cds(
+    api_state: ApiAccess<'_>,
+    app_state: AppAccess<'_>,
+    cd_ids: Vec<i64>,
+    height: u32,
+    width: u32,
+    crop: Option<ThumbnailCrop>,
+    max_inline_size: u64,
+) -> PluginResult<HashMap<i64, InlineThumbnailResponse>> {
+    let api = api_state.api().await?;
+    let tolerance = app_state.thumbnail_size_tolerance().await;
+    let (min_size, max_size) = thumbnail_size_range(height, width, tolerance);
+    let thumbs = api
+        .file
+        .get_inline_thumbnails_for_cds(cd_ids, min_size, max_size, crop, max_inline_size)
+        .await?;
+
+    Ok(thumbs)
+}
+
 #[tauri::command]
 pub async fn get_file_metadata(
     api_state: ApiAccess<'_>,
@@ -109,6 +565,20 @@ pub async fn get_file_metadata(
     Ok(metadata)
 }
 
+#[tauri::command]
+pub async fn get_file_storage_info(
+    api_state: ApiAccess<'_>,
+    id: i64,
+) -> PluginResult<FileStorageInfoResponse> {
+    let api = api_state.api().await?;
+    let storage_info = api
+        .file
+        .get_file_storage_info(FileIdentifier::ID(id))
+        .await?;
+
+    Ok(storage_info)
+}
+
 #[tauri::command]
 pub async fn update_file_name(
     api_state: ApiAccess<'_>,
@@ -124,6 +594,54 @@ pub async fn update_file_name(
     Ok(metadata)
 }
 
+#[tauri::command]
+pub async fn update_file_comment(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    comment: Option<String>,
+) -> PluginResult<FileMetadataResponse> {
+    let api = api_state.api().await?;
+    let metadata = api
+        .file
+        .update_file_comment(FileIdentifier::ID(id), comment)
+        .await?;
+
+    Ok(metadata)
+}
+
+/// Updates the name, comment and status of a file at once
+#[tauri::command]
+pub async fn update_file_metadata(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    name: Option<String>,
+    comment: Option<String>,
+    status: Option<FileStatus>,
+    undelete: Option<bool>,
+) -> PluginResult<FileMetadataResponse> {
+    let api = api_state.api().await?;
+    let metadata = api
+        .file
+        .update_file_metadata(
+            FileIdentifier::ID(id),
+            name,
+            comment,
+            status,
+            undelete.unwrap_or(false),
+        )
+        .await?;
+
+    Ok(metadata)
+}
+
+#[tauri::command]
+pub async fn touch_file(api_state: ApiAccess<'_>, id: i64) -> PluginResult<FileMetadataResponse> {
+    let api = api_state.api().await?;
+    let metadata = api.file.touch_file(FileIdentifier::ID(id)).await?;
+
+    Ok(metadata)
+}
+
 #[tauri::command]
 pub async fn update_file_status(
     api_state: ApiAccess<'_>,
@@ -140,11 +658,139 @@ pub async fn update_file_status(
 }
 
 #[tauri::command]
-pub async fn delete_file(api_state: ApiAccess<'_>, id: i64) -> PluginResult<()> {
+pub async fn archive_files(
+    api_state: ApiAccess<'_>,
+    ids: Vec<i64>,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
     let api = api_state.api().await?;
-    api.file.delete_file(FileIdentifier::ID(id)).await?;
+    let files = api.file.archive_files(ids).await?;
 
-    Ok(())
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn unarchive_files(
+    api_state: ApiAccess<'_>,
+    ids: Vec<i64>,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api.file.unarchive_files(ids).await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn restore_files(
+    api_state: ApiAccess<'_>,
+    ids: Vec<i64>,
+) -> PluginResult<Vec<FileBasicDataResponse>> {
+    let api = api_state.api().await?;
+    let files = api.file.restore_files(ids).await?;
+
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn set_file_indexed(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    indexed: bool,
+) -> PluginResult<FileBasicDataResponse> {
+    let api = api_state.api().await?;
+    let file = api
+        .file
+        .set_file_indexed(FileIdentifier::ID(id), indexed)
+        .await?;
+
+    Ok(file)
+}
+
+#[tauri::command]
+pub async fn update_file_mime_type(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    mime_type: String,
+) -> PluginResult<FileBasicDataResponse> {
+    let api = api_state.api().await?;
+    let file = api
+        .file
+        .update_file_mime_type(FileIdentifier::ID(id), mime_type)
+        .await?;
+
+    Ok(file)
+}
+
+#[tauri::command]
+pub async fn delete_file(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    dry_run: Option<bool>,
+) -> PluginResult<FileDeletionResponse> {
+    let api = api_state.api().await?;
+    let deletion = api
+        .file
+        .delete_file(FileIdentifier::ID(id), dry_run.unwrap_or(false))
+        .await?;
+
+    Ok(deletion)
+}
+
+#[tauri::command]
+pub async fn probe_file(
+    api_state: ApiAccess<'_>,
+    path: String,
+) -> PluginResult<ProbedFileResponse> {
+    let api = api_state.api().await?;
+    let probed = api.file.probe_file(path).await?;
+
+    Ok(probed)
+}
+
+#[tauri::command]
+pub async fn prepare_delete_files(
+    api_state: ApiAccess<'_>,
+    ids: Vec<i64>,
+) -> PluginResult<DeleteFilesPreviewResponse> {
+    let api = api_state.api().await?;
+    let preview = api.file.prepare_delete_files(ids).await?;
+
+    Ok(preview)
+}
+
+#[tauri::command]
+pub async fn delete_files(api_state: ApiAccess<'_>, token: String) -> PluginResult<u64> {
+    let api = api_state.api().await?;
+    let count = api.file.delete_files(token).await?;
+
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn bulk_rename_files(
+    api_state: ApiAccess<'_>,
+    ids: Vec<i64>,
+    pattern: String,
+    dry_run: bool,
+) -> PluginResult<Vec<RenamedFileResponse>> {
+    let api = api_state.api().await?;
+    let renamed = api.file.bulk_rename_files(ids, pattern, dry_run).await?;
+
+    Ok(renamed)
+}
+
+#[tauri::command]
+pub async fn create_timeline_thumbnails(
+    api_state: ApiAccess<'_>,
+    id: i64,
+    count: u32,
+) -> PluginResult<Vec<(f64, ThumbnailMetadataResponse)>> {
+    let api = api_state.api().await?;
+    let thumbnails = api
+        .file
+        .create_timeline_thumbnails(FileIdentifier::ID(id), count)
+        .await?;
+
+    Ok(thumbnails)
 }
 
 #[tauri::command]
@@ -178,70 +824,132 @@ pub async fn save_file_locally(
 }
 
 #[tauri::command]
-pub async fn delete_thumbnails(api_state: ApiAccess<'_>, id: i64) -> PluginResult<()> {
+pub async fn delete_thumbnails(api_state: ApiAccess<'_>, id: i64) -> PluginResult<u64> {
     let api = api_state.api().await?;
-    api.file.delete_thumbnails(FileIdentifier::ID(id)).await?;
+    let freed_bytes = api.file.delete_thumbnails(FileIdentifier::ID(id)).await?;
 
-    Ok(())
+    Ok(freed_bytes)
+}
+
+#[tauri::command]
+pub async fn clear_all_thumbnails(api_state: ApiAccess<'_>) -> PluginResult<u64> {
+    let api = api_state.api().await?;
+    let freed_bytes = api.file.clear_all_thumbnails().await?;
+
+    Ok(freed_bytes)
 }
 
+/// Resolves the given paths to the files they contain.
+/// Hidden and system files are skipped unless `skip_hidden` is set to false.
+/// Symlinks are skipped unless another way of handling them is given
 #[tauri::command]
-pub async fn resolve_paths_to_files(paths: Vec<String>) -> PluginResult<Vec<FileOSMetadata>> {
-    let mut files = Vec::new();
+pub async fn resolve_paths_to_files(
+    paths: Vec<String>,
+    skip_hidden: Option<bool>,
+    symlinks: Option<SymlinkHandling>,
+) -> PluginResult<ResolvedFiles> {
+    let skip_hidden = skip_hidden.unwrap_or(true);
+    let symlinks = symlinks.unwrap_or_default();
+    let mut resolved = ResolvedFiles {
+        files: Vec::new(),
+        skipped_count: 0,
+        skipped_symlinks: Vec::new(),
+    };
+    let mut visited = HashSet::new();
 
     for path in paths {
         let path = PathBuf::from(path);
-        if path.exists() {
-            files.append(&mut resolve_path_to_files(path).await?);
+        if fs::symlink_metadata(&path).await.is_ok() {
+            resolve_path_to_files(path, skip_hidden, symlinks, &mut visited, &mut resolved).await?;
         }
     }
 
-    Ok(files)
+    Ok(resolved)
 }
 
-/// Resolves a path into several file metadata objects
-#[tracing::instrument(level = "debug")]
-async fn resolve_path_to_files(path: PathBuf) -> PluginResult<Vec<FileOSMetadata>> {
-    let mut files = Vec::new();
+/// Resolves a path into several file metadata objects.
+/// Directories are traversed breadth first and every file or directory
+/// is only visited once so that cycles created by symlinks are never entered
+#[tracing::instrument(level = "debug", skip(visited, resolved))]
+async fn resolve_path_to_files(
+    path: PathBuf,
+    skip_hidden: bool,
+    symlinks: SymlinkHandling,
+    visited: &mut HashSet<FileKey>,
+    resolved: &mut ResolvedFiles,
+) -> PluginResult<()> {
+    if skip_hidden && !path.is_dir() && is_hidden_or_system_file(&path) {
+        resolved.skipped_count += 1;
+        return Ok(());
+    }
+    let mut pending = VecDeque::from(vec![path]);
+
+    while let Some(path) = pending.pop_front() {
+        let is_symlink = fs::symlink_metadata(&path).await?.file_type().is_symlink();
+        let skip_symlink = match symlinks {
+            SymlinkHandling::Follow => false,
+            SymlinkHandling::Skip => true,
+            SymlinkHandling::ImportAsIs => !path.is_file(),
+        };
+        if is_symlink && skip_symlink {
+            resolved
+                .skipped_symlinks
+                .push(path.to_string_lossy().to_string());
+            continue;
+        }
+        if !path.is_dir() && !path.is_file() {
+            // broken symlinks and special files
+            if is_symlink {
+                resolved
+                    .skipped_symlinks
+                    .push(path.to_string_lossy().to_string());
+            }
+            continue;
+        }
+        if !visited.insert(file_key(&path).await?) {
+            continue;
+        }
 
-    if path.is_dir() {
-        let mut read_dir = fs::read_dir(path).await?;
+        if path.is_dir() {
+            let mut read_dir = fs::read_dir(path).await?;
 
-        while let Some(entry) = read_dir.next_entry().await? {
-            let subdir_entries = resolve_subdir(entry).await?;
-            for entry in subdir_entries.into_iter().filter(|e| !e.path().is_dir()) {
-                let metadata = retrieve_file_information(entry.path()).await?;
-                files.push(metadata);
+            while let Some(entry) = read_dir.next_entry().await? {
+                if skip_hidden && is_hidden_or_system_file(&entry.path()) {
+                    resolved.skipped_count += 1;
+                } else {
+                    pending.push_back(entry.path());
+                }
             }
+        } else {
+            let metadata = retrieve_file_information(path).await?;
+            resolved.files.push(metadata);
         }
-    } else {
-        let metadata = retrieve_file_information(path).await?;
-        files.push(metadata);
     }
 
-    Ok(files)
+    Ok(())
 }
 
-/// Iteratively resolves a directory into its sub components
-#[tracing::instrument(level = "debug")]
-async fn resolve_subdir(entry: DirEntry) -> PluginResult<Vec<DirEntry>> {
-    let mut entries = vec![entry];
+#[cfg(unix)]
+async fn file_key(path: &Path) -> PluginResult<FileKey> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).await?;
 
-    let mut i = 0;
+    Ok((metadata.dev(), metadata.ino()))
+}
 
-    while i < entries.len() {
-        let entry = &entries[i];
+#[cfg(not(unix))]
+async fn file_key(path: &Path) -> PluginResult<FileKey> {
+    let path = fs::canonicalize(path).await?;
 
-        if entry.path().is_dir() {
-            let mut read_dir = fs::read_dir(entry.path()).await?;
-            while let Some(entry) = read_dir.next_entry().await? {
-                entries.push(entry);
-            }
-        }
-        i += 1;
-    }
+    Ok(path)
+}
 
-    Ok(entries)
+/// Returns if the given path is a dotfile or a known system file
+fn is_hidden_or_system_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .map(|name| name.starts_with('.') || SYSTEM_FILE_NAMES.contains(&name.as_str()))
+        .unwrap_or(false)
 }
 
 /// Retrieves information about a path that MUST be a file and returns
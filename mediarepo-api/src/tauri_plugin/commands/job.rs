@@ -1,6 +1,9 @@
 use crate::tauri_plugin::commands::ApiAccess;
 use crate::tauri_plugin::error::PluginResult;
-use crate::types::jobs::JobType;
+use crate::types::jobs::{
+    DatabaseBackupResponse, HashAlgorithm, JobInfoResponse, JobProgressResponse, JobType,
+};
+use futures::StreamExt;
 
 #[tauri::command]
 pub async fn run_job(api_state: ApiAccess<'_>, job_type: JobType, sync: bool) -> PluginResult<()> {
@@ -17,3 +20,70 @@ pub async fn is_job_running(api_state: ApiAccess<'_>, job_type: JobType) -> Plug
 
     Ok(running)
 }
+
+#[tauri::command]
+pub async fn cancel_job(api_state: ApiAccess<'_>, job_type: JobType) -> PluginResult<bool> {
+    let api = api_state.api().await?;
+    let cancelled = api.job.cancel_job(job_type).await?;
+
+    Ok(cancelled)
+}
+
+#[tauri::command]
+pub async fn pause_job(api_state: ApiAccess<'_>, job_type: JobType) -> PluginResult<bool> {
+    let api = api_state.api().await?;
+    let paused = api.job.pause_job(job_type).await?;
+
+    Ok(paused)
+}
+
+#[tauri::command]
+pub async fn resume_job(api_state: ApiAccess<'_>, job_type: JobType) -> PluginResult<bool> {
+    let api = api_state.api().await?;
+    let resumed = api.job.resume_job(job_type).await?;
+
+    Ok(resumed)
+}
+
+#[tauri::command]
+pub async fn list_jobs(api_state: ApiAccess<'_>) -> PluginResult<Vec<JobInfoResponse>> {
+    let api = api_state.api().await?;
+    let jobs = api.job.list_jobs().await?;
+
+    Ok(jobs)
+}
+
+#[tauri::command]
+pub async fn get_job_progress(
+    api_state: ApiAccess<'_>,
+    job_type: JobType,
+) -> PluginResult<Option<JobProgressResponse>> {
+    let api = api_state.api().await?;
+    let progress = api.job.job_progress(job_type).await?;
+
+    Ok(progress)
+}
+
+#[tauri::command]
+pub async fn migrate_storage(
+    api_state: ApiAccess<'_>,
+    algorithm: HashAlgorithm,
+) -> PluginResult<Option<JobProgressResponse>> {
+    let api = api_state.api().await?;
+    let mut stream = Box::pin(api.job.migrate_storage(algorithm).await?);
+    let mut progress = None;
+
+    while let Some(update) = stream.next().await {
+        progress = Some(update?);
+    }
+
+    Ok(progress)
+}
+
+#[tauri::command]
+pub async fn backup_database_now(api_state: ApiAccess<'_>) -> PluginResult<DatabaseBackupResponse> {
+    let api = api_state.api().await?;
+    let backup = api.job.backup_now().await?;
+
+    Ok(backup)
+}
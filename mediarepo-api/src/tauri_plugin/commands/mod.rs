@@ -1,20 +1,24 @@
 use tauri::State;
 
+pub use attribute::*;
 pub use daemon::*;
 pub use file::*;
 pub use job::*;
 pub use repo::*;
 pub use tag::*;
 pub use preset::*;
+pub use settings::*;
 
 use crate::tauri_plugin::state::{ApiState, AppState, BufferState};
 
+pub mod attribute;
 pub mod daemon;
 pub mod file;
 pub mod job;
 pub mod repo;
 pub mod tag;
 pub mod preset;
+pub mod settings;
 
 pub type ApiAccess<'a> = State<'a, ApiState>;
 pub type AppAccess<'a> = State<'a, AppState>;
@@ -1,17 +1,28 @@
+use crate::client_api::connection::ConnectionState;
 use crate::client_api::protocol::ApiProtocolListener;
 use crate::client_api::ApiClient;
 use crate::tauri_plugin::commands::{ApiAccess, AppAccess, BufferAccess};
 use crate::tauri_plugin::error::{PluginError, PluginResult};
 use crate::tauri_plugin::settings::{save_settings, Repository};
-use crate::types::repo::{FrontendState, RepositoryMetadata, SizeMetadata, SizeType};
+use crate::types::misc::CapabilitiesResponse;
+use crate::types::repo::{
+    CheckIntegrityEvent, DedupStatsResponse, FrontendState, IntegrityReportResponse,
+    OrphanedBlobResponse, RepositoryMetadata, SizeMetadata, SizeType, StorageResponse,
+};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::mem;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime};
 use tokio::fs;
 use tokio::time::Duration;
 
 static REPO_CONFIG_FILE: &str = "repo.toml";
+static CONNECTION_STATE_EVENT: &str = "connection_state";
+
+/// The event the progress of integrity checks is sent to the frontend with
+static INTEGRITY_PROGRESS_EVENT: &str = "integrity_progress";
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RepoConfig {
@@ -157,8 +168,9 @@ pub async fn close_local_repository(
 }
 
 #[tauri::command]
-pub async fn select_repository(
+pub async fn select_repository<R: Runtime>(
     name: String,
+    app: AppHandle<R>,
     app_state: AppAccess<'_>,
     api_state: ApiAccess<'_>,
 ) -> PluginResult<()> {
@@ -181,6 +193,7 @@ pub async fn select_repository(
         get_repo_address(path).await?
     };
     let client = ApiClient::connect::<ApiProtocolListener>(address).await?;
+    forward_connection_state(&client, app);
     api_state.set_api(client).await;
 
     let mut active_repo = app_state.active_repo.write().await;
@@ -197,6 +210,21 @@ pub async fn select_repository(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_connection_state(api_state: ApiAccess<'_>) -> PluginResult<ConnectionState> {
+    let api = api_state.api().await?;
+
+    Ok(api.connection_state())
+}
+
+#[tauri::command]
+pub async fn get_capabilities(api_state: ApiAccess<'_>) -> PluginResult<CapabilitiesResponse> {
+    let api = api_state.api().await?;
+    let capabilities = api.capabilities().await?;
+
+    Ok(capabilities)
+}
+
 #[tauri::command]
 pub async fn get_repo_metadata(api_state: ApiAccess<'_>) -> PluginResult<RepositoryMetadata> {
     let api = api_state.api().await?;
@@ -213,6 +241,70 @@ pub async fn get_size(api_state: ApiAccess<'_>, size_type: SizeType) -> PluginRe
     Ok(size)
 }
 
+#[tauri::command]
+pub async fn get_dedup_stats(api_state: ApiAccess<'_>) -> PluginResult<DedupStatsResponse> {
+    let api = api_state.api().await?;
+    let stats = api.repo.get_dedup_stats().await?;
+
+    Ok(stats)
+}
+
+#[tauri::command]
+pub async fn list_storages(api_state: ApiAccess<'_>) -> PluginResult<Vec<StorageResponse>> {
+    let api = api_state.api().await?;
+    let storages = api.repo.list_storages().await?;
+
+    Ok(storages)
+}
+
+#[tauri::command]
+pub async fn find_orphaned_blobs(
+    api_state: ApiAccess<'_>,
+) -> PluginResult<Vec<OrphanedBlobResponse>> {
+    let api = api_state.api().await?;
+    let blobs = api.repo.find_orphaned_blobs().await?;
+
+    Ok(blobs)
+}
+
+#[tauri::command]
+pub async fn cleanup_orphaned_blobs(
+    api_state: ApiAccess<'_>,
+    dry_run: bool,
+) -> PluginResult<Vec<OrphanedBlobResponse>> {
+    let api = api_state.api().await?;
+    let blobs = api.repo.cleanup_orphaned_blobs(dry_run).await?;
+
+    Ok(blobs)
+}
+
+/// Checks the stored files against their content descriptors. The progress
+/// is emitted to the frontend after each checked content
+#[tauri::command]
+pub async fn check_integrity<R: Runtime>(
+    app: AppHandle<R>,
+    api_state: ApiAccess<'_>,
+    tag_ids: Vec<i64>,
+) -> PluginResult<IntegrityReportResponse> {
+    let api = api_state.api().await?;
+    let mut stream = Box::pin(api.repo.check_integrity(tag_ids).await?);
+
+    while let Some(event) = stream.next().await {
+        match event? {
+            CheckIntegrityEvent::Progress(progress) => {
+                if let Err(e) = app.emit_all(INTEGRITY_PROGRESS_EVENT, progress) {
+                    tracing::error!("failed to emit integrity progress: {}", e);
+                }
+            }
+            CheckIntegrityEvent::Finished(report) => return Ok(report),
+        }
+    }
+
+    Err(PluginError::from(
+        "the integrity check ended without a result",
+    ))
+}
+
 #[tauri::command]
 pub async fn get_frontend_state(api_state: ApiAccess<'_>) -> PluginResult<Option<String>> {
     let api = api_state.api().await?;
@@ -265,3 +357,18 @@ async fn close_selected_repository(app_state: &AppAccess<'_>) -> PluginResult<()
 
     Ok(())
 }
+
+/// Emits an event to the frontend every time the connection state of the client changes
+fn forward_connection_state<R: Runtime>(client: &ApiClient, app: AppHandle<R>) {
+    let mut receiver = client.subscribe_connection_state();
+
+    tauri::async_runtime::spawn(async move {
+        while receiver.changed().await.is_ok() {
+            let state = *receiver.borrow();
+
+            if let Err(e) = app.emit_all(CONNECTION_STATE_EVENT, state) {
+                tracing::error!("failed to emit connection state: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,13 @@
+use crate::tauri_plugin::commands::AppAccess;
+use crate::tauri_plugin::error::PluginResult;
+use crate::tauri_plugin::settings;
+
+/// Repairs the settings file and returns a description of every repair
+#[tauri::command]
+pub async fn repair_settings(app_state: AppAccess<'_>) -> PluginResult<Vec<String>> {
+    let mut settings = app_state.settings.write().await;
+    let (repaired, repairs) = settings::repair_settings()?;
+    *settings = repaired;
+
+    Ok(repairs)
+}
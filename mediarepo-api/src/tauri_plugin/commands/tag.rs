@@ -1,8 +1,12 @@
-use std::collections::HashMap;
 use crate::tauri_plugin::commands::ApiAccess;
 use crate::tauri_plugin::error::PluginResult;
+use crate::types::filtering::FilterExpression;
 use crate::types::identifier::FileIdentifier;
-use crate::types::tags::{NamespaceResponse, TagResponse};
+use crate::types::tags::{
+    FileTagsPageResponse, IdentifyingTagsResponse, NamespaceMergeResponse, NamespaceResponse,
+    TagAggregateResponse, TagAliasResponse, TagChangeResponse, TagResponse,
+};
+use std::collections::HashMap;
 
 #[tauri::command]
 pub async fn get_all_tags(api_state: ApiAccess<'_>) -> PluginResult<Vec<TagResponse>> {
@@ -31,6 +35,22 @@ pub async fn get_tags_for_file(
     Ok(tags)
 }
 
+#[tauri::command]
+pub async fn get_tags_for_file_paginated(
+    id: i64,
+    offset: u64,
+    limit: u64,
+    api_state: ApiAccess<'_>,
+) -> PluginResult<FileTagsPageResponse> {
+    let api = api_state.api().await?;
+    let page = api
+        .tag
+        .get_tags_for_file_paginated(FileIdentifier::ID(id), offset, limit)
+        .await?;
+
+    Ok(page)
+}
+
 #[tauri::command]
 pub async fn get_tags_for_files(
     cds: Vec<String>,
@@ -43,7 +63,10 @@ pub async fn get_tags_for_files(
 }
 
 #[tauri::command]
-pub async fn get_file_tag_map(cds: Vec<String>, api_state: ApiAccess<'_>) -> PluginResult<HashMap<String, Vec<TagResponse>>> {
+pub async fn get_file_tag_map(
+    cds: Vec<String>,
+    api_state: ApiAccess<'_>,
+) -> PluginResult<HashMap<String, Vec<TagResponse>>> {
     let api = api_state.api().await?;
     let mappings = api.tag.get_file_tag_map(cds).await?;
 
@@ -54,13 +77,246 @@ pub async fn get_file_tag_map(cds: Vec<String>, api_state: ApiAccess<'_>) -> Plu
 pub async fn create_tags(
     api_state: ApiAccess<'_>,
     tags: Vec<String>,
-) -> PluginResult<Vec<TagResponse>> {
+) -> PluginResult<Vec<Result<TagResponse, String>>> {
     let api = api_state.api().await?;
     let tags = api.tag.create_tags(tags).await?;
 
     Ok(tags)
 }
 
+#[tauri::command]
+pub async fn aggregate_tags_for_files(
+    api_state: ApiAccess<'_>,
+    file_ids: Vec<i64>,
+    intersection: bool,
+) -> PluginResult<TagAggregateResponse> {
+    let api = api_state.api().await?;
+    let aggregate = api
+        .tag
+        .aggregate_tags_for_files(file_ids, intersection)
+        .await?;
+
+    Ok(aggregate)
+}
+
+#[tauri::command]
+pub async fn add_tags_to_files(
+    api_state: ApiAccess<'_>,
+    file_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> PluginResult<u64> {
+    let api = api_state.api().await?;
+    let created = api.tag.add_tags_to_files(file_ids, tag_ids).await?;
+
+    Ok(created)
+}
+
+#[tauri::command]
+pub async fn remove_tags_from_files(
+    api_state: ApiAccess<'_>,
+    file_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> PluginResult<u64> {
+    let api = api_state.api().await?;
+    let removed = api.tag.remove_tags_from_files(file_ids, tag_ids).await?;
+
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn get_minimal_identifying_tags(
+    api_state: ApiAccess<'_>,
+    id: i64,
+) -> PluginResult<IdentifyingTagsResponse> {
+    let api = api_state.api().await?;
+    let tags = api
+        .tag
+        .minimal_identifying_tags(FileIdentifier::ID(id))
+        .await?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn get_missing_tags_for_file(
+    api_state: ApiAccess<'_>,
+    file_id: i64,
+    reference_file_ids: Vec<i64>,
+) -> PluginResult<Vec<(TagResponse, u64)>> {
+    let api = api_state.api().await?;
+    let tags = api
+        .tag
+        .missing_tags_for_file(file_id, reference_file_ids)
+        .await?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn get_tag_counts(
+    api_state: ApiAccess<'_>,
+    tag_ids: Option<Vec<i64>>,
+) -> PluginResult<Vec<(TagResponse, u64)>> {
+    let api = api_state.api().await?;
+    let counts = api.tag.get_tag_counts(tag_ids).await?;
+
+    Ok(counts)
+}
+
+#[tauri::command]
+pub async fn autocomplete_tags(
+    api_state: ApiAccess<'_>,
+    prefix: String,
+    limit: u64,
+) -> PluginResult<Vec<(TagResponse, u64)>> {
+    let api = api_state.api().await?;
+    let tags = api.tag.autocomplete_tags(prefix, limit).await?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn find_tag_variants(
+    api_state: ApiAccess<'_>,
+    tag: String,
+) -> PluginResult<Vec<TagResponse>> {
+    let api = api_state.api().await?;
+    let tags = api.tag.find_tag_variants(tag).await?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn find_similar_tags(api_state: ApiAccess<'_>) -> PluginResult<Vec<Vec<TagResponse>>> {
+    let api = api_state.api().await?;
+    let similar_tags = api.tag.find_similar_tags().await?;
+
+    Ok(similar_tags)
+}
+
+#[tauri::command]
+pub async fn merge_tags(
+    api_state: ApiAccess<'_>,
+    from_ids: Vec<i64>,
+    into_id: i64,
+) -> PluginResult<TagResponse> {
+    let api = api_state.api().await?;
+    let tag = api.tag.merge_tags(from_ids, into_id).await?;
+
+    Ok(tag)
+}
+
+#[tauri::command]
+pub async fn get_all_tag_aliases(api_state: ApiAccess<'_>) -> PluginResult<Vec<TagAliasResponse>> {
+    let api = api_state.api().await?;
+    let aliases = api.tag.get_all_tag_aliases().await?;
+
+    Ok(aliases)
+}
+
+#[tauri::command]
+pub async fn get_tag_aliases(
+    api_state: ApiAccess<'_>,
+    tag_id: i64,
+) -> PluginResult<Vec<TagAliasResponse>> {
+    let api = api_state.api().await?;
+    let aliases = api.tag.get_tag_aliases(tag_id).await?;
+
+    Ok(aliases)
+}
+
+#[tauri::command]
+pub async fn add_tag_alias(
+    api_state: ApiAccess<'_>,
+    alias: String,
+    tag_id: i64,
+) -> PluginResult<TagAliasResponse> {
+    let api = api_state.api().await?;
+    let alias = api.tag.add_tag_alias(alias, tag_id).await?;
+
+    Ok(alias)
+}
+
+#[tauri::command]
+pub async fn remove_tag_alias(api_state: ApiAccess<'_>, alias_id: i64) -> PluginResult<()> {
+    let api = api_state.api().await?;
+    api.tag.remove_tag_alias(alias_id).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_tag_implications(
+    api_state: ApiAccess<'_>,
+    tag_id: i64,
+) -> PluginResult<Vec<TagResponse>> {
+    let api = api_state.api().await?;
+    let tags = api.tag.get_tag_implications(tag_id).await?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn add_tag_implication(
+    api_state: ApiAccess<'_>,
+    tag_id: i64,
+    implied_tag_id: i64,
+) -> PluginResult<()> {
+    let api = api_state.api().await?;
+    api.tag.add_tag_implication(tag_id, implied_tag_id).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_tag_implication(
+    api_state: ApiAccess<'_>,
+    tag_id: i64,
+    implied_tag_id: i64,
+) -> PluginResult<()> {
+    let api = api_state.api().await?;
+    api.tag
+        .remove_tag_implication(tag_id, implied_tag_id)
+        .await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resolve_tag_implications(
+    api_state: ApiAccess<'_>,
+    tag_ids: Vec<i64>,
+) -> PluginResult<Vec<TagResponse>> {
+    let api = api_state.api().await?;
+    let tags = api.tag.resolve_implications(tag_ids).await?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn merge_namespaces(
+    api_state: ApiAccess<'_>,
+    from_id: i64,
+    into_id: i64,
+) -> PluginResult<NamespaceMergeResponse> {
+    let api = api_state.api().await?;
+    let merge = api.tag.merge_namespaces(from_id, into_id).await?;
+
+    Ok(merge)
+}
+
+#[tauri::command]
+pub async fn convert_tag_to_namespaced(
+    api_state: ApiAccess<'_>,
+    tag_id: i64,
+    namespace: String,
+) -> PluginResult<TagResponse> {
+    let api = api_state.api().await?;
+    let tag = api.tag.convert_tag_to_namespaced(tag_id, namespace).await?;
+
+    Ok(tag)
+}
+
 #[tauri::command]
 pub async fn change_file_tags(
     api_state: ApiAccess<'_>,
@@ -76,3 +332,29 @@ pub async fn change_file_tags(
 
     Ok(tags)
 }
+
+#[tauri::command]
+pub async fn get_tag_facets(
+    api_state: ApiAccess<'_>,
+    filters: Vec<FilterExpression>,
+    limit: u64,
+) -> PluginResult<Vec<(TagResponse, u64)>> {
+    let api = api_state.api().await?;
+    let facets = api.tag.get_tag_facets(filters, limit).await?;
+
+    Ok(facets)
+}
+
+#[tauri::command]
+pub async fn get_recent_tag_changes_for_file(
+    id: i64,
+    api_state: ApiAccess<'_>,
+) -> PluginResult<Vec<TagChangeResponse>> {
+    let api = api_state.api().await?;
+    let changes = api
+        .tag
+        .get_recent_tag_changes_for_file(FileIdentifier::ID(id))
+        .await?;
+
+    Ok(changes)
+}
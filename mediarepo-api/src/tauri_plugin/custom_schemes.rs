@@ -1,7 +1,9 @@
 use crate::client_api::ApiClient;
 use crate::tauri_plugin::background_tasks::TaskContext;
 use crate::tauri_plugin::error::{PluginError, PluginResult};
-use crate::tauri_plugin::state::{ApiState, BufferState};
+use crate::tauri_plugin::state::{ApiState, AppState, BufferState};
+use crate::tauri_plugin::utils::thumbnail_size_range;
+use crate::types::files::ThumbnailCrop;
 use crate::types::identifier::FileIdentifier;
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -22,7 +24,11 @@ pub fn register_custom_uri_schemes<R: Runtime>(builder: Builder<R>) -> Builder<R
             let runtime = Arc::clone(&runtime);
             move |a, r| runtime.block_on(content_scheme(a, r))
         })
-        .register_uri_scheme_protocol("thumb", move |a, r| runtime.block_on(thumb_scheme(a, r)))
+        .register_uri_scheme_protocol("thumb", {
+            let runtime = Arc::clone(&runtime);
+            move |a, r| runtime.block_on(thumb_scheme(a, r))
+        })
+        .register_uri_scheme_protocol("sheet", move |a, r| runtime.block_on(sheet_scheme(a, r)))
 }
 
 fn build_uri_runtime() -> PluginResult<TokioRuntime> {
@@ -58,13 +64,27 @@ fn once_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Result<Resp
 async fn content_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Result<Response> {
     let buf_state = app.state::<BufferState>();
     let hash = request.uri().trim_start_matches("content://");
+    let range_header = request
+        .headers()
+        .get("Range")
+        .and_then(|value| value.to_str().ok());
 
     if let Some(buffer) = buf_state.get_entry(hash) {
         tracing::debug!("Fetching content from cache");
-        ResponseBuilder::new()
-            .status(200)
-            .mimetype(&buffer.mime)
-            .body(buffer.buf)
+        let size = buffer.buf.len() as u64;
+
+        match range_header.and_then(|header| parse_range_header(header, size)) {
+            Some(RangeRequest::Satisfiable(range)) => {
+                let bytes = buffer.buf[range.start as usize..=range.end as usize].to_vec();
+                partial_content_response(&buffer.mime, range.start, size, bytes)
+            }
+            Some(RangeRequest::Unsatisfiable) => range_not_satisfiable_response(size),
+            None => ResponseBuilder::new()
+                .status(200)
+                .mimetype(&buffer.mime)
+                .header("Accept-Ranges", "bytes")
+                .body(buffer.buf),
+        }
     } else {
         tracing::debug!("Fetching content from daemon");
 
@@ -76,6 +96,28 @@ async fn content_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Re
             .get_file(FileIdentifier::CD(hash.to_string()))
             .await?;
         let mime = file.mime_type;
+
+        if let Some(header) = range_header {
+            // ranges are read from the daemon without loading or caching the whole file
+            let size = api
+                .file
+                .get_file_metadata(FileIdentifier::ID(file.id))
+                .await?
+                .size;
+
+            match parse_range_header(header, size) {
+                Some(RangeRequest::Satisfiable(range)) => {
+                    let bytes = api
+                        .file
+                        .read_file_range(FileIdentifier::ID(file.id), range.start, range.len())
+                        .await?;
+                    tracing::debug!("Received {} content bytes", bytes.len());
+                    return partial_content_response(&mime, range.start, size, bytes);
+                }
+                Some(RangeRequest::Unsatisfiable) => return range_not_satisfiable_response(size),
+                None => {}
+            }
+        }
         let bytes = api
             .file
             .read_file(FileIdentifier::CD(hash.to_string()))
@@ -86,10 +128,88 @@ async fn content_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Re
         ResponseBuilder::new()
             .status(200)
             .mimetype(&mime)
+            .header("Accept-Ranges", "bytes")
             .body(bytes)
     }
 }
 
+/// The maximum number of bytes sent for a single range request so that
+/// open ended ranges don't load the whole file
+const MAX_RANGE_LENGTH: u64 = 4 * 1024 * 1024;
+
+/// The first range of a `Range` header resolved against the size of the content
+enum RangeRequest {
+    Satisfiable(ByteRange),
+    Unsatisfiable,
+}
+
+/// A range of bytes with an inclusive end
+struct ByteRange {
+    start: u64,
+    end: u64,
+}
+
+impl ByteRange {
+    fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Parses the first range of a `Range` header. Returns None if the header
+/// isn't a valid byte range in which case the whole content is sent
+fn parse_range_header(header: &str, size: u64) -> Option<RangeRequest> {
+    let ranges = header.trim().strip_prefix("bytes=")?;
+    let (start, end) = ranges.split(',').next()?.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let (start, end) = if start.is_empty() {
+        let suffix_length = end.parse::<u64>().ok()?;
+        if suffix_length == 0 || size == 0 {
+            return Some(RangeRequest::Unsatisfiable);
+        }
+        (size.saturating_sub(suffix_length), size - 1)
+    } else {
+        let start = start.parse::<u64>().ok()?;
+        let end = if end.is_empty() {
+            u64::MAX
+        } else {
+            end.parse::<u64>().ok()?
+        };
+        if end < start {
+            return None;
+        }
+        if start >= size {
+            return Some(RangeRequest::Unsatisfiable);
+        }
+        (start, end.min(size - 1))
+    };
+    let end = end.min(start.saturating_add(MAX_RANGE_LENGTH - 1));
+
+    Some(RangeRequest::Satisfiable(ByteRange { start, end }))
+}
+
+fn partial_content_response(mime: &str, start: u64, size: u64, bytes: Vec<u8>) -> Result<Response> {
+    if bytes.is_empty() {
+        return range_not_satisfiable_response(size);
+    }
+    let end = start + bytes.len() as u64 - 1;
+
+    ResponseBuilder::new()
+        .status(206)
+        .mimetype(mime)
+        .header("Accept-Ranges", "bytes")
+        .header("Content-Range", format!("bytes {}-{}/{}", start, end, size))
+        .body(bytes)
+}
+
+fn range_not_satisfiable_response(size: u64) -> Result<Response> {
+    ResponseBuilder::new()
+        .mimetype("text/plain")
+        .status(416)
+        .header("Content-Range", format!("bytes */{}", size))
+        .body("Range not satisfiable".as_bytes().to_vec())
+}
+
 #[tracing::instrument(level = "debug", skip_all)]
 async fn thumb_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Result<Response> {
     let buf_state = app.state::<BufferState>();
@@ -113,12 +233,34 @@ async fn thumb_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Resu
         .and_then(|w| w.parse::<u32>().ok())
         .unwrap_or(250);
 
+    // the daemon's configured crop is used if none is given
+    let crop = query_pairs.get("crop").and_then(|c| match c.as_ref() {
+        "fit" => Some(ThumbnailCrop::Fit),
+        "center" => Some(ThumbnailCrop::CenterCrop),
+        _ => None,
+    });
+
+    // the position of the timeline thumbnail of videos as a fraction of the duration
+    let position = query_pairs.get("t").and_then(|t| t.parse::<f64>().ok());
+
+    // `exact` renders a thumbnail of the requested size if none is stored
+    // while `best` accepts stored thumbnails within the configured tolerance
+    let tolerance = match query_pairs.get("mode").map(|m| m.as_ref()) {
+        Some("exact") => 0.0,
+        _ => app.state::<AppState>().thumbnail_size_tolerance().await,
+    };
+
     if let Some(buffer) = buf_state.get_entry(request.uri()) {
         tracing::debug!("Fetching content from cache");
-        ResponseBuilder::new()
-            .status(200)
-            .mimetype(&buffer.mime)
-            .body(buffer.buf)
+        let mut response = ResponseBuilder::new().status(200).mimetype(&buffer.mime);
+
+        if let Some((width, height)) = buffer.dimensions {
+            response = response
+                .header("X-Thumbnail-Width", width.to_string())
+                .header("X-Thumbnail-Height", height.to_string());
+        }
+
+        response.body(buffer.buf)
     } else {
         tracing::debug!("Content not loaded. Signaling retry.");
         let task_ctx = app.state::<TaskContext>();
@@ -130,15 +272,20 @@ async fn thumb_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Resu
             let api_state = app.state::<ApiState>();
             let api = api_state.api().await?;
 
+            let thumb_request = ThumbnailRequest {
+                hash: hash.to_string(),
+                width,
+                height,
+                tolerance,
+                crop,
+                position,
+            };
             add_fetch_thumbnail_task(
-                request.uri(),
                 task_ctx,
                 buf_state,
                 api,
-                hash.to_string(),
                 request.uri().to_string(),
-                width,
-                height,
+                thumb_request,
             )
             .await;
         }
@@ -151,29 +298,113 @@ async fn thumb_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Resu
     }
 }
 
+#[tracing::instrument(level = "debug", skip_all)]
+async fn sheet_scheme<R: Runtime>(app: &AppHandle<R>, request: &Request) -> Result<Response> {
+    let buf_state = app.state::<BufferState>();
+
+    let url = Url::parse(request.uri())?;
+    let hash = url
+        .domain()
+        .ok_or_else(|| PluginError::from("Missing Domain"))?;
+
+    let query_pairs = url
+        .query_pairs()
+        .collect::<HashMap<Cow<'_, str>, Cow<'_, str>>>();
+
+    let rows = query_pairs
+        .get("rows")
+        .and_then(|r| r.parse::<u32>().ok())
+        .unwrap_or(3);
+
+    let cols = query_pairs
+        .get("cols")
+        .and_then(|c| c.parse::<u32>().ok())
+        .unwrap_or(3);
+
+    if let Some(buffer) = buf_state.get_entry(request.uri()) {
+        tracing::debug!("Fetching content from cache");
+        ResponseBuilder::new()
+            .status(200)
+            .mimetype(&buffer.mime)
+            .body(buffer.buf)
+    } else {
+        tracing::debug!("Content not loaded. Signaling retry.");
+        let task_ctx = app.state::<TaskContext>();
+
+        let state = task_ctx.task_state(request.uri()).await;
+
+        if state.is_none() || state.unwrap().error() {
+            let buf_state = buf_state.inner().clone();
+            let api_state = app.state::<ApiState>();
+            let api = api_state.api().await?;
+            let hash = hash.to_string();
+            let request_uri = request.uri().to_string();
+
+            task_ctx
+                .add_task(request.uri(), async move {
+                    tracing::debug!("Fetching content from daemon");
+                    let (sheet, bytes) = api
+                        .file
+                        .get_contact_sheet(FileIdentifier::CD(hash), rows, cols)
+                        .await?;
+                    tracing::debug!("Received {} content bytes", bytes.len());
+                    buf_state.add_entry(request_uri, sheet.mime_type.clone(), bytes);
+
+                    Ok(())
+                })
+                .await;
+        }
+
+        ResponseBuilder::new()
+            .mimetype("text/plain")
+            .status(301)
+            .header("Retry-After", "1")
+            .body("Content loading. Retry in 1s.".as_bytes().to_vec())
+    }
+}
+
+/// The thumbnail of a content requested through the thumb scheme
+struct ThumbnailRequest {
+    hash: String,
+    width: u32,
+    height: u32,
+    /// How much the size of a stored thumbnail may differ from the requested one
+    tolerance: f32,
+    crop: Option<ThumbnailCrop>,
+    /// The position of video timeline thumbnails as a fraction of the duration
+    position: Option<f64>,
+}
+
 async fn add_fetch_thumbnail_task(
-    name: &str,
     task_ctx: State<'_, TaskContext>,
     buf_state: BufferState,
     api: ApiClient,
-    hash: String,
     request_uri: String,
-    width: u32,
-    height: u32,
+    thumb_request: ThumbnailRequest,
 ) {
     task_ctx
-        .add_task(name, async move {
+        .add_task(request_uri.clone(), async move {
             tracing::debug!("Fetching content from daemon");
+            let ThumbnailRequest {
+                hash,
+                width,
+                height,
+                tolerance,
+                crop,
+                position,
+            } = thumb_request;
+            let (min_size, max_size) = thumbnail_size_range(height, width, tolerance);
             let (thumb, bytes) = api
                 .file
-                .get_thumbnail_of_size(
-                    FileIdentifier::CD(hash),
-                    ((height as f32 * 0.5) as u32, (width as f32 * 0.5) as u32),
-                    ((height as f32 * 1.5) as u32, (width as f32 * 1.5) as u32),
-                )
+                .get_thumbnail_of_size(FileIdentifier::CD(hash), min_size, max_size, crop, position)
                 .await?;
             tracing::debug!("Received {} content bytes", bytes.len());
-            buf_state.add_entry(request_uri, thumb.mime_type.clone(), bytes.clone());
+            buf_state.add_image_entry(
+                request_uri,
+                thumb.mime_type.clone(),
+                bytes.clone(),
+                (thumb.width, thumb.height),
+            );
 
             Ok(())
         })
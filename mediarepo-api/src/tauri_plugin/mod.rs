@@ -3,6 +3,7 @@ use tauri::{AppHandle, Builder, Invoke, Manager, Runtime};
 
 use state::ApiState;
 
+use crate::tauri_plugin::settings::{load_settings, DEFAULT_MAX_BUFFER_CACHE_SIZE};
 use crate::tauri_plugin::state::{AppState, BufferState};
 use std::thread;
 use std::time::Duration;
@@ -18,8 +19,6 @@ mod utils;
 use crate::tauri_plugin::background_tasks::{start_background_task_runtime, TaskContext};
 use commands::*;
 
-const MAX_BUFFER_SIZE: usize = 2 * 1024 * 1024 * 1024; // 2GiB
-
 pub fn register_plugin<R: Runtime>(builder: Builder<R>) -> Builder<R> {
     let repo_plugin = MediarepoPlugin::new();
 
@@ -35,11 +34,29 @@ impl<R: Runtime> MediarepoPlugin<R> {
         Self {
             invoke_handler: Box::new(tauri::generate_handler![
                 get_all_files,
+                get_corrupt_files,
+                find_duplicate_files,
+                find_similar_files,
+                get_most_viewed_files,
+                get_files_missing_metadata,
+                find_files_by_name,
+                find_files_near,
+                find_files_from_source,
+                get_file_by_external_id,
+                touch_file,
                 find_files,
+                find_files_with_statuses,
+                search_files_by_text,
+                stream_files,
+                stop_search,
+                find_file_ids,
                 get_file_thumbnails,
+                get_thumbnail_metadata_for_cds,
+                get_inline_thumbnails_for_cds,
                 get_repositories,
                 get_all_tags,
                 get_tags_for_file,
+                get_tags_for_file_paginated,
                 get_tags_for_files,
                 get_active_repository,
                 add_repository,
@@ -49,16 +66,45 @@ impl<R: Runtime> MediarepoPlugin<R> {
                 check_daemon_running,
                 stop_daemon,
                 disconnect_repository,
+                get_connection_state,
+                get_capabilities,
                 close_local_repository,
                 check_local_repository_exists,
                 remove_repository,
                 change_file_tags,
                 create_tags,
+                merge_namespaces,
+                convert_tag_to_namespaced,
+                find_tag_variants,
+                find_similar_tags,
+                merge_tags,
+                get_all_tag_aliases,
+                get_tag_aliases,
+                add_tag_alias,
+                remove_tag_alias,
+                get_tag_implications,
+                add_tag_implication,
+                remove_tag_implication,
+                resolve_tag_implications,
+                aggregate_tags_for_files,
+                get_tag_counts,
+                autocomplete_tags,
+                get_missing_tags_for_file,
+                get_minimal_identifying_tags,
+                add_tags_to_files,
+                remove_tags_from_files,
                 update_file_name,
+                update_file_comment,
+                update_file_metadata,
                 resolve_paths_to_files,
                 add_local_file,
+                import_files,
+                import_directory,
+                cancel_import,
+                export_files,
                 save_file_locally,
                 delete_thumbnails,
+                clear_all_thumbnails,
                 read_file,
                 delete_repository,
                 has_executable,
@@ -66,17 +112,51 @@ impl<R: Runtime> MediarepoPlugin<R> {
                 set_frontend_state,
                 get_all_namespaces,
                 get_files,
+                get_cds_for_ids,
                 get_repo_metadata,
                 get_size,
+                get_dedup_stats,
+                list_storages,
+                find_orphaned_blobs,
+                cleanup_orphaned_blobs,
+                check_integrity,
                 get_file_metadata,
+                get_file_storage_info,
                 run_job,
                 update_file_status,
+                archive_files,
+                unarchive_files,
+                restore_files,
+                set_file_indexed,
+                update_file_mime_type,
                 delete_file,
+                probe_file,
+                prepare_delete_files,
+                delete_files,
+                bulk_rename_files,
+                create_timeline_thumbnails,
                 get_file_tag_map,
+                get_tag_facets,
+                get_recent_tag_changes_for_file,
                 all_sorting_presets,
                 add_sorting_preset,
                 delete_sorting_preset,
-                is_job_running
+                all_attribute_fields,
+                add_attribute_field,
+                update_attribute_field,
+                delete_attribute_field,
+                get_file_attributes,
+                set_file_attribute,
+                remove_file_attribute,
+                is_job_running,
+                cancel_job,
+                pause_job,
+                resume_job,
+                list_jobs,
+                get_job_progress,
+                backup_database_now,
+                migrate_storage,
+                repair_settings
             ]),
         }
     }
@@ -96,7 +176,10 @@ impl<R: Runtime> Plugin<R> for MediarepoPlugin<R> {
         let api_state = ApiState::new();
         app.manage(api_state);
 
-        let buffer_state = BufferState::default();
+        let buffer_cache_size = load_settings()
+            .map(|s| s.max_buffer_cache_size)
+            .unwrap_or(DEFAULT_MAX_BUFFER_CACHE_SIZE);
+        let buffer_state = BufferState::with_budget(buffer_cache_size);
         app.manage(buffer_state.clone());
 
         let repo_state = AppState::load()?;
@@ -109,7 +192,6 @@ impl<R: Runtime> Plugin<R> for MediarepoPlugin<R> {
         thread::spawn(move || loop {
             thread::sleep(Duration::from_secs(10));
             buffer_state.clear_expired();
-            buffer_state.trim_to_size(MAX_BUFFER_SIZE);
         });
 
         Ok(())
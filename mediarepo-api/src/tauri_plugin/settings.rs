@@ -5,10 +5,17 @@ use serde::{Deserialize, Serialize};
 use serde_piecewise_default::DeserializePiecewiseDefault;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 static SETTINGS_FILE: &str = "settings.toml";
 
+/// The default fraction by which served thumbnails may differ from the requested size
+pub const DEFAULT_THUMBNAIL_SIZE_TOLERANCE: f32 = 0.5;
+
+/// The default number of bytes the served contents and thumbnails are cached up to
+pub const DEFAULT_MAX_BUFFER_CACHE_SIZE: usize = 512 * 1024 * 1024;
+
 #[derive(Serialize, Debug, Deserialize, Clone)]
 pub struct Repository {
     pub(crate) name: String,
@@ -22,6 +29,12 @@ pub struct Repository {
 pub struct Settings {
     pub daemon_path: Option<String>,
     pub repositories: HashMap<String, Repository>,
+    /// The fraction by which thumbnails may be smaller or bigger than the requested size.
+    /// Thumbnails that are requested with an exact size ignore the tolerance
+    pub thumbnail_size_tolerance: f32,
+    /// How many bytes of `content://`, `thumb://` and `sheet://` responses are cached.
+    /// The least recently used responses are removed when the size is exceeded
+    pub max_buffer_cache_size: usize,
 }
 
 impl Default for Settings {
@@ -29,6 +42,8 @@ impl Default for Settings {
         Self {
             daemon_path: find_daemon_executable().map(|e| e.to_string_lossy().to_string()),
             repositories: HashMap::new(),
+            thumbnail_size_tolerance: DEFAULT_THUMBNAIL_SIZE_TOLERANCE,
+            max_buffer_cache_size: DEFAULT_MAX_BUFFER_CACHE_SIZE,
         }
     }
 }
@@ -50,22 +65,175 @@ pub fn save_settings(settings: &Settings) -> PluginResult<()> {
     Ok(())
 }
 
-/// Loads the settings from the file
+/// Loads the settings from the file.
+/// A file that can't be parsed is backed up and replaced with the default settings
+/// so that a corrupt file doesn't prevent the plugin from starting
 #[tracing::instrument(level = "debug")]
 pub fn load_settings() -> PluginResult<Settings> {
-    let dirs = ProjectDirs::from("com", "trivernis", "mediarepo")
-        .expect("Failed to get project directories");
-    let config_path = dirs.config_dir().to_path_buf();
-    if !config_path.exists() {
-        fs::create_dir_all(&config_path)?;
+    let settings_path = get_settings_path();
+    if let Some(config_path) = settings_path.parent() {
+        if !config_path.exists() {
+            fs::create_dir_all(config_path)?;
+        }
     }
-    let settings_path = config_path.join(SETTINGS_FILE);
     if !settings_path.exists() {
         let settings = Settings::default();
         save_settings(&settings)?;
     }
-    let config_str = fs::read_to_string(settings_path)?;
-    let settings = toml::from_str(&config_str)?;
+    let config_str = fs::read_to_string(&settings_path)?;
+
+    match toml::from_str(&config_str) {
+        Ok(settings) => Ok(settings),
+        Err(e) => {
+            tracing::error!("failed to parse the settings file: {}", e);
+            let backup_path = backup_settings_file(&settings_path)?;
+            tracing::warn!(
+                "restored the default settings. The corrupt file was moved to {:?}",
+                backup_path
+            );
+            let settings = Settings::default();
+            save_settings(&settings)?;
+
+            Ok(settings)
+        }
+    }
+}
+
+/// Validates each field of the settings file and replaces invalid or missing
+/// fields with their defaults. Invalid repository entries are removed.
+/// Returns the repaired settings and a description of every repair
+#[tracing::instrument(level = "debug")]
+pub fn repair_settings() -> PluginResult<(Settings, Vec<String>)> {
+    let settings_path = get_settings_path();
+    if !settings_path.exists() {
+        let settings = load_settings()?;
+        return Ok((settings, vec![String::from("created a new settings file")]));
+    }
+    let config_str = fs::read_to_string(&settings_path)?;
+    let mut repairs = Vec::new();
+
+    let table = match toml::from_str::<toml::Value>(&config_str) {
+        Ok(toml::Value::Table(table)) => table,
+        Ok(_) => {
+            repairs.push(String::from("the settings file doesn't contain a table"));
+            toml::value::Table::new()
+        }
+        Err(e) => {
+            repairs.push(format!("the settings file couldn't be parsed: {}", e));
+            toml::value::Table::new()
+        }
+    };
+    let settings = Settings {
+        daemon_path: repair_daemon_path(table.get("daemon_path"), &mut repairs),
+        repositories: repair_repositories(table.get("repositories"), &mut repairs),
+        thumbnail_size_tolerance: repair_thumbnail_size_tolerance(
+            table.get("thumbnail_size_tolerance"),
+            &mut repairs,
+        ),
+        max_buffer_cache_size: repair_max_buffer_cache_size(
+            table.get("max_buffer_cache_size"),
+            &mut repairs,
+        ),
+    };
+
+    if !repairs.is_empty() {
+        let backup_path = backup_settings_file(&settings_path)?;
+        tracing::info!(
+            "backed up the settings to {:?} before repairing",
+            backup_path
+        );
+        save_settings(&settings)?;
+    }
+
+    Ok((settings, repairs))
+}
+
+fn repair_daemon_path(value: Option<&toml::Value>, repairs: &mut Vec<String>) -> Option<String> {
+    let find_executable = || find_daemon_executable().map(|e| e.to_string_lossy().to_string());
+
+    match value {
+        Some(toml::Value::String(path)) if PathBuf::from(path).exists() => Some(path.clone()),
+        Some(toml::Value::String(path)) => {
+            repairs.push(format!("the daemon executable {} doesn't exist", path));
+            find_executable()
+        }
+        Some(_) => {
+            repairs.push(String::from("the daemon path isn't a string"));
+            find_executable()
+        }
+        None => find_executable(),
+    }
+}
+
+fn repair_thumbnail_size_tolerance(value: Option<&toml::Value>, repairs: &mut Vec<String>) -> f32 {
+    match value {
+        Some(toml::Value::Float(tolerance)) if (0.0..1.0).contains(tolerance) => *tolerance as f32,
+        Some(toml::Value::Integer(0)) => 0.0,
+        Some(_) => {
+            repairs.push(String::from(
+                "the thumbnail size tolerance isn't a number between 0 and 1",
+            ));
+            DEFAULT_THUMBNAIL_SIZE_TOLERANCE
+        }
+        None => DEFAULT_THUMBNAIL_SIZE_TOLERANCE,
+    }
+}
+
+fn repair_max_buffer_cache_size(value: Option<&toml::Value>, repairs: &mut Vec<String>) -> usize {
+    match value {
+        Some(toml::Value::Integer(size)) if *size >= 0 => *size as usize,
+        Some(_) => {
+            repairs.push(String::from(
+                "the maximum buffer cache size isn't a number of bytes",
+            ));
+            DEFAULT_MAX_BUFFER_CACHE_SIZE
+        }
+        None => DEFAULT_MAX_BUFFER_CACHE_SIZE,
+    }
+}
+
+fn repair_repositories(
+    value: Option<&toml::Value>,
+    repairs: &mut Vec<String>,
+) -> HashMap<String, Repository> {
+    let entries = match value {
+        Some(toml::Value::Table(entries)) => entries,
+        Some(_) => {
+            repairs.push(String::from("the repositories aren't a table"));
+            return HashMap::new();
+        }
+        None => return HashMap::new(),
+    };
+    let mut repositories = HashMap::new();
+
+    for (key, entry) in entries {
+        match entry.clone().try_into::<Repository>() {
+            Ok(repo) if repo.path.is_none() && repo.address.is_none() => {
+                repairs.push(format!(
+                    "removed repository {} because it has neither a path nor an address",
+                    key
+                ));
+            }
+            Ok(repo) => {
+                repositories.insert(key.clone(), repo);
+            }
+            Err(e) => {
+                repairs.push(format!("removed invalid repository {}: {}", key, e));
+            }
+        }
+    }
+
+    repositories
+}
+
+/// Copies the settings file next to the original with a timestamp in its name
+fn backup_settings_file(settings_path: &Path) -> PluginResult<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let backup_path = settings_path.with_file_name(format!("{}.{}.bak", SETTINGS_FILE, timestamp));
+    fs::copy(settings_path, &backup_path)?;
 
-    Ok(settings)
+    Ok(backup_path)
 }
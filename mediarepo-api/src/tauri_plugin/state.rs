@@ -1,6 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::mem;
-use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -13,7 +12,9 @@ use crate::client_api::ApiClient;
 use crate::daemon_management::cli::DaemonCli;
 use crate::daemon_management::find_daemon_executable;
 use crate::tauri_plugin::error::{PluginError, PluginResult};
-use crate::tauri_plugin::settings::{load_settings, save_settings, Repository, Settings};
+use crate::tauri_plugin::settings::{
+    load_settings, save_settings, Repository, Settings, DEFAULT_MAX_BUFFER_CACHE_SIZE,
+};
 
 pub struct ApiState {
     inner: Arc<RwLock<Option<ApiClient>>>,
@@ -62,6 +63,11 @@ pub struct VolatileBuffer {
     pub valid_until: Instant,
     pub mime: String,
     pub buf: Vec<u8>,
+    /// The (width, height) of buffered images
+    pub dimensions: Option<(u32, u32)>,
+    /// The position of cached buffers in the least recently used order.
+    /// Buffers without a position expire instead
+    last_used: Option<u64>,
 }
 
 impl VolatileBuffer {
@@ -70,31 +76,93 @@ impl VolatileBuffer {
             valid_until: Instant::now() + Duration::from_secs(120), // buffers that weren't accessed get deleted after 2 minutes
             mime,
             buf,
+            dimensions: None,
+            last_used: None,
         }
     }
 }
 
-#[derive(Default, Clone)]
+/// Keeps track of the least recently used order and the total size of the cached buffers
+#[derive(Default)]
+struct BufferCache {
+    budget: usize,
+    size: usize,
+    next_use: u64,
+    order: BTreeMap<u64, String>,
+}
+
+impl BufferCache {
+    /// Moves the key to the end of the order and returns its new position
+    fn touch(&mut self, key: &str, last_used: Option<u64>) -> u64 {
+        if let Some(last_used) = last_used {
+            self.order.remove(&last_used);
+        }
+        let position = self.next_use;
+        self.next_use += 1;
+        self.order.insert(position, key.to_owned());
+
+        position
+    }
+}
+
+#[derive(Clone)]
 pub struct BufferState {
     pub buffer: Arc<ParkingRwLock<HashMap<String, Mutex<VolatileBuffer>>>>,
+    cache: Arc<Mutex<BufferCache>>,
+}
+
+impl Default for BufferState {
+    fn default() -> Self {
+        Self::with_budget(DEFAULT_MAX_BUFFER_CACHE_SIZE)
+    }
 }
 
 impl BufferState {
-    /// Adds a cached buffer to the buffer state
+    /// Creates a buffer state that keeps the cached buffers below the budget in bytes
+    pub fn with_budget(budget: usize) -> Self {
+        Self {
+            buffer: Default::default(),
+            cache: Arc::new(Mutex::new(BufferCache {
+                budget,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Adds a cached buffer to the buffer state. The least recently used
+    /// buffers are removed when the buffers would exceed the budget
     pub fn add_entry(&self, key: String, mime: String, bytes: Vec<u8>) {
-        let mut buffers = self.buffer.write();
-        let buffer = VolatileBuffer::new(mime, bytes);
-        buffers.insert(key, Mutex::new(buffer));
+        self.add_cached_buffer(key, VolatileBuffer::new(mime, bytes));
+    }
+
+    /// Adds a cached image buffer together with its (width, height)
+    pub fn add_image_entry(
+        &self,
+        key: String,
+        mime: String,
+        bytes: Vec<u8>,
+        dimensions: (u32, u32),
+    ) {
+        let mut buffer = VolatileBuffer::new(mime, bytes);
+        buffer.dimensions = Some(dimensions);
+        self.add_cached_buffer(key, buffer);
     }
 
-    /// Returns the cloned buffer entry and flags it for expiration
+    /// Returns the cloned buffer entry. Cached buffers become the most recently used ones
+    /// while other buffers are flagged for expiration
     pub fn get_entry(&self, key: &str) -> Option<VolatileBuffer> {
         let buffers = self.buffer.read();
         let entry = buffers.get(key);
 
         if let Some(entry) = entry {
             let mut entry = entry.lock();
-            entry.valid_until = Instant::now() + Duration::from_secs(30); // ttl is 30 seconds after being accessed
+
+            if entry.last_used.is_some() {
+                let position = self.cache.lock().touch(key, entry.last_used);
+                entry.last_used = Some(position);
+            } else {
+                entry.valid_until = Instant::now() + Duration::from_secs(30); // ttl is 30 seconds after being accessed
+            }
 
             Some(entry.clone())
         } else {
@@ -102,66 +170,75 @@ impl BufferState {
         }
     }
 
-    /// Clears all expired entries
+    /// Clears all expired entries. Cached buffers don't expire
     pub fn clear_expired(&self) {
-        let keys: Vec<String> = {
-            let buffer = self.buffer.read();
-            buffer.keys().cloned().collect()
-        };
-
-        for key in keys {
-            let valid_until = {
-                let buffer = self.buffer.read();
-                let entry = buffer.get(&key).unwrap().lock();
-                entry.valid_until.clone()
-            };
-            if valid_until < Instant::now() {
-                let mut buffer = self.buffer.write();
-                buffer.remove(&key);
-            }
-        }
+        let now = Instant::now();
+        let mut buffers = self.buffer.write();
+        buffers.retain(|_, entry| {
+            let entry = entry.get_mut();
+            entry.last_used.is_some() || entry.valid_until >= now
+        });
     }
 
     /// Clears the buffer completely
     pub fn clear(&self) {
         let mut buffer = self.buffer.write();
+        let mut cache = self.cache.lock();
         buffer.clear();
+        cache.order.clear();
+        cache.size = 0;
     }
 
-    /// Trims the buffer to the given target size
-    pub fn trim_to_size(&self, target_size: usize) {
-        let mut size = self.get_size();
-        if size < target_size {
+    fn add_cached_buffer(&self, key: String, mut buffer: VolatileBuffer) {
+        let mut buffers = self.buffer.write();
+        let mut cache = self.cache.lock();
+        remove_buffer(&mut buffers, &mut cache, &key);
+        let size = buffer.buf.len();
+
+        if size > cache.budget {
+            tracing::debug!("not caching {} because it is bigger than the budget", key);
             return;
         }
+        let target_size = cache.budget - size;
+        evict_buffers(&mut buffers, &mut cache, target_size);
+        buffer.last_used = Some(cache.touch(&key, None));
+        cache.size += size;
+        buffers.insert(key, Mutex::new(buffer));
+    }
+}
 
-        let mut keys: Vec<String> = {
-            let buffer = self.buffer.read();
-            buffer.keys().cloned().collect()
-        };
-        keys.reverse();
-
-        while size > target_size && keys.len() > 0 {
-            let key = keys.pop().unwrap();
-            let mut buffers = self.buffer.write();
-
-            if let Some(entry) = buffers.remove(&key) {
-                size -= entry.lock().buf.len();
-            }
+/// Removes the buffer and its place in the cache
+fn remove_buffer(
+    buffers: &mut HashMap<String, Mutex<VolatileBuffer>>,
+    cache: &mut BufferCache,
+    key: &str,
+) {
+    if let Some(buffer) = buffers.remove(key) {
+        let buffer = buffer.into_inner();
+
+        if let Some(last_used) = buffer.last_used {
+            cache.order.remove(&last_used);
+            cache.size -= buffer.buf.len();
         }
     }
+}
 
-    /// Calculates the size of the whole buffer
-    pub fn get_size(&self) -> usize {
-        let buffer = self.buffer.read();
-        let mut size = 0;
-
-        for value in buffer.deref().values() {
-            let value = value.lock();
-            size += value.buf.len();
+/// Removes the least recently used buffers until the cached buffers fit into the target size
+fn evict_buffers(
+    buffers: &mut HashMap<String, Mutex<VolatileBuffer>>,
+    cache: &mut BufferCache,
+    target_size: usize,
+) {
+    while cache.size > target_size {
+        let oldest = match cache.order.keys().next() {
+            Some(position) => *position,
+            None => break,
+        };
+        if let Some(key) = cache.order.remove(&oldest) {
+            if let Some(buffer) = buffers.remove(&key) {
+                cache.size -= buffer.into_inner().buf.len();
+            }
         }
-
-        size
     }
 }
 
@@ -185,6 +262,11 @@ impl AppState {
         Ok(state)
     }
 
+    /// Returns the fraction by which thumbnails may differ from the requested size
+    pub async fn thumbnail_size_tolerance(&self) -> f32 {
+        self.settings.read().await.thumbnail_size_tolerance
+    }
+
     /// Returns the daemon cli client
     pub async fn get_daemon_cli(&self, repo_path: String) -> PluginResult<DaemonCli> {
         let mut settings = self.settings.write().await;
@@ -10,3 +10,21 @@ pub fn system_time_to_naive_date_time(system_time: SystemTime) -> NaiveDateTime
         epoch_duration.subsec_nanos(),
     )
 }
+
+/// Returns the (min, max) bounds of (height, width) a thumbnail for the requested size
+/// may have when it may differ from the size by the tolerance.
+/// A tolerance of 0 only allows thumbnails that fit the size exactly
+pub fn thumbnail_size_range(height: u32, width: u32, tolerance: f32) -> ((u32, u32), (u32, u32)) {
+    let scale = |size: u32, factor: f32| (size as f32 * factor).round() as u32;
+
+    (
+        (
+            scale(height, 1.0 - tolerance),
+            scale(width, 1.0 - tolerance),
+        ),
+        (
+            scale(height, 1.0 + tolerance),
+            scale(width, 1.0 + tolerance),
+        ),
+    )
+}
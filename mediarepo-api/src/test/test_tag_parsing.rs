@@ -0,0 +1,50 @@
+use crate::types::tags::parse_tag_list;
+
+#[test]
+fn it_splits_tags_on_commas() {
+    let tags = parse_tag_list("cat, dog,character:foo", false);
+
+    assert_eq!(tags, vec!["cat", "dog", "character:foo"]);
+}
+
+#[test]
+fn it_trims_whitespace_around_namespaces() {
+    let tags = parse_tag_list("  character :  foo bar  ,\tsky ", false);
+
+    assert_eq!(tags, vec!["character:foo bar", "sky"]);
+}
+
+#[test]
+fn it_keeps_separators_in_quoted_tags() {
+    let tags = parse_tag_list(r#""hello, world", series:"one, two", cat"#, false);
+
+    assert_eq!(tags, vec!["hello, world", "series:one, two", "cat"]);
+}
+
+#[test]
+fn it_splits_on_newlines_only_if_requested() {
+    let input = "cat\r\ndog, bird\nfish";
+
+    assert_eq!(
+        parse_tag_list(input, true),
+        vec!["cat", "dog", "bird", "fish"]
+    );
+    assert_eq!(
+        parse_tag_list(input, false),
+        vec!["cat\r\ndog", "bird\nfish"]
+    );
+}
+
+#[test]
+fn it_skips_empty_and_duplicate_entries() {
+    let tags = parse_tag_list(",, cat,,  , cat , character:, :dog, \"\"", false);
+
+    assert_eq!(tags, vec!["cat", "dog"]);
+}
+
+#[test]
+fn it_treats_unterminated_quotes_as_quoted_until_the_end() {
+    let tags = parse_tag_list("cat, \"dog, bird", false);
+
+    assert_eq!(tags, vec!["cat", "dog, bird"]);
+}
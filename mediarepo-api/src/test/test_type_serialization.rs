@@ -1,8 +1,13 @@
-use crate::types::files::GetFileThumbnailOfSizeRequest;
+use crate::types::files::{
+    FileMetadataResponse, GetFileThumbnailOfSizeRequest, InlineThumbnailResponse, ThumbnailCrop,
+    ThumbnailMetadataResponse,
+};
 use crate::types::filtering::{
-    FilterExpression, FilterQuery, SortDirection, SortKey, TagQuery, ValueComparator,
+    FilterExpression, FilterQuery, Orientation, PropertyQuery, SortDirection, SortKey, TagQuery,
+    ValueComparator,
 };
 use crate::types::identifier::FileIdentifier;
+use crate::types::misc::InfoResponse;
 use bromine::payload::DynamicSerializer;
 use bromine::prelude::IPCResult;
 use chrono::NaiveDateTime;
@@ -20,10 +25,28 @@ fn it_serializes_get_file_thumbnail_of_size_requests() {
         id: FileIdentifier::ID(0),
         max_size: (u32::MAX, u32::MAX),
         min_size: (0, 0),
+        crop: Some(ThumbnailCrop::CenterCrop),
+        position: Some(0.5),
     })
     .unwrap();
 }
 
+#[test]
+fn it_serializes_inline_thumbnails() {
+    let thumbnail = test_serialization(InlineThumbnailResponse {
+        metadata: ThumbnailMetadataResponse {
+            file_hash: String::from("hash"),
+            height: 16,
+            width: 16,
+            crop: ThumbnailCrop::Fit,
+            mime_type: String::from("image/png"),
+        },
+        data: Some(vec![0, 1, 2, 255]),
+    })
+    .unwrap();
+    assert_eq!(thumbnail.data, Some(vec![0, 1, 2, 255]));
+}
+
 #[test]
 fn it_serializes_tag_queries() {
     test_serialization(TagQuery {
@@ -42,6 +65,76 @@ fn it_serializes_filter_expressions() {
     .unwrap();
 }
 
+#[test]
+fn it_serializes_negated_namespace_wildcards() {
+    let query = test_serialization(TagQuery::namespace("meta", true)).unwrap();
+
+    assert!(query.negate);
+    assert_eq!(query.tag, "meta:*");
+    assert_eq!(query.wildcard_namespace(), Some("meta"));
+}
+
+#[test]
+fn it_detects_wildcards_in_any_position() {
+    let query = test_serialization(TagQuery::new("*:smile", false)).unwrap();
+
+    assert!(query.is_wildcard());
+    assert_eq!(query.wildcard_namespace(), None);
+    assert!(TagQuery::new("*girl", true).is_wildcard());
+    assert_eq!(TagQuery::new("*:*", false).wildcard_namespace(), None);
+}
+
+#[test]
+fn it_serializes_or_expressions_with_negated_wildcards() {
+    let expression = test_serialization(FilterExpression::OrExpression(vec![
+        FilterQuery::Tag(TagQuery::namespace("meta", true)),
+        FilterQuery::Tag(TagQuery::new("creator:jo*", false)),
+    ]))
+    .unwrap();
+
+    if let FilterExpression::OrExpression(queries) = expression {
+        let tags: Vec<(String, bool)> = queries
+            .into_iter()
+            .filter_map(|q| match q {
+                FilterQuery::Tag(tag) => Some((tag.tag, tag.negate)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                (String::from("meta:*"), true),
+                (String::from("creator:jo*"), false)
+            ]
+        );
+    } else {
+        panic!("expected an or expression");
+    }
+}
+
+#[test]
+fn it_serializes_dimension_property_queries() {
+    let expression = FilterExpression::OrExpression(vec![
+        FilterQuery::Property(PropertyQuery::Orientation(Orientation::Landscape)),
+        FilterQuery::Property(PropertyQuery::aspect_ratio_between(1.5, 2.0)),
+    ]);
+
+    if let FilterExpression::OrExpression(queries) = test_serialization(expression).unwrap() {
+        assert!(matches!(
+            queries[0],
+            FilterQuery::Property(PropertyQuery::Orientation(Orientation::Landscape))
+        ));
+        assert!(matches!(
+            queries[1],
+            FilterQuery::Property(PropertyQuery::AspectRatio(ValueComparator::Between((
+                min, max
+            )))) if min == 1.5 && max == 2.0
+        ));
+    } else {
+        panic!("expected an or expression");
+    }
+}
+
 #[test]
 fn it_serializes_sort_keys() {
     test_serialization(SortKey::FileName(SortDirection::Descending)).unwrap();
@@ -56,10 +149,43 @@ fn it_serializes_value_comparators() {
     .unwrap();
 }
 
-fn test_serialization<T: Serialize + DeserializeOwned>(data: T) -> IPCResult<()> {
+#[test]
+fn it_defaults_to_thumbnail_generation_for_older_daemons() {
+    let info: InfoResponse = serde_json::from_str(
+        r#"{"name": "mediarepo", "version": "1.0.0", "api_version": [1, 0, 0]}"#,
+    )
+    .unwrap();
+
+    assert!(info.auto_generate_thumbnails);
+}
+
+#[test]
+fn it_deserializes_file_metadata_without_a_location() {
+    let metadata: FileMetadataResponse = serde_json::from_str(
+        r#"{
+            "file_id": 1,
+            "name": null,
+            "comment": null,
+            "creation_time": "2022-03-12T10:00:00",
+            "change_time": "2022-03-12T10:00:00",
+            "import_time": "2022-03-12T10:00:00",
+            "size": 4,
+            "view_count": 0,
+            "access_time": null,
+            "width": null,
+            "height": null
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(metadata.latitude, None);
+    assert_eq!(metadata.longitude, None);
+}
+
+fn test_serialization<T: Serialize + DeserializeOwned>(data: T) -> IPCResult<T> {
     let serializer = DynamicSerializer::first_available();
     let bytes = serializer.serialize(data)?;
-    let _: T = serializer.deserialize(&bytes[..])?;
+    let data: T = serializer.deserialize(&bytes[..])?;
 
-    Ok(())
+    Ok(data)
 }
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::identifier::FileIdentifier;
+
+/// The type the values of a custom attribute field have to be of.
+/// Values are always transferred as text. Numbers are decimal numbers,
+/// booleans are `true` or `false` and dates have the format `YYYY-MM-DD`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AttributeValueType {
+    Text,
+    Number,
+    Boolean,
+    Date,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttributeFieldResponse {
+    pub id: i64,
+    pub name: String,
+    pub value_type: AttributeValueType,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AddAttributeFieldRequest {
+    pub name: String,
+    pub value_type: AttributeValueType,
+}
+
+/// Renames a field or changes its type. Omitted properties stay unchanged
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateAttributeFieldRequest {
+    pub id: i64,
+    pub name: Option<String>,
+    pub value_type: Option<AttributeValueType>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileAttributeResponse {
+    pub file_id: i64,
+    pub field: AttributeFieldResponse,
+    /// The value in the normalized text form of the fields type
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetFileAttributeRequest {
+    pub file_id: FileIdentifier,
+    pub field_id: i64,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RemoveFileAttributeRequest {
+    pub file_id: FileIdentifier,
+    pub field_id: i64,
+}
@@ -8,6 +8,14 @@ pub struct ReadFileRequest {
     pub id: FileIdentifier,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReadFileRangeRequest {
+    pub id: FileIdentifier,
+    pub offset: u64,
+    /// The maximum number of bytes to read. Less bytes are returned if the file ends before
+    pub length: u64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GetFileThumbnailsRequest {
     pub id: FileIdentifier,
@@ -18,6 +26,59 @@ pub struct GetFileThumbnailOfSizeRequest {
     pub id: FileIdentifier,
     pub min_size: (u32, u32),
     pub max_size: (u32, u32),
+    /// Uses the crop configured in the daemon's settings if not set
+    #[serde(default)]
+    pub crop: Option<ThumbnailCrop>,
+    /// Returns the timeline thumbnail closest to the position given as a fraction
+    /// of the duration for videos. Ignored for other files
+    #[serde(default)]
+    pub position: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateTimelineThumbnailsRequest {
+    pub id: FileIdentifier,
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetContactSheetRequest {
+    pub id: FileIdentifier,
+    pub rows: u32,
+    pub cols: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetThumbnailsForCdsRequest {
+    pub cd_ids: Vec<i64>,
+    pub min_size: (u32, u32),
+    pub max_size: (u32, u32),
+    /// Uses the crop configured in the daemon's settings if not set
+    #[serde(default)]
+    pub crop: Option<ThumbnailCrop>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetInlineThumbnailsForCdsRequest {
+    pub cd_ids: Vec<i64>,
+    pub min_size: (u32, u32),
+    pub max_size: (u32, u32),
+    /// Uses the crop configured in the daemon's settings if not set
+    #[serde(default)]
+    pub crop: Option<ThumbnailCrop>,
+    /// Thumbnails with more bytes are returned without their contents.
+    /// The daemon applies its own upper limit to this value
+    pub max_inline_size: u64,
+}
+
+/// How a thumbnail is fitted into the requested size
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThumbnailCrop {
+    /// Keeps the aspect ratio of the file
+    #[default]
+    Fit,
+    /// Crops the center of the file so that the thumbnail fills the whole size
+    CenterCrop,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -25,6 +86,27 @@ pub struct GetFileTagsRequest {
     pub id: FileIdentifier,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetFileTagsPageRequest {
+    pub id: FileIdentifier,
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetFilesPageRequest {
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FilesPageResponse {
+    /// The files of the page ordered by the default sort keys of the daemon
+    pub files: Vec<FileBasicDataResponse>,
+    /// The number of all files
+    pub total: u64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GetFilesTagsRequest {
     pub cds: Vec<String>,
@@ -41,6 +123,10 @@ pub struct FileBasicDataResponse {
     pub status: FileStatus,
     pub cd: String,
     pub mime_type: String,
+    pub corrupt: bool,
+    pub external_id: Option<String>,
+    /// Files that aren't indexed are excluded from searches
+    pub indexed: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -59,6 +145,35 @@ pub struct FileMetadataResponse {
     pub change_time: NaiveDateTime,
     pub import_time: NaiveDateTime,
     pub size: u64,
+    #[serde(default)]
+    pub view_count: u64,
+    pub access_time: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+}
+
+/// A metadata field that can be missing on incompletely imported files
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FileMetadataField {
+    Name,
+    /// Only images store dimensions
+    Dimensions,
+    Size,
+    MimeType,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileStorageInfoResponse {
+    pub storage: String,
+    pub hash_algorithm: String,
+    pub cd: String,
+    pub size: u64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -73,25 +188,321 @@ pub struct FileOSMetadata {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ThumbnailMetadataResponse {
     pub file_hash: String,
+    /// The exact height of the stored thumbnail in pixels
     pub height: u32,
+    /// The exact width of the stored thumbnail in pixels
     pub width: u32,
+    #[serde(default)]
+    pub crop: ThumbnailCrop,
     pub mime_type: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InlineThumbnailResponse {
+    pub metadata: ThumbnailMetadataResponse,
+    /// The contents of the thumbnail if it is small enough to be inlined.
+    /// Otherwise it has to be fetched separately
+    pub data: Option<Vec<u8>>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UpdateFileNameRequest {
     pub file_id: FileIdentifier,
     pub name: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateFileCommentRequest {
+    pub file_id: FileIdentifier,
+    /// The new comment. The comment is removed if none is given
+    pub comment: Option<String>,
+}
+
+/// Changes the name, comment and status of a file at once.
+/// Fields that aren't given stay unchanged
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateFileMetadataRequest {
+    pub file_id: FileIdentifier,
+    pub name: Option<String>,
+    pub comment: Option<String>,
+    pub status: Option<FileStatus>,
+    /// Allows deleted files to get another status
+    #[serde(default)]
+    pub undelete: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UpdateFileStatusRequest {
     pub file_id: FileIdentifier,
     pub status: FileStatus,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetFileIndexedRequest {
+    pub file_id: FileIdentifier,
+    pub indexed: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FindFilesNearRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_km: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FindSimilarFilesRequest {
+    pub id: FileIdentifier,
+    /// The maximum number of bits in which the perceptual hashes may differ
+    pub max_distance: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimilarFileResponse {
+    pub file: FileBasicDataResponse,
+    /// The number of bits in which the perceptual hashes differ
+    pub distance: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateFileMimeTypeRequest {
+    pub file_id: FileIdentifier,
+    pub mime_type: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AddFileRequestHeader {
     pub metadata: FileOSMetadata,
     pub tags: Vec<String>,
+    pub external_id: Option<String>,
+}
+
+/// Imports files from paths on the machine of the daemon.
+/// The progress is sent as [ImportFilesEvent]s while the files are imported
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportFilesRequest {
+    /// Identifies the import so that it can be cancelled while it's running
+    pub import_id: String,
+    pub files: Vec<ImportFileEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportFileEntry {
+    pub metadata: FileOSMetadata,
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ImportFilesEvent {
+    /// Sent after each processed file
+    Progress(ImportProgressResponse),
+    /// Sent once after the last file was processed or the import was cancelled
+    Finished(ImportFilesResponse),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportProgressResponse {
+    pub processed: u64,
+    pub total: u64,
+    /// The path of the file that was processed last
+    pub current_file: String,
+    /// The number of files that failed to import so far
+    pub failed: u64,
+    /// Why the file that was processed last failed to import
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportFilesResponse {
+    pub files: Vec<FileBasicDataResponse>,
+    pub failures: Vec<ImportFailure>,
+    /// If the import was cancelled before all files were processed
+    pub cancelled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Imports the files of a folder and its subfolders on the machine of the daemon.
+/// The progress is sent as [ImportDirectoryEvent]s while the folder is walked
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportDirectoryRequest {
+    /// Identifies the import so that it can be cancelled while it's running
+    pub import_id: String,
+    pub path: String,
+    pub options: ImportDirectoryOptions,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ImportDirectoryOptions {
+    /// A glob pattern that the path relative to the folder has to match
+    pub pattern: Option<String>,
+    /// The extensions of the imported files. All files are imported if empty
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Adds the folders between the imported folder and a file to it as tags
+    pub path_tags: Option<PathTags>,
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    #[serde(default)]
+    pub include_hidden: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PathTags {
+    /// Every folder becomes a tag without a namespace
+    Tags,
+    /// Every folder becomes a tag of the namespace
+    Namespace(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ImportDirectoryEvent {
+    /// Sent after each processed path
+    Progress(DirectoryImportProgressResponse),
+    /// Sent once after the folder was walked or the import was cancelled
+    Finished(ImportDirectoryResponse),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DirectoryImportProgressResponse {
+    /// The path that was processed last
+    pub path: String,
+    pub imported: u64,
+    /// The number of files with existing contents, symlinks that weren't followed
+    /// and folders that were already visited
+    pub skipped: u64,
+    pub failed: u64,
+    /// Why the path that was processed last failed to import
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportDirectoryResponse {
+    pub imported: u64,
+    pub skipped: u64,
+    pub failures: Vec<ImportFailure>,
+    /// If the import was cancelled before all files were processed
+    pub cancelled: bool,
+}
+
+/// Writes files into a folder on the machine of the daemon.
+/// The progress is sent as [ExportFilesEvent]s while the files are written
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportFilesRequest {
+    pub ids: Vec<i64>,
+    pub target_dir: String,
+    /// Writes a `<file>.txt` next to every file that lists its tags one per line
+    #[serde(default)]
+    pub write_tag_sidecars: bool,
+    /// Puts the files into subfolders named after their tag of the namespace
+    pub group_by_namespace: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ExportFilesEvent {
+    /// Sent after each written file
+    Progress(ExportProgressResponse),
+    /// Sent once with the paths of all written files
+    Finished(Vec<String>),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportProgressResponse {
+    pub exported: u64,
+    pub total: u64,
+    /// The path of the file that was written last
+    pub path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteFileRequest {
+    pub id: FileIdentifier,
+    /// Only returns what would be removed without deleting the file
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// What was removed when deleting a file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileDeletionResponse {
+    pub file_id: i64,
+    /// If the content with its tags, thumbnails and blob was removed.
+    /// Contents that are shared with other files are kept
+    pub content_removed: bool,
+    pub tag_mappings: u64,
+    pub thumbnails: u64,
+    /// The size of the removed blob in bytes
+    pub freed_bytes: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PrepareDeleteFilesRequest {
+    pub ids: Vec<i64>,
+}
+
+/// Changes the status of the files. Files that can't get the status are left unchanged
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChangeFilesStatusRequest {
+    pub ids: Vec<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteFilesPreviewResponse {
+    /// The token that confirms the deletion with a [DeleteFilesRequest].
+    /// It can only be used once and expires after a minute
+    pub token: String,
+    pub file_count: u64,
+    /// The combined size of all files in bytes
+    pub total_size: u64,
+    /// Some of the files that will be deleted
+    pub sample: Vec<FileBasicDataResponse>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteFilesRequest {
+    pub token: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProbeFileRequest {
+    /// The path of the file on the machine the daemon runs on
+    pub path: String,
+}
+
+/// The metadata a file would be imported with
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProbedFileResponse {
+    pub name: Option<String>,
+    pub mime_type: String,
+    pub size: u64,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Pairs of exif tag names and their values
+    pub exif: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BulkRenameFilesRequest {
+    pub ids: Vec<i64>,
+    /// The pattern of the new names. The placeholders `{id}`, `{index}` and `{<namespace>}`
+    /// are replaced with the file's id, its position in the list starting at 1
+    /// and its tags of the namespace
+    pub pattern: String,
+    /// Returns the new names without renaming the files
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RenamedFileResponse {
+    pub id: i64,
+    pub name: String,
 }
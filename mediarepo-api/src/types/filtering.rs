@@ -1,11 +1,84 @@
-use crate::types::files::FileStatus;
+use crate::types::files::{FileBasicDataResponse, FileStatus};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FindFilesRequest {
     pub filters: Vec<FilterExpression>,
+    /// The files are sorted by the default sort keys of the daemon if this is empty
     pub sort_expression: Vec<SortKey>,
+    pub max_results: Option<u64>,
+    /// Responds with a [FindFileIdsResponse] that only contains the ids of the files
+    pub ids_only: bool,
+    /// Skips the given number of matching files. Setting the offset or limit
+    /// paginates the results in the database instead of returning all of them
+    #[serde(default)]
+    pub offset: Option<u64>,
+    /// The maximum number of files on the requested page
+    #[serde(default)]
+    pub limit: Option<u64>,
+    /// Also returns files that are excluded from searches
+    #[serde(default)]
+    pub include_unindexed: bool,
+    /// Only returns files with one of the statuses. Archived files are left out
+    /// if no statuses are given and the filters don't query the status
+    #[serde(default)]
+    pub include_statuses: Option<Vec<FileStatus>>,
+}
+
+/// Searches for files like [FindFilesRequest] but sends the results in pages
+/// so that the first files arrive before all of them have been loaded
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StreamFilesRequest {
+    /// Identifies the search so that it can be stopped while it's running
+    pub search_id: String,
+    pub filters: Vec<FilterExpression>,
+    /// The files are sorted by the default sort keys of the daemon if this is empty
+    pub sort_expression: Vec<SortKey>,
+    /// The number of files sent per page
+    pub page_size: u64,
+    pub max_results: Option<u64>,
+    /// Also returns files that are excluded from searches
+    #[serde(default)]
+    pub include_unindexed: bool,
+    /// Only returns files with one of the statuses. Archived files are left out
+    /// if no statuses are given and the filters don't query the status
+    #[serde(default)]
+    pub include_statuses: Option<Vec<FileStatus>>,
+}
+
+/// Searches the names and comments of files for a text.
+/// Files excluded from searches are never returned
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchFilesByTextRequest {
+    pub query: String,
+    /// The files are sorted by the default sort keys of the daemon if this is empty
+    #[serde(default)]
+    pub sort_expression: Vec<SortKey>,
+    #[serde(default)]
+    pub offset: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FindFilesResponse {
+    pub files: Vec<FileBasicDataResponse>,
+    /// If more files matched the filters than the requested maximum
+    pub truncated: bool,
+    /// The number of all matching files if a page was requested
+    #[serde(default)]
+    pub total: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FindFileIdsResponse {
+    pub ids: Vec<i64>,
+    /// If more files matched the filters than the requested maximum
+    pub truncated: bool,
+    /// The number of all matching files if a page was requested
+    #[serde(default)]
+    pub total: Option<u64>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -20,12 +93,42 @@ pub enum FilterQuery {
     Property(PropertyQuery),
 }
 
+/// A query for a tag by name. A `*` in the name or namespace matches any
+/// characters, so `meta:*` matches all tags in the `meta` namespace and
+/// `*:smile` matches `smile` in any namespace.
+/// Negated queries match files that have none of the matching tags.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TagQuery {
     pub negate: bool,
     pub tag: String,
 }
 
+impl TagQuery {
+    pub fn new<S: ToString>(tag: S, negate: bool) -> Self {
+        Self {
+            negate,
+            tag: tag.to_string(),
+        }
+    }
+
+    /// Creates a query matching all tags of a namespace
+    pub fn namespace<S: AsRef<str>>(namespace: S, negate: bool) -> Self {
+        Self::new(format!("{}:*", namespace.as_ref()), negate)
+    }
+
+    /// Returns if the query contains a wildcard that can match multiple tags
+    pub fn is_wildcard(&self) -> bool {
+        self.tag.contains('*')
+    }
+
+    /// Returns the namespace if the query matches all tags of a namespace
+    pub fn wildcard_namespace(&self) -> Option<&str> {
+        self.tag
+            .strip_suffix(":*")
+            .filter(|namespace| !namespace.is_empty() && !namespace.contains('*'))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PropertyQuery {
     Status(FileStatus),
@@ -36,6 +139,27 @@ pub enum PropertyQuery {
     TagCount(ValueComparator<u64>),
     Cd(String),
     Id(i64),
+    Width(ValueComparator<u32>),
+    Height(ValueComparator<u32>),
+    /// The width of an image divided by its height
+    AspectRatio(ValueComparator<f64>),
+    Orientation(Orientation),
+    /// Matches files whose name or comment contains the text ignoring the casing
+    Text(String),
+}
+
+impl PropertyQuery {
+    /// Creates a query for images with an aspect ratio between min and max (inclusive)
+    pub fn aspect_ratio_between(min: f64, max: f64) -> Self {
+        Self::AspectRatio(ValueComparator::Between((min, max)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+    Square,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -13,4 +13,59 @@ pub enum JobType {
     GenerateThumbnails,
     CheckIntegrity,
     Vacuum,
+    /// Resumes the last storage migration that didn't finish.
+    /// New migrations are started with a [MigrateStorageRequest]
+    MigrateStorage,
+    /// Calculates the perceptual hashes of images that don't have one
+    GeneratePhashes,
+    /// Detects the mime types of files that were stored without a known type
+    ResniffMimeTypes,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MigrateStorageRequest {
+    pub algorithm: HashAlgorithm,
+}
+
+/// The hashing algorithms that can be used for content descriptors
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha2_256,
+    Sha2_512,
+    Sha3_256,
+    Sha3_512,
+    Blake2b256,
+    Blake2b512,
+    Blake3,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobProgressResponse {
+    pub current: u64,
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobState {
+    Queued,
+    Scheduled,
+    Running,
+    Paused,
+    Finished,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobInfoResponse {
+    pub job_type: JobType,
+    pub state: JobState,
+    /// None if the job doesn't report its progress
+    pub progress: Option<JobProgressResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DatabaseBackupResponse {
+    /// The path of the backup file on the daemons machine
+    pub path: String,
+    /// The size of the backup in bytes
+    pub size: u64,
 }
@@ -5,15 +5,43 @@ pub struct InfoResponse {
     pub name: String,
     pub version: String,
     pub(crate) api_version: (u32, u32, u32),
+    /// If disabled, imported files might not have thumbnails yet
+    #[serde(default = "default_auto_generate_thumbnails")]
+    pub auto_generate_thumbnails: bool,
+}
+
+/// Daemons that don't report the setting always create thumbnails on import
+fn default_auto_generate_thumbnails() -> bool {
+    true
+}
+
+/// The features supported by the daemon.
+/// Features that are unknown to older daemons default to being unsupported
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapabilitiesResponse {
+    /// If thumbnails can be created for videos. This requires ffmpeg to be installed
+    pub video_thumbnails: bool,
+    /// If avif images can be decoded to create thumbnails
+    pub avif_decoding: bool,
+    /// If thumbnails are created when files get imported
+    pub auto_generate_thumbnails: bool,
+    /// If images are checked for corruption when they get imported
+    pub image_validation: bool,
+    pub case_sensitive_tags: bool,
+    /// The maximum size of the data of a single request or response in bytes.
+    /// Older daemons don't report a limit
+    pub max_message_size: Option<u64>,
 }
 
 impl InfoResponse {
     /// Creates a new info response
-    pub fn new(name: String, version: String) -> Self {
+    pub fn new(name: String, version: String, auto_generate_thumbnails: bool) -> Self {
         Self {
             name,
             version,
             api_version: get_api_version(),
+            auto_generate_thumbnails,
         }
     }
 
@@ -1,3 +1,4 @@
+pub mod attributes;
 pub mod files;
 pub mod filtering;
 pub mod identifier;
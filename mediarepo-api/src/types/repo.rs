@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::types::files::FileBasicDataResponse;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FrontendState {
     pub state: Option<String>,
@@ -15,12 +17,77 @@ pub struct RepositoryMetadata {
     pub hash_count: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DedupStatsResponse {
+    pub file_count: u64,
+    /// The number of unique contents stored for all files
+    pub blob_count: u64,
+    /// The number of bytes that would be stored additionally without deduplication
+    pub bytes_saved: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SizeMetadata {
     pub size_type: SizeType,
     pub size: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StorageResponse {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OrphanedBlobResponse {
+    pub path: String,
+    /// The encoded content descriptor of the blob. None for leftover staging files
+    pub cd: Option<String>,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CleanupOrphanedBlobsRequest {
+    /// Only returns the blobs that would be deleted
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CheckIntegrityRequest {
+    /// Only checks the contents of files with any of the tags. All contents are checked if empty
+    pub tag_ids: Vec<i64>,
+}
+
+/// The events of an integrity check. The last event is always [CheckIntegrityEvent::Finished]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum CheckIntegrityEvent {
+    Progress(IntegrityProgressResponse),
+    Finished(IntegrityReportResponse),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IntegrityProgressResponse {
+    pub checked: u64,
+    pub total: u64,
+    pub corrupt: u64,
+    pub missing: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IntegrityReportResponse {
+    pub checked: u64,
+    /// Contents whose stored file doesn't match the content descriptor
+    pub corrupt: Vec<DamagedContentResponse>,
+    /// Contents without a stored file
+    pub missing: Vec<DamagedContentResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DamagedContentResponse {
+    pub cd: String,
+    pub files: Vec<FileBasicDataResponse>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Eq, Hash)]
 pub enum SizeType {
     Total,
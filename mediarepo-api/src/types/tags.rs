@@ -1,5 +1,9 @@
+use crate::types::filtering::FilterExpression;
 use crate::types::identifier::FileIdentifier;
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::mem;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TagResponse {
@@ -14,9 +18,174 @@ pub struct NamespaceResponse {
     pub name: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MergeNamespacesRequest {
+    pub from_id: i64,
+    pub into_id: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MergeTagsRequest {
+    pub from_ids: Vec<i64>,
+    pub into_id: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConvertTagToNamespacedRequest {
+    pub tag_id: i64,
+    pub namespace: String,
+}
+
+/// An alternative name of a tag that resolves to the tag when tagging or searching
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagAliasResponse {
+    pub id: i64,
+    /// The alias in the form `namespace:name`
+    pub name: String,
+    pub tag_id: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AddTagAliasRequest {
+    /// The alias in the form `namespace:name`
+    pub alias: String,
+    pub tag_id: i64,
+}
+
+/// An implication assigning the implied tag whenever the tag is assigned
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagImplicationRequest {
+    pub tag_id: i64,
+    pub implied_tag_id: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NamespaceMergeResponse {
+    /// The ids of tags that were moved into the target namespace
+    pub moved_tags: Vec<i64>,
+    /// Maps the ids of deleted tags to the ids of the tags they were merged into
+    pub merged_tags: HashMap<i64, i64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChangeFileTagsRequest {
     pub file_id: FileIdentifier,
     pub removed_tags: Vec<i64>,
     pub added_tags: Vec<i64>,
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagFacetsRequest {
+    pub filters: Vec<FilterExpression>,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AggregateTagsRequest {
+    pub file_ids: Vec<i64>,
+    /// Only returns tags that are assigned to all files
+    pub intersection: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagFilesRequest {
+    pub file_ids: Vec<i64>,
+    pub tag_ids: Vec<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MissingTagsRequest {
+    pub file_id: i64,
+    /// The files whose tags are suggested for the file
+    pub reference_file_ids: Vec<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AutocompleteTagsRequest {
+    /// The start of the namespace or name. `namespace:partial` only matches
+    /// tags of the namespace whose name starts with the partial name
+    pub prefix: String,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetTagCountsRequest {
+    /// The tags to count including the ones that aren't assigned to any file.
+    /// All tags are counted if no tags are given
+    pub tag_ids: Option<Vec<i64>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagAggregateResponse {
+    /// The number of files the tags were aggregated for
+    pub file_count: u64,
+    /// The tags with the number of files they are assigned to
+    pub tags: Vec<(TagResponse, u64)>,
+}
+
+/// A combination of tags that selects as few files as possible
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdentifyingTagsResponse {
+    pub tags: Vec<TagResponse>,
+    /// The number of files that have all of the tags
+    pub file_count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileTagsPageResponse {
+    /// The tags of the page ordered by namespace and name
+    pub tags: Vec<TagResponse>,
+    /// The number of all tags of the file
+    pub total: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagChangeResponse {
+    pub tag: TagResponse,
+    pub added: bool,
+    pub change_time: NaiveDateTime,
+}
+
+/// Splits pasted text like `cat, dog, character:foo` into individual tags.
+/// Entries are separated by commas and optionally by newlines.
+/// Separators inside double quotes are part of the tag (e.g. `series:"one, two"`)
+/// and the quotes themselves are removed.
+/// Whitespace around entries and around the namespace separator is trimmed,
+/// empty entries and duplicates are skipped.
+pub fn parse_tag_list(input: &str, split_on_newlines: bool) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut entry = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => push_tag_entry(&mut tags, mem::take(&mut entry)),
+            '\n' | '\r' if !quoted && split_on_newlines => {
+                push_tag_entry(&mut tags, mem::take(&mut entry))
+            }
+            c => entry.push(c),
+        }
+    }
+    push_tag_entry(&mut tags, entry);
+
+    tags
+}
+
+fn push_tag_entry(tags: &mut Vec<String>, entry: String) {
+    let tag = match entry.split_once(':') {
+        Some((namespace, name)) if !namespace.trim().is_empty() => {
+            let name = name.trim();
+            if name.is_empty() {
+                return;
+            }
+            format!("{}:{}", namespace.trim(), name)
+        }
+        Some((_, name)) => name.trim().to_owned(),
+        None => entry.trim().to_owned(),
+    };
+
+    if !tag.is_empty() && !tags.contains(&tag) {
+        tags.push(tag);
+    }
+}
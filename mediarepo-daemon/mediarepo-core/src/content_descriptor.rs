@@ -1,10 +1,120 @@
-use multihash::{Code, MultihashDigest};
+use multihash::{
+    Blake2b256, Blake2b512, Blake3_256, Code, Hasher, Multihash, MultihashDigest, Sha2_256,
+    Sha2_512, Sha3_256, Sha3_512,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::error::RepoResult;
+use crate::error::{RepoError, RepoResult};
+use crate::settings::ContentDescriptorEncoding;
+
+/// The hashing algorithms that can be used to create content descriptors
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha2_256,
+    Sha2_512,
+    Sha3_256,
+    Sha3_512,
+    Blake2b256,
+    Blake2b512,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Returns the multihash code of the algorithm
+    pub fn code(&self) -> Code {
+        match self {
+            HashAlgorithm::Sha2_256 => Code::Sha2_256,
+            HashAlgorithm::Sha2_512 => Code::Sha2_512,
+            HashAlgorithm::Sha3_256 => Code::Sha3_256,
+            HashAlgorithm::Sha3_512 => Code::Sha3_512,
+            HashAlgorithm::Blake2b256 => Code::Blake2b256,
+            HashAlgorithm::Blake2b512 => Code::Blake2b512,
+            HashAlgorithm::Blake3 => Code::Blake3_256,
+        }
+    }
+
+    /// Returns the algorithm that was used to create the given descriptor
+    /// or None if the algorithm isn't supported
+    pub fn of_descriptor(descriptor: &[u8]) -> RepoResult<Option<Self>> {
+        let descriptor = if is_v1_content_descriptor(descriptor) {
+            convert_v1_descriptor_to_v2(descriptor)?
+        } else {
+            descriptor.to_vec()
+        };
+        let code = Multihash::from_bytes(&descriptor)
+            .map_err(|_| RepoError::from("invalid content descriptor"))?
+            .code();
+        let algorithm = [
+            HashAlgorithm::Sha2_256,
+            HashAlgorithm::Sha2_512,
+            HashAlgorithm::Sha3_256,
+            HashAlgorithm::Sha3_512,
+            HashAlgorithm::Blake2b256,
+            HashAlgorithm::Blake2b512,
+            HashAlgorithm::Blake3,
+        ]
+        .iter()
+        .copied()
+        .find(|a| u64::from(a.code()) == code);
+
+        Ok(algorithm)
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher + Send + Sync> {
+        match self {
+            HashAlgorithm::Sha2_256 => Box::new(Sha2_256::default()),
+            HashAlgorithm::Sha2_512 => Box::new(Sha2_512::default()),
+            HashAlgorithm::Sha3_256 => Box::new(Sha3_256::default()),
+            HashAlgorithm::Sha3_512 => Box::new(Sha3_512::default()),
+            HashAlgorithm::Blake2b256 => Box::new(Blake2b256::default()),
+            HashAlgorithm::Blake2b512 => Box::new(Blake2b512::default()),
+            HashAlgorithm::Blake3 => Box::new(Blake3_256::default()),
+        }
+    }
+}
 
 /// Creates a new content descriptor for the given file
 pub fn create_content_descriptor(bytes: &[u8]) -> Vec<u8> {
-    Code::Sha2_256.digest(bytes).to_bytes()
+    create_content_descriptor_with(bytes, HashAlgorithm::default())
+}
+
+/// Creates a new content descriptor for the given file with the given hashing algorithm
+pub fn create_content_descriptor_with(bytes: &[u8], algorithm: HashAlgorithm) -> Vec<u8> {
+    algorithm.code().digest(bytes).to_bytes()
+}
+
+/// Creates a content descriptor for contents that are read in chunks.
+/// The result is the same as the one of [create_content_descriptor_with] for the whole content
+pub struct ContentDescriptorHasher {
+    hasher: Box<dyn Hasher + Send + Sync>,
+    code: Code,
+}
+
+impl Default for ContentDescriptorHasher {
+    fn default() -> Self {
+        Self::new(HashAlgorithm::default())
+    }
+}
+
+impl ContentDescriptorHasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self {
+            hasher: algorithm.hasher(),
+            code: algorithm.code(),
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.code
+            .wrap(self.hasher.finalize())
+            .expect("the digests of the supported algorithms always fit into a multihash")
+            .to_bytes()
+    }
 }
 
 /// Encodes a content descriptor while respecting the version
@@ -16,6 +126,22 @@ pub fn encode_content_descriptor(descriptor: &[u8]) -> String {
     }
 }
 
+/// Encodes a content descriptor with the given encoding.
+/// V1 descriptors are returned as they are stored
+pub fn encode_content_descriptor_as(
+    descriptor: &[u8],
+    encoding: ContentDescriptorEncoding,
+) -> String {
+    if is_v1_content_descriptor(descriptor) {
+        return encode_content_descriptor_v1(descriptor);
+    }
+    match encoding {
+        ContentDescriptorEncoding::Base32 => encode_content_descriptor_v2(descriptor),
+        ContentDescriptorEncoding::Base64Url => data_encoding::BASE64URL_NOPAD.encode(descriptor),
+        ContentDescriptorEncoding::Hex => data_encoding::HEXLOWER.encode(descriptor),
+    }
+}
+
 /// Encodes a v1 descriptor that is already stored encoded in the database (only interprets it as string)
 pub fn encode_content_descriptor_v1(descriptor: &[u8]) -> String {
     String::from_utf8_lossy(descriptor).to_string()
@@ -26,13 +152,30 @@ pub fn encode_content_descriptor_v2(descriptor: &[u8]) -> String {
     data_encoding::BASE32_DNSSEC.encode(descriptor)
 }
 
-/// Decodes a content descriptor
+/// Decodes a content descriptor in any of the supported encodings
 pub fn decode_content_descriptor<S: AsRef<str>>(descriptor: S) -> RepoResult<Vec<u8>> {
     // check for v1 descriptor with a fixed length of 53 starting with the prefix of the base and hash
     if is_v1_content_descriptor_string(descriptor.as_ref()) {
-        decode_content_descriptor_v1(descriptor)
-    } else {
-        decode_content_descriptor_v2(descriptor)
+        return decode_content_descriptor_v1(descriptor);
+    }
+    let bytes = descriptor.as_ref().as_bytes();
+    let base32_result = data_encoding::BASE32_DNSSEC.decode(bytes);
+
+    // the encodings produce strings of different lengths for the same multihash
+    // so only one of them can decode into a valid multihash
+    let alternatives = [
+        data_encoding::HEXLOWER_PERMISSIVE.decode(bytes),
+        data_encoding::BASE64URL_NOPAD.decode(bytes),
+    ];
+    let decoded = base32_result
+        .iter()
+        .chain(alternatives.iter().flatten())
+        .find(|data| Multihash::from_bytes(data).is_ok())
+        .cloned();
+
+    match decoded {
+        Some(data) => Ok(data),
+        None => Ok(base32_result?),
     }
 }
 
@@ -55,6 +198,30 @@ pub fn convert_v1_descriptor_to_v2(descriptor_v1: &[u8]) -> RepoResult<Vec<u8>>
     Ok(data)
 }
 
+/// Returns the name of the hashing algorithm that was used to create the content descriptor
+pub fn content_descriptor_hash_algorithm(descriptor: &[u8]) -> RepoResult<String> {
+    let descriptor = if is_v1_content_descriptor(descriptor) {
+        convert_v1_descriptor_to_v2(descriptor)?
+    } else {
+        descriptor.to_vec()
+    };
+    let code = Multihash::from_bytes(&descriptor)
+        .map_err(|_| RepoError::from("invalid content descriptor"))?
+        .code();
+    let name = match code {
+        0x12 => String::from("sha2-256"),
+        0x13 => String::from("sha2-512"),
+        0x14 => String::from("sha3-512"),
+        0x16 => String::from("sha3-256"),
+        0x1e => String::from("blake3"),
+        0xb220 => String::from("blake2b-256"),
+        0xb240 => String::from("blake2b-512"),
+        code => format!("unknown (0x{:x})", code),
+    };
+
+    Ok(name)
+}
+
 /// Checks if a binary descriptor is v1
 pub fn is_v1_content_descriptor(descriptor: &[u8]) -> bool {
     descriptor.len() == 56 && descriptor.starts_with(b"bciq")
@@ -29,9 +29,15 @@ pub enum RepoError {
     #[error(transparent)]
     Raw(StringError),
 
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+
     #[error(transparent)]
     Thumbnailer(#[from] thumbnailer::error::ThumbError),
 
+    #[error("invalid mime type {0}")]
+    InvalidMimeType(#[from] mime::FromStrError),
+
     #[error("no free tcp port available")]
     PortUnavailable,
 
@@ -46,6 +52,31 @@ pub enum RepoError {
 
     #[error("bincode de-/serialization failed {0}")]
     Bincode(#[from] bincode::Error),
+
+    #[error("invalid tag \"{tag}\": {reason}")]
+    InvalidTag { tag: String, reason: String },
+
+    #[error("invalid value \"{value}\" for the attribute \"{field}\": {reason}")]
+    InvalidAttributeValue {
+        field: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("invalid filter expression: {0}")]
+    InvalidFilter(String),
+
+    #[error("the wildcard \"{pattern}\" matches more than {limit} tags")]
+    TooManyWildcardMatches { pattern: String, limit: usize },
+
+    #[error("failed to create thumbnail: {0}")]
+    ThumbnailFailed(String),
+
+    #[error("the external id \"{0}\" already belongs to another file")]
+    DuplicateExternalId(String),
+
+    #[error("the message of {size} bytes exceeds the limit of {limit} bytes. Large data has to be transferred with the chunked or streaming apis")]
+    MessageTooLarge { size: u64, limit: u64 },
 }
 
 #[derive(Error, Debug)]
@@ -1,45 +1,162 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
 
 use tokio::fs;
-use tokio::fs::{File, OpenOptions};
-use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
 
-use crate::content_descriptor::{create_content_descriptor, encode_content_descriptor};
-use crate::error::RepoResult;
-use crate::utils::get_folder_size;
+use crate::content_descriptor::{
+    convert_v1_descriptor_to_v2, decode_content_descriptor, encode_content_descriptor,
+    is_v1_content_descriptor, ContentDescriptorHasher, HashAlgorithm,
+};
+use crate::error::{RepoError, RepoResult};
+use crate::fs::storage_backend::{FilesystemBackend, StorageBackend};
 
+const STAGING_BUFFER_SIZE: usize = 64 * 1024;
+
+const STAGING_PREFIX: &str = ".staging-";
+
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A file in the hash store
+#[derive(Clone, Debug)]
+pub struct StoredBlob {
+    pub path: PathBuf,
+    /// The descriptor of the file. None for staging files left behind by interrupted imports
+    pub descriptor: Option<Vec<u8>>,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// The state of a stored file compared with its content descriptor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlobState {
+    Valid,
+    /// The content of the file doesn't match the descriptor anymore
+    Corrupt,
+    Missing,
+}
+
+/// Stores files by their content descriptor in a [StorageBackend].
+/// Added files are staged and hashed in a local folder before they are moved into the backend
 #[derive(Clone, Debug)]
 pub struct FileHashStore {
     path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+    algorithm: HashAlgorithm,
 }
 
 impl FileHashStore {
+    /// Creates a store that keeps the files in the folder
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        let backend = Arc::new(FilesystemBackend::new(path.clone()));
+
+        Self::with_backend(path, backend)
+    }
+
+    /// Creates a store that keeps the files in the backend and stages added files in the folder
+    pub fn with_backend(staging_path: PathBuf, backend: Arc<dyn StorageBackend>) -> Self {
+        Self {
+            path: staging_path,
+            backend,
+            algorithm: HashAlgorithm::default(),
+        }
+    }
+
+    /// Sets the hashing algorithm that is used for the descriptors of added files
+    pub fn with_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Returns the hashing algorithm that is used for the descriptors of added files
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
     }
 
     /// Adds a file that can be read to the hash store and returns the resulting hash identifier
+    /// with the size of the file. The content is written to a staging file while it is hashed
+    /// so that it never has to be kept in memory completely
     pub async fn add_file<R: AsyncRead + Unpin>(
+        &self,
+        reader: R,
+        extension: Option<&str>,
+    ) -> RepoResult<(Vec<u8>, u64)> {
+        self.add_file_with_algorithm(reader, extension, self.algorithm)
+            .await
+    }
+
+    /// Adds a file to the hash store like [FileHashStore::add_file]
+    /// but hashes it with the given algorithm
+    pub async fn add_file_with_algorithm<R: AsyncRead + Unpin>(
         &self,
         mut reader: R,
         extension: Option<&str>,
-    ) -> RepoResult<Vec<u8>> {
-        let mut buf = Vec::new();
-        reader.read_to_end(&mut buf).await?;
-        let descriptor = create_content_descriptor(&buf);
-        let file_path = self.descriptor_to_file_path(&descriptor);
-        let folder_path = file_path.parent().unwrap();
-
-        if !folder_path.exists() {
-            fs::create_dir(folder_path).await?;
-        }
-        let mut file_path = self.descriptor_to_file_path(&descriptor);
+        algorithm: HashAlgorithm,
+    ) -> RepoResult<(Vec<u8>, u64)> {
+        let staging_path = self.staging_path();
+        let (descriptor, size) = match stage_file(&mut reader, &staging_path, algorithm).await {
+            Ok(result) => result,
+            Err(e) => {
+                let _ = fs::remove_file(&staging_path).await;
+                return Err(e);
+            }
+        };
+        let mut key = descriptor_to_key(&descriptor);
         if let Some(extension) = extension {
-            file_path.set_extension(extension);
+            key = format!("{}.{}", key, extension);
+        }
+        if let Err(e) = self.backend.store_entry(&key, &staging_path).await {
+            let _ = fs::remove_file(&staging_path).await;
+            return Err(e);
         }
-        fs::write(file_path, buf).await?;
 
-        Ok(descriptor)
+        Ok((descriptor, size))
+    }
+
+    /// Copies the stored file into the other store under the same descriptor.
+    /// The copy is hashed while it is staged and only stored if it matches the descriptor
+    pub async fn copy_file_to(&self, descriptor: &[u8], target: &FileHashStore) -> RepoResult<()> {
+        let algorithm = HashAlgorithm::of_descriptor(descriptor)?
+            .ok_or_else(|| RepoError::from("unsupported hash algorithm"))?;
+        let expected = if is_v1_content_descriptor(descriptor) {
+            convert_v1_descriptor_to_v2(descriptor)?
+        } else {
+            descriptor.to_vec()
+        };
+        let (_, mut reader) = self.get_file(descriptor).await?;
+        let staging_path = target.staging_path();
+        let copied_descriptor = match stage_file(&mut reader, &staging_path, algorithm).await {
+            Ok((copied_descriptor, _)) => copied_descriptor,
+            Err(e) => {
+                let _ = fs::remove_file(&staging_path).await;
+                return Err(e);
+            }
+        };
+        if copied_descriptor != expected {
+            let _ = fs::remove_file(&staging_path).await;
+            return Err(RepoError::from(&*format!(
+                "file {} doesn't match its content descriptor",
+                encode_content_descriptor(descriptor)
+            )));
+        }
+        if let Err(e) = target
+            .backend
+            .store_entry(&descriptor_to_key(descriptor), &staging_path)
+            .await
+        {
+            let _ = fs::remove_file(&staging_path).await;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Returns if a file is stored for the descriptor
+    pub async fn contains_file(&self, descriptor: &[u8]) -> RepoResult<bool> {
+        self.backend.exists(&descriptor_to_key(descriptor)).await
     }
 
     /// Returns the file extension and a reader for the file by hash
@@ -47,69 +164,189 @@ impl FileHashStore {
         &self,
         descriptor: &[u8],
     ) -> RepoResult<(Option<String>, BufReader<File>)> {
-        let file_path = self.descriptor_to_file_path(descriptor);
-        tracing::debug!("Opening file {:?}", file_path);
-        let extension = file_path
+        let key = descriptor_to_key(descriptor);
+        let extension = Path::new(&key)
             .extension()
             .and_then(|s| s.to_str())
             .map(|s| s.to_string());
-        let file = OpenOptions::new().read(true).open(file_path).await?;
+        let file = self.backend.read_entry(&key).await?;
         let reader = BufReader::new(file);
 
         Ok((extension, reader))
     }
 
+    /// Returns the local path the file stored for the descriptor is read from
+    pub fn file_path(&self, descriptor: &[u8]) -> PathBuf {
+        self.backend.entry_path(&descriptor_to_key(descriptor))
+    }
+
     /// Renames a file
     pub async fn rename_file(
         &self,
         src_descriptor: &[u8],
         dst_descriptor: &[u8],
     ) -> RepoResult<()> {
-        let src_path = self.descriptor_to_file_path(src_descriptor);
-        if !src_path.exists() {
-            tracing::warn!("file {:?} doesn't exist", src_path);
+        let src_key = descriptor_to_key(src_descriptor);
+        if !self.backend.exists(&src_key).await? {
+            tracing::warn!("file {} doesn't exist", src_key);
             return Ok(());
         }
-        let dst_path = self.descriptor_to_file_path(dst_descriptor);
-        let dst_parent = dst_path.parent().unwrap();
-        if !dst_parent.exists() {
-            fs::create_dir(dst_parent).await?;
-        }
-        fs::rename(src_path, dst_path).await?;
+        let dst_key = descriptor_to_key(dst_descriptor);
 
-        Ok(())
+        self.backend.rename_entry(&src_key, &dst_key).await
     }
 
     pub async fn delete_file(&self, descriptor: &[u8]) -> RepoResult<()> {
-        let path = self.descriptor_to_file_path(descriptor);
-        if !path.exists() {
-            tracing::warn!("file {:?} doesn't exist", path);
+        let key = descriptor_to_key(descriptor);
+        if !self.backend.exists(&key).await? {
+            tracing::warn!("file {} doesn't exist", key);
             return Ok(());
         }
-        fs::remove_file(path).await?;
+
+        self.backend.delete_entry(&key).await
+    }
+
+    /// Hashes the stored file again with the algorithm of its descriptor
+    /// and returns if the result still matches the descriptor
+    pub async fn verify_file(&self, descriptor: &[u8]) -> RepoResult<bool> {
+        let algorithm = HashAlgorithm::of_descriptor(descriptor)?
+            .ok_or_else(|| RepoError::from("unsupported hash algorithm"))?;
+        let expected = if is_v1_content_descriptor(descriptor) {
+            convert_v1_descriptor_to_v2(descriptor)?
+        } else {
+            descriptor.to_vec()
+        };
+
+        self.backend
+            .verify(&descriptor_to_key(descriptor), &expected, algorithm)
+            .await
+    }
+
+    /// Returns if the file stored for the descriptor exists and still matches the descriptor
+    pub async fn check_file(&self, descriptor: &[u8]) -> RepoResult<BlobState> {
+        if !self.contains_file(descriptor).await? {
+            return Ok(BlobState::Missing);
+        }
+        let state = if self.verify_file(descriptor).await? {
+            BlobState::Valid
+        } else {
+            BlobState::Corrupt
+        };
+
+        Ok(state)
+    }
+
+    /// Returns all stored files and the staging files that don't belong
+    /// to an import of the current process
+    pub async fn blobs(&self) -> RepoResult<Vec<StoredBlob>> {
+        let mut blobs: Vec<StoredBlob> = self
+            .backend
+            .entries()
+            .await?
+            .into_iter()
+            .filter_map(|entry| {
+                let name = entry.key.rsplit('/').next().unwrap_or_default();
+                let descriptor = decode_content_descriptor(name).ok()?;
+
+                Some(StoredBlob {
+                    path: self.backend.entry_path(&entry.key),
+                    descriptor: Some(descriptor),
+                    size: entry.size,
+                    modified: entry.modified,
+                })
+            })
+            .collect();
+        let own_staging_prefix = format!("{}{}-", STAGING_PREFIX, std::process::id());
+        let mut entries = fs::read_dir(&self.path).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let metadata = entry.metadata().await?;
+
+            if metadata.is_file()
+                && name.starts_with(STAGING_PREFIX)
+                && !name.starts_with(&own_staging_prefix)
+            {
+                blobs.push(StoredBlob {
+                    path: entry.path(),
+                    descriptor: None,
+                    size: metadata.len(),
+                    modified: metadata.modified()?,
+                });
+            }
+        }
+
+        Ok(blobs)
+    }
+
+    /// Deletes a file that was returned by [FileHashStore::blobs]
+    pub async fn delete_blob(&self, blob: &StoredBlob) -> RepoResult<()> {
+        if let Some(descriptor) = &blob.descriptor {
+            self.backend
+                .delete_entry(&descriptor_to_key(descriptor))
+                .await?;
+        } else {
+            fs::remove_file(&blob.path).await?;
+        }
 
         Ok(())
     }
 
-    /// Scans the size of the folder
+    /// Returns the size of the stored file in bytes
+    pub async fn get_file_size(&self, descriptor: &[u8]) -> RepoResult<u64> {
+        self.backend
+            .entry_size(&descriptor_to_key(descriptor))
+            .await
+    }
+
+    /// Returns the size of all stored files
     #[inline]
     pub async fn get_size(&self) -> RepoResult<u64> {
-        get_folder_size(self.path.to_owned()).await
+        self.backend.size().await
     }
 
-    fn descriptor_to_file_path(&self, descriptor: &[u8]) -> PathBuf {
-        let descriptor_string = encode_content_descriptor(descriptor);
-        let mut path = self.descriptor_string_to_folder_path(&descriptor_string);
-        path.push(descriptor_string);
-
-        path
+    /// Returns a new path in the staging folder that belongs to the current process
+    fn staging_path(&self) -> PathBuf {
+        self.path.join(format!(
+            "{}{}-{}",
+            STAGING_PREFIX,
+            std::process::id(),
+            STAGING_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))
     }
+}
 
-    fn descriptor_string_to_folder_path(&self, descriptor: &str) -> PathBuf {
-        assert!(descriptor.len() >= 2);
-        let mut path = self.path.clone();
-        path.push(&descriptor[descriptor.len() - 3..descriptor.len() - 1]);
+/// Returns the key of the file stored for the descriptor.
+/// Files are grouped into folders by two characters of their encoded descriptor
+fn descriptor_to_key(descriptor: &[u8]) -> String {
+    let descriptor_string = encode_content_descriptor(descriptor);
+    assert!(descriptor_string.len() >= 3);
+    let folder = &descriptor_string[descriptor_string.len() - 3..descriptor_string.len() - 1];
+
+    format!("{}/{}", folder, descriptor_string)
+}
 
-        path
+/// Copies the content of the reader into the staging file and returns its descriptor and size
+async fn stage_file<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    staging_path: &Path,
+    algorithm: HashAlgorithm,
+) -> RepoResult<(Vec<u8>, u64)> {
+    let mut writer = BufWriter::new(File::create(staging_path).await?);
+    let mut hasher = ContentDescriptorHasher::new(algorithm);
+    let mut buf = vec![0u8; STAGING_BUFFER_SIZE];
+    let mut size = 0u64;
+
+    loop {
+        let read = reader.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+        writer.write_all(&buf[..read]).await?;
+        size += read as u64;
     }
+    writer.flush().await?;
+
+    Ok((hasher.finish(), size))
 }
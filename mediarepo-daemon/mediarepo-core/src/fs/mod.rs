@@ -1,3 +1,4 @@
 pub mod drop_file;
 pub mod file_hash_store;
+pub mod storage_backend;
 pub mod thumbnail_store;
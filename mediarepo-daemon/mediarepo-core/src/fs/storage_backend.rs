@@ -0,0 +1,180 @@
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use tokio::fs;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncReadExt;
+
+use crate::content_descriptor::{ContentDescriptorHasher, HashAlgorithm};
+use crate::error::RepoResult;
+use crate::utils::get_folder_size;
+
+const VERIFY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// An entry of a storage backend
+#[derive(Clone, Debug)]
+pub struct StorageEntry {
+    pub key: String,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// Stores the contents of files under keys. Keys are relative paths
+/// using `/` as separator that backends can map to their own layout
+#[async_trait]
+pub trait StorageBackend: Debug + Send + Sync {
+    /// Moves the staged file into the storage under the key
+    async fn store_entry(&self, key: &str, staged_path: &Path) -> RepoResult<()>;
+
+    /// Opens the entry for reading. Entries are read from local files so that they
+    /// can be seeked and passed to external programs. Backends for remote storage
+    /// have to download the entry first
+    async fn read_entry(&self, key: &str) -> RepoResult<File>;
+
+    async fn delete_entry(&self, key: &str) -> RepoResult<()>;
+
+    async fn rename_entry(&self, src_key: &str, dst_key: &str) -> RepoResult<()>;
+
+    async fn exists(&self, key: &str) -> RepoResult<bool>;
+
+    /// Returns the size of the entry in bytes
+    async fn entry_size(&self, key: &str) -> RepoResult<u64>;
+
+    /// Returns all entries of the storage
+    async fn entries(&self) -> RepoResult<Vec<StorageEntry>>;
+
+    /// Returns the size of all entries in bytes
+    async fn size(&self) -> RepoResult<u64>;
+
+    /// Returns the local path the entry is read from
+    fn entry_path(&self, key: &str) -> PathBuf;
+
+    /// Hashes the entry with the algorithm and returns if the result matches the expected descriptor
+    async fn verify(
+        &self,
+        key: &str,
+        expected: &[u8],
+        algorithm: HashAlgorithm,
+    ) -> RepoResult<bool> {
+        let mut file = self.read_entry(key).await?;
+        let mut hasher = ContentDescriptorHasher::new(algorithm);
+        let mut buf = vec![0u8; VERIFY_BUFFER_SIZE];
+
+        loop {
+            let read = file.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+
+        Ok(hasher.finish() == expected)
+    }
+}
+
+/// Stores entries as files in a folder on the local filesystem
+#[derive(Clone, Debug)]
+pub struct FilesystemBackend {
+    root: PathBuf,
+}
+
+impl FilesystemBackend {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Creates the parent folder of the entry if it doesn't exist
+    async fn create_parent(&self, path: &Path) -> RepoResult<()> {
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for FilesystemBackend {
+    async fn store_entry(&self, key: &str, staged_path: &Path) -> RepoResult<()> {
+        let path = self.entry_path(key);
+        self.create_parent(&path).await?;
+        fs::rename(staged_path, path).await?;
+
+        Ok(())
+    }
+
+    async fn read_entry(&self, key: &str) -> RepoResult<File> {
+        let path = self.entry_path(key);
+        tracing::debug!("Opening file {:?}", path);
+        let file = OpenOptions::new().read(true).open(path).await?;
+
+        Ok(file)
+    }
+
+    async fn delete_entry(&self, key: &str) -> RepoResult<()> {
+        fs::remove_file(self.entry_path(key)).await?;
+
+        Ok(())
+    }
+
+    async fn rename_entry(&self, src_key: &str, dst_key: &str) -> RepoResult<()> {
+        let dst_path = self.entry_path(dst_key);
+        self.create_parent(&dst_path).await?;
+        fs::rename(self.entry_path(src_key), dst_path).await?;
+
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> RepoResult<bool> {
+        Ok(self.entry_path(key).exists())
+    }
+
+    async fn entry_size(&self, key: &str) -> RepoResult<u64> {
+        let metadata = fs::metadata(self.entry_path(key)).await?;
+
+        Ok(metadata.len())
+    }
+
+    /// Returns the files in the folders of the root. Files directly in the root
+    /// are not entries but staging files of the hash store
+    async fn entries(&self) -> RepoResult<Vec<StorageEntry>> {
+        let mut entries = Vec::new();
+        let mut folders = fs::read_dir(&self.root).await?;
+
+        while let Some(folder) = folders.next_entry().await? {
+            if !folder.metadata().await?.is_dir() {
+                continue;
+            }
+            let folder_name = folder.file_name().to_string_lossy().to_string();
+            let mut files = fs::read_dir(folder.path()).await?;
+
+            while let Some(file) = files.next_entry().await? {
+                let metadata = file.metadata().await?;
+
+                if !metadata.is_file() {
+                    continue;
+                }
+                entries.push(StorageEntry {
+                    key: format!("{}/{}", folder_name, file.file_name().to_string_lossy()),
+                    size: metadata.len(),
+                    modified: metadata.modified()?,
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+
+    async fn size(&self) -> RepoResult<u64> {
+        get_folder_size(self.root.to_owned()).await
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        key.split('/')
+            .fold(self.root.clone(), |path, part| path.join(part))
+    }
+}
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::io::Result;
 use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncWriteExt, BufWriter};
@@ -20,6 +21,26 @@ pub struct Dimensions {
     pub width: u32,
 }
 
+/// How a thumbnail is fitted into the size it was created for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ThumbnailCrop {
+    /// Keeps the aspect ratio so that the thumbnail fits into the size
+    #[default]
+    Fit,
+    /// Crops the center of the image so that the thumbnail fills the size
+    CenterCrop,
+}
+
+/// Suffix of the file names of center cropped thumbnails
+const CROP_SUFFIX: &str = "crop";
+
+/// Prefix of the file names of video contact sheets
+const SHEET_PREFIX: &str = "sheet-";
+
+/// Prefix of the file names of video timeline thumbnails.
+/// The prefix is followed by the position in the video in permille
+const TIMELINE_PREFIX: &str = "timeline-";
+
 impl ThumbnailStore {
     pub fn new(path: PathBuf) -> Self {
         Self { path }
@@ -32,10 +53,17 @@ impl ThumbnailStore {
         &self,
         parent_id: S,
         size: Dimensions,
+        crop: ThumbnailCrop,
         data: &[u8],
     ) -> Result<PathBuf> {
         let parent_dir = self.path.join(parent_id.to_string());
-        let entry_path = parent_dir.join(format!("{}-{}", size.height, size.width));
+        let file_name = match crop {
+            ThumbnailCrop::Fit => format!("{}-{}", size.height, size.width),
+            ThumbnailCrop::CenterCrop => {
+                format!("{}-{}-{}", size.height, size.width, CROP_SUFFIX)
+            }
+        };
+        let entry_path = parent_dir.join(file_name);
 
         if !parent_dir.exists() {
             fs::create_dir_all(parent_dir).await?;
@@ -53,12 +81,110 @@ impl ThumbnailStore {
         Ok(entry_path)
     }
 
+    /// Stores a contact sheet with the given number of rows and columns for a parent id
+    #[tracing::instrument(level = "debug", skip(self, data))]
+    pub async fn add_contact_sheet<S: ToString + Debug>(
+        &self,
+        parent_id: S,
+        rows: u32,
+        cols: u32,
+        data: &[u8],
+    ) -> Result<PathBuf> {
+        let parent_dir = self.path.join(parent_id.to_string());
+
+        if !parent_dir.exists() {
+            fs::create_dir_all(&parent_dir).await?;
+        }
+        let entry_path = parent_dir.join(contact_sheet_name(rows, cols));
+        fs::write(&entry_path, data).await?;
+
+        Ok(entry_path)
+    }
+
+    /// Returns the path of the contact sheet with the given number of rows and columns
+    /// if it has been created before
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_contact_sheet<S: ToString + Debug>(
+        &self,
+        parent_id: S,
+        rows: u32,
+        cols: u32,
+    ) -> Option<PathBuf> {
+        let path = self
+            .path
+            .join(parent_id.to_string())
+            .join(contact_sheet_name(rows, cols));
+
+        if path.exists() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the timeline thumbnails of a parent id with the given thumbnails
+    /// that are stored with their position as a fraction of the video's duration
+    #[tracing::instrument(level = "debug", skip(self, thumbnails))]
+    pub async fn set_timeline_thumbnails<S: ToString + Debug>(
+        &self,
+        parent_id: S,
+        thumbnails: Vec<(f64, Vec<u8>)>,
+    ) -> Result<Vec<(f64, PathBuf)>> {
+        let parent_dir = self.path.join(parent_id.to_string());
+
+        for (_, path) in self.get_timeline_thumbnails(parent_id).await? {
+            fs::remove_file(path).await?;
+        }
+        if !parent_dir.exists() {
+            fs::create_dir_all(&parent_dir).await?;
+        }
+        let mut entries = Vec::with_capacity(thumbnails.len());
+
+        for (position, data) in thumbnails {
+            let permille = (position.clamp(0.0, 1.0) * 1000.0).round() as u32;
+            let entry_path = parent_dir.join(format!("{}{}", TIMELINE_PREFIX, permille));
+            fs::write(&entry_path, data).await?;
+            entries.push((permille as f64 / 1000.0, entry_path));
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the timeline thumbnails of a parent id with their position
+    /// as a fraction of the video's duration
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_timeline_thumbnails<S: ToString + Debug>(
+        &self,
+        parent_id: S,
+    ) -> Result<Vec<(f64, PathBuf)>> {
+        let parent_dir = self.path.join(parent_id.to_string());
+        if !parent_dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut entries = Vec::new();
+        let mut dir = fs::read_dir(parent_dir).await?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            let file_name = entry.file_name();
+            let position = file_name
+                .to_string_lossy()
+                .strip_prefix(TIMELINE_PREFIX)
+                .and_then(|p| p.parse::<u32>().ok());
+
+            if let Some(permille) = position {
+                entries.push((permille as f64 / 1000.0, entry.path()));
+            }
+        }
+
+        Ok(entries)
+    }
+
     /// Returns all thumbnails for a parent id
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_thumbnails<S: ToString + Debug>(
         &self,
         parent_id: S,
-    ) -> Result<Vec<(Dimensions, PathBuf)>> {
+    ) -> Result<Vec<(Dimensions, ThumbnailCrop, PathBuf)>> {
         let mut entries = Vec::new();
         let parent_dir = self.path.join(parent_id.to_string());
         if !parent_dir.exists() {
@@ -70,13 +196,21 @@ impl ThumbnailStore {
             let file_name = entry.file_name();
             let name = file_name.to_string_lossy();
 
+            if name.starts_with(SHEET_PREFIX) || name.starts_with(TIMELINE_PREFIX) {
+                continue;
+            }
+            let (name, crop) = match name.strip_suffix(CROP_SUFFIX) {
+                Some(name) => (name.trim_end_matches('-'), ThumbnailCrop::CenterCrop),
+                None => (&*name, ThumbnailCrop::Fit),
+            };
+
             let (height, width) = name
                 .split_once('-')
                 .and_then(|(height, width)| {
                     Some((height.parse::<u32>().ok()?, width.parse::<u32>().ok()?))
                 })
                 .unwrap_or((255, 255));
-            entries.push((Dimensions { height, width }, entry.path()))
+            entries.push((Dimensions { height, width }, crop, entry.path()))
         }
 
         Ok(entries)
@@ -99,18 +233,39 @@ impl ThumbnailStore {
         fs::rename(src_dir, dst_dir).await
     }
 
-    /// Deletes all thumbnails of a parent
+    /// Deletes all thumbnails of a parent and returns the number of bytes freed
     #[tracing::instrument(level = "debug")]
-    pub async fn delete_parent<S: AsRef<str> + Debug>(&self, parent: S) -> Result<()> {
-        let path = PathBuf::from(parent.as_ref());
+    pub async fn delete_parent<S: AsRef<str> + Debug>(&self, parent: S) -> RepoResult<u64> {
+        let path = self.path.join(parent.as_ref());
 
         if !path.exists() {
             tracing::warn!("directory {:?} doesn't exist", path);
-            return Ok(());
+            return Ok(0);
         }
+        let size = get_folder_size(path.clone()).await?;
         fs::remove_dir_all(&path).await?;
 
-        Ok(())
+        Ok(size)
+    }
+
+    /// Deletes all stored thumbnails and returns the number of bytes freed
+    #[tracing::instrument(level = "debug")]
+    pub async fn clear(&self) -> RepoResult<u64> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let size = self.get_size().await?;
+        let mut dir = fs::read_dir(&self.path).await?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                fs::remove_dir_all(entry.path()).await?;
+            } else {
+                fs::remove_file(entry.path()).await?;
+            }
+        }
+
+        Ok(size)
     }
 
     /// Returns the size of the folder
@@ -119,3 +274,7 @@ impl ThumbnailStore {
         get_folder_size(self.path.to_owned()).await
     }
 }
+
+fn contact_sheet_name(rows: u32, cols: u32) -> String {
+    format!("{}{}x{}", SHEET_PREFIX, rows, cols)
+}
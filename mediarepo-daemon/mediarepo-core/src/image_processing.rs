@@ -0,0 +1,302 @@
+#[cfg(feature = "avif")]
+use std::io::Cursor;
+use std::io::{BufRead, Seek};
+use std::panic::{self, AssertUnwindSafe};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use exif::{Exif, In, Reader, Tag, Value};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use mime::Mime;
+
+use crate::error::{RepoError, RepoResult};
+
+/// If the daemon was compiled with support for decoding avif images
+pub const AVIF_SUPPORTED: bool = cfg!(feature = "avif");
+
+/// The capture metadata of an image read from its exif data
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExifData {
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    /// The local time the image was taken at
+    pub capture_time: Option<NaiveDateTime>,
+    /// The decimal (latitude, longitude) the image was taken at
+    pub location: Option<(f64, f64)>,
+    /// The exif orientation from 1 to 8 describing how the image has to be
+    /// transformed to be displayed upright
+    pub orientation: Option<u32>,
+}
+
+/// The number of compared pixel pairs per row and the number of rows of a perceptual hash
+const PHASH_WIDTH: u32 = 8;
+const PHASH_HEIGHT: u32 = 8;
+
+/// Fully decodes the given bytes as an image of the given mime type to make sure
+/// that the content isn't corrupted. Contents with a mime type that isn't a decodable
+/// image format are always considered valid.
+#[tracing::instrument(level = "debug", skip(bytes))]
+pub fn validate_image(bytes: &[u8], mime_type: &str) -> RepoResult<()> {
+    match ImageFormat::from_mime_type(mime_type) {
+        // the webp decoder of the image crate doesn't support the extended format
+        Some(ImageFormat::WebP) => validate_webp(bytes),
+        Some(ImageFormat::Avif) if !cfg!(feature = "avif") => Ok(()),
+        Some(format) => {
+            image::load_from_memory_with_format(bytes, format)?;
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Validates the image like [validate_image] on a blocking thread so that decoding
+/// doesn't block the async runtime. The contents are returned with the result
+pub async fn validate_image_blocking(
+    bytes: Vec<u8>,
+    mime_type: String,
+) -> RepoResult<(Vec<u8>, RepoResult<()>)> {
+    tokio::task::spawn_blocking(move || {
+        let result = validate_image(&bytes, &mime_type);
+        (bytes, result)
+    })
+    .await
+    .map_err(|e| RepoError::from(&*e.to_string()))
+}
+
+/// Decodes a webp image with libwebp. Animated images are only checked for valid headers
+fn validate_webp(bytes: &[u8]) -> RepoResult<()> {
+    let features =
+        webp::BitstreamFeatures::new(bytes).ok_or_else(|| RepoError::from("invalid webp image"))?;
+
+    if !features.has_animation() && webp::Decoder::new(bytes).decode().is_none() {
+        Err(RepoError::from("invalid webp image"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Calculates a 64 bit difference hash of an image that stays the same or changes
+/// only in a few bits when the image is scaled, recompressed or slightly edited.
+/// The similarity of two images is the number of bits that differ between their hashes
+#[tracing::instrument(level = "debug", skip(bytes))]
+pub fn perceptual_hash(bytes: &[u8], mime_type: &str) -> RepoResult<u64> {
+    let image = match ImageFormat::from_mime_type(mime_type) {
+        Some(ImageFormat::WebP) => webp::Decoder::new(bytes)
+            .decode()
+            .map(|image| image.to_image())
+            .ok_or_else(|| RepoError::from("invalid webp image"))?,
+        Some(ImageFormat::Avif) if !cfg!(feature = "avif") => {
+            return Err(RepoError::from("avif images are not supported"))
+        }
+        Some(format) => image::load_from_memory_with_format(bytes, format)?,
+        None => return Err(RepoError::from("the content is not a decodable image")),
+    };
+    let pixels = image
+        .resize_exact(PHASH_WIDTH + 1, PHASH_HEIGHT, FilterType::Triangle)
+        .into_luma8();
+    let mut hash = 0u64;
+
+    for y in 0..PHASH_HEIGHT {
+        for x in 0..PHASH_WIDTH {
+            let brighter = pixels.get_pixel(x, y).0[0] < pixels.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | brighter as u64;
+        }
+    }
+
+    Ok(hash)
+}
+
+/// Returns the number of bits that differ between two perceptual hashes
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Converts images that the thumbnailer can't decode by itself into png images
+/// and returns the bytes to create thumbnails from with their mime type
+#[tracing::instrument(level = "debug", skip(bytes))]
+pub fn prepare_thumbnail_source(bytes: Vec<u8>, mime_type: Mime) -> RepoResult<(Vec<u8>, Mime)> {
+    #[cfg(feature = "avif")]
+    if mime_type.essence_str() == "image/avif" {
+        let image = image::load_from_memory_with_format(&bytes, ImageFormat::Avif)?;
+        let mut buf = Cursor::new(Vec::new());
+        image.write_to(&mut buf, image::ImageOutputFormat::Png)?;
+
+        return Ok((buf.into_inner(), mime::IMAGE_PNG));
+    }
+
+    Ok((bytes, mime_type))
+}
+
+/// Runs the thumbnail creation and converts a panic of the decoders into
+/// [RepoError::ThumbnailFailed] so that malformed files can't take down the daemon
+pub fn catch_thumbnail_panic<T, F: FnOnce() -> RepoResult<T>>(create: F) -> RepoResult<T> {
+    // nothing that is shared with other threads is touched by decoding
+    panic::catch_unwind(AssertUnwindSafe(create)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("the decoder panicked"));
+        tracing::error!("thumbnail creation panicked: {}", message);
+
+        Err(RepoError::ThumbnailFailed(message))
+    })
+}
+
+/// Reads the (width, height) of an image from its header without decoding the whole image
+#[tracing::instrument(level = "debug", skip(reader))]
+pub fn read_image_dimensions<R: BufRead + Seek>(
+    mut reader: R,
+    mime_type: &str,
+) -> Option<(u32, u32)> {
+    match ImageFormat::from_mime_type(mime_type)? {
+        // the webp decoder of the image crate doesn't support the extended format
+        ImageFormat::WebP => {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).ok()?;
+            webp::BitstreamFeatures::new(&bytes)
+                .map(|features| (features.width(), features.height()))
+        }
+        format => image::io::Reader::with_format(reader, format)
+            .into_dimensions()
+            .ok(),
+    }
+}
+
+/// Reads the gps coordinates stored in the exif data of an image
+/// and returns them as decimal (latitude, longitude)
+#[tracing::instrument(level = "debug", skip(reader))]
+pub fn read_gps_location<R: BufRead + Seek>(reader: &mut R) -> Option<(f64, f64)> {
+    let exif = Reader::new().read_from_container(reader).ok()?;
+
+    gps_location(&exif)
+}
+
+/// Reads the capture metadata stored in the exif data of an image.
+/// Returns `None` if the image has no exif data or none of the read fields
+#[tracing::instrument(level = "debug", skip(reader))]
+pub fn read_exif<R: BufRead + Seek>(reader: &mut R) -> Option<ExifData> {
+    let exif = Reader::new().read_from_container(reader).ok()?;
+    let capture_time = [Tag::DateTimeOriginal, Tag::DateTimeDigitized, Tag::DateTime]
+        .iter()
+        .find_map(|tag| read_date_time(&exif, *tag));
+    let orientation = exif
+        .get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|f| f.value.get_uint(0))
+        .filter(|o| (1..=8).contains(o));
+    let data = ExifData {
+        camera_make: read_ascii(&exif, Tag::Make),
+        camera_model: read_ascii(&exif, Tag::Model),
+        capture_time,
+        location: gps_location(&exif),
+        orientation,
+    };
+
+    if data == ExifData::default() {
+        None
+    } else {
+        Some(data)
+    }
+}
+
+/// Transforms a decoded image so that it is displayed upright according to the
+/// orientation stored in its exif data
+pub fn apply_exif_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// Reads the exif fields of an image as pairs of tag name and displayable value
+#[tracing::instrument(level = "debug", skip(reader))]
+pub fn read_exif_fields<R: BufRead + Seek>(reader: &mut R) -> Vec<(String, String)> {
+    match Reader::new().read_from_container(reader) {
+        Ok(exif) => exif
+            .fields()
+            .filter(|f| f.ifd_num == In::PRIMARY)
+            .map(|f| {
+                let value = f.display_value().with_unit(&exif).to_string();
+                (f.tag.to_string(), value)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn gps_location(exif: &Exif) -> Option<(f64, f64)> {
+    let latitude = read_gps_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S')?;
+    let longitude = read_gps_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W')?;
+
+    if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) {
+        Some((latitude, longitude))
+    } else {
+        None
+    }
+}
+
+/// Reads a text field without the padding that some cameras add
+fn read_ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => {
+            let value = String::from_utf8_lossy(values.first()?);
+            let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+
+            if value.is_empty() {
+                None
+            } else {
+                Some(value.to_owned())
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Reads a timestamp without a timezone as it is stored by the camera
+fn read_date_time(exif: &Exif, tag: Tag) -> Option<NaiveDateTime> {
+    let date_time = match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => exif::DateTime::from_ascii(values.first()?).ok()?,
+        _ => return None,
+    };
+
+    NaiveDate::from_ymd_opt(
+        date_time.year as i32,
+        date_time.month as u32,
+        date_time.day as u32,
+    )?
+    .and_hms_opt(
+        date_time.hour as u32,
+        date_time.minute as u32,
+        date_time.second as u32,
+    )
+}
+
+/// Converts a coordinate stored as degrees, minutes and seconds into a decimal value
+fn read_gps_coordinate(exif: &Exif, tag: Tag, ref_tag: Tag, negative_ref: u8) -> Option<f64> {
+    let dms = match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(dms) if dms.len() == 3 && dms.iter().all(|r| r.denom != 0) => {
+            dms[0].to_f64() + dms[1].to_f64() / 60.0 + dms[2].to_f64() / 3600.0
+        }
+        _ => return None,
+    };
+    let is_negative = match &exif.get_field(ref_tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values
+            .first()
+            .and_then(|v| v.first())
+            .map(|r| r.to_ascii_uppercase() == negative_ref)
+            .unwrap_or(false),
+        _ => false,
+    };
+
+    if is_negative {
+        Some(-dms)
+    } else {
+        Some(dms)
+    }
+}
@@ -1,8 +1,12 @@
 pub use bincode;
+pub use exif;
 pub use futures;
+pub use glob;
+pub use image;
 pub use itertools;
 pub use mediarepo_api;
 pub use mediarepo_api::bromine;
+pub use mime;
 pub use thumbnailer;
 pub use tokio_graceful_shutdown;
 pub use trait_bound_typemap;
@@ -11,7 +15,9 @@ pub mod content_descriptor;
 pub mod context;
 pub mod error;
 pub mod fs;
+pub mod image_processing;
 pub mod settings;
 pub mod tracing_layer_list;
 pub mod type_keys;
 pub mod utils;
+pub mod video_processing;
@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Periodic copies of the database taken while the daemon is running.
+/// Backups are consistent snapshots even if files are imported at the same time.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BackupSettings {
+    pub enabled: bool,
+
+    /// The directory the backups are stored in relative to the repository
+    pub directory: String,
+
+    /// The number of hours between two backups
+    pub interval_hours: u64,
+
+    /// The number of backups that are kept. Older backups are deleted after a new one was created
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: String::from("backups"),
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+impl BackupSettings {
+    #[inline]
+    pub fn backup_dir(&self, root: &Path) -> PathBuf {
+        root.join(&self.directory)
+    }
+
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_hours.max(1) * 60 * 60)
+    }
+}
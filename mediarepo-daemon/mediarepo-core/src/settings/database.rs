@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Durability settings of the sqlite database.
+///
+/// The defaults (`Wal` + `Full`) never lose committed transactions.
+/// Lowering `synchronous` to `Normal` speeds up large imports considerably
+/// but transactions committed right before a power loss or os crash
+/// may be rolled back. With `Off` a power loss can also corrupt the database.
+/// Crashes of the daemon itself don't lose data in any of the modes.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DatabaseSettings {
+    pub journal_mode: JournalMode,
+    pub synchronous: SynchronousMode,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            journal_mode: JournalMode::Wal,
+            synchronous: SynchronousMode::Full,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum JournalMode {
+    /// Writes changes to a separate log that gets merged into the database later.
+    /// Allows reads while writing
+    Wal,
+    /// Keeps a rollback journal that is deleted after every transaction
+    Delete,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum SynchronousMode {
+    /// Leaves syncing to disk to the os
+    Off,
+    /// Syncs at the most critical moments only
+    Normal,
+    /// Syncs after every transaction
+    Full,
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+use crate::content_descriptor::HashAlgorithm;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImportSettings {
+    /// Fully decodes imported images to detect corrupt files.
+    /// Corrupt files are still imported but flagged as such.
+    pub validate_images: bool,
+    /// Creates thumbnails when a file is imported.
+    /// If disabled, thumbnails are only created when they are first requested
+    /// or by running the thumbnail generation job.
+    pub auto_generate_thumbnails: bool,
+    /// The hashing algorithm for the content descriptors of imported files.
+    /// Existing files keep their descriptors until the storage is migrated to the algorithm.
+    pub hash_algorithm: HashAlgorithm,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            validate_images: false,
+            auto_generate_thumbnails: true,
+            hash_algorithm: HashAlgorithm::default(),
+        }
+    }
+}
@@ -4,23 +4,41 @@ use std::path::{Path, PathBuf};
 use config::{Config, FileFormat};
 use serde::{Deserialize, Serialize};
 
+pub use backup::*;
+pub use database::*;
+pub use import::*;
 pub use logging::*;
 pub use paths::*;
 pub use server::*;
+pub use sorting::*;
+pub use tags::*;
+pub use thumbnails::*;
 
 use crate::error::RepoResult;
 use crate::settings::v1::SettingsV1;
 
+mod backup;
+mod database;
+mod import;
 mod logging;
 mod paths;
 mod server;
+mod sorting;
+mod tags;
+mod thumbnails;
 pub mod v1;
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct Settings {
     pub server: ServerSettings,
     pub paths: PathSettings,
+    pub database: DatabaseSettings,
     pub logging: LoggingSettings,
+    pub import: ImportSettings,
+    pub thumbnails: ThumbnailSettings,
+    pub tags: TagSettings,
+    pub sorting: SortingSettings,
+    pub backup: BackupSettings,
 }
 
 impl Settings {
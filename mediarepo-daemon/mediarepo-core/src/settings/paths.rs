@@ -30,6 +30,11 @@ impl PathSettings {
         root.join(&self.files_directory)
     }
 
+    /// Sets the folder of the file storage. Relative paths are resolved against the repository root
+    pub fn set_files_dir<S: ToString>(&mut self, path: S) {
+        self.files_directory = path.to_string();
+    }
+
     #[inline]
     pub fn thumbs_dir(&self, root: &Path) -> PathBuf {
         root.join(&self.thumbnail_directory)
@@ -2,13 +2,48 @@ use std::net::IpAddr;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+/// The default size limit of a single message in bytes (256 MiB)
+const DEFAULT_MAX_MESSAGE_SIZE: u64 = 256 * 1024 * 1024;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ServerSettings {
+    /// The encoding of content descriptors returned by the api.
+    /// Incoming descriptors are accepted in any of the supported encodings.
+    pub content_descriptor_encoding: ContentDescriptorEncoding,
+    /// The maximum size of the data of a single request or response in bytes.
+    /// Larger data has to be transferred with the chunked or streaming apis.
+    pub max_message_size: u64,
     pub tcp: TcpServerSettings,
     #[cfg(unix)]
     pub unix_socket: UnixSocketServerSettings,
 }
 
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            content_descriptor_encoding: Default::default(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            tcp: Default::default(),
+            #[cfg(unix)]
+            unix_socket: Default::default(),
+        }
+    }
+}
+
+/// The encoding of content descriptors in api responses.
+/// Files and thumbnails are always stored with base32 encoded descriptors
+/// regardless of this setting.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub enum ContentDescriptorEncoding {
+    /// Lowercase base32 without padding (DNSSEC alphabet). This is the default.
+    #[default]
+    Base32,
+    /// Url safe base64 without padding
+    Base64Url,
+    /// Lowercase hexadecimal
+    Hex,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TcpServerSettings {
     pub enabled: bool,
@@ -0,0 +1,81 @@
+use mediarepo_api::types::filtering::{SortDirection, SortKey, SortNamespace};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SortingSettings {
+    /// The keys that files are sorted by when a request doesn't specify any.
+    /// Applies to listing all files and to searches.
+    /// An empty list returns files in the order they are stored in the database.
+    pub default_keys: Vec<SortKeySetting>,
+}
+
+impl Default for SortingSettings {
+    fn default() -> Self {
+        Self {
+            default_keys: vec![SortKeySetting {
+                property: SortProperty::ImportTime,
+                namespace: None,
+                direction: SortDirection::Descending,
+            }],
+        }
+    }
+}
+
+impl SortingSettings {
+    /// Returns the default keys as sort keys of the api.
+    /// Namespace keys without a namespace are skipped
+    pub fn default_sort_keys(&self) -> Vec<SortKey> {
+        self.default_keys
+            .iter()
+            .filter_map(SortKeySetting::to_sort_key)
+            .collect()
+    }
+}
+
+/// A sort key in the settings, e.g.
+/// ```toml
+/// [[sorting.default_keys]]
+/// property = "Namespace"
+/// namespace = "page"
+/// direction = "Ascending"
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SortKeySetting {
+    pub property: SortProperty,
+    /// The namespace to sort by for the `Namespace` property
+    pub namespace: Option<String>,
+    pub direction: SortDirection,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum SortProperty {
+    Namespace,
+    FileName,
+    FileSize,
+    ImportTime,
+    CreateTime,
+    ChangeTime,
+    FileType,
+    NumTags,
+}
+
+impl SortKeySetting {
+    fn to_sort_key(&self) -> Option<SortKey> {
+        let direction = self.direction.clone();
+        let key = match self.property {
+            SortProperty::Namespace => SortKey::Namespace(SortNamespace {
+                name: self.namespace.clone()?,
+                direction,
+            }),
+            SortProperty::FileName => SortKey::FileName(direction),
+            SortProperty::FileSize => SortKey::FileSize(direction),
+            SortProperty::ImportTime => SortKey::FileImportedTime(direction),
+            SortProperty::CreateTime => SortKey::FileCreatedTime(direction),
+            SortProperty::ChangeTime => SortKey::FileChangeTime(direction),
+            SortProperty::FileType => SortKey::FileType(direction),
+            SortProperty::NumTags => SortKey::NumTags(direction),
+        };
+
+        Some(key)
+    }
+}
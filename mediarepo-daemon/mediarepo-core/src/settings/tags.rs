@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TagSettings {
+    /// Stores and matches tags with their original casing instead of lowercasing them.
+    ///
+    /// Tags created while this is disabled have been stored lowercase and keep
+    /// that casing when it gets enabled, so searching for `Cat` won't find an
+    /// existing `cat` anymore. When it gets disabled again, mixed-case tags are
+    /// still found by lowercase queries, but adding the same tag in a different
+    /// casing creates a new lowercase tag next to the existing one.
+    pub case_sensitive: bool,
+
+    /// The maximum number of characters of tag names and namespaces.
+    /// Existing tags exceeding the limit are kept.
+    pub max_length: usize,
+
+    /// Characters that are rejected in new tags in addition to control characters
+    pub disallowed_characters: String,
+
+    /// Removes leading and trailing whitespace from the names and namespaces of new tags
+    pub trim_whitespace: bool,
+
+    /// The maximum number of tags a single wildcard search term like `*girl`
+    /// may expand to. Searches exceeding it are rejected with an error.
+    pub max_wildcard_matches: usize,
+}
+
+impl Default for TagSettings {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            max_length: 255,
+            disallowed_characters: String::new(),
+            trim_whitespace: true,
+            max_wildcard_matches: 1000,
+        }
+    }
+}
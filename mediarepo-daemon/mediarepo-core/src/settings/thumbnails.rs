@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use thumbnailer::ThumbnailSize;
+
+use crate::fs::thumbnail_store::ThumbnailCrop;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThumbnailSettings {
+    /// How thumbnails are fitted into the requested size when a client doesn't specify it.
+    /// `CenterCrop` creates thumbnails that fill the whole size for uniform grids.
+    pub crop: ThumbnailCrop,
+    /// The sizes of the thumbnails that are created for every file.
+    /// Thumbnails of other sizes are only created when they are requested.
+    pub sizes: Vec<ThumbnailSizeSetting>,
+}
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            crop: ThumbnailCrop::default(),
+            sizes: vec![ThumbnailSizeSetting::Medium],
+        }
+    }
+}
+
+/// A size of the thumbnails that are created for every file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ThumbnailSizeSetting {
+    Small,
+    Medium,
+    Large,
+}
+
+impl From<ThumbnailSizeSetting> for ThumbnailSize {
+    fn from(size: ThumbnailSizeSetting) -> Self {
+        match size {
+            ThumbnailSizeSetting::Small => ThumbnailSize::Small,
+            ThumbnailSizeSetting::Medium => ThumbnailSize::Medium,
+            ThumbnailSizeSetting::Large => ThumbnailSize::Large,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 use futures::future;
 use tokio::fs::{self, OpenOptions};
@@ -6,28 +7,56 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 
 use crate::error::RepoResult;
 
-/// Parses a normalized tag into its two components of namespace and tag
-pub fn parse_namespace_and_tag(norm_tag: String) -> (Option<String>, String) {
+/// Parses a normalized tag into its two components of namespace and tag.
+/// The tag is lowercased unless `case_sensitive` is set
+pub fn parse_namespace_and_tag(norm_tag: String, case_sensitive: bool) -> (Option<String>, String) {
+    let norm_tag = if case_sensitive {
+        norm_tag
+    } else {
+        norm_tag.to_lowercase()
+    };
     norm_tag
-        .to_lowercase()
         .split_once(':')
         .map(|(n, t)| (Some(n.trim().to_string()), t.trim().to_string()))
-        .unwrap_or((None, norm_tag.trim().to_string()))
+        .unwrap_or_else(|| (None, norm_tag.trim().to_string()))
 }
 
 /// Parses all tags from a file
-pub async fn parse_tags_file(path: &Path) -> RepoResult<Vec<(Option<String>, String)>> {
+pub async fn parse_tags_file(
+    path: &Path,
+    case_sensitive: bool,
+) -> RepoResult<Vec<(Option<String>, String)>> {
     let file = OpenOptions::new().read(true).open(path).await?;
     let mut lines = BufReader::new(file).lines();
     let mut tags = Vec::new();
 
     while let Some(line) = lines.next_line().await? {
-        tags.push(parse_namespace_and_tag(line));
+        tags.push(parse_namespace_and_tag(line, case_sensitive));
     }
 
     Ok(tags)
 }
 
+/// The number of bytes at the start of a file that are read to detect its type
+pub const MIME_SNIFF_LENGTH: u64 = 8192;
+
+/// Detects the mime type of a file from the magic bytes at the start of its content.
+/// Returns None if the type isn't recognized
+pub fn sniff_mime_type(bytes: &[u8]) -> Option<String> {
+    infer::get(bytes).map(|kind| kind.mime_type().to_string())
+}
+
+/// Checks if ffmpeg can be executed. The thumbnailer requires it to create thumbnails for videos
+pub fn is_ffmpeg_installed() -> bool {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
 /// Iteratively scans the size of a folder
 #[tracing::instrument(level = "debug")]
 pub async fn get_folder_size(path: PathBuf) -> RepoResult<u64> {
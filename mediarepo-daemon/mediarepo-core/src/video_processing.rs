@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, ImageFormat, RgbImage};
+
+use crate::error::{RepoError, RepoResult};
+use crate::utils::is_ffmpeg_installed;
+
+/// The width of a single frame of a contact sheet
+const SHEET_FRAME_WIDTH: u32 = 320;
+
+/// Extracts `rows * cols` evenly spaced frames of the video and tiles them
+/// row by row into a single image. Requires ffmpeg and ffprobe to be installed
+pub fn create_contact_sheet(video: &Path, rows: u32, cols: u32) -> RepoResult<DynamicImage> {
+    let frame_count = rows * cols;
+
+    if frame_count == 0 {
+        return Err(RepoError::from(
+            "a contact sheet needs at least one row and column",
+        ));
+    }
+    let frames = extract_video_frames(video, frame_count)?
+        .into_iter()
+        .map(|(_, frame)| frame)
+        .collect();
+
+    Ok(tile_frames(frames, rows, cols))
+}
+
+/// Extracts `count` evenly spaced frames of the video together with their position
+/// as a fraction of the video's duration. Requires ffmpeg and ffprobe to be installed
+pub fn extract_video_frames(video: &Path, count: u32) -> RepoResult<Vec<(f64, DynamicImage)>> {
+    let duration = video_duration(video)?;
+
+    (0..count)
+        .map(|i| {
+            // the frames are taken from the middle of equally long sections
+            // so that the black first and last frames are skipped
+            let fraction = (i as f64 + 0.5) / count as f64;
+            let frame = extract_video_frame(video, duration * fraction)?;
+
+            Ok((fraction, frame))
+        })
+        .collect()
+}
+
+/// Extracts the frame in the middle of the video as a png image to create thumbnails from.
+/// Videos without a readable duration fall back to their first frame
+pub fn extract_thumbnail_frame(video: &Path) -> RepoResult<Vec<u8>> {
+    if !is_ffmpeg_installed() {
+        return Err(RepoError::ThumbnailFailed(String::from(
+            "ffmpeg is required to create thumbnails of videos but it isn't installed",
+        )));
+    }
+    let position = video_duration(video)
+        .map(|duration| duration / 2.0)
+        .unwrap_or(0.0);
+
+    extract_png_frame(video, position)
+        .or_else(|_| extract_png_frame(video, 0.0))
+        .map_err(|_| RepoError::ThumbnailFailed(String::from("the video has no decodable frames")))
+}
+
+/// Places the frames row by row in a grid of the given size.
+/// Every frame is scaled into a cell with the aspect ratio of the first frame
+fn tile_frames(frames: Vec<DynamicImage>, rows: u32, cols: u32) -> DynamicImage {
+    let (first_width, first_height) = frames
+        .first()
+        .map(|f| (f.width().max(1), f.height().max(1)))
+        .unwrap_or((1, 1));
+    let cell_width = SHEET_FRAME_WIDTH;
+    let cell_height =
+        ((cell_width as u64 * first_height as u64) / first_width as u64).max(1) as u32;
+    let mut sheet = RgbImage::new(cell_width * cols, cell_height * rows);
+
+    for (i, frame) in frames.into_iter().enumerate() {
+        let frame = frame
+            .resize(cell_width, cell_height, FilterType::Triangle)
+            .into_rgb8();
+        let column = i as u32 % cols;
+        let row = i as u32 / cols;
+        let x = column * cell_width + (cell_width - frame.width()) / 2;
+        let y = row * cell_height + (cell_height - frame.height()) / 2;
+        imageops::overlay(&mut sheet, &frame, x as i64, y as i64);
+    }
+
+    DynamicImage::ImageRgb8(sheet)
+}
+
+/// Returns the duration of the video in seconds
+fn video_duration(video: &Path) -> RepoResult<f64> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(video)
+        .stderr(Stdio::null())
+        .output()?;
+    let duration = String::from_utf8_lossy(&output.stdout);
+
+    duration
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|d| output.status.success() && *d > 0.0)
+        .ok_or_else(|| RepoError::from("failed to read the duration of the video"))
+}
+
+/// Decodes the frame at the given position in seconds
+fn extract_video_frame(video: &Path, position: f64) -> RepoResult<DynamicImage> {
+    let png_bytes = extract_png_frame(video, position)?;
+
+    Ok(image::load_from_memory_with_format(
+        &png_bytes,
+        ImageFormat::Png,
+    )?)
+}
+
+/// Returns the frame at the given position in seconds encoded as png
+fn extract_png_frame(video: &Path, position: f64) -> RepoResult<Vec<u8>> {
+    let output = Command::new("ffmpeg")
+        .args([
+            "-loglevel",
+            "error",
+            "-ss",
+            &format!("{:.3}", position),
+            "-i",
+        ])
+        .arg(video)
+        .args([
+            "-frames:v",
+            "1",
+            "-c:v",
+            "png",
+            "-f",
+            "image2pipe",
+            "pipe:1",
+        ])
+        .stderr(Stdio::null())
+        .output()?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(RepoError::from(&*format!(
+            "failed to extract the video frame at {:.3}s",
+            position
+        )));
+    }
+
+    Ok(output.stdout)
+}
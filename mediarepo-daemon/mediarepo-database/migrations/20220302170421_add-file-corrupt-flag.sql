@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE files
+    ADD COLUMN corrupt BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,8 @@
+-- Add migration script here
+ALTER TABLE file_metadata
+    ADD COLUMN view_count INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE file_metadata
+    ADD COLUMN access_time DATETIME;
+
+CREATE INDEX file_metadata_view_count ON file_metadata (view_count);
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE files
+    ADD COLUMN external_id VARCHAR(255);
+
+CREATE UNIQUE INDEX file_external_id ON files (external_id);
@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE file_metadata
+    ADD COLUMN latitude REAL;
+ALTER TABLE file_metadata
+    ADD COLUMN longitude REAL;
+
+CREATE INDEX file_metadata_location ON file_metadata (latitude, longitude);
@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE file_metadata
+    ADD COLUMN width INTEGER;
+ALTER TABLE file_metadata
+    ADD COLUMN height INTEGER;
+
+CREATE INDEX file_metadata_dimensions ON file_metadata (width, height);
@@ -0,0 +1,9 @@
+-- Add migration script here
+CREATE TABLE tag_aliases
+(
+    id     INTEGER PRIMARY KEY AUTOINCREMENT,
+    name   VARCHAR(512) NOT NULL UNIQUE,
+    tag_id INTEGER      NOT NULL REFERENCES tags (id) ON DELETE CASCADE
+);
+
+CREATE INDEX tag_alias_tag_id ON tag_aliases (tag_id);
@@ -0,0 +1,13 @@
+-- Add migration script here
+CREATE TABLE tag_implications
+(
+    tag_id         INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    implied_tag_id INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (tag_id, implied_tag_id)
+);
+
+CREATE INDEX tag_implication_implied_tag_id ON tag_implications (implied_tag_id);
+
+-- mappings that were only added because another tag of the content implies them
+ALTER TABLE cd_tag_mappings
+    ADD COLUMN implied BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE file_metadata
+    ADD COLUMN phash INTEGER;
+
+CREATE INDEX file_metadata_phash ON file_metadata (phash);
@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TABLE attribute_fields
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    name       VARCHAR(128) NOT NULL UNIQUE,
+    value_type INTEGER      NOT NULL
+);
+
+CREATE TABLE file_attributes
+(
+    file_id  INTEGER NOT NULL REFERENCES files (id) ON DELETE CASCADE,
+    field_id INTEGER NOT NULL REFERENCES attribute_fields (id) ON DELETE CASCADE,
+    value    TEXT    NOT NULL,
+    PRIMARY KEY (file_id, field_id)
+);
+
+CREATE INDEX file_attribute_field_id ON file_attributes (field_id);
@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE file_exif
+(
+    file_id      INTEGER PRIMARY KEY REFERENCES files (id) ON DELETE CASCADE,
+    camera_make  VARCHAR(128),
+    camera_model VARCHAR(128),
+    capture_time DATETIME,
+    latitude     REAL,
+    longitude    REAL,
+    orientation  INTEGER
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE files
+    ADD COLUMN indexed BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,3 @@
+-- Add migration script here
+CREATE INDEX index_tag_name_nocase ON tags (name COLLATE NOCASE);
+CREATE INDEX index_namespace_name_nocase ON namespaces (name COLLATE NOCASE);
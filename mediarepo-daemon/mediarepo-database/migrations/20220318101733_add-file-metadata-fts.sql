@@ -0,0 +1,36 @@
+-- Add migration script here
+CREATE VIRTUAL TABLE file_metadata_fts USING fts5
+(
+    name,
+    comment,
+    content = 'file_metadata',
+    content_rowid = 'file_id',
+    tokenize = 'trigram'
+);
+
+INSERT INTO file_metadata_fts (file_metadata_fts)
+VALUES ('rebuild');
+
+CREATE TRIGGER file_metadata_fts_insert
+    AFTER INSERT
+    ON file_metadata
+BEGIN
+    INSERT INTO file_metadata_fts (rowid, name, comment) VALUES (new.file_id, new.name, new.comment);
+END;
+
+CREATE TRIGGER file_metadata_fts_delete
+    AFTER DELETE
+    ON file_metadata
+BEGIN
+    INSERT INTO file_metadata_fts (file_metadata_fts, rowid, name, comment)
+    VALUES ('delete', old.file_id, old.name, old.comment);
+END;
+
+CREATE TRIGGER file_metadata_fts_update
+    AFTER UPDATE OF name, comment
+    ON file_metadata
+BEGIN
+    INSERT INTO file_metadata_fts (file_metadata_fts, rowid, name, comment)
+    VALUES ('delete', old.file_id, old.name, old.comment);
+    INSERT INTO file_metadata_fts (rowid, name, comment) VALUES (new.file_id, new.name, new.comment);
+END;
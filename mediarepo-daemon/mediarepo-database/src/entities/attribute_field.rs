@@ -0,0 +1,38 @@
+use sea_orm::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "attribute_fields")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub name: String,
+    pub value_type: AttributeValueType,
+}
+
+/// The type the values of a field are validated against
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "u32", db_type = "Integer")]
+pub enum AttributeValueType {
+    #[sea_orm(num_value = 10)]
+    Text,
+    #[sea_orm(num_value = 20)]
+    Number,
+    #[sea_orm(num_value = 30)]
+    Boolean,
+    #[sea_orm(num_value = 40)]
+    Date,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::file_attribute::Entity")]
+    FileAttribute,
+}
+
+impl Related<super::file_attribute::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FileAttribute.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -7,6 +7,8 @@ pub struct Model {
     pub cd_id: i64,
     #[sea_orm(primary_key)]
     pub tag_id: i64,
+    /// If the mapping only exists because another tag of the content implies it
+    pub implied: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -8,6 +8,9 @@ pub struct Model {
     pub status: i32,
     pub mime_type: String,
     pub cd_id: i64,
+    pub corrupt: bool,
+    pub external_id: Option<String>,
+    pub indexed: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,35 @@
+use sea_orm::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "file_attributes")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub file_id: i64,
+    #[sea_orm(primary_key)]
+    pub field_id: i64,
+    pub value: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::file::Entity",
+        from = "Column::FileId",
+        to = "super::file::Column::Id"
+    )]
+    File,
+    #[sea_orm(
+        belongs_to = "super::attribute_field::Entity",
+        from = "Column::FieldId",
+        to = "super::attribute_field::Column::Id"
+    )]
+    AttributeField,
+}
+
+impl Related<super::attribute_field::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AttributeField.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
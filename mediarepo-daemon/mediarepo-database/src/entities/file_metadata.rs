@@ -12,6 +12,13 @@ pub struct Model {
     pub import_time: NaiveDateTime,
     pub creation_time: NaiveDateTime,
     pub change_time: NaiveDateTime,
+    pub view_count: i64,
+    pub access_time: Option<NaiveDateTime>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub phash: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -22,6 +22,8 @@ pub enum JobType {
     CheckIntegrity,
     #[sea_orm(num_value = 50)]
     Vacuum,
+    #[sea_orm(num_value = 60)]
+    MigrateStorage,
 }
 
 impl TryFromU64 for JobType {
@@ -32,6 +34,7 @@ impl TryFromU64 for JobType {
             30 => Self::GenerateThumbs,
             40 => Self::CheckIntegrity,
             50 => Self::Vacuum,
+            60 => Self::MigrateStorage,
             _ => return Err(DbErr::Custom(String::from("Invalid job type"))),
         };
 
@@ -1,7 +1,10 @@
+pub mod attribute_field;
 pub mod content_descriptor;
 pub mod content_descriptor_source;
 pub mod content_descriptor_tag;
 pub mod file;
+pub mod file_attribute;
+pub mod file_exif;
 pub mod file_metadata;
 pub mod job_state;
 pub mod namespace;
@@ -10,3 +13,5 @@ pub mod sorting_preset;
 pub mod sorting_preset_key;
 pub mod source;
 pub mod tag;
+pub mod tag_alias;
+pub mod tag_implication;
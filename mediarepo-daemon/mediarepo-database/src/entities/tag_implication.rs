@@ -0,0 +1,28 @@
+use sea_orm::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "tag_implications")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub tag_id: i64,
+    #[sea_orm(primary_key)]
+    pub implied_tag_id: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tag::Entity",
+        from = "Column::TagId",
+        to = "super::tag::Column::Id"
+    )]
+    Tag,
+    #[sea_orm(
+        belongs_to = "super::tag::Entity",
+        from = "Column::ImpliedTagId",
+        to = "super::tag::Column::Id"
+    )]
+    ImpliedTag,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
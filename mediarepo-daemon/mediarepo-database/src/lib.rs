@@ -1,24 +1,81 @@
 use std::time::Duration;
 
-use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use sea_orm::{DatabaseConnection, SqlxSqliteConnector};
 use sqlx::migrate::MigrateDatabase;
+use sqlx::pool::PoolOptions;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous};
+use sqlx::ConnectOptions;
 
 use mediarepo_core::error::RepoDatabaseResult;
+use mediarepo_core::settings::{DatabaseSettings, JournalMode, SynchronousMode};
 
 pub mod entities;
 pub mod queries;
 
-/// Connects to the database, runs migrations and returns the RepoDatabase wrapper type
-pub async fn get_database<S: AsRef<str>>(uri: S) -> RepoDatabaseResult<DatabaseConnection> {
-    migrate(uri.as_ref()).await?;
-    let mut opt = ConnectOptions::new(uri.as_ref().to_string());
-    opt.connect_timeout(Duration::from_secs(10))
-        .idle_timeout(Duration::from_secs(10))
-        .sqlx_logging(false);
+/// The maximum number of pooled connections to a database file
+const MAX_CONNECTIONS: u32 = 10;
 
-    let conn = Database::connect(opt).await?;
+/// Connects to the database, runs migrations and returns the RepoDatabase wrapper type.
+/// In-memory databases (`:memory:`) are migrated through the pool because every
+/// separate connection would open a new empty database.
+pub async fn get_database<S: AsRef<str>>(
+    uri: S,
+    settings: &DatabaseSettings,
+) -> RepoDatabaseResult<DatabaseConnection> {
+    let in_memory = is_in_memory_uri(uri.as_ref());
 
-    Ok(conn)
+    if !in_memory {
+        migrate(uri.as_ref()).await?;
+    }
+    let mut opt = uri
+        .as_ref()
+        .parse::<SqliteConnectOptions>()?
+        .journal_mode(journal_mode(settings.journal_mode))
+        .synchronous(synchronous(settings.synchronous));
+    opt.disable_statement_logging();
+
+    let pool_options = PoolOptions::new().connect_timeout(Duration::from_secs(10));
+    let pool_options = if in_memory {
+        // the database only lives as long as its connection
+        pool_options
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+    } else {
+        pool_options
+            .max_connections(MAX_CONNECTIONS)
+            .idle_timeout(Duration::from_secs(10))
+    };
+    let pool = pool_options.connect_with(opt).await?;
+
+    if in_memory {
+        sqlx::migrate!().run(&pool).await?;
+    }
+
+    Ok(SqlxSqliteConnector::from_sqlx_sqlite_pool(pool))
+}
+
+/// Checks if the uri points to an in-memory database
+fn is_in_memory_uri(uri: &str) -> bool {
+    let database = uri.trim_start_matches("sqlite:").trim_start_matches("//");
+
+    database == ":memory:" || database.contains("mode=memory")
+}
+
+fn journal_mode(mode: JournalMode) -> SqliteJournalMode {
+    match mode {
+        JournalMode::Wal => SqliteJournalMode::Wal,
+        JournalMode::Delete => SqliteJournalMode::Delete,
+    }
+}
+
+fn synchronous(mode: SynchronousMode) -> SqliteSynchronous {
+    match mode {
+        SynchronousMode::Off => SqliteSynchronous::Off,
+        SynchronousMode::Normal => SqliteSynchronous::Normal,
+        SynchronousMode::Full => SqliteSynchronous::Full,
+    }
 }
 
 async fn migrate(uri: &str) -> RepoDatabaseResult<()> {
@@ -28,6 +85,7 @@ async fn migrate(uri: &str) -> RepoDatabaseResult<()> {
     }
     let mut conn = sqlx::SqliteConnection::connect(uri).await?;
     sqlx::migrate!().run(&mut conn).await?;
+    conn.close().await?;
 
     Ok(())
 }
@@ -34,3 +34,35 @@ pub async fn get_all_counts(db: &DatabaseConnection) -> RepoResult<Counts> {
 
     Ok(counts)
 }
+
+#[derive(Debug, FromQueryResult)]
+pub struct DedupStats {
+    /// The number of files
+    pub file_count: i64,
+    /// The number of distinct contents referenced by files
+    pub blob_count: i64,
+    /// The size of all files that share their content with another file
+    pub bytes_saved: i64,
+}
+
+pub async fn get_dedup_stats(db: &DatabaseConnection) -> RepoResult<DedupStats> {
+    let stats = DedupStats::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        r#"
+    SELECT *
+    FROM (SELECT COUNT(*) AS file_count FROM files),
+         (SELECT COUNT(DISTINCT cd_id) AS blob_count FROM files),
+         (SELECT COALESCE(SUM(saved), 0) AS bytes_saved
+          FROM (SELECT SUM(fm.size) - MAX(fm.size) AS saved
+                FROM files f
+                         INNER JOIN file_metadata fm ON fm.file_id = f.id
+                GROUP BY f.cd_id))
+    "#,
+        vec![],
+    ))
+    .one(db)
+    .await?
+    .ok_or_else(|| RepoError::from("could not retrieve deduplication stats from database"))?;
+
+    Ok(stats)
+}
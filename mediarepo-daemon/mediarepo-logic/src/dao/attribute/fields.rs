@@ -0,0 +1,136 @@
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::{Set, Unchanged};
+use sea_orm::{DatabaseTransaction, TransactionTrait};
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::{attribute_field, file_attribute};
+
+use crate::dao::attribute::{normalize_value, AttributeDao};
+use crate::dto::{AddAttributeFieldDto, AttributeFieldDto, UpdateAttributeFieldDto};
+
+/// The maximum length of the name of an attribute field
+const MAX_FIELD_NAME_LENGTH: usize = 128;
+
+impl AttributeDao {
+    /// Registers a new custom attribute field whose values have to be of the given type
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_field(&self, dto: AddAttributeFieldDto) -> RepoResult<AttributeFieldDto> {
+        let name = validate_field_name(&dto.name)?;
+        let trx = self.ctx.db.begin().await?;
+        ensure_name_unused(&trx, &name, None).await?;
+        let model = attribute_field::ActiveModel {
+            name: Set(name),
+            value_type: Set(dto.value_type),
+            ..Default::default()
+        }
+        .insert(&trx)
+        .await?;
+        trx.commit().await?;
+
+        Ok(AttributeFieldDto::new(model))
+    }
+
+    /// Renames a field or changes its type. The type can only be changed
+    /// if all values already assigned to files are valid for the new type
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_field(
+        &self,
+        dto: UpdateAttributeFieldDto,
+    ) -> RepoResult<AttributeFieldDto> {
+        let trx = self.ctx.db.begin().await?;
+        let mut field = attribute_field::Entity::find_by_id(dto.id)
+            .one(&trx)
+            .await?
+            .ok_or_else(|| RepoError::from("attribute field not found"))?;
+
+        if let Some(name) = dto.name {
+            let name = validate_field_name(&name)?;
+            ensure_name_unused(&trx, &name, Some(field.id)).await?;
+            field.name = name;
+        }
+        if let Some(value_type) = dto.value_type.filter(|t| *t != field.value_type) {
+            field.value_type = value_type;
+            convert_values(&trx, &field).await?;
+        }
+        let model = attribute_field::ActiveModel {
+            id: Unchanged(field.id),
+            name: Set(field.name),
+            value_type: Set(field.value_type),
+        }
+        .update(&trx)
+        .await?;
+        trx.commit().await?;
+
+        Ok(AttributeFieldDto::new(model))
+    }
+
+    /// Deletes a field together with all of its values
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_field(&self, id: i64) -> RepoResult<()> {
+        attribute_field::Entity::delete_by_id(id)
+            .exec(&self.ctx.db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn validate_field_name(name: &str) -> RepoResult<String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        Err(RepoError::from(
+            "the name of an attribute field cannot be empty",
+        ))
+    } else if name.chars().count() > MAX_FIELD_NAME_LENGTH {
+        Err(RepoError::from(&*format!(
+            "the name of an attribute field cannot be longer than {} characters",
+            MAX_FIELD_NAME_LENGTH
+        )))
+    } else {
+        Ok(name.to_owned())
+    }
+}
+
+async fn ensure_name_unused(
+    trx: &DatabaseTransaction,
+    name: &str,
+    field_id: Option<i64>,
+) -> RepoResult<()> {
+    let existing = attribute_field::Entity::find()
+        .filter(attribute_field::Column::Name.eq(name))
+        .one(trx)
+        .await?;
+
+    match existing {
+        Some(existing) if Some(existing.id) != field_id => Err(RepoError::from(&*format!(
+            "an attribute field named \"{}\" already exists",
+            name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Converts all values of the field into the normalized form of its new type
+/// and fails if any of them isn't valid for that type
+async fn convert_values(
+    trx: &DatabaseTransaction,
+    field: &attribute_field::Model,
+) -> RepoResult<()> {
+    let attributes = file_attribute::Entity::find()
+        .filter(file_attribute::Column::FieldId.eq(field.id))
+        .all(trx)
+        .await?;
+
+    for attribute in attributes {
+        let value = normalize_value(field, &attribute.value)?;
+
+        if value != attribute.value {
+            let mut active_model: file_attribute::ActiveModel = attribute.into();
+            active_model.value = Set(value);
+            active_model.update(trx).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use chrono::NaiveDate;
+use sea_orm::prelude::*;
+use sea_orm::QueryOrder;
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::{attribute_field, file_attribute};
+
+use crate::dao_provider;
+use crate::dto::{AttributeFieldDto, AttributeValueType, FileAttributeDto};
+
+pub mod fields;
+pub mod values;
+
+dao_provider!(AttributeDao);
+
+/// The format dates are stored and accepted in
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+impl AttributeDao {
+    /// Returns all custom attribute fields ordered by name
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_fields(&self) -> RepoResult<Vec<AttributeFieldDto>> {
+        let fields = attribute_field::Entity::find()
+            .order_by_asc(attribute_field::Column::Name)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(AttributeFieldDto::new)
+            .collect();
+
+        Ok(fields)
+    }
+
+    /// Returns the attribute values assigned to a file ordered by the names of their fields
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn file_attributes(&self, file_id: i64) -> RepoResult<Vec<FileAttributeDto>> {
+        let attributes = file_attribute::Entity::find()
+            .find_also_related(attribute_field::Entity)
+            .filter(file_attribute::Column::FileId.eq(file_id))
+            .order_by_asc(attribute_field::Column::Name)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(|(attribute, field)| Some(FileAttributeDto::new(attribute, field?)))
+            .collect();
+
+        Ok(attributes)
+    }
+}
+
+/// Validates a value against the type of its field and returns it in
+/// the normalized form it is stored in
+pub(crate) fn normalize_value(field: &attribute_field::Model, value: &str) -> RepoResult<String> {
+    let value = value.trim();
+    let normalized = match field.value_type {
+        AttributeValueType::Text => Some(value.to_owned()),
+        AttributeValueType::Number => value
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|n| n.to_string()),
+        AttributeValueType::Boolean => value.parse::<bool>().ok().map(|b| b.to_string()),
+        AttributeValueType::Date => NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .ok()
+            .map(|d| d.format(DATE_FORMAT).to_string()),
+    };
+
+    normalized.ok_or_else(|| RepoError::InvalidAttributeValue {
+        field: field.name.clone(),
+        value: value.to_owned(),
+        reason: format!("expected a value of type {:?}", field.value_type),
+    })
+}
@@ -0,0 +1,157 @@
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::TransactionTrait;
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::{attribute_field, file, file_attribute};
+
+use crate::dao::attribute::{normalize_value, AttributeDao};
+use crate::dto::FileAttributeDto;
+
+impl AttributeDao {
+    /// Assigns a value of a custom field to a file or replaces the existing value.
+    /// Values that aren't valid for the type of the field are rejected
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_file_attribute(
+        &self,
+        file_id: i64,
+        field_id: i64,
+        value: String,
+    ) -> RepoResult<FileAttributeDto> {
+        let trx = self.ctx.db.begin().await?;
+        let field = attribute_field::Entity::find_by_id(field_id)
+            .one(&trx)
+            .await?
+            .ok_or_else(|| RepoError::from("attribute field not found"))?;
+        let value = normalize_value(&field, &value)?;
+
+        if file::Entity::find_by_id(file_id).one(&trx).await?.is_none() {
+            return Err(RepoError::from("file not found"));
+        }
+        let existing = file_attribute::Entity::find()
+            .filter(file_attribute::Column::FileId.eq(file_id))
+            .filter(file_attribute::Column::FieldId.eq(field_id))
+            .one(&trx)
+            .await?;
+        let model = if let Some(existing) = existing {
+            let mut active_model: file_attribute::ActiveModel = existing.into();
+            active_model.value = Set(value);
+            active_model.update(&trx).await?
+        } else {
+            file_attribute::ActiveModel {
+                file_id: Set(file_id),
+                field_id: Set(field_id),
+                value: Set(value),
+            }
+            .insert(&trx)
+            .await?
+        };
+        trx.commit().await?;
+
+        Ok(FileAttributeDto::new(model, field))
+    }
+
+    /// Removes the value of a field from a file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_file_attribute(&self, file_id: i64, field_id: i64) -> RepoResult<()> {
+        file_attribute::Entity::delete_many()
+            .filter(file_attribute::Column::FileId.eq(file_id))
+            .filter(file_attribute::Column::FieldId.eq(field_id))
+            .exec(&self.ctx.db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::error::RepoError;
+
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddAttributeFieldDto, AttributeValueType, UpdateAttributeFieldDto};
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_validates_attribute_values_against_their_field_type() {
+        let (repo, root) = test_repo("attributes").await;
+        let file = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let attribute_dao = repo.attribute();
+        let price = attribute_dao
+            .add_field(AddAttributeFieldDto {
+                name: String::from("price"),
+                value_type: AttributeValueType::Number,
+            })
+            .await
+            .unwrap();
+        let acquired = attribute_dao
+            .add_field(AddAttributeFieldDto {
+                name: String::from("acquired"),
+                value_type: AttributeValueType::Date,
+            })
+            .await
+            .unwrap();
+        assert!(attribute_dao
+            .add_field(AddAttributeFieldDto {
+                name: String::from("price"),
+                value_type: AttributeValueType::Text,
+            })
+            .await
+            .is_err());
+
+        let result = attribute_dao
+            .set_file_attribute(file.id(), price.id(), String::from("cheap"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(RepoError::InvalidAttributeValue { .. })
+        ));
+        let result = attribute_dao
+            .set_file_attribute(file.id(), acquired.id(), String::from("2022-02-30"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(RepoError::InvalidAttributeValue { .. })
+        ));
+        attribute_dao
+            .set_file_attribute(file.id(), price.id(), String::from(" 12.50 "))
+            .await
+            .unwrap();
+        attribute_dao
+            .set_file_attribute(file.id(), acquired.id(), String::from("2022-03-12"))
+            .await
+            .unwrap();
+
+        let attributes = attribute_dao.file_attributes(file.id()).await.unwrap();
+        let values: Vec<(&str, &str)> = attributes
+            .iter()
+            .map(|a| (a.field().name().as_str(), a.value().as_str()))
+            .collect();
+        assert_eq!(values, vec![("acquired", "2022-03-12"), ("price", "12.5")]);
+
+        let result = attribute_dao
+            .update_field(UpdateAttributeFieldDto {
+                id: acquired.id(),
+                name: None,
+                value_type: Some(AttributeValueType::Boolean),
+            })
+            .await;
+        assert!(result.is_err());
+        let field = attribute_dao
+            .update_field(UpdateAttributeFieldDto {
+                id: acquired.id(),
+                name: Some(String::from("bought")),
+                value_type: Some(AttributeValueType::Text),
+            })
+            .await
+            .unwrap();
+        assert_eq!(field.name(), "bought");
+        assert_eq!(field.value_type(), AttributeValueType::Text);
+
+        attribute_dao.delete_field(price.id()).await.unwrap();
+        let attributes = attribute_dao.file_attributes(file.id()).await.unwrap();
+        assert_eq!(attributes.len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,66 +1,248 @@
-use std::io::Cursor;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
 
 use chrono::{Local, NaiveDateTime};
 use sea_orm::ActiveValue::Set;
-use sea_orm::{ActiveModelTrait, DatabaseTransaction, TransactionTrait};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseTransaction, EntityTrait, QueryFilter,
+    QueryOrder, TransactionTrait,
+};
+use tokio::io::AsyncRead;
 
-use mediarepo_core::error::RepoResult;
-use mediarepo_core::thumbnailer::ThumbnailSize;
-use mediarepo_database::entities::{content_descriptor, file, file_metadata};
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::image_processing::{
+    catch_thumbnail_panic, perceptual_hash, read_exif, read_image_dimensions, ExifData,
+};
+use mediarepo_database::entities::{content_descriptor, file, file_exif, file_metadata};
 
 use crate::dao::file::FileDao;
-use crate::dto::{AddFileDto, FileDto};
+use crate::dto::{AddFileDto, AddFileInfoDto, FileDto};
 
 impl FileDao {
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn add(&self, add_dto: AddFileDto) -> RepoResult<FileDto> {
+        let (content, info) = add_dto.into_parts();
+        let (dto, _) = self.add_by_reader(Cursor::new(content), info).await?;
+
+        Ok(dto)
+    }
+
+    /// Adds a file by streaming its content from the reader into the storage
+    /// so that files larger than the available memory can be imported.
+    /// If a file with the same content exists, that file is returned instead
+    /// of adding a duplicate together with `true` as the second value
+    #[tracing::instrument(level = "debug", skip(self, reader))]
+    pub async fn add_by_reader<R: AsyncRead + Unpin>(
+        &self,
+        reader: R,
+        info: AddFileInfoDto,
+    ) -> RepoResult<(FileDto, bool)> {
+        let content = self.store_content(reader, &info.mime_type).await?;
+        let generate_thumbnails = info.generate_thumbnails;
         let trx = self.ctx.db.begin().await?;
-        let file_size = add_dto.content.len();
-        let cd_bin = self
-            .ctx
-            .main_storage
-            .add_file(Cursor::new(add_dto.content), None)
-            .await?;
-        let cd_model = content_descriptor::ActiveModel {
-            descriptor: Set(cd_bin),
-            ..Default::default()
-        };
-        let cd = cd_model.insert(&trx).await?;
+        let (dto, duplicate) = insert_file(&trx, content, info).await?;
+        trx.commit().await?;
 
-        let model = file::ActiveModel {
-            cd_id: Set(cd.id),
-            mime_type: Set(add_dto.mime_type),
-            ..Default::default()
+        if !duplicate {
+            self.create_import_thumbnails(&dto, generate_thumbnails)
+                .await?;
+        }
+
+        Ok((dto, duplicate))
+    }
+
+    /// Writes the content into the storage and reads the properties
+    /// stored in the database from it
+    pub(crate) async fn store_content<R: AsyncRead + Unpin>(
+        &self,
+        reader: R,
+        mime_type: &str,
+    ) -> RepoResult<StoredContent> {
+        let (descriptor, size) = self.ctx.main_storage.add_file(reader, None).await?;
+        let properties = if mime_type.starts_with("image/") {
+            self.read_image_properties(&descriptor, mime_type).await?
+        } else {
+            ImageProperties::default()
         };
-        let file: file::Model = model.insert(&trx).await?;
-
-        let metadata = add_file_metadata(
-            &trx,
-            file.id,
-            file_size as i64,
-            add_dto.creation_time,
-            add_dto.change_time,
-            add_dto.name,
-        )
-        .await?;
 
-        trx.commit().await?;
-        let dto = FileDto::new(file, cd, Some(metadata));
-        self.create_thumbnails(&dto, vec![ThumbnailSize::Medium])
+        Ok(StoredContent {
+            descriptor,
+            size,
+            properties,
+        })
+    }
+
+    /// Creates the thumbnails of a newly imported file if requested
+    pub(crate) async fn create_import_thumbnails(
+        &self,
+        dto: &FileDto,
+        generate_thumbnails: bool,
+    ) -> RepoResult<()> {
+        if dto.corrupt() {
+            tracing::debug!("skipping thumbnail creation for corrupt file");
+        } else if generate_thumbnails {
+            self.create_configured_thumbnails(dto).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the exif data, dimensions and perceptual hash of a stored image
+    async fn read_image_properties(
+        &self,
+        cd: &[u8],
+        mime_type: &str,
+    ) -> RepoResult<ImageProperties> {
+        let (_, reader) = self.ctx.main_storage.get_file(cd).await?;
+        let file = reader.into_inner().into_std().await;
+        let mime_type = mime_type.to_owned();
+
+        let properties = tokio::task::spawn_blocking(move || {
+            let mut reader = BufReader::new(file);
+            let exif = read_exif(&mut reader);
+            let dimensions = reader
+                .seek(SeekFrom::Start(0))
+                .ok()
+                .and_then(|_| read_image_dimensions(&mut reader, &mime_type));
+            let mut bytes = Vec::new();
+            let phash = reader
+                .seek(SeekFrom::Start(0))
+                .and_then(|_| reader.read_to_end(&mut bytes))
+                .ok()
+                .and_then(|_| catch_thumbnail_panic(|| perceptual_hash(&bytes, &mime_type)).ok());
+
+            ImageProperties {
+                location: exif.as_ref().and_then(|e| e.location),
+                dimensions,
+                phash,
+                exif,
+            }
+        })
+        .await
+        .map_err(|e| RepoError::from(&*e.to_string()))?;
+
+        Ok(properties)
+    }
+}
+
+/// The content of a file in the storage that hasn't been added to the database yet
+pub(crate) struct StoredContent {
+    descriptor: Vec<u8>,
+    size: u64,
+    properties: ImageProperties,
+}
+
+/// The properties read from the content of an image.
+/// All properties are missing for contents that aren't images
+#[derive(Default)]
+pub(crate) struct ImageProperties {
+    pub location: Option<(f64, f64)>,
+    pub dimensions: Option<(u32, u32)>,
+    pub phash: Option<u64>,
+    pub exif: Option<ExifData>,
+}
+
+/// Adds the file of stored content to the database.
+/// Returns the existing file with `true` if the content already belongs to a file
+pub(crate) async fn insert_file(
+    trx: &DatabaseTransaction,
+    mut content: StoredContent,
+    info: AddFileInfoDto,
+) -> RepoResult<(FileDto, bool)> {
+    let existing_cd = content_descriptor::Entity::find()
+        .filter(content_descriptor::Column::Descriptor.eq(content.descriptor.clone()))
+        .one(trx)
+        .await?;
+    if let Some(cd) = &existing_cd {
+        let existing_file = file::Entity::find()
+            .filter(file::Column::CdId.eq(cd.id))
+            .order_by_asc(file::Column::Id)
+            .one(trx)
             .await?;
+        if let Some(existing_file) = existing_file {
+            tracing::debug!("content already belongs to file {}", existing_file.id);
+            return Ok((FileDto::new(existing_file, cd.clone(), None), true));
+        }
+    }
+    if let Some(external_id) = &info.external_id {
+        check_external_id_unused(trx, external_id, None).await?;
+    }
+    let cd = if let Some(cd) = existing_cd {
+        cd
+    } else {
+        content_descriptor::ActiveModel {
+            descriptor: Set(content.descriptor),
+            ..Default::default()
+        }
+        .insert(trx)
+        .await?
+    };
 
-        Ok(dto)
+    let model = file::ActiveModel {
+        cd_id: Set(cd.id),
+        mime_type: Set(info.mime_type),
+        corrupt: Set(info.corrupt),
+        external_id: Set(info.external_id),
+        ..Default::default()
+    };
+    let file: file::Model = model.insert(trx).await?;
+    let exif = content.properties.exif.take();
+    // the time the photo was taken is more accurate than the time the file was created
+    let creation_time = exif
+        .as_ref()
+        .and_then(|e| e.capture_time)
+        .unwrap_or(info.creation_time);
+
+    let metadata = add_file_metadata(
+        trx,
+        file.id,
+        content.size as i64,
+        creation_time,
+        info.change_time,
+        info.name,
+        content.properties,
+    )
+    .await?;
+    if let Some(exif) = exif {
+        add_file_exif(trx, file.id, exif).await?;
+    }
+
+    Ok((FileDto::new(file, cd, Some(metadata)), false))
+}
+
+/// Fails with [RepoError::DuplicateExternalId] if a file other than the given one
+/// already has the external id
+pub(crate) async fn check_external_id_unused<C: ConnectionTrait>(
+    db: &C,
+    external_id: &str,
+    file_id: Option<i64>,
+) -> RepoResult<()> {
+    let mut query = file::Entity::find().filter(file::Column::ExternalId.eq(external_id));
+
+    if let Some(file_id) = file_id {
+        query = query.filter(file::Column::Id.ne(file_id));
+    }
+    if query.one(db).await?.is_some() {
+        return Err(RepoError::DuplicateExternalId(external_id.to_string()));
     }
+
+    Ok(())
 }
 
-async fn add_file_metadata(
+pub(crate) async fn add_file_metadata(
     trx: &DatabaseTransaction,
     file_id: i64,
     size: i64,
     creation_time: NaiveDateTime,
     change_time: NaiveDateTime,
     name: Option<String>,
+    properties: ImageProperties,
 ) -> RepoResult<file_metadata::Model> {
+    let ImageProperties {
+        location,
+        dimensions,
+        phash,
+        ..
+    } = properties;
     let metadata_model = file_metadata::ActiveModel {
         file_id: Set(file_id),
         size: Set(size),
@@ -68,6 +250,11 @@ async fn add_file_metadata(
         creation_time: Set(creation_time),
         change_time: Set(change_time),
         name: Set(name),
+        latitude: Set(location.map(|(lat, _)| lat)),
+        longitude: Set(location.map(|(_, lon)| lon)),
+        width: Set(dimensions.map(|(width, _)| width as i64)),
+        height: Set(dimensions.map(|(_, height)| height as i64)),
+        phash: Set(phash.map(|phash| phash as i64)),
         ..Default::default()
     };
 
@@ -75,3 +262,152 @@ async fn add_file_metadata(
 
     Ok(metadata)
 }
+
+async fn add_file_exif(trx: &DatabaseTransaction, file_id: i64, exif: ExifData) -> RepoResult<()> {
+    file_exif::ActiveModel {
+        file_id: Set(file_id),
+        camera_make: Set(exif.camera_make),
+        camera_model: Set(exif.camera_model),
+        capture_time: Set(exif.capture_time),
+        latitude: Set(exif.location.map(|(lat, _)| lat)),
+        longitude: Set(exif.location.map(|(_, lon)| lon)),
+        orientation: Set(exif.orientation.map(|o| o as i32)),
+    }
+    .insert(trx)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use mediarepo_core::content_descriptor::create_content_descriptor;
+    use mediarepo_core::error::RepoError;
+
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddFileDto, AddFileInfoDto, AddTagDto, UpdateFileDto};
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_streams_files_from_readers_into_the_storage() {
+        let (repo, root) = test_repo("reader").await;
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let now = Local::now().naive_local();
+
+        let (file, _) = repo
+            .file()
+            .add_by_reader(
+                &content[..],
+                AddFileInfoDto {
+                    mime_type: String::from("application/octet-stream"),
+                    creation_time: now,
+                    change_time: now,
+                    name: None,
+                    corrupt: false,
+                    external_id: None,
+                    generate_thumbnails: false,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(file.cd(), &create_content_descriptor(&content)[..]);
+        let metadata = repo.file().metadata(file.id()).await.unwrap().unwrap();
+        assert_eq!(metadata.size(), content.len() as i64);
+        assert_eq!(repo.file().get_bytes(file.cd()).await.unwrap(), content);
+
+        let staging_files = std::fs::read_dir(root.join("files"))
+            .unwrap()
+            .filter(|e| {
+                let name = e.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with(".staging")
+            })
+            .count();
+        assert_eq!(staging_files, 0);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_returns_existing_files_when_adding_duplicate_content() {
+        let (repo, root) = test_repo("duplicate-content").await;
+        let first = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let now = Local::now().naive_local();
+        let info = AddFileInfoDto {
+            mime_type: String::from("application/octet-stream"),
+            creation_time: now,
+            change_time: now,
+            name: Some(String::from("copy")),
+            corrupt: false,
+            external_id: None,
+            generate_thumbnails: false,
+        };
+
+        let (second, duplicate) = repo
+            .file()
+            .add_by_reader(&[1u8, 2, 3][..], info)
+            .await
+            .unwrap();
+        assert!(duplicate);
+        assert_eq!(second.id(), first.id());
+
+        let (file, tags) = repo
+            .add_file_with_tags(
+                AddFileDto {
+                    content: vec![1, 2, 3],
+                    mime_type: String::from("application/octet-stream"),
+                    creation_time: now,
+                    change_time: now,
+                    name: None,
+                    corrupt: false,
+                    external_id: None,
+                    generate_thumbnails: false,
+                },
+                vec![AddTagDto::from_tuple((None, String::from("copied")))],
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(file.id(), first.id());
+        assert_eq!(tags.len(), 1);
+        assert_eq!(repo.file().all().await.unwrap().len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_rejects_duplicate_external_ids() {
+        let (repo, root) = test_repo("duplicate-external-id").await;
+        let now = Local::now().naive_local();
+        let add_dto = |content: Vec<u8>| AddFileDto {
+            content,
+            mime_type: String::from("application/octet-stream"),
+            creation_time: now,
+            change_time: now,
+            name: None,
+            corrupt: false,
+            external_id: Some(String::from("post-1")),
+            generate_thumbnails: false,
+        };
+        let first = repo.file().add(add_dto(vec![1])).await.unwrap();
+        // adding the same content again returns the file that already has the id
+        let again = repo.file().add(add_dto(vec![1])).await.unwrap();
+        assert_eq!(again.id(), first.id());
+
+        let result = repo.file().add(add_dto(vec![2])).await;
+        assert!(matches!(result, Err(RepoError::DuplicateExternalId(id)) if id == "post-1"));
+        let other = add_file(&repo, vec![3], "application/octet-stream", false).await;
+        let update = |file_id: i64| UpdateFileDto {
+            id: file_id,
+            external_id: Some(Some(String::from("post-1"))),
+            ..Default::default()
+        };
+        let result = repo.file().update(update(other.id())).await;
+        assert!(matches!(result, Err(RepoError::DuplicateExternalId(_))));
+        assert!(repo.file().update(update(first.id())).await.is_ok());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
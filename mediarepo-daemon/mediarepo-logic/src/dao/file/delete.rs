@@ -7,12 +7,49 @@ use mediarepo_database::entities::{
 };
 
 use crate::dao::file::FileDao;
-use crate::dto::FileDto;
+use crate::dto::{FileDeletionDto, FileDto};
 
 impl FileDao {
+    /// Deletes the file with its metadata. The content with its tag mappings, thumbnails
+    /// and stored blob is only deleted if no other file shares it.
+    /// With `dry_run` nothing is deleted and only the report of what would be removed is returned.
+    /// The stored entries are deleted after the database changes were committed so that
+    /// a failure never leaves files pointing to missing blobs
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn delete(&self, file: FileDto) -> RepoResult<()> {
+    pub async fn delete(&self, file: FileDto, dry_run: bool) -> RepoResult<FileDeletionDto> {
         let trx = self.ctx.db.begin().await?;
+        let shared_count = file::Entity::find()
+            .filter(file::Column::CdId.eq(file.cd_id()))
+            .filter(file::Column::Id.ne(file.id()))
+            .count(&trx)
+            .await?;
+        let content_removed = shared_count == 0;
+
+        let deletion = if content_removed {
+            let tag_mappings = content_descriptor_tag::Entity::find()
+                .filter(content_descriptor_tag::Column::CdId.eq(file.cd_id()))
+                .count(&trx)
+                .await?;
+            let thumbnails = self.thumbnails(file.encoded_cd()).await?.len() as u64;
+            let freed_bytes = self
+                .ctx
+                .main_storage
+                .get_file_size(file.cd())
+                .await
+                .unwrap_or(0);
+            FileDeletionDto::new(
+                file.id(),
+                true,
+                tag_mappings as u64,
+                thumbnails,
+                freed_bytes,
+            )
+        } else {
+            FileDeletionDto::new(file.id(), false, 0, 0, 0)
+        };
+        if dry_run {
+            return Ok(deletion);
+        }
 
         file_metadata::Entity::delete_many()
             .filter(file_metadata::Column::FileId.eq(file.id()))
@@ -22,22 +59,84 @@ impl FileDao {
             .filter(file::Column::Id.eq(file.id()))
             .exec(&trx)
             .await?;
-        content_descriptor_tag::Entity::delete_many()
-            .filter(content_descriptor_tag::Column::CdId.eq(file.cd_id()))
-            .exec(&trx)
-            .await?;
-        content_descriptor::Entity::delete_many()
-            .filter(content_descriptor::Column::Id.eq(file.cd_id()))
-            .exec(&trx)
-            .await?;
 
+        if content_removed {
+            content_descriptor_tag::Entity::delete_many()
+                .filter(content_descriptor_tag::Column::CdId.eq(file.cd_id()))
+                .exec(&trx)
+                .await?;
+            content_descriptor::Entity::delete_many()
+                .filter(content_descriptor::Column::Id.eq(file.cd_id()))
+                .exec(&trx)
+                .await?;
+        }
+        trx.commit().await?;
+
+        if content_removed {
+            // leftover blobs are found and removed by the orphaned blob cleanup
+            if let Err(e) = self
+                .ctx
+                .thumbnail_storage
+                .delete_parent(&file.encoded_cd())
+                .await
+            {
+                tracing::warn!("failed to delete thumbnails of file {}: {}", file.id(), e);
+            }
+            if let Err(e) = self.ctx.main_storage.delete_file(file.cd()).await {
+                tracing::warn!("failed to delete blob of file {}: {}", file.id(), e);
+            }
+        }
+
+        Ok(deletion)
+    }
+
+    /// Deletes all thumbnails of a file and returns the number of bytes freed.
+    /// The thumbnails get recreated when requested again
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn clear_thumbnails(&self, file: &FileDto) -> RepoResult<u64> {
         self.ctx
             .thumbnail_storage
-            .delete_parent(&file.encoded_cd())
-            .await?;
-        self.ctx.main_storage.delete_file(file.cd()).await?;
-        trx.commit().await?;
+            .delete_parent(file.encoded_cd())
+            .await
+    }
+
+    /// Deletes the thumbnails of all files and returns the number of bytes freed
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn clear_all_thumbnails(&self) -> RepoResult<u64> {
+        self.ctx.thumbnail_storage.clear().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{add_file, add_file_sharing_content, test_repo};
+
+    #[tokio::test]
+    async fn it_keeps_shared_contents_when_deleting_files() {
+        let (repo, root) = test_repo("delete-shared").await;
+        let shared = add_file(&repo, vec![1, 2, 3, 4], "application/octet-stream", false).await;
+
+        let duplicate = add_file_sharing_content(&repo, &shared).await;
+
+        let preview = repo.file().delete(shared.clone(), true).await.unwrap();
+        assert!(!preview.content_removed());
+        assert!(repo.file().by_id(shared.id()).await.unwrap().is_some());
+
+        repo.file().delete(shared.clone(), false).await.unwrap();
+        assert!(repo.file().by_id(shared.id()).await.unwrap().is_none());
+        assert_eq!(
+            repo.file().get_bytes(duplicate.cd()).await.unwrap(),
+            vec![1, 2, 3, 4]
+        );
+
+        let preview = repo.file().delete(duplicate.clone(), true).await.unwrap();
+        assert!(preview.content_removed());
+        assert_eq!(preview.freed_bytes(), 4);
+        let deletion = repo.file().delete(duplicate.clone(), false).await.unwrap();
+        assert!(deletion.content_removed());
+        assert!(repo.file().get_bytes(duplicate.cd()).await.is_err());
 
-        Ok(())
+        std::fs::remove_dir_all(root).unwrap();
     }
 }
@@ -1,15 +1,19 @@
 use chrono::NaiveDateTime;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect};
-use sea_orm::Condition;
 use sea_orm::sea_query::{Alias, Expr, Query, SimpleExpr};
+use sea_orm::Condition;
+use sea_orm::{
+    ColumnTrait, EntityTrait, FromQueryResult, JoinType, Order, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait,
+};
 
 use mediarepo_core::error::RepoResult;
+use mediarepo_core::mediarepo_api::types::filtering::{SortDirection, SortKey};
 use mediarepo_database::entities::content_descriptor;
 use mediarepo_database::entities::content_descriptor_tag;
 use mediarepo_database::entities::file;
 use mediarepo_database::entities::file_metadata;
 
-use crate::dao::file::{FileDao, map_cd_and_file};
+use crate::dao::file::{map_cd_and_file, FileDao};
 use crate::dto::FileDto;
 
 macro_rules! apply_ordering_comparator {
@@ -29,19 +33,42 @@ macro_rules! apply_ordering_comparator {
 pub enum FilterProperty {
     TagId(NegatableComparator<i64>),
     TagWildcardIds(NegatableComparator<Vec<i64>>),
+    TagNamespace(NegatableComparator<NamespaceFilter>),
     ContentDescriptor(NegatableComparator<Vec<u8>>),
     TagCount(OrderingComparator<i64>),
     FileProperty(FilterFileProperty),
 }
 
+/// Matches files that have any tag in the namespace
+#[derive(Clone, Debug)]
+pub struct NamespaceFilter {
+    pub name: String,
+    pub case_sensitive: bool,
+}
+
 #[derive(Clone, Debug)]
 pub enum FilterFileProperty {
     Id(NegatableComparator<i64>),
     Status(NegatableComparator<i64>),
+    Indexed(bool),
     FileSize(OrderingComparator<i64>),
     ImportedTime(OrderingComparator<NaiveDateTime>),
     ChangedTime(OrderingComparator<NaiveDateTime>),
     CreatedTime(OrderingComparator<NaiveDateTime>),
+    Width(OrderingComparator<i64>),
+    Height(OrderingComparator<i64>),
+    AspectRatio(OrderingComparator<f64>),
+    Orientation(Orientation),
+    /// Matches files whose name or comment contains the text ignoring the casing
+    Text(String),
+}
+
+/// The orientation of an image derived from its stored dimensions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+    Square,
 }
 
 #[derive(Clone, Debug)]
@@ -76,10 +103,95 @@ impl FileDao {
 
         Ok(files)
     }
+
+    /// Finds the files on a page of the results ordered by the sort keys.
+    /// Returns the files of the page together with the total number of matching files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_paginated(
+        &self,
+        filters: Vec<Vec<FilterProperty>>,
+        offset: u64,
+        limit: u64,
+        sort: Vec<SortKey>,
+    ) -> RepoResult<(Vec<FileDto>, u64)> {
+        let main_condition = build_find_filter_conditions(filters);
+
+        let query = content_descriptor::Entity::find()
+            .find_also_related(file::Entity)
+            .filter(main_condition)
+            .group_by(file::Column::Id);
+        let total = query.clone().count(&self.ctx.db).await? as u64;
+
+        let metadata_join = file_metadata::Relation::File.def().rev();
+        let query = query.join(JoinType::LeftJoin, metadata_join);
+
+        let files = sort
+            .into_iter()
+            .fold(query, |query, key| {
+                let (expression, order) = build_sort_order(key);
+                query.order_by(expression, order)
+            })
+            .order_by_asc(file::Column::Id)
+            .offset(offset)
+            .limit(limit)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(map_cd_and_file)
+            .collect();
+
+        Ok((files, total))
+    }
+
+    /// Finds the ids of files by filters ordered by the sort keys
+    /// without loading any other data of the files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_ids(
+        &self,
+        filters: Vec<Vec<FilterProperty>>,
+        sort: Vec<SortKey>,
+    ) -> RepoResult<Vec<i64>> {
+        let main_condition = build_find_filter_conditions(filters);
+
+        let query = content_descriptor::Entity::find()
+            .select_only()
+            .column(file::Column::Id)
+            .join(
+                JoinType::InnerJoin,
+                file::Relation::ContentDescriptorId.def().rev(),
+            )
+            .join(
+                JoinType::LeftJoin,
+                file_metadata::Relation::File.def().rev(),
+            )
+            .filter(main_condition)
+            .group_by(file::Column::Id);
+
+        let ids = sort
+            .into_iter()
+            .fold(query, |query, key| {
+                let (expression, order) = build_sort_order(key);
+                query.order_by(expression, order)
+            })
+            .order_by_asc(file::Column::Id)
+            .into_model::<FileId>()
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+
+        Ok(ids)
+    }
+}
+
+#[derive(FromQueryResult)]
+struct FileId {
+    id: i64,
 }
 
 #[tracing::instrument(level = "debug")]
-fn build_find_filter_conditions(filters: Vec<Vec<FilterProperty>>) -> Condition {
+pub(crate) fn build_find_filter_conditions(filters: Vec<Vec<FilterProperty>>) -> Condition {
     filters
         .into_iter()
         .fold(Condition::all(), |all_cond, mut expression| {
@@ -106,6 +218,9 @@ fn build_single_filter(property: FilterProperty) -> SimpleExpr {
         FilterProperty::TagWildcardIds(wildcard_filter) => {
             build_tag_wildcard_ids_filter(wildcard_filter)
         }
+        FilterProperty::TagNamespace(namespace_filter) => {
+            build_tag_namespace_filter(namespace_filter)
+        }
         FilterProperty::ContentDescriptor(cd_filter) => build_content_descriptor_filter(cd_filter),
         FilterProperty::TagCount(count_filter) => build_tag_count_filter(count_filter),
         FilterProperty::FileProperty(property_filter) => {
@@ -152,6 +267,43 @@ fn build_tag_wildcard_ids_filter(filter: NegatableComparator<Vec<i64>>) -> Simpl
     }
 }
 
+fn build_tag_namespace_filter(filter: NegatableComparator<NamespaceFilter>) -> SimpleExpr {
+    let (operator, namespace) = match filter {
+        NegatableComparator::Is(namespace) => ("EXISTS", namespace),
+        NegatableComparator::IsNot(namespace) => ("NOT EXISTS", namespace),
+    };
+    let (name_condition, name) = if namespace.case_sensitive {
+        ("namespaces.name = ?", namespace.name)
+    } else {
+        // LIKE ignores the casing of ascii characters in sqlite
+        (
+            r"namespaces.name LIKE ? ESCAPE '\'",
+            escape_like_pattern(&namespace.name),
+        )
+    };
+
+    Expr::cust_with_values(
+        &format!(
+            r#"{} (
+            SELECT 1 FROM cd_tag_mappings
+            INNER JOIN tags ON tags.id = cd_tag_mappings.tag_id
+            INNER JOIN namespaces ON namespaces.id = tags.namespace_id
+            WHERE cd_tag_mappings.cd_id = content_descriptors.id AND {}
+        )"#,
+            operator, name_condition
+        ),
+        vec![name],
+    )
+}
+
+/// Escapes the wildcard characters of a LIKE pattern
+pub(crate) fn escape_like_pattern(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('%', r"\%")
+        .replace('_', r"\_")
+}
+
 fn build_content_descriptor_filter(filter: NegatableComparator<Vec<u8>>) -> SimpleExpr {
     match filter {
         NegatableComparator::Is(cd) => content_descriptor::Column::Descriptor.eq(cd),
@@ -187,11 +339,66 @@ fn build_tag_count_filter(filter: OrderingComparator<i64>) -> SimpleExpr {
     )
 }
 
+/// Converts a sort key into the expression and order of an ORDER BY clause.
+/// Files without a value are ordered before the files with a value
+fn build_sort_order(key: SortKey) -> (SimpleExpr, Order) {
+    let (expression, direction) = match key {
+        SortKey::Namespace(namespace) => (
+            build_namespace_sort_value(namespace.name),
+            namespace.direction,
+        ),
+        SortKey::FileName(direction) => (Expr::col(file_metadata::Column::Name).into(), direction),
+        SortKey::FileSize(direction) => (Expr::col(file_metadata::Column::Size).into(), direction),
+        SortKey::FileImportedTime(direction) => {
+            (Expr::col(file_metadata::Column::ImportTime).into(), direction)
+        }
+        SortKey::FileCreatedTime(direction) => (
+            Expr::col(file_metadata::Column::CreationTime).into(),
+            direction,
+        ),
+        SortKey::FileChangeTime(direction) => {
+            (Expr::col(file_metadata::Column::ChangeTime).into(), direction)
+        }
+        SortKey::FileType(direction) => (Expr::col(file::Column::MimeType).into(), direction),
+        SortKey::NumTags(direction) => (
+            Expr::cust(
+                "(SELECT COUNT(*) FROM cd_tag_mappings WHERE cd_tag_mappings.cd_id = content_descriptors.id)",
+            ),
+            direction,
+        ),
+    };
+    let order = match direction {
+        SortDirection::Ascending => Order::Asc,
+        SortDirection::Descending => Order::Desc,
+    };
+
+    (expression, order)
+}
+
+/// The smallest tag name of the file in the namespace. Numeric names are
+/// compared as numbers and ordered before all other names
+fn build_namespace_sort_value(namespace: String) -> SimpleExpr {
+    Expr::cust_with_values(
+        r#"(
+            SELECT MIN(CASE
+                WHEN tags.name GLOB '*[0-9]*' AND tags.name NOT GLOB '*[^0-9.]*'
+                THEN CAST(tags.name AS REAL)
+                ELSE tags.name
+            END) FROM cd_tag_mappings
+            INNER JOIN tags ON tags.id = cd_tag_mappings.tag_id
+            INNER JOIN namespaces ON namespaces.id = tags.namespace_id
+            WHERE cd_tag_mappings.cd_id = content_descriptors.id AND namespaces.name = ?
+        )"#,
+        vec![namespace],
+    )
+}
+
 #[inline]
 fn build_file_property_filter(property: FilterFileProperty) -> SimpleExpr {
     match property {
         FilterFileProperty::Id(id_filter) => build_file_id_filter(id_filter),
         FilterFileProperty::Status(status_filter) => build_file_status_filter(status_filter),
+        FilterFileProperty::Indexed(indexed) => file::Column::Indexed.eq(indexed),
         FilterFileProperty::FileSize(size_filter) => {
             build_file_metadata_filter(build_file_size_filter(size_filter))
         }
@@ -204,6 +411,19 @@ fn build_file_property_filter(property: FilterFileProperty) -> SimpleExpr {
         FilterFileProperty::CreatedTime(time_filter) => {
             build_file_metadata_filter(build_file_created_time_filter(time_filter))
         }
+        FilterFileProperty::Width(width_filter) => {
+            build_file_metadata_filter(build_file_width_filter(width_filter))
+        }
+        FilterFileProperty::Height(height_filter) => {
+            build_file_metadata_filter(build_file_height_filter(height_filter))
+        }
+        FilterFileProperty::AspectRatio(ratio_filter) => {
+            build_file_metadata_filter(build_file_aspect_ratio_filter(ratio_filter))
+        }
+        FilterFileProperty::Orientation(orientation) => {
+            build_file_metadata_filter(build_file_orientation_filter(orientation))
+        }
+        FilterFileProperty::Text(text) => build_file_metadata_filter(build_file_text_filter(text)),
     }
 }
 
@@ -246,3 +466,296 @@ fn build_file_changed_time_filter(filter: OrderingComparator<NaiveDateTime>) ->
 fn build_file_created_time_filter(filter: OrderingComparator<NaiveDateTime>) -> SimpleExpr {
     apply_ordering_comparator!(file_metadata::Column::CreationTime, filter)
 }
+
+fn build_file_width_filter(filter: OrderingComparator<i64>) -> SimpleExpr {
+    apply_ordering_comparator!(file_metadata::Column::Width, filter)
+}
+
+fn build_file_height_filter(filter: OrderingComparator<i64>) -> SimpleExpr {
+    apply_ordering_comparator!(file_metadata::Column::Height, filter)
+}
+
+/// Compares the width divided by the height. Files without dimensions never match
+fn build_file_aspect_ratio_filter(filter: OrderingComparator<f64>) -> SimpleExpr {
+    let ratio = Expr::expr(Expr::cust(
+        "CAST(file_metadata.width AS REAL) / file_metadata.height",
+    ));
+
+    apply_ordering_comparator!(ratio, filter)
+}
+
+fn build_file_orientation_filter(orientation: Orientation) -> SimpleExpr {
+    let width = Expr::col(file_metadata::Column::Width);
+    let height = Expr::col(file_metadata::Column::Height);
+
+    match orientation {
+        Orientation::Portrait => width.less_than(height),
+        Orientation::Landscape => width.greater_than(height),
+        Orientation::Square => width.equals(file_metadata::Entity, file_metadata::Column::Height),
+    }
+}
+
+/// The number of characters the trigram index of the names and comments needs for a lookup
+const TRIGRAM_LENGTH: usize = 3;
+
+fn build_file_text_filter(text: String) -> SimpleExpr {
+    if text.chars().count() >= TRIGRAM_LENGTH {
+        // a phrase of the trigram tokenizer matches every name or comment containing the text
+        // ignoring the casing
+        let phrase = format!("\"{}\"", text.replace('"', "\"\""));
+
+        Expr::cust_with_values(
+            "file_metadata.file_id IN (SELECT rowid FROM file_metadata_fts WHERE file_metadata_fts MATCH ?)",
+            vec![phrase],
+        )
+    } else {
+        // shorter texts can't be looked up in the index and need to be compared to every row
+        let pattern = format!("%{}%", escape_like_pattern(&text));
+
+        Expr::cust_with_values(
+            r"file_metadata.name LIKE ? ESCAPE '\'",
+            vec![pattern.clone()],
+        )
+        .or(Expr::cust_with_values(
+            r"file_metadata.comment LIKE ? ESCAPE '\'",
+            vec![pattern],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::mediarepo_api::types::filtering::{SortDirection, SortKey, SortNamespace};
+
+    use crate::dao::file::find::NegatableComparator::{Is, IsNot};
+    use crate::dao::file::find::{
+        FilterFileProperty, FilterProperty, OrderingComparator, Orientation,
+    };
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddTagDto, FileDto};
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_finds_files_by_groups_of_alternative_tags() {
+        let (repo, root) = test_repo("tag-groups").await;
+        let tag_ids: Vec<i64> = repo
+            .tag()
+            .add_all(
+                ["red", "blue", "cat", "nsfw"]
+                    .iter()
+                    .map(|name| AddTagDto {
+                        namespace: None,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id())
+            .collect();
+        let (red, blue, cat, nsfw) = (tag_ids[0], tag_ids[1], tag_ids[2], tag_ids[3]);
+        let mut file_ids = Vec::new();
+        for (i, tags) in [
+            vec![red, cat],
+            vec![blue, cat],
+            vec![red, cat, nsfw],
+            vec![cat],
+            vec![red],
+        ]
+        .iter()
+        .enumerate()
+        {
+            let file = add_file(&repo, vec![i as u8], "application/octet-stream", false).await;
+            repo.tag()
+                .upsert_mappings(vec![file.cd_id()], tags.clone())
+                .await
+                .unwrap();
+            file_ids.push(file.id());
+        }
+        let find_ids = |filters| {
+            let repo = &repo;
+            async move {
+                let mut ids = repo.file().find_ids(filters, vec![]).await.unwrap();
+                ids.sort_unstable();
+                ids
+            }
+        };
+
+        let filters = vec![
+            vec![
+                FilterProperty::TagId(Is(red)),
+                FilterProperty::TagId(Is(blue)),
+            ],
+            vec![FilterProperty::TagId(Is(cat))],
+            vec![FilterProperty::TagId(IsNot(nsfw))],
+        ];
+        assert_eq!(find_ids(filters).await, vec![file_ids[0], file_ids[1]]);
+        let filters = vec![vec![
+            FilterProperty::TagWildcardIds(Is(vec![])),
+            FilterProperty::TagId(IsNot(cat)),
+        ]];
+        assert_eq!(find_ids(filters).await, vec![file_ids[4]]);
+        let filters = vec![vec![FilterProperty::TagWildcardIds(Is(vec![]))]];
+        assert!(find_ids(filters).await.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_excludes_unindexed_files_from_filtered_searches() {
+        let (repo, root) = test_repo("unindexed").await;
+        let indexed = add_file(&repo, vec![1], "application/octet-stream", false).await;
+        let unindexed = add_file(&repo, vec![2], "application/octet-stream", false).await;
+        assert!(unindexed.indexed());
+
+        let unindexed = repo
+            .file()
+            .set_indexed(unindexed.id(), false)
+            .await
+            .unwrap();
+        assert!(!unindexed.indexed());
+        let filter = vec![vec![FilterProperty::FileProperty(
+            FilterFileProperty::Indexed(true),
+        )]];
+        assert_eq!(
+            repo.file().find_ids(filter, vec![]).await.unwrap(),
+            vec![indexed.id()]
+        );
+        assert_eq!(repo.file().find_ids(vec![], vec![]).await.unwrap().len(), 2);
+        let file = repo.file().by_id(unindexed.id()).await.unwrap().unwrap();
+        assert!(!file.indexed());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_filters_images_by_stored_dimensions() {
+        let (repo, root) = test_repo("dimensions").await;
+        let landscape = add_file(&repo, png_bytes(40, 20), "image/png", false).await;
+        let portrait = add_file(&repo, png_bytes(20, 40), "image/png", false).await;
+        let square = add_file(&repo, png_bytes(30, 30), "image/png", false).await;
+
+        let metadata = repo.file().metadata(landscape.id()).await.unwrap().unwrap();
+        assert_eq!(metadata.width(), Some(40));
+        assert_eq!(metadata.height(), Some(20));
+
+        let find_ids = |property| {
+            let repo = &repo;
+            async move {
+                let filter = vec![vec![FilterProperty::FileProperty(property)]];
+                let files = repo.file().find(filter).await.unwrap();
+                files.iter().map(|f| f.id()).collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            find_ids(FilterFileProperty::Orientation(Orientation::Landscape)).await,
+            vec![landscape.id()]
+        );
+        assert_eq!(
+            find_ids(FilterFileProperty::Orientation(Orientation::Portrait)).await,
+            vec![portrait.id()]
+        );
+        assert_eq!(
+            find_ids(FilterFileProperty::Orientation(Orientation::Square)).await,
+            vec![square.id()]
+        );
+        assert_eq!(
+            find_ids(FilterFileProperty::AspectRatio(
+                OrderingComparator::Between((0.9, 2.0))
+            ))
+            .await,
+            vec![landscape.id(), square.id()]
+        );
+        assert_eq!(
+            find_ids(FilterFileProperty::Width(OrderingComparator::Greater(30))).await,
+            vec![landscape.id()]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_finds_only_the_ids_of_matching_files() {
+        let (repo, root) = test_repo("find-ids").await;
+        let wide = add_file(&repo, png_bytes(40, 20), "image/png", false).await;
+        let narrow = add_file(&repo, png_bytes(10, 20), "image/png", false).await;
+        let other = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let width_filter = |comparator| {
+            vec![vec![FilterProperty::FileProperty(
+                FilterFileProperty::Width(comparator),
+            )]]
+        };
+
+        assert_eq!(
+            repo.file().find_ids(vec![], vec![]).await.unwrap(),
+            vec![wide.id(), narrow.id(), other.id()]
+        );
+        assert_eq!(
+            repo.file()
+                .find_ids(width_filter(OrderingComparator::Greater(20)), vec![])
+                .await
+                .unwrap(),
+            vec![wide.id()]
+        );
+        let filter = width_filter(OrderingComparator::Less(50));
+        let ids: Vec<i64> = repo
+            .file()
+            .find(filter.clone())
+            .await
+            .unwrap()
+            .iter()
+            .map(|f| f.id())
+            .collect();
+        assert_eq!(repo.file().find_ids(filter, vec![]).await.unwrap(), ids);
+        assert_eq!(
+            repo.file()
+                .find_ids(vec![], vec![SortKey::FileSize(SortDirection::Ascending)])
+                .await
+                .unwrap(),
+            vec![other.id(), narrow.id(), wide.id()]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_returns_sorted_pages_of_files() {
+        let (repo, root) = test_repo("pagination").await;
+        let mut files = Vec::new();
+        for size in [10, 40, 20, 30] {
+            files.push(add_file(&repo, vec![1; size], "application/octet-stream", false).await);
+        }
+        let by_size = || vec![SortKey::FileSize(SortDirection::Descending)];
+        let ids = |files: Vec<FileDto>| files.iter().map(|f| f.id()).collect::<Vec<i64>>();
+
+        let (page, total) = repo.files_paginated(1, 2, by_size()).await.unwrap();
+        assert_eq!(total, 4);
+        assert_eq!(ids(page), vec![files[3].id(), files[2].id()]);
+
+        let size_filter = vec![vec![FilterProperty::FileProperty(
+            FilterFileProperty::FileSize(OrderingComparator::Greater(15)),
+        )]];
+        let (page, total) = repo
+            .file()
+            .find_paginated(size_filter, 2, 2, by_size())
+            .await
+            .unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(ids(page), vec![files[2].id()]);
+
+        let untagged_keys = vec![
+            SortKey::Namespace(SortNamespace {
+                name: String::from("page"),
+                direction: SortDirection::Ascending,
+            }),
+            SortKey::NumTags(SortDirection::Descending),
+        ];
+        let (page, total) = repo.files_paginated(0, 10, untagged_keys).await.unwrap();
+        assert_eq!(total, 4);
+        assert_eq!(ids(page), ids(files));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
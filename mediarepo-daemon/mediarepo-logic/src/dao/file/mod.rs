@@ -1,11 +1,22 @@
+use std::collections::HashMap;
+
+use mediarepo_core::content_descriptor::{
+    content_descriptor_hash_algorithm, encode_content_descriptor,
+};
 use sea_orm::prelude::*;
-use tokio::io::AsyncReadExt;
+use sea_orm::sea_query::Expr;
+use sea_orm::{Condition, FromQueryResult, JoinType, QueryOrder, QuerySelect};
+use std::io::SeekFrom;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
+use crate::dao::file::find::escape_like_pattern;
+use crate::dao::tag::add::BATCH_SIZE;
 use crate::dao_provider;
 use mediarepo_core::error::RepoResult;
+use mediarepo_core::fs::thumbnail_store::{Dimensions, ThumbnailCrop};
 use mediarepo_database::entities::{content_descriptor, file, file_metadata};
 
-use crate::dto::{FileDto, FileMetadataDto, ThumbnailDto};
+use crate::dto::{FileDto, FileMetadataDto, FileStorageInfoDto, MetadataField, ThumbnailDto};
 
 pub mod add;
 pub mod delete;
@@ -14,6 +25,30 @@ pub mod update;
 
 dao_provider!(FileDao);
 
+/// The name of the storage all files are stored in
+const MAIN_STORAGE_NAME: &str = "main";
+
+/// The mean radius of the earth used for distance calculations
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, FromQueryResult)]
+struct Descriptor {
+    descriptor: Vec<u8>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct FileDescriptor {
+    file_id: i64,
+    descriptor: Vec<u8>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Location {
+    file_id: i64,
+    latitude: f64,
+    longitude: f64,
+}
+
 impl FileDao {
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn all(&self) -> RepoResult<Vec<FileDto>> {
@@ -28,6 +63,181 @@ impl FileDao {
         Ok(files)
     }
 
+    /// Returns the content descriptors of all files without loading any other data
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_cds(&self) -> RepoResult<Vec<Vec<u8>>> {
+        self.all_cds_paginated(0, i64::MAX as u64).await
+    }
+
+    /// Returns a page of the content descriptors of all files ordered by their id
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_cds_paginated(&self, offset: u64, limit: u64) -> RepoResult<Vec<Vec<u8>>> {
+        let cds = content_descriptor::Entity::find()
+            .select_only()
+            .column(content_descriptor::Column::Descriptor)
+            .join(
+                JoinType::InnerJoin,
+                file::Relation::ContentDescriptorId.def().rev(),
+            )
+            .order_by_asc(content_descriptor::Column::Id)
+            .order_by_asc(file::Column::Id)
+            .offset(offset)
+            .limit(limit)
+            .into_model::<Descriptor>()
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(|d| d.descriptor)
+            .collect();
+
+        Ok(cds)
+    }
+
+    /// Returns all files that have been flagged as corrupt
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_corrupt(&self) -> RepoResult<Vec<FileDto>> {
+        let files = file::Entity::find()
+            .find_also_related(content_descriptor::Entity)
+            .filter(file::Column::Corrupt.eq(true))
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(map_file_and_cd)
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Returns the files with the highest view count
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn most_viewed(&self, limit: u64) -> RepoResult<Vec<FileDto>> {
+        let files = file::Entity::find()
+            .inner_join(file_metadata::Entity)
+            .find_also_related(content_descriptor::Entity)
+            .filter(file_metadata::Column::ViewCount.gt(0))
+            .order_by_desc(file_metadata::Column::ViewCount)
+            .limit(limit)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(map_file_and_cd)
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Returns all files that miss any of the given metadata fields.
+    /// Files without a metadata entry miss all metadata fields
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn missing_metadata(&self, fields: Vec<MetadataField>) -> RepoResult<Vec<FileDto>> {
+        if fields.is_empty() {
+            return Ok(vec![]);
+        }
+        let condition = fields.into_iter().fold(Condition::any(), |cond, field| {
+            cond.add(missing_metadata_condition(field))
+        });
+
+        let files = file::Entity::find()
+            .left_join(file_metadata::Entity)
+            .find_also_related(content_descriptor::Entity)
+            .filter(condition)
+            .order_by_asc(file::Column::Id)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(map_file_and_cd)
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Returns all files whose name contains the given pattern ignoring the casing
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_by_name(&self, pattern: String) -> RepoResult<Vec<FileDto>> {
+        let files = file::Entity::find()
+            .inner_join(file_metadata::Entity)
+            .find_also_related(content_descriptor::Entity)
+            .filter(Expr::cust_with_values(
+                // LIKE ignores the casing of ascii characters in sqlite
+                r"file_metadata.name LIKE ? ESCAPE '\'",
+                vec![format!("%{}%", escape_like_pattern(&pattern))],
+            ))
+            .order_by_asc(file_metadata::Column::Name)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(map_file_and_cd)
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Returns all files with a location within the given radius (in km)
+    /// ordered by their distance to the given coordinates
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_near(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+    ) -> RepoResult<Vec<FileDto>> {
+        let locations: Vec<Location> = file_metadata::Entity::find()
+            .select_only()
+            .column(file_metadata::Column::FileId)
+            .column(file_metadata::Column::Latitude)
+            .column(file_metadata::Column::Longitude)
+            .filter(bounding_box_condition(latitude, longitude, radius_km))
+            .into_model::<Location>()
+            .all(&self.ctx.db)
+            .await?;
+        let distances: HashMap<i64, f64> = locations
+            .into_iter()
+            .map(|l| {
+                let distance = haversine_distance(latitude, longitude, l.latitude, l.longitude);
+                (l.file_id, distance)
+            })
+            .filter(|(_, distance)| *distance <= radius_km)
+            .collect();
+        let mut files = self.all_by_id(distances.keys().cloned().collect()).await?;
+        files.sort_by(|a, b| distances[&a.id()].total_cmp(&distances[&b.id()]));
+
+        Ok(files)
+    }
+
+    /// Returns all image files that don't have a perceptual hash yet
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn images_without_phash(&self) -> RepoResult<Vec<FileDto>> {
+        let files = file::Entity::find()
+            .find_also_related(content_descriptor::Entity)
+            .join(
+                JoinType::InnerJoin,
+                file_metadata::Relation::File.def().rev(),
+            )
+            .filter(file::Column::MimeType.starts_with("image/"))
+            .filter(file_metadata::Column::Phash.is_null())
+            .order_by_asc(file::Column::Id)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(map_file_and_cd)
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Returns the file that was imported with the given external id
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn by_external_id(&self, external_id: String) -> RepoResult<Option<FileDto>> {
+        let file = file::Entity::find()
+            .find_also_related(content_descriptor::Entity)
+            .filter(file::Column::ExternalId.eq(external_id))
+            .one(&self.ctx.db)
+            .await?
+            .and_then(map_file_and_cd);
+
+        Ok(file)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     #[inline]
     pub async fn by_id(&self, id: i64) -> RepoResult<Option<FileDto>> {
@@ -58,6 +268,25 @@ impl FileDao {
         Ok(files)
     }
 
+    /// Returns all files with one of the given content descriptor ids
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_by_cd_id(&self, cd_ids: Vec<i64>) -> RepoResult<Vec<FileDto>> {
+        if cd_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let files = file::Entity::find()
+            .find_also_related(content_descriptor::Entity)
+            .filter(file::Column::CdId.is_in(cd_ids))
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .filter_map(map_file_and_cd)
+            .collect();
+
+        Ok(files)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn all_by_id(&self, ids: Vec<i64>) -> RepoResult<Vec<FileDto>> {
         if ids.is_empty() {
@@ -76,6 +305,31 @@ impl FileDao {
         Ok(files)
     }
 
+    /// Returns the content descriptors of the files with the given ids without loading
+    /// any other data. Ids without a file are omitted
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn cds_by_id(&self, ids: Vec<i64>) -> RepoResult<HashMap<i64, Vec<u8>>> {
+        let mut cd_map = HashMap::new();
+
+        for chunk in ids.chunks(BATCH_SIZE) {
+            let file_cds = file::Entity::find()
+                .select_only()
+                .column_as(file::Column::Id, "file_id")
+                .column(content_descriptor::Column::Descriptor)
+                .join(
+                    JoinType::InnerJoin,
+                    file::Relation::ContentDescriptorId.def(),
+                )
+                .filter(file::Column::Id.is_in(chunk.to_vec()))
+                .into_model::<FileDescriptor>()
+                .all(&self.ctx.db)
+                .await?;
+            cd_map.extend(file_cds.into_iter().map(|f| (f.file_id, f.descriptor)));
+        }
+
+        Ok(cd_map)
+    }
+
     pub async fn metadata(&self, file_id: i64) -> RepoResult<Option<FileMetadataDto>> {
         self.all_metadata(vec![file_id])
             .await
@@ -99,23 +353,79 @@ impl FileDao {
         Ok(metadata)
     }
 
-    /// Returns all thumbnails for a cd
+    /// Returns all thumbnails for a cd ordered by their area starting with the smallest.
+    /// Thumbnails of the same area are ordered by width and fitted thumbnails
+    /// come before cropped ones
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn thumbnails(&self, encoded_cd: String) -> RepoResult<Vec<ThumbnailDto>> {
-        let thumbnails = self
+        let mut thumbnails: Vec<ThumbnailDto> = self
             .ctx
             .thumbnail_storage
             .get_thumbnails(&encoded_cd)
             .await?
             .into_iter()
-            .map(|(size, path)| {
-                ThumbnailDto::new(path, encoded_cd.clone(), size, String::from("image/png"))
+            .map(|(size, crop, path)| {
+                ThumbnailDto::new(
+                    path,
+                    encoded_cd.clone(),
+                    size,
+                    crop,
+                    String::from("image/png"),
+                )
             })
             .collect();
+        thumbnails.sort_by_key(|thumb| {
+            let Dimensions { height, width } = thumb.size();
+            (
+                u64::from(*height) * u64::from(*width),
+                *width,
+                thumb.crop() == ThumbnailCrop::CenterCrop,
+            )
+        });
 
         Ok(thumbnails)
     }
 
+    /// Returns all thumbnails for the given content descriptor ids.
+    /// Thumbnails are only kept in the thumbnail store, so it is read once for every content
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn thumbnails_by_cd_ids(
+        &self,
+        cd_ids: Vec<i64>,
+    ) -> RepoResult<HashMap<i64, Vec<ThumbnailDto>>> {
+        if cd_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let cds = content_descriptor::Entity::find()
+            .filter(content_descriptor::Column::Id.is_in(cd_ids))
+            .all(&self.ctx.db)
+            .await?;
+        let mut thumbnail_map = HashMap::new();
+
+        for cd in cds {
+            let thumbnails = self
+                .thumbnails(encode_content_descriptor(&cd.descriptor))
+                .await?;
+            thumbnail_map.insert(cd.id, thumbnails);
+        }
+
+        Ok(thumbnail_map)
+    }
+
+    /// Returns where and how the blob of the file is stored
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn storage_info(&self, file: &FileDto) -> RepoResult<FileStorageInfoDto> {
+        let hash_algorithm = content_descriptor_hash_algorithm(file.cd())?;
+        let size = self.ctx.main_storage.get_file_size(file.cd()).await?;
+
+        Ok(FileStorageInfoDto::new(
+            MAIN_STORAGE_NAME.to_string(),
+            hash_algorithm,
+            file.cd().to_vec(),
+            size,
+        ))
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_bytes(&self, cd: &[u8]) -> RepoResult<Vec<u8>> {
         let mut buf = Vec::new();
@@ -124,6 +434,18 @@ impl FileDao {
 
         Ok(buf)
     }
+
+    /// Reads up to `length` bytes of the file starting at the offset.
+    /// Less bytes are returned if the file ends before
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_byte_range(&self, cd: &[u8], offset: u64, length: u64) -> RepoResult<Vec<u8>> {
+        let mut buf = Vec::new();
+        let mut reader = self.ctx.main_storage.get_file(cd).await?.1;
+        reader.seek(SeekFrom::Start(offset)).await?;
+        reader.take(length).read_to_end(&mut buf).await?;
+
+        Ok(buf)
+    }
 }
 
 fn map_file_and_cd(
@@ -137,3 +459,306 @@ fn map_cd_and_file(
 ) -> Option<FileDto> {
     file.map(|f| FileDto::new(f, cd, None))
 }
+
+fn missing_metadata_condition(field: MetadataField) -> Condition {
+    match field {
+        MetadataField::Name => Condition::all().add(file_metadata::Column::Name.is_null()),
+        MetadataField::Dimensions => Condition::all()
+            .add(file::Column::MimeType.starts_with("image/"))
+            .add(
+                Condition::any()
+                    .add(file_metadata::Column::Width.is_null())
+                    .add(file_metadata::Column::Height.is_null()),
+            ),
+        MetadataField::Size => Condition::all().add(file_metadata::Column::Size.is_null()),
+        // files with an unknown type are imported as octet streams
+        MetadataField::MimeType => Condition::any()
+            .add(file::Column::MimeType.eq(""))
+            .add(file::Column::MimeType.eq("application/octet-stream")),
+    }
+}
+
+/// Builds a condition that matches all locations in a box around the given coordinates
+/// that contains the circle with the given radius
+fn bounding_box_condition(latitude: f64, longitude: f64, radius_km: f64) -> Condition {
+    let angular_radius = radius_km / EARTH_RADIUS_KM;
+    let lat_delta = angular_radius.to_degrees();
+    let lat_condition =
+        file_metadata::Column::Latitude.between(latitude - lat_delta, latitude + lat_delta);
+    let lat_cos = latitude.to_radians().cos().abs();
+
+    let contains_pole = latitude.abs() + lat_delta >= 90.0;
+    // the meridians touching the circle are at asin(sin δ / cos φ) from its center,
+    // if sin δ reaches cos φ the circle spans all meridians
+    let spans_all_meridians = angular_radius.sin() >= lat_cos;
+    let lon_delta = if spans_all_meridians {
+        180.0
+    } else {
+        (angular_radius.sin() / lat_cos).asin().to_degrees()
+    };
+    let (min_lon, max_lon) = (longitude - lon_delta, longitude + lon_delta);
+
+    let lon_condition = if contains_pole || spans_all_meridians {
+        Condition::all().add(file_metadata::Column::Longitude.is_not_null())
+    } else if min_lon < -180.0 {
+        Condition::any()
+            .add(file_metadata::Column::Longitude.gte(min_lon + 360.0))
+            .add(file_metadata::Column::Longitude.lte(max_lon))
+    } else if max_lon > 180.0 {
+        Condition::any()
+            .add(file_metadata::Column::Longitude.gte(min_lon))
+            .add(file_metadata::Column::Longitude.lte(max_lon - 360.0))
+    } else {
+        Condition::all().add(file_metadata::Column::Longitude.between(min_lon, max_lon))
+    };
+
+    Condition::all().add(lat_condition).add(lon_condition)
+}
+
+/// Calculates the great circle distance between two coordinates in km
+fn haversine_distance(lat_a: f64, lon_a: f64, lat_b: f64, lon_b: f64) -> f64 {
+    let d_lat = (lat_b - lat_a).to_radians();
+    let d_lon = (lon_b - lon_a).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat_a.to_radians().cos() * lat_b.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::fs::thumbnail_store::ThumbnailCrop;
+    use mediarepo_core::thumbnailer::ThumbnailSize;
+
+    use sea_orm::ActiveModelTrait;
+    use sea_orm::ActiveValue::Set;
+
+    use mediarepo_database::entities::file_metadata;
+
+    use crate::dao::DaoProvider;
+    use crate::dto::{MetadataField, UpdateFileMetadataDto};
+    use crate::testing::fixtures::{add_file, add_file_sharing_content, png_bytes, test_repo};
+
+    use super::haversine_distance;
+
+    #[tokio::test]
+    async fn it_returns_thumbnails_ordered_by_area() {
+        let (repo, root) = test_repo("thumbnail-order").await;
+        let file = add_file(&repo, png_bytes(1200, 900), "image/png", false).await;
+        repo.file()
+            .create_thumbnails(
+                &file,
+                vec![
+                    ThumbnailSize::Large,
+                    ThumbnailSize::Small,
+                    ThumbnailSize::Medium,
+                ],
+            )
+            .await
+            .unwrap();
+        repo.file()
+            .create_thumbnails_with_crop(
+                &file,
+                vec![ThumbnailSize::Small],
+                ThumbnailCrop::CenterCrop,
+            )
+            .await
+            .unwrap();
+
+        let areas: Vec<u32> = repo
+            .file()
+            .thumbnails(file.encoded_cd())
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.size().height * t.size().width)
+            .collect();
+        assert_eq!(areas.len(), 4);
+        assert!(areas.windows(2).all(|w| w[0] <= w[1]));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_returns_the_cds_of_file_ids() {
+        let (repo, root) = test_repo("cds-by-id").await;
+        let first = add_file(&repo, vec![1, 2], "application/octet-stream", false).await;
+        let second = add_file(&repo, vec![3, 4], "application/octet-stream", false).await;
+
+        let cds = repo
+            .file()
+            .cds_by_id(vec![first.id(), second.id(), second.id() + 100])
+            .await
+            .unwrap();
+        assert_eq!(cds.len(), 2);
+        assert_eq!(cds[&first.id()], first.cd());
+        assert_eq!(cds[&second.id()], second.cd());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_returns_the_files_of_content_descriptor_ids() {
+        let (repo, root) = test_repo("files-by-cd-id").await;
+        let file = add_file(&repo, vec![1], "image/png", false).await;
+        let shared = add_file_sharing_content(&repo, &file).await;
+        let other = add_file(&repo, vec![2], "image/png", false).await;
+        add_file(&repo, vec![3], "image/png", false).await;
+
+        let mut ids: Vec<i64> = repo
+            .file()
+            .all_by_cd_id(vec![file.cd_id(), other.cd_id()])
+            .await
+            .unwrap()
+            .iter()
+            .map(|f| f.id())
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![file.id(), shared.id(), other.id()]);
+        assert!(repo.file().all_by_cd_id(vec![]).await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_finds_files_by_partial_names() {
+        let (repo, root) = test_repo("find-by-name").await;
+
+        for (i, name) in ["Holiday.png", "holiday_2.png", "work.png"]
+            .iter()
+            .enumerate()
+        {
+            let file = add_file(&repo, vec![i as u8], "image/png", false).await;
+            repo.file()
+                .update_metadata(UpdateFileMetadataDto {
+                    file_id: file.id(),
+                    name: Some(Some(name.to_string())),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+        let find_names = |pattern: &str| {
+            let file_dao = repo.file();
+            let pattern = pattern.to_string();
+            async move {
+                let mut names = Vec::new();
+
+                for file in file_dao.find_by_name(pattern).await.unwrap() {
+                    let metadata = file_dao.metadata(file.id()).await.unwrap().unwrap();
+                    names.push(metadata.name().cloned().unwrap());
+                }
+                names
+            }
+        };
+        assert_eq!(
+            find_names("HOLIDAY").await,
+            vec!["Holiday.png", "holiday_2.png"]
+        );
+        // wildcards of the pattern are matched literally
+        assert_eq!(find_names("y_").await, vec!["holiday_2.png"]);
+        assert!(find_names("%").await.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_returns_pages_of_all_cds() {
+        let (repo, root) = test_repo("cd-pages").await;
+        let mut cds = Vec::new();
+        for i in 0..5 {
+            let file = add_file(&repo, vec![i], "application/octet-stream", false).await;
+            cds.push(file.cd().to_vec());
+        }
+
+        assert_eq!(repo.file().all_cds().await.unwrap(), cds);
+        let mut pages = Vec::new();
+        for offset in (0..6).step_by(2) {
+            pages.push(repo.file().all_cds_paginated(offset, 2).await.unwrap());
+        }
+        assert_eq!(
+            pages,
+            vec![cds[..2].to_vec(), cds[2..4].to_vec(), cds[4..].to_vec()]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_reads_byte_ranges_of_files() {
+        let (repo, root) = test_repo("byte-range").await;
+        let content: Vec<u8> = (0..100).collect();
+        let file = add_file(&repo, content.clone(), "application/octet-stream", false).await;
+
+        let range = repo.file().get_byte_range(file.cd(), 10, 5).await.unwrap();
+        assert_eq!(range, content[10..15].to_vec());
+        let range = repo.file().get_byte_range(file.cd(), 95, 10).await.unwrap();
+        assert_eq!(range, content[95..].to_vec());
+        let range = repo
+            .file()
+            .get_byte_range(file.cd(), 200, 10)
+            .await
+            .unwrap();
+        assert!(range.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_finds_files_missing_metadata_fields() {
+        let (repo, root) = test_repo("missing-metadata").await;
+        let image = add_file(&repo, png_bytes(10, 10), "image/png", false).await;
+        let broken_image = add_file(&repo, vec![1, 2, 3], "image/png", false).await;
+        let unknown = add_file(&repo, vec![4, 5, 6], "application/octet-stream", false).await;
+        let missing_ids = |fields| {
+            let repo = &repo;
+            async move {
+                let files = repo.file().missing_metadata(fields).await.unwrap();
+                files.iter().map(|f| f.id()).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            missing_ids(vec![MetadataField::Dimensions]).await,
+            vec![broken_image.id()]
+        );
+        assert_eq!(
+            missing_ids(vec![MetadataField::MimeType]).await,
+            vec![unknown.id()]
+        );
+        assert_eq!(
+            missing_ids(vec![MetadataField::Dimensions, MetadataField::MimeType]).await,
+            vec![broken_image.id(), unknown.id()]
+        );
+        assert_eq!(
+            missing_ids(vec![MetadataField::Name]).await,
+            vec![image.id(), broken_image.id(), unknown.id()]
+        );
+        assert!(missing_ids(vec![MetadataField::Size]).await.is_empty());
+        assert!(missing_ids(vec![]).await.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_finds_files_at_the_edge_of_the_radius() {
+        let (repo, root) = test_repo("find-near-edge").await;
+        let file = add_file(&repo, png_bytes(1, 1), "image/png", false).await;
+        file_metadata::ActiveModel {
+            file_id: Set(file.id()),
+            latitude: Set(Some(61.55)),
+            longitude: Set(Some(20.0)),
+            ..Default::default()
+        }
+        .update(repo.db())
+        .await
+        .unwrap();
+        assert!(haversine_distance(60.0, 0.0, 61.55, 20.0) < 1100.0);
+
+        let found = repo.file().find_near(60.0, 0.0, 1100.0).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id(), file.id());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -2,29 +2,53 @@ use std::fmt::Debug;
 use std::io::Cursor;
 use std::str::FromStr;
 
+use chrono::Local;
 use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
 use sea_orm::ActiveValue::{Set, Unchanged};
 use sea_orm::{NotSet, TransactionTrait};
 
 use mediarepo_core::error::{RepoError, RepoResult};
-use mediarepo_core::fs::thumbnail_store::Dimensions;
+use mediarepo_core::fs::thumbnail_store::{Dimensions, ThumbnailCrop};
+use mediarepo_core::image::imageops::FilterType;
+use mediarepo_core::image::{self, DynamicImage, ImageFormat, ImageOutputFormat};
+use mediarepo_core::image_processing::{
+    apply_exif_orientation, catch_thumbnail_panic, perceptual_hash, prepare_thumbnail_source,
+    read_exif,
+};
+use mediarepo_core::mime::Mime;
 use mediarepo_core::thumbnailer;
 use mediarepo_core::thumbnailer::ThumbnailSize;
+use mediarepo_core::video_processing::extract_thumbnail_frame;
 use mediarepo_database::entities::{content_descriptor, file, file_metadata};
 
+use crate::dao::file::add::check_external_id_unused;
 use crate::dao::file::FileDao;
 use crate::dao::opt_to_active_val;
-use crate::dto::{FileDto, FileMetadataDto, ThumbnailDto, UpdateFileDto, UpdateFileMetadataDto};
+use crate::dao::tag::add::BATCH_SIZE;
+use crate::dto::{
+    FileDto, FileMetadataDto, FileStatus, ThumbnailDto, UpdateFileDto, UpdateFileMetadataDto,
+};
+
+/// How much bigger than the requested size the source of cropped thumbnails is
+const CROP_SOURCE_SCALE: u32 = 4;
 
 impl FileDao {
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn update(&self, update_dto: UpdateFileDto) -> RepoResult<FileDto> {
         let trx = self.ctx.db.begin().await?;
+
+        if let Some(Some(external_id)) = &update_dto.external_id {
+            check_external_id_unused(&trx, external_id, Some(update_dto.id)).await?;
+        }
         let model = file::ActiveModel {
             id: Set(update_dto.id),
             cd_id: update_dto.cd_id.map(Set).unwrap_or(NotSet),
             mime_type: update_dto.mime_type.map(Set).unwrap_or(NotSet),
             status: update_dto.status.map(|v| Set(v as i32)).unwrap_or(NotSet),
+            corrupt: update_dto.corrupt.map(Set).unwrap_or(NotSet),
+            external_id: opt_to_active_val(update_dto.external_id),
+            indexed: update_dto.indexed.map(Set).unwrap_or(NotSet),
         };
         let file_model = model.update(&trx).await?;
         let cd = file_model
@@ -37,6 +61,69 @@ impl FileDao {
         Ok(FileDto::new(file_model, cd, None))
     }
 
+    /// Overrides the detected mime type of a file
+    /// The mime type is validated but the file contents stay untouched
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_mime_type(&self, file_id: i64, mime_type: String) -> RepoResult<FileDto> {
+        let mime_type = Mime::from_str(&mime_type)?;
+
+        self.update(UpdateFileDto {
+            id: file_id,
+            mime_type: Some(mime_type.to_string()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Sets if a file is included in searches. Files that aren't indexed
+    /// can still be retrieved by their id
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_indexed(&self, file_id: i64, indexed: bool) -> RepoResult<FileDto> {
+        self.update(UpdateFileDto {
+            id: file_id,
+            indexed: Some(indexed),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Changes the status of the files that currently have the `from` status
+    /// and returns the changed files. Files with another status are left unchanged
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn set_status(
+        &self,
+        file_ids: Vec<i64>,
+        from: FileStatus,
+        status: FileStatus,
+    ) -> RepoResult<Vec<FileDto>> {
+        let trx = self.ctx.db.begin().await?;
+        let mut files = Vec::new();
+
+        for chunk in file_ids.chunks(BATCH_SIZE) {
+            let models = file::Entity::find()
+                .find_also_related(content_descriptor::Entity)
+                .filter(file::Column::Id.is_in(chunk.to_vec()))
+                .filter(file::Column::Status.eq(from as i32))
+                .all(&trx)
+                .await?;
+            if models.is_empty() {
+                continue;
+            }
+            file::Entity::update_many()
+                .col_expr(file::Column::Status, Expr::value(status as i32))
+                .filter(file::Column::Id.is_in(models.iter().map(|(f, _)| f.id)))
+                .exec(&trx)
+                .await?;
+            files.extend(models.into_iter().filter_map(|(mut file, cd)| {
+                file.status = status as i32;
+                cd.map(|cd| FileDto::new(file, cd, None))
+            }));
+        }
+        trx.commit().await?;
+
+        Ok(files)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn update_metadata(
         &self,
@@ -55,41 +142,561 @@ impl FileDao {
         Ok(FileMetadataDto::new(metadata))
     }
 
+    /// Updates the metadata and the status of a file in a single transaction.
+    /// A deleted file only gets another status if `undelete` is set
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_metadata_and_status(
+        &self,
+        update_dto: UpdateFileMetadataDto,
+        status: Option<FileStatus>,
+        undelete: bool,
+    ) -> RepoResult<FileMetadataDto> {
+        let file_id = update_dto.file_id;
+        let trx = self.ctx.db.begin().await?;
+
+        if let Some(status) = status {
+            let file = file::Entity::find_by_id(file_id)
+                .one(&trx)
+                .await?
+                .ok_or_else(|| RepoError::from("file not found"))?;
+            if !FileStatus::from_value(file.status).can_change_to(status, undelete) {
+                return Err(RepoError::from(&*format!(
+                    "file {} is deleted and has to be undeleted to change its status to {:?}",
+                    file_id, status
+                )));
+            }
+            file::ActiveModel {
+                id: Unchanged(file_id),
+                status: Set(status as i32),
+                ..Default::default()
+            }
+            .update(&trx)
+            .await?;
+        }
+        if update_dto.name.is_some()
+            || update_dto.comment.is_some()
+            || update_dto.size.is_some()
+            || update_dto.change_time.is_some()
+        {
+            file_metadata::ActiveModel {
+                file_id: Unchanged(file_id),
+                name: opt_to_active_val(update_dto.name),
+                comment: opt_to_active_val(update_dto.comment),
+                size: opt_to_active_val(update_dto.size),
+                change_time: opt_to_active_val(update_dto.change_time),
+                ..Default::default()
+            }
+            .update(&trx)
+            .await?;
+        }
+        let metadata = file_metadata::Entity::find_by_id(file_id)
+            .one(&trx)
+            .await?
+            .ok_or_else(|| RepoError::from("file metadata not found"))?;
+        trx.commit().await?;
+
+        Ok(FileMetadataDto::new(metadata))
+    }
+
+    /// Marks the file as viewed by incrementing its view count
+    /// and setting the access time to now
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn touch(&self, file_id: i64) -> RepoResult<FileMetadataDto> {
+        file_metadata::Entity::update_many()
+            .col_expr(
+                file_metadata::Column::ViewCount,
+                Expr::col(file_metadata::Column::ViewCount).add(1),
+            )
+            .col_expr(
+                file_metadata::Column::AccessTime,
+                Expr::value(Local::now().naive_local()),
+            )
+            .filter(file_metadata::Column::FileId.eq(file_id))
+            .exec(&self.ctx.db)
+            .await?;
+
+        self.metadata(file_id)
+            .await?
+            .ok_or_else(|| RepoError::from("file metadata not found"))
+    }
+
+    /// Calculates the perceptual hash of an image file and stores it in its metadata.
+    /// Returns `None` for files that aren't images or can't be decoded
     #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn generate_phash(&self, file: &FileDto) -> RepoResult<Option<u64>> {
+        if !file.mime_type().starts_with("image/") {
+            return Ok(None);
+        }
+        let bytes = self.get_bytes(file.cd()).await?;
+        let mime_type = file.mime_type().to_owned();
+        let phash = tokio::task::spawn_blocking(move || {
+            catch_thumbnail_panic(|| perceptual_hash(&bytes, &mime_type))
+        })
+        .await
+        .map_err(|e| RepoError::from(&*e.to_string()))?;
+
+        match phash {
+            Ok(phash) => {
+                file_metadata::Entity::update_many()
+                    .col_expr(file_metadata::Column::Phash, Expr::value(phash as i64))
+                    .filter(file_metadata::Column::FileId.eq(file.id()))
+                    .exec(&self.ctx.db)
+                    .await?;
+                Ok(Some(phash))
+            }
+            Err(e) => {
+                tracing::debug!("failed to hash image {}: {}", file.id(), e);
+                Ok(None)
+            }
+        }
+    }
+
+    #[inline]
     pub async fn create_thumbnails<I: IntoIterator<Item = ThumbnailSize> + Debug>(
         &self,
         file: &FileDto,
         sizes: I,
     ) -> RepoResult<Vec<ThumbnailDto>> {
-        let bytes = self.get_bytes(file.cd()).await?;
+        self.create_thumbnails_with_crop(file, sizes, ThumbnailCrop::Fit)
+            .await
+    }
+
+    /// Creates the thumbnails of the configured sizes that don't exist for the file yet
+    /// and returns the created thumbnails
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn create_configured_thumbnails(
+        &self,
+        file: &FileDto,
+    ) -> RepoResult<Vec<ThumbnailDto>> {
+        let existing = self.thumbnails(file.encoded_cd()).await?;
+        let missing_sizes: Vec<ThumbnailSize> = self
+            .ctx
+            .thumbnail_sizes
+            .iter()
+            .filter(|size| {
+                !existing
+                    .iter()
+                    .any(|thumb| is_thumbnail_of_size(thumb, size))
+            })
+            .cloned()
+            .collect();
+
+        if missing_sizes.is_empty() {
+            Ok(vec![])
+        } else {
+            self.create_thumbnails(file, missing_sizes).await
+        }
+    }
+
+    /// Creates thumbnails of the given sizes that are fitted into the sizes with the given crop
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn create_thumbnails_with_crop<I: IntoIterator<Item = ThumbnailSize> + Debug>(
+        &self,
+        file: &FileDto,
+        sizes: I,
+        crop: ThumbnailCrop,
+    ) -> RepoResult<Vec<ThumbnailDto>> {
         let mime_type =
             mime::Mime::from_str(file.mime_type()).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        let thumbnails =
-            thumbnailer::create_thumbnails(Cursor::new(bytes), mime_type.clone(), sizes)?;
-        let mut dtos = Vec::new();
+        let (bytes, source_mime_type) = if mime_type.type_() == mime::VIDEO {
+            let path = self.ctx.main_storage.file_path(file.cd());
+            let frame = tokio::task::spawn_blocking(move || extract_thumbnail_frame(&path))
+                .await
+                .map_err(|e| RepoError::from(&*e.to_string()))??;
+            (frame, mime::IMAGE_PNG)
+        } else {
+            (self.get_bytes(file.cd()).await?, mime_type)
+        };
+        let sizes: Vec<ThumbnailSize> = sizes.into_iter().collect();
 
-        for thumbnail in thumbnails {
-            let mut buf = Cursor::new(Vec::new());
-            let size = thumbnail.size();
-            let size = Dimensions {
-                height: size.1,
-                width: size.0,
-            };
-            thumbnail.write_png(&mut buf)?;
+        // decoding and resizing blocks for a while so it must not run on the async executor
+        let thumbnails = tokio::task::spawn_blocking(move || {
+            catch_thumbnail_panic(|| encode_thumbnails(bytes, source_mime_type, sizes, crop))
+        })
+        .await
+        .map_err(|e| RepoError::from(&*e.to_string()))??;
+        let mut dtos = Vec::new();
 
+        for (size, data) in thumbnails {
             let path = self
                 .ctx
                 .thumbnail_storage
-                .add_thumbnail(file.encoded_cd(), size.clone(), &buf.into_inner())
+                .add_thumbnail(file.encoded_cd(), size.clone(), crop, &data)
                 .await?;
             dtos.push(ThumbnailDto::new(
                 path,
                 file.encoded_cd(),
                 size,
-                mime_type.to_string(),
+                crop,
+                mime::IMAGE_PNG.to_string(),
             ))
         }
 
         Ok(dtos)
     }
 }
+
+/// Returns if the thumbnail was fitted into the given size.
+/// Fitted thumbnails keep the aspect ratio so only their longer side matches the size
+fn is_thumbnail_of_size(thumbnail: &ThumbnailDto, size: &ThumbnailSize) -> bool {
+    let (height, width) = size.dimensions();
+    let Dimensions {
+        height: thumb_height,
+        width: thumb_width,
+    } = thumbnail.size();
+
+    thumbnail.crop() == ThumbnailCrop::Fit
+        && (*thumb_height == height && *thumb_width <= width
+            || *thumb_width == width && *thumb_height <= height)
+}
+
+/// Creates thumbnails of the given sizes and encodes them as png
+fn encode_thumbnails(
+    bytes: Vec<u8>,
+    mime_type: Mime,
+    sizes: Vec<ThumbnailSize>,
+    crop: ThumbnailCrop,
+) -> RepoResult<Vec<(Dimensions, Vec<u8>)>> {
+    // the exif data gets lost when the source is converted
+    let orientation = read_exif(&mut Cursor::new(&bytes))
+        .and_then(|e| e.orientation)
+        .filter(|o| *o != 1);
+    let (bytes, source_mime_type) = prepare_thumbnail_source(bytes, mime_type)?;
+
+    if crop == ThumbnailCrop::CenterCrop {
+        return encode_cropped_thumbnails(bytes, source_mime_type, sizes, orientation);
+    }
+    // the sizes are the bounding boxes of the upright image
+    let sizes = if orientation.map(|o| o >= 5).unwrap_or(false) {
+        sizes
+            .into_iter()
+            .map(|s| {
+                let (width, height) = s.dimensions();
+                ThumbnailSize::Custom((height, width))
+            })
+            .collect()
+    } else {
+        sizes
+    };
+    let thumbnails = thumbnailer::create_thumbnails(Cursor::new(bytes), source_mime_type, sizes)?;
+    let mut encoded = Vec::with_capacity(thumbnails.len());
+
+    for thumbnail in thumbnails {
+        let mut buf = Cursor::new(Vec::new());
+        let size = thumbnail.size();
+        thumbnail.write_png(&mut buf)?;
+
+        if let Some(orientation) = orientation {
+            let thumbnail = image::load_from_memory_with_format(buf.get_ref(), ImageFormat::Png)?;
+            encoded.push(encode_png(apply_exif_orientation(thumbnail, orientation))?);
+        } else {
+            encoded.push((
+                Dimensions {
+                    height: size.1,
+                    width: size.0,
+                },
+                buf.into_inner(),
+            ));
+        }
+    }
+
+    Ok(encoded)
+}
+
+/// Creates center cropped thumbnails from a bigger thumbnail so that the thumbnailer
+/// still takes care of the different file types.
+/// The bigger thumbnail covers the sizes for aspect ratios up to [CROP_SOURCE_SCALE]:1
+fn encode_cropped_thumbnails(
+    bytes: Vec<u8>,
+    mime_type: Mime,
+    sizes: Vec<ThumbnailSize>,
+    orientation: Option<u32>,
+) -> RepoResult<Vec<(Dimensions, Vec<u8>)>> {
+    let source_size = sizes
+        .iter()
+        .map(|s| {
+            let (width, height) = s.dimensions();
+            width.max(height)
+        })
+        .max()
+        .unwrap_or(0)
+        * CROP_SOURCE_SCALE;
+    let source = thumbnailer::create_thumbnails(
+        Cursor::new(bytes),
+        mime_type,
+        [ThumbnailSize::Custom((source_size, source_size))],
+    )?
+    .into_iter()
+    .next()
+    .ok_or_else(|| RepoError::from("could not create thumbnail source"))?;
+    let mut buf = Cursor::new(Vec::new());
+    source.write_png(&mut buf)?;
+    let mut source = image::load_from_memory_with_format(buf.get_ref(), ImageFormat::Png)?;
+    if let Some(orientation) = orientation {
+        source = apply_exif_orientation(source, orientation);
+    }
+    let mut encoded = Vec::with_capacity(sizes.len());
+
+    for size in sizes {
+        let (width, height) = size.dimensions();
+        let thumbnail = source.resize_to_fill(width, height, FilterType::Lanczos3);
+        encoded.push(encode_png(thumbnail)?);
+    }
+
+    Ok(encoded)
+}
+
+fn encode_png(image: DynamicImage) -> RepoResult<(Dimensions, Vec<u8>)> {
+    let mut buf = Cursor::new(Vec::new());
+    image.write_to(&mut buf, ImageOutputFormat::Png)?;
+    let dimensions = Dimensions {
+        height: image.height(),
+        width: image.width(),
+    };
+
+    Ok((dimensions, buf.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::error::{RepoError, RepoResult};
+    use mediarepo_core::fs::thumbnail_store::ThumbnailCrop;
+    use mediarepo_core::image_processing::{catch_thumbnail_panic, validate_image};
+    use mediarepo_core::thumbnailer::ThumbnailSize;
+
+    use crate::dao::repo::Repo;
+    use crate::dao::DaoProvider;
+    use crate::dto::{FileStatus, UpdateFileMetadataDto};
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_creates_thumbnails_for_webp_images() {
+        let bytes = include_bytes!("../../../test-assets/sample.webp");
+        assert_thumbnail_created("webp", bytes, "image/webp").await;
+    }
+
+    #[cfg(feature = "avif")]
+    #[tokio::test]
+    async fn it_creates_thumbnails_for_avif_images() {
+        let bytes = include_bytes!("../../../test-assets/sample.avif");
+        assert_thumbnail_created("avif", bytes, "image/avif").await;
+    }
+
+    #[tokio::test]
+    async fn it_creates_each_configured_thumbnail_size_once() {
+        let (repo, root) = test_repo("configured-thumbnail-sizes").await;
+        let repo = repo.with_thumbnail_sizes(vec![ThumbnailSize::Small, ThumbnailSize::Large]);
+        let file = add_file(&repo, png_bytes(64, 32), "image/png", true).await;
+
+        let thumbnails = repo.file().thumbnails(file.encoded_cd()).await.unwrap();
+        let widths: Vec<u32> = thumbnails.iter().map(|t| t.size().width).collect();
+        assert_eq!(widths, vec![128, 512]);
+
+        let created = repo
+            .file()
+            .create_configured_thumbnails(&file)
+            .await
+            .unwrap();
+        assert!(created.is_empty());
+        assert_eq!(
+            repo.file()
+                .thumbnails(file.encoded_cd())
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_requires_an_undelete_to_restore_deleted_files() {
+        let (repo, root) = test_repo("update-metadata-status").await;
+        let file = add_file(&repo, vec![1], "image/png", false).await;
+        let update = |name: &str| UpdateFileMetadataDto {
+            file_id: file.id(),
+            name: Some(Some(name.to_string())),
+            comment: Some(Some(format!("comment of {}", name))),
+            ..Default::default()
+        };
+
+        let metadata = repo
+            .file()
+            .update_metadata_and_status(update("a"), Some(FileStatus::Deleted), false)
+            .await
+            .unwrap();
+        assert_eq!(metadata.name(), Some(&String::from("a")));
+        assert_eq!(metadata.comment(), Some(&String::from("comment of a")));
+        let status = |repo: &Repo| {
+            let file_dao = repo.file();
+            let id = file.id();
+            async move { file_dao.by_id(id).await.unwrap().unwrap().status() }
+        };
+        assert!(matches!(status(&repo).await, FileStatus::Deleted));
+
+        let result = repo
+            .file()
+            .update_metadata_and_status(update("b"), Some(FileStatus::Imported), false)
+            .await;
+        assert!(result.is_err());
+        assert!(matches!(status(&repo).await, FileStatus::Deleted));
+        let metadata = repo.file().metadata(file.id()).await.unwrap().unwrap();
+        assert_eq!(metadata.name(), Some(&String::from("a")));
+
+        let metadata = repo
+            .file()
+            .update_metadata_and_status(update("b"), Some(FileStatus::Imported), true)
+            .await
+            .unwrap();
+        assert_eq!(metadata.name(), Some(&String::from("b")));
+        assert!(matches!(status(&repo).await, FileStatus::Imported));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_only_changes_the_status_of_files_with_the_source_status() {
+        let (repo, root) = test_repo("set-status").await;
+        let imported = add_file(&repo, vec![1], "image/png", false).await;
+        let deleted = add_file(&repo, vec![2], "image/png", false).await;
+        repo.file()
+            .set_status(
+                vec![deleted.id()],
+                FileStatus::Imported,
+                FileStatus::Deleted,
+            )
+            .await
+            .unwrap();
+        let ids = vec![imported.id(), deleted.id()];
+
+        let archived = repo
+            .file()
+            .set_status(ids.clone(), FileStatus::Imported, FileStatus::Archived)
+            .await
+            .unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].id(), imported.id());
+        assert!(matches!(archived[0].status(), FileStatus::Archived));
+        let stored = repo.file().by_id(deleted.id()).await.unwrap().unwrap();
+        assert!(matches!(stored.status(), FileStatus::Deleted));
+
+        let restored = repo
+            .file()
+            .set_status(ids.clone(), FileStatus::Deleted, FileStatus::Imported)
+            .await
+            .unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].id(), deleted.id());
+        let stored = repo.file().by_id(imported.id()).await.unwrap().unwrap();
+        assert!(matches!(stored.status(), FileStatus::Archived));
+
+        let unarchived = repo
+            .file()
+            .set_status(ids, FileStatus::Archived, FileStatus::Imported)
+            .await
+            .unwrap();
+        assert_eq!(unarchived.len(), 1);
+        let stored = repo.file().by_id(imported.id()).await.unwrap().unwrap();
+        assert!(matches!(stored.status(), FileStatus::Imported));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_creates_center_cropped_thumbnails_next_to_fitted_ones() {
+        let (repo, root) = test_repo("thumbnail-crop").await;
+        let file = add_file(&repo, png_bytes(60, 20), "image/png", false).await;
+        let size = ThumbnailSize::Custom((16, 16));
+        repo.file()
+            .create_thumbnails(&file, vec![size])
+            .await
+            .unwrap();
+        repo.file()
+            .create_thumbnails_with_crop(&file, vec![size], ThumbnailCrop::CenterCrop)
+            .await
+            .unwrap();
+
+        let mut thumbnails: Vec<(ThumbnailCrop, u32, u32)> = repo
+            .file()
+            .thumbnails(file.encoded_cd())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.crop(), t.size().width, t.size().height))
+            .collect();
+        thumbnails.sort_by_key(|(crop, ..)| *crop == ThumbnailCrop::CenterCrop);
+        assert_eq!(
+            thumbnails,
+            vec![
+                (ThumbnailCrop::Fit, 16, 5),
+                (ThumbnailCrop::CenterCrop, 16, 16)
+            ]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_fails_thumbnail_creation_of_malformed_images() {
+        let (repo, root) = test_repo("malformed-thumbnail").await;
+        let mut bytes = png_bytes(64, 64);
+        bytes.truncate(bytes.len() / 2);
+        let file = add_file(&repo, bytes, "image/png", false).await;
+
+        assert!(repo
+            .file()
+            .create_thumbnails(&file, vec![ThumbnailSize::Medium])
+            .await
+            .is_err());
+        assert!(repo
+            .file()
+            .thumbnails(file.encoded_cd())
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_fails_thumbnail_creation_of_undecodable_videos() {
+        let (repo, root) = test_repo("undecodable-video").await;
+        let file = add_file(&repo, b"not a video".to_vec(), "video/mp4", false).await;
+
+        let result = repo
+            .file()
+            .create_thumbnails(&file, vec![ThumbnailSize::Medium])
+            .await;
+        assert!(matches!(result, Err(RepoError::ThumbnailFailed(_))));
+        assert!(repo
+            .file()
+            .thumbnails(file.encoded_cd())
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn it_converts_decoder_panics_into_errors() {
+        let result: RepoResult<()> = catch_thumbnail_panic(|| panic!("malformed chunk"));
+
+        assert!(matches!(
+            result,
+            Err(RepoError::ThumbnailFailed(message)) if message == "malformed chunk"
+        ));
+    }
+
+    async fn assert_thumbnail_created(name: &str, bytes: &[u8], mime_type: &str) {
+        validate_image(bytes, mime_type).unwrap();
+
+        let (repo, root) = test_repo(&format!("thumbnail-{}", name)).await;
+        let file = add_file(&repo, bytes.to_vec(), mime_type, true).await;
+        let thumbnails = repo.file().thumbnails(file.encoded_cd()).await.unwrap();
+        assert_eq!(thumbnails.len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
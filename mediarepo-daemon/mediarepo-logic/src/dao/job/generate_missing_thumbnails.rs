@@ -2,7 +2,6 @@ use crate::dao::job::JobDao;
 use crate::dao::DaoProvider;
 use mediarepo_core::error::RepoResult;
 use mediarepo_core::futures;
-use mediarepo_core::thumbnailer::ThumbnailSize;
 
 impl JobDao {
     /// Generates thumbnails for files that are still missing some
@@ -20,9 +19,7 @@ impl JobDao {
 
         futures::future::join_all(missing_thumbnails.into_iter().map(|f| async {
             let file = f;
-            file_dao
-                .create_thumbnails(&file, vec![ThumbnailSize::Medium])
-                .await
+            file_dao.create_configured_thumbnails(&file).await
         }))
         .await;
 
@@ -0,0 +1,22 @@
+use crate::dao::job::JobDao;
+use crate::dao::DaoProvider;
+use mediarepo_core::error::RepoResult;
+
+impl JobDao {
+    /// Calculates the perceptual hashes of all images that were imported
+    /// before hashes were stored or that don't have one for another reason.
+    /// Returns the number of images a hash was stored for
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn generate_phashes(&self) -> RepoResult<usize> {
+        let file_dao = self.file();
+        let mut count = 0;
+
+        for file in file_dao.images_without_phash().await? {
+            if file_dao.generate_phash(&file).await?.is_some() {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+}
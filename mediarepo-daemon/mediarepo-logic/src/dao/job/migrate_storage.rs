@@ -0,0 +1,181 @@
+use crate::dao::job::JobDao;
+use mediarepo_core::content_descriptor::{
+    encode_content_descriptor, is_v1_content_descriptor, HashAlgorithm,
+};
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::content_descriptor;
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::TransactionTrait;
+
+impl JobDao {
+    /// Returns the ids of all content descriptors that weren't created with the given algorithm
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn descriptors_to_migrate(&self, algorithm: HashAlgorithm) -> RepoResult<Vec<i64>> {
+        let cds: Vec<content_descriptor::Model> =
+            content_descriptor::Entity::find().all(&self.ctx.db).await?;
+        let mut ids = Vec::new();
+
+        for cd in cds {
+            if HashAlgorithm::of_descriptor(&cd.descriptor)? != Some(algorithm) {
+                ids.push(cd.id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Stores the file of a content descriptor again under a descriptor created with the given algorithm
+    /// and replaces the descriptor. The stored file is verified against both descriptors
+    /// before the old one is removed. Returns false if the descriptor already uses the algorithm
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn migrate_descriptor(&self, id: i64, algorithm: HashAlgorithm) -> RepoResult<bool> {
+        let cd = content_descriptor::Entity::find_by_id(id)
+            .one(&self.ctx.db)
+            .await?
+            .ok_or_else(|| RepoError::from("content descriptor not found"))?;
+        let src_cd = cd.descriptor;
+
+        if HashAlgorithm::of_descriptor(&src_cd)? == Some(algorithm) {
+            return Ok(false);
+        }
+        if is_v1_content_descriptor(&src_cd) {
+            return Err(RepoError::from(
+                "v1 content descriptors need to be converted before migrating the storage",
+            ));
+        }
+        let storage = &self.ctx.main_storage;
+
+        if !storage.verify_file(&src_cd).await? {
+            return Err(RepoError::from(&*format!(
+                "file {} doesn't match its content descriptor",
+                encode_content_descriptor(&src_cd)
+            )));
+        }
+        let (_, reader) = storage.get_file(&src_cd).await?;
+        let (dst_cd, _) = storage
+            .add_file_with_algorithm(reader, None, algorithm)
+            .await?;
+
+        if !storage.verify_file(&dst_cd).await? {
+            storage.delete_file(&dst_cd).await?;
+            return Err(RepoError::from(&*format!(
+                "the migrated copy of file {} doesn't match its content descriptor",
+                encode_content_descriptor(&src_cd)
+            )));
+        }
+        let src_encoded = encode_content_descriptor(&src_cd);
+        let dst_encoded = encode_content_descriptor(&dst_cd);
+
+        let trx = self.ctx.db.begin().await?;
+        content_descriptor::ActiveModel {
+            id: Set(id),
+            descriptor: Set(dst_cd.clone()),
+        }
+        .update(&trx)
+        .await?;
+        self.ctx
+            .thumbnail_storage
+            .rename_parent(&src_encoded, &dst_encoded)
+            .await?;
+
+        if let Err(e) = trx.commit().await {
+            self.ctx
+                .thumbnail_storage
+                .rename_parent(&dst_encoded, &src_encoded)
+                .await?;
+            storage.delete_file(&dst_cd).await?;
+
+            return Err(e.into());
+        }
+        storage.delete_file(&src_cd).await?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::content_descriptor::{create_content_descriptor_with, HashAlgorithm};
+
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_migrates_stored_files_to_another_hash_algorithm() {
+        let (repo, root) = test_repo("migrate-storage").await;
+        let image_content = png_bytes(10, 10);
+        let image = add_file(&repo, image_content.clone(), "image/png", true).await;
+        let other = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let job_dao = repo.job();
+
+        let ids = job_dao
+            .descriptors_to_migrate(HashAlgorithm::Blake3)
+            .await
+            .unwrap();
+        assert_eq!(ids, vec![image.cd_id(), other.cd_id()]);
+
+        for id in ids {
+            assert!(job_dao
+                .migrate_descriptor(id, HashAlgorithm::Blake3)
+                .await
+                .unwrap());
+        }
+        assert!(!job_dao
+            .migrate_descriptor(image.cd_id(), HashAlgorithm::Blake3)
+            .await
+            .unwrap());
+        assert!(job_dao
+            .descriptors_to_migrate(HashAlgorithm::Blake3)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let migrated = repo.file().by_id(image.id()).await.unwrap().unwrap();
+        assert_eq!(
+            migrated.cd(),
+            &create_content_descriptor_with(&image_content, HashAlgorithm::Blake3)[..]
+        );
+        assert_eq!(
+            repo.file().get_bytes(migrated.cd()).await.unwrap(),
+            image_content
+        );
+        assert!(repo.file().get_bytes(image.cd()).await.is_err());
+        assert_eq!(
+            repo.file()
+                .thumbnails(migrated.encoded_cd())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_keeps_files_that_dont_match_their_descriptor_when_migrating() {
+        let (repo, root) = test_repo("migrate-storage-corrupt").await;
+        let file = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let stored_path = std::fs::read_dir(root.join("files"))
+            .unwrap()
+            .flat_map(|folder| std::fs::read_dir(folder.unwrap().path()).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.ends_with(file.encoded_cd()))
+            .unwrap();
+        std::fs::write(&stored_path, [4, 5, 6]).unwrap();
+
+        let result = repo
+            .job()
+            .migrate_descriptor(file.cd_id(), HashAlgorithm::Sha3_256)
+            .await;
+        assert!(result.is_err());
+
+        let unchanged = repo.file().by_id(file.id()).await.unwrap().unwrap();
+        assert_eq!(unchanged.cd(), file.cd());
+        assert!(stored_path.exists());
+        assert_eq!(std::fs::read_dir(root.join("files")).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
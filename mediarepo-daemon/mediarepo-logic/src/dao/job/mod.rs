@@ -1,7 +1,9 @@
 use crate::dao_provider;
 
 pub mod generate_missing_thumbnails;
+pub mod generate_phashes;
 pub mod migrate_content_descriptors;
+pub mod migrate_storage;
 pub mod sqlite_operations;
 pub mod state;
 
@@ -3,6 +3,7 @@ use mediarepo_core::error::RepoError::Corrupted;
 use mediarepo_core::error::RepoResult;
 use sea_orm::DatabaseBackend::Sqlite;
 use sea_orm::{ConnectionTrait, FromQueryResult, Statement};
+use std::path::Path;
 
 #[derive(Debug, FromQueryResult)]
 struct IntegrityCheckResult {
@@ -33,6 +34,22 @@ impl JobDao {
 
         Ok(())
     }
+
+    /// Writes a consistent copy of the database to the given path
+    /// while the database can still be read and written
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn backup_database(&self, path: &Path) -> RepoResult<()> {
+        self.ctx
+            .db
+            .execute(Statement::from_sql_and_values(
+                Sqlite,
+                "VACUUM INTO ?;",
+                vec![path.to_string_lossy().to_string().into()],
+            ))
+            .await?;
+
+        Ok(())
+    }
 }
 
 fn map_check_result(result: IntegrityCheckResult) -> RepoResult<()> {
@@ -42,3 +59,27 @@ fn map_check_result(result: IntegrityCheckResult) -> RepoResult<()> {
         Err(Corrupted(result.integrity_check))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{add_file, file_repo, test_repo};
+
+    #[tokio::test]
+    async fn it_backs_up_the_database_into_a_readable_copy() {
+        let (_, root) = test_repo("backup").await;
+        // in-memory databases also write their backups into memory
+        let repo = file_repo(&root, "repo.db").await;
+        add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+
+        repo.job()
+            .backup_database(&root.join("backup.db"))
+            .await
+            .unwrap();
+
+        let backup = file_repo(&root, "backup.db").await;
+        assert_eq!(backup.file().all().await.unwrap().len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -2,12 +2,15 @@ use sea_orm::{ActiveValue, DatabaseConnection};
 
 use mediarepo_core::fs::file_hash_store::FileHashStore;
 use mediarepo_core::fs::thumbnail_store::ThumbnailStore;
+use mediarepo_core::thumbnailer::ThumbnailSize;
 
+use crate::dao::attribute::AttributeDao;
 use crate::dao::file::FileDao;
 use crate::dao::job::JobDao;
 use crate::dao::sorting_preset::SortingPresetDao;
 use crate::dao::tag::TagDao;
 
+pub mod attribute;
 pub mod file;
 pub mod job;
 pub mod repo;
@@ -42,6 +45,8 @@ pub struct DaoContext {
     pub db: DatabaseConnection,
     pub main_storage: FileHashStore,
     pub thumbnail_storage: ThumbnailStore,
+    /// The sizes of the thumbnails that are created for every file
+    pub thumbnail_sizes: Vec<ThumbnailSize>,
 }
 
 pub trait DaoProvider {
@@ -62,6 +67,10 @@ pub trait DaoProvider {
     fn sorting_preset(&self) -> SortingPresetDao {
         SortingPresetDao::new(self.dao_ctx())
     }
+
+    fn attribute(&self) -> AttributeDao {
+        AttributeDao::new(self.dao_ctx())
+    }
 }
 
 fn opt_to_active_val<T: Into<sea_orm::Value>>(opt: Option<T>) -> ActiveValue<T> {
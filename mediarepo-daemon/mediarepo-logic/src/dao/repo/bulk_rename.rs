@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use sea_orm::ActiveValue::{Set, Unchanged};
+use sea_orm::{ActiveModelTrait, TransactionTrait};
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::file_metadata;
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+
+impl Repo {
+    /// Renames the given files by expanding the pattern for every file.
+    /// Supported placeholders are `{id}`, `{index}` (the 1-based position in the given list)
+    /// and `{<namespace>}` which expands to the file's tags of that namespace.
+    /// Returns the new name of every file in the order of the input. If `dry_run` is set
+    /// the names are only computed and nothing is changed
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn bulk_rename(
+        &self,
+        file_ids: Vec<i64>,
+        pattern: &str,
+        dry_run: bool,
+    ) -> RepoResult<Vec<(i64, String)>> {
+        let files = self.file().all_by_id(file_ids.clone()).await?;
+        let cd_ids_by_file: HashMap<i64, i64> = files.iter().map(|f| (f.id(), f.cd_id())).collect();
+        let namespaced_tags = self
+            .tag()
+            .cdids_with_namespaced_tags(files.iter().map(|f| f.cd_id()).collect())
+            .await?;
+
+        let mut names = Vec::with_capacity(file_ids.len());
+        for (index, file_id) in file_ids.into_iter().enumerate() {
+            let cd_id = cd_ids_by_file
+                .get(&file_id)
+                .ok_or_else(|| RepoError::from(&*format!("file {} not found", file_id)))?;
+            // files can share their content so the tags are needed for every file of the cd
+            let tags = namespaced_tags.get(cd_id).cloned().unwrap_or_default();
+            let name = expand_rename_pattern(pattern, |placeholder| match placeholder {
+                "id" => file_id.to_string(),
+                "index" => (index + 1).to_string(),
+                namespace => tags
+                    .get(namespace)
+                    .map(|names| names.join(","))
+                    .unwrap_or_default(),
+            });
+            names.push((file_id, name));
+        }
+
+        if !dry_run {
+            let trx = self.db().begin().await?;
+
+            for (file_id, name) in &names {
+                file_metadata::ActiveModel {
+                    file_id: Unchanged(*file_id),
+                    name: Set(Some(name.clone())),
+                    ..Default::default()
+                }
+                .update(&trx)
+                .await?;
+            }
+            trx.commit().await?;
+        }
+
+        Ok(names)
+    }
+}
+
+/// Replaces every `{placeholder}` of the pattern with the value returned for it.
+/// Braces that aren't closed are kept as they are
+fn expand_rename_pattern<F: FnMut(&str) -> String>(pattern: &str, mut value: F) -> String {
+    let mut expanded = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let after_start = &rest[start + 1..];
+
+        match after_start.find('}') {
+            Some(end) => {
+                expanded.push_str(&value(after_start[..end].trim()));
+                rest = &after_start[end + 1..];
+            }
+            None => {
+                expanded.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    expanded.push_str(rest);
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::dto::AddTagDto;
+    use crate::testing::fixtures::{add_file, add_file_sharing_content, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_renames_files_by_a_pattern() {
+        let (repo, root) = test_repo("bulk-rename").await;
+        let first = add_file(&repo, png_bytes(2, 2), "image/png", false).await;
+        let second = add_file(&repo, png_bytes(3, 3), "image/png", false).await;
+        let tags = repo
+            .tag()
+            .add_all(
+                vec![AddTagDto {
+                    namespace: Some(String::from("series")),
+                    name: String::from("holiday"),
+                }],
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        repo.tag()
+            .upsert_mappings(vec![first.cd_id()], vec![tags[0].id()])
+            .await
+            .unwrap();
+        let ids = vec![second.id(), first.id()];
+        let expected = vec![
+            (second.id(), format!("_1-{}.png", second.id())),
+            (first.id(), format!("holiday_2-{}.png", first.id())),
+        ];
+
+        let preview = repo
+            .bulk_rename(ids.clone(), "{series}_{index}-{id}.png", true)
+            .await
+            .unwrap();
+        assert_eq!(preview, expected);
+        assert_eq!(
+            repo.file()
+                .metadata(first.id())
+                .await
+                .unwrap()
+                .unwrap()
+                .name(),
+            None
+        );
+
+        repo.bulk_rename(ids, "{series}_{index}-{id}.png", false)
+            .await
+            .unwrap();
+        for (id, name) in expected {
+            let metadata = repo.file().metadata(id).await.unwrap().unwrap();
+            assert_eq!(metadata.name(), Some(&name));
+        }
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_expands_the_tags_of_files_sharing_their_content() {
+        let (repo, root) = test_repo("bulk-rename-shared").await;
+        let file = add_file(&repo, png_bytes(2, 2), "image/png", false).await;
+        let duplicate = add_file_sharing_content(&repo, &file).await;
+        let tags = repo
+            .tag()
+            .add_all(
+                vec![AddTagDto {
+                    namespace: Some(String::from("series")),
+                    name: String::from("holiday"),
+                }],
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], vec![tags[0].id()])
+            .await
+            .unwrap();
+
+        let names = repo
+            .bulk_rename(vec![file.id(), duplicate.id()], "{series}", true)
+            .await
+            .unwrap();
+        assert_eq!(
+            names,
+            vec![
+                (file.id(), String::from("holiday")),
+                (duplicate.id(), String::from("holiday"))
+            ]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
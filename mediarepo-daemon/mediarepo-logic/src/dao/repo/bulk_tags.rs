@@ -0,0 +1,136 @@
+use sea_orm::prelude::*;
+use sea_orm::{FromQueryResult, QuerySelect, TransactionTrait};
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_database::entities::file;
+
+use crate::dao::repo::Repo;
+use crate::dao::tag::mappings::{count_mappings, remove_mappings, upsert_mappings};
+
+#[derive(Debug, FromQueryResult)]
+struct FileCdId {
+    cd_id: i64,
+}
+
+impl Repo {
+    /// Assigns the tags to all given files and the tags implied by them.
+    /// Mappings that already exist are skipped. Returns the number of newly created
+    /// mappings between the files and the given tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_tags_to_files(
+        &self,
+        file_ids: Vec<i64>,
+        tag_ids: Vec<i64>,
+    ) -> RepoResult<u64> {
+        let cd_ids = self.cd_ids_for_files(file_ids).await?;
+        if cd_ids.is_empty() || tag_ids.is_empty() {
+            return Ok(0);
+        }
+        let trx = self.db().begin().await?;
+        let existing = count_mappings(&trx, &cd_ids, &tag_ids).await?;
+        upsert_mappings(&trx, cd_ids.clone(), tag_ids.clone()).await?;
+        let created = count_mappings(&trx, &cd_ids, &tag_ids).await? - existing;
+        trx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// Removes the tags from all given files together with the implied tags that
+    /// aren't required anymore. Returns the number of removed mappings of the given tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_tags_from_files(
+        &self,
+        file_ids: Vec<i64>,
+        tag_ids: Vec<i64>,
+    ) -> RepoResult<u64> {
+        let cd_ids = self.cd_ids_for_files(file_ids).await?;
+        if cd_ids.is_empty() || tag_ids.is_empty() {
+            return Ok(0);
+        }
+        let trx = self.db().begin().await?;
+        let removed = remove_mappings(&trx, cd_ids, tag_ids).await?;
+        trx.commit().await?;
+
+        Ok(removed)
+    }
+
+    /// Returns the distinct content descriptor ids of the given files
+    async fn cd_ids_for_files(&self, file_ids: Vec<i64>) -> RepoResult<Vec<i64>> {
+        let cd_ids = file::Entity::find()
+            .select_only()
+            .column(file::Column::CdId)
+            .filter(file::Column::Id.is_in(file_ids))
+            .group_by(file::Column::CdId)
+            .into_model::<FileCdId>()
+            .all(self.db())
+            .await?
+            .into_iter()
+            .map(|f| f.cd_id)
+            .collect();
+
+        Ok(cd_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::dto::AddTagDto;
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_adds_and_removes_tags_of_many_files_at_once() {
+        let (repo, root) = test_repo("bulk-tags").await;
+        let mut file_ids = Vec::new();
+        for i in 0..3 {
+            let file = add_file(&repo, vec![i], "application/octet-stream", false).await;
+            file_ids.push(file.id());
+        }
+        let tag_ids: Vec<i64> = repo
+            .tag()
+            .add_all(
+                ["a", "b"]
+                    .iter()
+                    .map(|name| AddTagDto {
+                        namespace: None,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id())
+            .collect();
+        repo.add_tags_to_files(vec![file_ids[0]], vec![tag_ids[0]])
+            .await
+            .unwrap();
+
+        let created = repo
+            .add_tags_to_files(file_ids.clone(), tag_ids.clone())
+            .await
+            .unwrap();
+        assert_eq!(created, 5);
+        let created = repo
+            .add_tags_to_files(file_ids.clone(), tag_ids.clone())
+            .await
+            .unwrap();
+        assert_eq!(created, 0);
+
+        let removed = repo
+            .remove_tags_from_files(file_ids[..2].to_vec(), vec![tag_ids[0]])
+            .await
+            .unwrap();
+        assert_eq!(removed, 2);
+        let (tags, _) = repo
+            .tag()
+            .aggregate_tags_for_files(file_ids, false)
+            .await
+            .unwrap();
+        let counts: Vec<(i64, u64)> = tags.into_iter().map(|(t, c)| (t.id(), c)).collect();
+        assert_eq!(counts, vec![(tag_ids[1], 3), (tag_ids[0], 1)]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::fs::thumbnail_store::{Dimensions, ThumbnailCrop};
+use mediarepo_core::image::{self, ImageOutputFormat};
+use mediarepo_core::video_processing::create_contact_sheet;
+
+use crate::dao::repo::Repo;
+use crate::dto::{FileDto, ThumbnailDto};
+
+/// The maximum number of rows and columns of a contact sheet
+const MAX_SHEET_TILES: u32 = 10;
+
+impl Repo {
+    /// Returns the previously created contact sheet of a video
+    /// with the given number of rows and columns
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn contact_sheet(
+        &self,
+        file: &FileDto,
+        rows: u32,
+        cols: u32,
+    ) -> RepoResult<Option<ThumbnailDto>> {
+        let path = match self
+            .thumbnail_storage
+            .get_contact_sheet(file.encoded_cd(), rows, cols)
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let (width, height) = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || image::image_dimensions(path)
+        })
+        .await
+        .map_err(|e| RepoError::from(&*e.to_string()))??;
+
+        Ok(Some(contact_sheet_dto(file, path, width, height)))
+    }
+
+    /// Creates a contact sheet of evenly spaced frames of a video tiled into `rows` x `cols`.
+    /// The sheet is stored next to the thumbnails of the file and replaces an existing sheet
+    /// with the same layout
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn create_contact_sheet(
+        &self,
+        file: &FileDto,
+        rows: u32,
+        cols: u32,
+    ) -> RepoResult<ThumbnailDto> {
+        if !file.mime_type().starts_with("video/") {
+            return Err(RepoError::from(
+                "contact sheets can only be created for videos",
+            ));
+        }
+        if rows == 0 || cols == 0 || rows > MAX_SHEET_TILES || cols > MAX_SHEET_TILES {
+            return Err(RepoError::from(&*format!(
+                "the rows and columns of a contact sheet must be between 1 and {}",
+                MAX_SHEET_TILES
+            )));
+        }
+        let video_path = self.main_storage.file_path(file.cd());
+
+        // ffmpeg is run for every frame which blocks for a while
+        let (width, height, data) = tokio::task::spawn_blocking(move || {
+            let sheet = create_contact_sheet(&video_path, rows, cols)?;
+            let mut buf = Cursor::new(Vec::new());
+            sheet.write_to(&mut buf, ImageOutputFormat::Jpeg(85))?;
+
+            RepoResult::Ok((sheet.width(), sheet.height(), buf.into_inner()))
+        })
+        .await
+        .map_err(|e| RepoError::from(&*e.to_string()))??;
+        let path = self
+            .thumbnail_storage
+            .add_contact_sheet(file.encoded_cd(), rows, cols, &data)
+            .await?;
+
+        Ok(contact_sheet_dto(file, path, width, height))
+    }
+}
+
+fn contact_sheet_dto(file: &FileDto, path: PathBuf, width: u32, height: u32) -> ThumbnailDto {
+    ThumbnailDto::new(
+        path,
+        file.encoded_cd(),
+        Dimensions { height, width },
+        ThumbnailCrop::Fit,
+        String::from("image/jpeg"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_only_creates_contact_sheets_for_videos() {
+        let (repo, root) = test_repo("contact-sheet").await;
+        let file = add_file(&repo, png_bytes(4, 4), "image/png", false).await;
+
+        assert!(repo.create_contact_sheet(&file, 3, 3).await.is_err());
+        assert!(repo.contact_sheet(&file, 3, 3).await.unwrap().is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
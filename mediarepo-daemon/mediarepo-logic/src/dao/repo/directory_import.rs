@@ -0,0 +1,395 @@
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use tokio::fs;
+
+use mediarepo_core::content_descriptor::create_content_descriptor_with;
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::glob::Pattern;
+use mediarepo_core::image_processing::validate_image_blocking;
+use mediarepo_core::settings::{ImportSettings, TagSettings};
+use mediarepo_core::utils::sniff_mime_type;
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+use crate::dto::{AddFileDto, AddTagDto};
+
+/// How the folders of an imported file are turned into tags
+#[derive(Clone, Debug)]
+pub enum PathTags {
+    /// Every folder becomes a tag without a namespace
+    Tags,
+    /// Every folder becomes a tag of the namespace
+    Namespace(String),
+}
+
+/// Options for importing the files of a folder
+#[derive(Clone, Debug, Default)]
+pub struct DirectoryImportOptions {
+    /// A glob pattern that the path relative to the imported folder has to match
+    pub pattern: Option<String>,
+    /// The extensions of files that are imported. All files are imported if empty
+    pub extensions: Vec<String>,
+    /// Adds the folders between the imported folder and the file as tags
+    pub path_tags: Option<PathTags>,
+    pub follow_symlinks: bool,
+    pub include_hidden: bool,
+}
+
+/// The progress of a folder import after a file was processed
+#[derive(Clone, Debug)]
+pub struct DirectoryImportProgress {
+    pub path: PathBuf,
+    pub imported: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    /// The reason why the path couldn't be imported
+    pub error: Option<String>,
+}
+
+/// The result of importing a folder
+#[derive(Clone, Debug, Default)]
+pub struct DirectoryImportSummary {
+    pub imported: u64,
+    pub skipped: u64,
+    /// The paths that couldn't be imported with the reason
+    pub failures: Vec<(PathBuf, String)>,
+    /// If the import was cancelled before all files were processed
+    pub cancelled: bool,
+}
+
+impl DirectoryImportSummary {
+    /// Counts the result of processing the path where `Ok(false)` means skipped
+    fn record(&mut self, path: &Path, result: RepoResult<bool>) -> DirectoryImportProgress {
+        let error = match result {
+            Ok(true) => {
+                self.imported += 1;
+                None
+            }
+            Ok(false) => {
+                self.skipped += 1;
+                None
+            }
+            Err(e) => {
+                tracing::warn!("failed to import {:?}: {}", path, e);
+                self.failures.push((path.to_owned(), e.to_string()));
+                Some(e.to_string())
+            }
+        };
+
+        DirectoryImportProgress {
+            path: path.to_owned(),
+            imported: self.imported,
+            skipped: self.skipped,
+            failed: self.failures.len() as u64,
+            error,
+        }
+    }
+}
+
+impl Repo {
+    /// Imports the files of the folder and all of its subfolders. Files whose content already
+    /// exists, symlinks that aren't followed and folders that were already visited through
+    /// another path are skipped so that symlink loops are never entered. Files and folders
+    /// that can't be read are reported as failures without stopping the import.
+    /// The progress is passed to the callback after each path. The import stops before
+    /// the next path once the cancellation flag is set
+    #[tracing::instrument(level = "debug", skip(self, cancelled, on_progress))]
+    pub async fn import_directory<F, Fut>(
+        &self,
+        path: &Path,
+        options: DirectoryImportOptions,
+        import_settings: &ImportSettings,
+        tag_settings: &TagSettings,
+        cancelled: &AtomicBool,
+        mut on_progress: F,
+    ) -> RepoResult<DirectoryImportSummary>
+    where
+        F: FnMut(DirectoryImportProgress) -> Fut,
+        Fut: Future<Output = RepoResult<()>>,
+    {
+        let pattern = options
+            .pattern
+            .as_deref()
+            .map(Pattern::new)
+            .transpose()
+            .map_err(|e| RepoError::from(&*format!("invalid pattern: {}", e)))?;
+        let extensions: Vec<String> = options
+            .extensions
+            .iter()
+            .map(|e| e.trim_start_matches('.').to_lowercase())
+            .collect();
+        let mut summary = DirectoryImportSummary::default();
+        let mut visited = HashSet::new();
+        let mut folders = VecDeque::from(vec![path.to_owned()]);
+
+        'walk: while let Some(folder) = folders.pop_front() {
+            if cancelled.load(Ordering::SeqCst) {
+                summary.cancelled = true;
+                break;
+            }
+            match fs::canonicalize(&folder).await {
+                Ok(canonical) => {
+                    if !visited.insert(canonical) {
+                        on_progress(summary.record(&folder, Ok(false))).await?;
+                        continue;
+                    }
+                }
+                Err(e) => {
+                    on_progress(summary.record(&folder, Err(e.into()))).await?;
+                    continue;
+                }
+            }
+            let mut entries = match fs::read_dir(&folder).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    on_progress(summary.record(&folder, Err(e.into()))).await?;
+                    continue;
+                }
+            };
+
+            loop {
+                let entry = match entries.next_entry().await {
+                    Ok(Some(entry)) => entry,
+                    Ok(None) => break,
+                    Err(e) => {
+                        on_progress(summary.record(&folder, Err(e.into()))).await?;
+                        break;
+                    }
+                };
+                let entry_path = entry.path();
+
+                if !options.include_hidden && is_hidden(&entry_path) {
+                    continue;
+                }
+                if cancelled.load(Ordering::SeqCst) {
+                    summary.cancelled = true;
+                    break 'walk;
+                }
+                let is_symlink = match entry.file_type().await {
+                    Ok(file_type) => file_type.is_symlink(),
+                    Err(e) => {
+                        on_progress(summary.record(&entry_path, Err(e.into()))).await?;
+                        continue;
+                    }
+                };
+                if is_symlink && !options.follow_symlinks {
+                    on_progress(summary.record(&entry_path, Ok(false))).await?;
+                    continue;
+                }
+                let metadata = match fs::metadata(&entry_path).await {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        on_progress(summary.record(&entry_path, Err(e.into()))).await?;
+                        continue;
+                    }
+                };
+                if metadata.is_dir() {
+                    folders.push_back(entry_path);
+                    continue;
+                }
+                let relative_path = entry_path.strip_prefix(path).unwrap_or(&entry_path);
+
+                if !metadata.is_file()
+                    || !matches_extension(&entry_path, &extensions)
+                    || !pattern
+                        .as_ref()
+                        .map(|p| p.matches_path(relative_path))
+                        .unwrap_or(true)
+                {
+                    continue;
+                }
+                let tags = path_tags(relative_path, &options.path_tags, tag_settings);
+                let result = self
+                    .import_directory_file(&entry_path, tags, import_settings, tag_settings)
+                    .await;
+                on_progress(summary.record(&entry_path, result)).await?;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Adds the file with the tags and returns false if its content already exists
+    async fn import_directory_file(
+        &self,
+        path: &Path,
+        tags: Vec<AddTagDto>,
+        import_settings: &ImportSettings,
+        tag_settings: &TagSettings,
+    ) -> RepoResult<bool> {
+        let content = fs::read(path).await?;
+        let cd = create_content_descriptor_with(&content, self.main_storage.algorithm());
+
+        if self.file().by_cd(cd).await?.is_some() {
+            return Ok(false);
+        }
+        let metadata = fs::metadata(path).await?;
+        let mime_type = mime_guess::from_path(path)
+            .first()
+            .map(|m| m.to_string())
+            .or_else(|| sniff_mime_type(&content))
+            .unwrap_or_else(|| String::from("application/octet-stream"));
+        let (content, corrupt) = if import_settings.validate_images {
+            match validate_image_blocking(content, mime_type.clone()).await? {
+                (content, Ok(_)) => (content, false),
+                (content, Err(e)) => {
+                    tracing::warn!("imported file {:?} is corrupt: {}", path, e);
+                    (content, true)
+                }
+            }
+        } else {
+            (content, false)
+        };
+        let add_dto = AddFileDto {
+            content,
+            mime_type,
+            creation_time: to_naive_time(metadata.created()),
+            change_time: to_naive_time(metadata.modified()),
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()),
+            corrupt,
+            external_id: None,
+            generate_thumbnails: import_settings.auto_generate_thumbnails,
+        };
+        self.add_file_with_tags(add_dto, tags, tag_settings).await?;
+
+        Ok(true)
+    }
+}
+
+/// Returns the tags for the folders of the path relative to the imported folder
+fn path_tags(
+    relative_path: &Path,
+    mode: &Option<PathTags>,
+    settings: &TagSettings,
+) -> Vec<AddTagDto> {
+    let namespace = match mode {
+        Some(PathTags::Tags) => None,
+        Some(PathTags::Namespace(namespace)) => Some(namespace.to_owned()),
+        None => return Vec::new(),
+    };
+
+    relative_path
+        .parent()
+        .map(|folders| {
+            folders
+                .components()
+                .filter_map(|c| match c {
+                    Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                    _ => None,
+                })
+                .map(|name| AddTagDto {
+                    namespace: namespace.clone(),
+                    name: if settings.case_sensitive {
+                        name
+                    } else {
+                        name.to_lowercase()
+                    },
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn matches_extension(path: &Path, extensions: &[String]) -> bool {
+    extensions.is_empty()
+        || path
+            .extension()
+            .map(|e| extensions.contains(&e.to_string_lossy().to_lowercase()))
+            .unwrap_or(false)
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// Converts a file time of the metadata and falls back to the current time
+/// on platforms that don't support it
+fn to_naive_time(time: std::io::Result<SystemTime>) -> NaiveDateTime {
+    time.map(|t| DateTime::<Local>::from(t).naive_local())
+        .unwrap_or_else(|_| Local::now().naive_local())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use mediarepo_core::content_descriptor::create_content_descriptor;
+    use mediarepo_core::settings::{ImportSettings, TagSettings};
+
+    use crate::dao::repo::directory_import::{DirectoryImportOptions, PathTags};
+    use crate::dao::DaoProvider;
+    use crate::dto::TagDto;
+    use crate::testing::fixtures::test_repo;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_imports_directories_with_folder_tags() {
+        let (repo, root) = test_repo("import-directory").await;
+        let source = root.join("source");
+        std::fs::create_dir_all(source.join("a").join("b")).unwrap();
+        std::fs::write(source.join("one.png"), [1]).unwrap();
+        std::fs::write(source.join("dup.png"), [1]).unwrap();
+        std::fs::write(source.join("a").join("b").join("two.JPG"), [2]).unwrap();
+        std::fs::write(source.join("three.txt"), [3]).unwrap();
+        std::fs::write(source.join(".hidden.png"), [4]).unwrap();
+        std::os::unix::fs::symlink(&source, source.join("a").join("loop")).unwrap();
+        std::os::unix::fs::symlink(source.join("missing"), source.join("broken.png")).unwrap();
+        let options = DirectoryImportOptions {
+            extensions: vec![String::from(".png"), String::from("jpg")],
+            path_tags: Some(PathTags::Namespace(String::from("folder"))),
+            follow_symlinks: true,
+            ..Default::default()
+        };
+
+        let mut progress = Vec::new();
+        let summary = repo
+            .import_directory(
+                &source,
+                options,
+                &ImportSettings {
+                    auto_generate_thumbnails: false,
+                    ..Default::default()
+                },
+                &TagSettings::default(),
+                &AtomicBool::new(false),
+                |p| {
+                    progress.push(p.path);
+                    async { Ok(()) }
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.skipped, 2);
+        assert_eq!(summary.failures.len(), 1);
+        assert_eq!(summary.failures[0].0, source.join("broken.png"));
+        assert!(!summary.cancelled);
+        assert_eq!(progress.len(), 5);
+
+        let file = repo
+            .file()
+            .by_cd(create_content_descriptor(&[2]))
+            .await
+            .unwrap()
+            .unwrap();
+        let mut tags: Vec<String> = repo
+            .tag()
+            .tags_for_cd(file.cd_id())
+            .await
+            .unwrap()
+            .iter()
+            .map(TagDto::normalized_name)
+            .collect();
+        tags.sort();
+        assert_eq!(tags, vec!["folder:a", "folder:b"]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::{FromQueryResult, QueryOrder, QuerySelect};
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_database::entities::{content_descriptor, file};
+
+use crate::dao::repo::Repo;
+use crate::dto::FileDto;
+
+#[derive(Debug, FromQueryResult)]
+struct SharedCd {
+    cd_id: i64,
+}
+
+impl Repo {
+    /// Returns groups of files that share the same content ordered by their ids.
+    /// Files that share their content also share their tags so all but one file
+    /// of a group can be deleted without losing any tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_duplicate_files(&self) -> RepoResult<Vec<Vec<FileDto>>> {
+        let shared_cd_ids: Vec<i64> = file::Entity::find()
+            .select_only()
+            .column(file::Column::CdId)
+            .group_by(file::Column::CdId)
+            .having(Expr::expr(Expr::col(file::Column::Id).count()).gt(1))
+            .into_model::<SharedCd>()
+            .all(self.db())
+            .await?
+            .into_iter()
+            .map(|c| c.cd_id)
+            .collect();
+        if shared_cd_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let files = file::Entity::find()
+            .find_also_related(content_descriptor::Entity)
+            .filter(file::Column::CdId.is_in(shared_cd_ids))
+            .order_by_asc(file::Column::CdId)
+            .order_by_asc(file::Column::Id)
+            .all(self.db())
+            .await?;
+        let mut groups: Vec<Vec<FileDto>> = Vec::new();
+
+        for (file, cd) in files {
+            let cd = match cd {
+                Some(cd) => cd,
+                None => continue,
+            };
+            match groups.last_mut() {
+                Some(group) if group[0].cd_id() == file.cd_id => {
+                    group.push(FileDto::new(file, cd, None))
+                }
+                _ => groups.push(vec![FileDto::new(file, cd, None)]),
+            }
+        }
+
+        Ok(groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::fixtures::{add_file, add_file_sharing_content, test_repo};
+
+    #[tokio::test]
+    async fn it_groups_files_sharing_their_content() {
+        let (repo, root) = test_repo("duplicate-groups").await;
+        let shared = add_file(&repo, vec![1, 2, 3, 4], "application/octet-stream", false).await;
+        add_file(&repo, vec![5, 6], "application/octet-stream", false).await;
+        assert!(repo.find_duplicate_files().await.unwrap().is_empty());
+
+        let duplicate = add_file_sharing_content(&repo, &shared).await;
+
+        let groups = repo.find_duplicate_files().await.unwrap();
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<i64> = groups[0].iter().map(|f| f.id()).collect();
+        assert_eq!(ids, vec![shared.id(), duplicate.id()]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
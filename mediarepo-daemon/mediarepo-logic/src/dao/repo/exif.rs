@@ -0,0 +1,111 @@
+use sea_orm::prelude::*;
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_database::entities::file_exif;
+
+use crate::dao::repo::Repo;
+use crate::dto::{FileDto, FileExifDto};
+
+impl Repo {
+    /// Returns the exif data that was read from an image when it was imported.
+    /// Files that aren't images or don't contain exif data have none
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn exif_for_file(&self, file: &FileDto) -> RepoResult<Option<FileExifDto>> {
+        let exif = file_exif::Entity::find_by_id(file.id())
+            .one(self.db())
+            .await?
+            .map(FileExifDto::new);
+
+        Ok(exif)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use chrono::NaiveDate;
+
+    use mediarepo_core::exif::experimental::Writer;
+    use mediarepo_core::exif::{Field, In, Tag, Value};
+    use mediarepo_core::image::{DynamicImage, ImageOutputFormat};
+    use mediarepo_core::thumbnailer::ThumbnailSize;
+
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_stores_exif_data_and_rotates_thumbnails() {
+        let (repo, root) = test_repo("exif").await;
+        let fields = vec![
+            Field {
+                tag: Tag::Model,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![b"Camera\0".to_vec()]),
+            },
+            Field {
+                tag: Tag::DateTimeOriginal,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![b"2021:07:04 13:37:00".to_vec()]),
+            },
+            Field {
+                tag: Tag::Orientation,
+                ifd_num: In::PRIMARY,
+                value: Value::Short(vec![6]),
+            },
+        ];
+        let file = add_file(&repo, jpeg_with_exif(64, 32, &fields), "image/jpeg", false).await;
+
+        let exif = repo.exif_for_file(&file).await.unwrap().unwrap();
+        assert_eq!(exif.camera_model().map(|m| m.as_str()), Some("Camera"));
+        assert_eq!(exif.camera_make(), None);
+        assert_eq!(exif.orientation(), Some(6));
+        let capture_time = NaiveDate::from_ymd(2021, 7, 4).and_hms(13, 37, 0);
+        assert_eq!(exif.capture_time(), Some(capture_time));
+        let metadata = repo.file().metadata(file.id()).await.unwrap().unwrap();
+        assert_eq!(metadata.creation_time(), capture_time);
+
+        // the image is stored rotated by 90 degrees so the upright thumbnails are portrait
+        let thumbnails = repo
+            .file()
+            .create_thumbnails(
+                &file,
+                vec![ThumbnailSize::Medium, ThumbnailSize::Custom((64, 32))],
+            )
+            .await
+            .unwrap();
+        let sizes: Vec<(u32, u32)> = thumbnails
+            .iter()
+            .map(|t| (t.size().width, t.size().height))
+            .collect();
+        assert_eq!(sizes, vec![(128, 256), (16, 32)]);
+
+        let png = add_file(&repo, png_bytes(4, 4), "image/png", false).await;
+        assert!(repo.exif_for_file(&png).await.unwrap().is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Creates a jpeg image with an exif segment containing the given fields
+    fn jpeg_with_exif(width: u32, height: u32, fields: &[Field]) -> Vec<u8> {
+        let mut jpeg = Cursor::new(Vec::new());
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut jpeg, ImageOutputFormat::Jpeg(90))
+            .unwrap();
+        let mut writer = Writer::new();
+        fields.iter().for_each(|f| writer.push_field(f));
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let jpeg = jpeg.into_inner();
+        let mut bytes = jpeg[..2].to_vec();
+        bytes.extend_from_slice(&[0xFF, 0xE1]);
+        bytes.extend_from_slice(&(tiff.len() as u16 + 8).to_be_bytes());
+        bytes.extend_from_slice(b"Exif\0\0");
+        bytes.extend_from_slice(&tiff);
+        bytes.extend_from_slice(&jpeg[2..]);
+
+        bytes
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+use mediarepo_core::content_descriptor::encode_content_descriptor;
+use mediarepo_core::error::{RepoError, RepoResult};
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+use crate::dto::FileDto;
+
+/// Options for exporting files into a folder
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    /// Writes a `<file>.txt` next to every file that lists its tags one per line
+    pub write_tag_sidecars: bool,
+    /// Puts the files into subfolders named after their tag of the namespace.
+    /// Files without a tag of the namespace are put into the target folder
+    pub group_by_namespace: Option<String>,
+}
+
+/// The progress of an export after a file was written
+#[derive(Clone, Debug)]
+pub struct ExportProgress {
+    pub exported: u64,
+    pub total: u64,
+    pub path: PathBuf,
+}
+
+impl Repo {
+    /// Writes the contents of the files into the target folder and returns the written paths
+    /// in the order of the given ids. Files are named after their metadata name and fall back
+    /// to their content descriptor. A counter is appended to names that are already taken.
+    /// The progress is passed to the callback after each file
+    #[tracing::instrument(level = "debug", skip(self, on_progress))]
+    pub async fn export_files<F, Fut>(
+        &self,
+        file_ids: Vec<i64>,
+        target_dir: &Path,
+        options: ExportOptions,
+        mut on_progress: F,
+    ) -> RepoResult<Vec<PathBuf>>
+    where
+        F: FnMut(ExportProgress) -> Fut,
+        Fut: Future<Output = RepoResult<()>>,
+    {
+        let mut files: HashMap<i64, FileDto> = self
+            .file()
+            .all_by_id(file_ids.clone())
+            .await?
+            .into_iter()
+            .map(|f| (f.id(), f))
+            .collect();
+        let mut names: HashMap<i64, String> = self
+            .file()
+            .all_metadata(file_ids.clone())
+            .await?
+            .into_iter()
+            .filter_map(|m| Some((m.file_id(), m.name()?.to_owned())))
+            .collect();
+        let total = file_ids.len() as u64;
+        let mut paths = Vec::with_capacity(file_ids.len());
+
+        for file_id in file_ids {
+            let file = files
+                .remove(&file_id)
+                .ok_or_else(|| RepoError::from(&*format!("file {} not found", file_id)))?;
+            let mut tags: Vec<String> = self
+                .tag()
+                .tags_for_cd(file.cd_id())
+                .await?
+                .into_iter()
+                .map(|t| t.normalized_name())
+                .collect();
+            tags.sort();
+            let folder = options
+                .group_by_namespace
+                .as_ref()
+                .and_then(|namespace| {
+                    let prefix = format!("{}:", namespace);
+                    tags.iter()
+                        .find_map(|tag| tag.strip_prefix(&prefix))
+                        .and_then(sanitize_file_name)
+                })
+                .map(|value| target_dir.join(value))
+                .unwrap_or_else(|| target_dir.to_owned());
+            fs::create_dir_all(&folder).await?;
+
+            let name = names
+                .remove(&file_id)
+                .and_then(|name| sanitize_file_name(&name))
+                .unwrap_or_else(|| fallback_file_name(&file));
+            let path = free_export_path(&folder, &name, options.write_tag_sidecars);
+            let (_, mut reader) = self.main_storage.get_file(file.cd()).await?;
+            let mut writer = BufWriter::new(File::create(&path).await?);
+            tokio::io::copy(&mut reader, &mut writer).await?;
+            writer.flush().await?;
+
+            if options.write_tag_sidecars {
+                fs::write(sidecar_path(&path), tags.join("\n")).await?;
+            }
+            paths.push(path.clone());
+            on_progress(ExportProgress {
+                exported: paths.len() as u64,
+                total,
+                path,
+            })
+            .await?;
+        }
+
+        Ok(paths)
+    }
+}
+
+/// Replaces path separators so that names can't point outside of the export folder.
+/// Returns None for names that can't be used as file names
+fn sanitize_file_name(name: &str) -> Option<String> {
+    let name = name.trim().replace(['/', '\\', '\0'], "_");
+
+    if name.chars().all(|c| c == '.') {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Returns the encoded content descriptor with the extension of the mime type
+fn fallback_file_name(file: &FileDto) -> String {
+    let name = encode_content_descriptor(file.cd());
+
+    match mime_guess::get_mime_extensions_str(file.mime_type()).and_then(|e| e.first()) {
+        Some(extension) => format!("{}.{}", name, extension),
+        None => name,
+    }
+}
+
+/// Returns the path of the name in the folder. If the file or its sidecar already
+/// exists, a counter is appended to the name until a free path is found
+fn free_export_path(folder: &Path, name: &str, with_sidecar: bool) -> PathBuf {
+    let is_taken = |path: &Path| path.exists() || (with_sidecar && sidecar_path(path).exists());
+    let mut path = folder.join(name);
+    let name_path = Path::new(name);
+    let stem = name_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_owned());
+    let extension = name_path.extension().map(|e| e.to_string_lossy());
+    let mut counter = 1;
+
+    while is_taken(&path) {
+        let name = match &extension {
+            Some(extension) => format!("{} ({}).{}", stem, counter, extension),
+            None => format!("{} ({})", stem, counter),
+        };
+        path = folder.join(name);
+        counter += 1;
+    }
+
+    path
+}
+
+fn sidecar_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.txt", path.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::content_descriptor::encode_content_descriptor;
+
+    use crate::dao::repo::export::ExportOptions;
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddTagDto, FileDto, TagDto, UpdateFileMetadataDto};
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_exports_files_with_tag_sidecars() {
+        let (repo, root) = test_repo("export").await;
+        let mut files = Vec::new();
+
+        for (content, name) in [
+            (vec![1], Some("a.png")),
+            (vec![2], Some("a.png")),
+            (vec![3], None),
+        ] {
+            let file = add_file(&repo, content, "image/png", false).await;
+            repo.file()
+                .update_metadata(UpdateFileMetadataDto {
+                    file_id: file.id(),
+                    name: Some(name.map(String::from)),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            files.push(file);
+        }
+        let tags: Vec<i64> = repo
+            .tag()
+            .add_all(
+                vec![
+                    AddTagDto {
+                        namespace: Some(String::from("character")),
+                        name: String::from("alice"),
+                    },
+                    AddTagDto {
+                        namespace: None,
+                        name: String::from("sky"),
+                    },
+                ],
+                &Default::default(),
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(TagDto::id)
+            .collect();
+        repo.add_tags_to_files(vec![files[0].id(), files[2].id()], tags)
+            .await
+            .unwrap();
+        let target = root.join("export");
+        let options = ExportOptions {
+            write_tag_sidecars: true,
+            group_by_namespace: Some(String::from("character")),
+        };
+        let file_ids = files.iter().map(FileDto::id).collect();
+
+        let mut progress = Vec::new();
+        let paths = repo
+            .export_files(file_ids, &target, options.clone(), |p| {
+                progress.push((p.exported, p.total));
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+        let fallback_name = format!("{}.png", encode_content_descriptor(files[2].cd()));
+        assert_eq!(
+            paths,
+            vec![
+                target.join("alice").join("a.png"),
+                target.join("a.png"),
+                target.join("alice").join(fallback_name),
+            ]
+        );
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+        assert_eq!(std::fs::read(&paths[1]).unwrap(), vec![2]);
+        assert_eq!(
+            std::fs::read_to_string(target.join("alice").join("a.png.txt")).unwrap(),
+            "character:alice\nsky"
+        );
+        assert_eq!(
+            std::fs::read_to_string(target.join("a.png.txt")).unwrap(),
+            ""
+        );
+
+        let paths = repo
+            .export_files(vec![files[1].id()], &target, options, |_| async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(paths, vec![target.join("a (1).png")]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::prelude::*;
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_database::entities::{content_descriptor_tag, file};
+
+use crate::dao::repo::Repo;
+use crate::dao::tag::add::BATCH_SIZE;
+use crate::dao::DaoProvider;
+use crate::dto::{FileDto, TagDto};
+
+impl Repo {
+    /// Returns a small combination of the tags of the file that together match as few
+    /// files as possible and the number of files matching them. The tags are chosen greedily
+    /// starting with the tag assigned to the fewest files. Files sharing the content of the
+    /// file can't be told apart and are always matched
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn minimal_identifying_tags(&self, file: &FileDto) -> RepoResult<(Vec<TagDto>, u64)> {
+        let tags = self.tag().tags_for_cd(file.cd_id()).await?;
+        let counts = self
+            .tag()
+            .counts_for_tags(tags.iter().map(TagDto::id).collect())
+            .await?;
+        let mut remaining: HashMap<i64, TagDto> = tags.into_iter().map(|t| (t.id(), t)).collect();
+        let rarest_id = counts
+            .iter()
+            .filter(|(id, _)| remaining.contains_key(id))
+            .min_by_key(|(id, count)| (**count, **id))
+            .map(|(id, _)| *id);
+        let rarest_id = if let Some(id) = rarest_id {
+            id
+        } else {
+            let file_count = file::Entity::find().count(self.db()).await? as u64;
+            return Ok((vec![], file_count));
+        };
+        let mut selected = vec![remaining.remove(&rarest_id).unwrap()];
+        let mut candidates = self.cd_ids_with_tag(rarest_id).await?;
+
+        while candidates.len() > 1 && !remaining.is_empty() {
+            let cd_ids: Vec<i64> = candidates.iter().copied().collect();
+            let tag_ids: Vec<i64> = remaining.keys().copied().collect();
+            let mut matches: HashMap<i64, HashSet<i64>> = HashMap::new();
+
+            for chunk in cd_ids.chunks(BATCH_SIZE) {
+                for mapping in content_descriptor_tag::Entity::find()
+                    .filter(content_descriptor_tag::Column::CdId.is_in(chunk.to_vec()))
+                    .filter(content_descriptor_tag::Column::TagId.is_in(tag_ids.clone()))
+                    .all(self.db())
+                    .await?
+                {
+                    matches
+                        .entry(mapping.tag_id)
+                        .or_default()
+                        .insert(mapping.cd_id);
+                }
+            }
+            let best = matches
+                .into_iter()
+                .min_by_key(|(id, cd_ids)| (cd_ids.len(), *id))
+                .filter(|(_, cd_ids)| cd_ids.len() < candidates.len());
+
+            if let Some((tag_id, cd_ids)) = best {
+                selected.push(remaining.remove(&tag_id).unwrap());
+                candidates = cd_ids;
+            } else {
+                break;
+            }
+        }
+        let mut file_count = 0;
+        let cd_ids: Vec<i64> = candidates.into_iter().collect();
+
+        for chunk in cd_ids.chunks(BATCH_SIZE) {
+            file_count += file::Entity::find()
+                .filter(file::Column::CdId.is_in(chunk.to_vec()))
+                .count(self.db())
+                .await? as u64;
+        }
+
+        Ok((selected, file_count))
+    }
+
+    async fn cd_ids_with_tag(&self, tag_id: i64) -> RepoResult<HashSet<i64>> {
+        let cd_ids = content_descriptor_tag::Entity::find()
+            .filter(content_descriptor_tag::Column::TagId.eq(tag_id))
+            .all(self.db())
+            .await?
+            .into_iter()
+            .map(|m| m.cd_id)
+            .collect();
+
+        Ok(cd_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::dto::AddTagDto;
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_finds_a_small_tag_combination_identifying_a_file() {
+        let (repo, root) = test_repo("identifying-tags").await;
+        let tag_ids: Vec<i64> = repo
+            .tag()
+            .add_all(
+                ["x", "y", "common"]
+                    .iter()
+                    .map(|name| AddTagDto {
+                        namespace: None,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id())
+            .collect();
+        let (x, y, common) = (tag_ids[0], tag_ids[1], tag_ids[2]);
+        let mut files = Vec::new();
+        for (i, tags) in [
+            vec![x, y, common],
+            vec![x, common],
+            vec![y, common],
+            vec![common],
+            vec![],
+        ]
+        .iter()
+        .enumerate()
+        {
+            let file = add_file(&repo, vec![i as u8], "application/octet-stream", false).await;
+            repo.tag()
+                .upsert_mappings(vec![file.cd_id()], tags.clone())
+                .await
+                .unwrap();
+            files.push(file);
+        }
+        let identifying_tags = |file| {
+            let repo = &repo;
+            async move {
+                let (tags, count) = repo.minimal_identifying_tags(file).await.unwrap();
+                (tags.iter().map(|t| t.id()).collect::<Vec<_>>(), count)
+            }
+        };
+
+        assert_eq!(identifying_tags(&files[0]).await, (vec![x, y], 1));
+        assert_eq!(identifying_tags(&files[1]).await, (vec![x], 2));
+        assert_eq!(identifying_tags(&files[3]).await, (vec![common], 4));
+        assert_eq!(identifying_tags(&files[4]).await, (vec![], 5));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use std::io::Cursor;
+
+use sea_orm::TransactionTrait;
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_core::itertools::Itertools;
+use mediarepo_core::settings::TagSettings;
+
+use crate::dao::file::add::insert_file;
+use crate::dao::repo::Repo;
+use crate::dao::tag::add::{add_or_get_valid_tags, validate_tag};
+use crate::dao::tag::mappings::upsert_mappings;
+use crate::dao::DaoProvider;
+use crate::dto::{AddFileDto, AddTagDto, FileDto, TagDto};
+
+impl Repo {
+    /// Adds a file and assigns the tags to it in a single transaction so that
+    /// the file never exists without its tags. Returns the file with all of its tags.
+    /// If a file with the same content exists, the tags are added to that file instead
+    #[tracing::instrument(level = "debug", skip(self, add_dto))]
+    pub async fn add_file_with_tags(
+        &self,
+        add_dto: AddFileDto,
+        tags: Vec<AddTagDto>,
+        settings: &TagSettings,
+    ) -> RepoResult<(FileDto, Vec<TagDto>)> {
+        let tags = tags
+            .into_iter()
+            .map(|t| validate_tag(t, settings))
+            .collect::<RepoResult<Vec<AddTagDto>>>()?;
+        let (content, info) = add_dto.into_parts();
+        let generate_thumbnails = info.generate_thumbnails;
+        let content = self
+            .file()
+            .store_content(Cursor::new(content), &info.mime_type)
+            .await?;
+
+        let trx = self.db().begin().await?;
+        let (file, duplicate) = insert_file(&trx, content, info).await?;
+        let tag_ids = add_or_get_valid_tags(&trx, tags)
+            .await?
+            .into_values()
+            .map(|t| t.id())
+            .unique()
+            .collect();
+        upsert_mappings(&trx, vec![file.cd_id()], tag_ids).await?;
+        trx.commit().await?;
+
+        if !duplicate {
+            self.file()
+                .create_import_thumbnails(&file, generate_thumbnails)
+                .await?;
+        }
+        let tags = self.tag().tags_for_cd(file.cd_id()).await?;
+
+        Ok((file, tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddFileDto, AddTagDto};
+    use crate::testing::fixtures::test_repo;
+
+    #[tokio::test]
+    async fn it_adds_files_together_with_their_tags() {
+        let (repo, root) = test_repo("add-with-tags").await;
+        let now = Local::now().naive_local();
+        let add_dto = |content: Vec<u8>| AddFileDto {
+            content,
+            mime_type: String::from("application/octet-stream"),
+            creation_time: now,
+            change_time: now,
+            name: None,
+            corrupt: false,
+            external_id: None,
+            generate_thumbnails: false,
+        };
+
+        let (file, tags) = repo
+            .add_file_with_tags(
+                add_dto(vec![1, 2, 3]),
+                vec![
+                    AddTagDto::from_tuple((Some(String::from("character")), String::from("alice"))),
+                    AddTagDto::from_tuple((None, String::from("outdoors"))),
+                ],
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        let mut names: Vec<String> = tags.iter().map(|t| t.normalized_name()).collect();
+        names.sort();
+        assert_eq!(names, vec!["character:alice", "outdoors"]);
+        assert_eq!(repo.tag().tags_for_cd(file.cd_id()).await.unwrap().len(), 2);
+
+        let invalid_tag = AddTagDto::from_tuple((None, String::new()));
+        assert!(repo
+            .add_file_with_tags(
+                add_dto(vec![4, 5, 6]),
+                vec![invalid_tag],
+                &Default::default()
+            )
+            .await
+            .is_err());
+        assert_eq!(repo.file().all().await.unwrap().len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
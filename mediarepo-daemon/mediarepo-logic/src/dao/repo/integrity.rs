@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_core::fs::file_hash_store::BlobState;
+
+use crate::dao::file::find::FilterProperty;
+use crate::dao::file::find::NegatableComparator::Is;
+use crate::dao::repo::Repo;
+use crate::dao::tag::add::BATCH_SIZE;
+use crate::dao::DaoProvider;
+use crate::dto::FileDto;
+
+/// A content whose stored file is missing or doesn't match its content descriptor
+#[derive(Clone, Debug)]
+pub struct DamagedContent {
+    pub descriptor: Vec<u8>,
+    /// The files sharing the content
+    pub files: Vec<FileDto>,
+}
+
+/// The result of checking the stored files against their content descriptors
+#[derive(Clone, Debug, Default)]
+pub struct IntegrityReport {
+    pub checked: u64,
+    pub corrupt: Vec<DamagedContent>,
+    pub missing: Vec<DamagedContent>,
+}
+
+/// The progress of an integrity check after a content was checked
+#[derive(Clone, Copy, Debug)]
+pub struct IntegrityProgress {
+    pub checked: u64,
+    pub total: u64,
+    pub corrupt: u64,
+    pub missing: u64,
+}
+
+impl Repo {
+    /// Reads the stored file of every content and compares its hash with the content
+    /// descriptor. Only the contents of files with any of the given tags are checked
+    /// unless no tags are given. The progress is passed to the callback after each content
+    #[tracing::instrument(level = "debug", skip(self, on_progress))]
+    pub async fn check_integrity<F, Fut>(
+        &self,
+        tag_ids: Vec<i64>,
+        mut on_progress: F,
+    ) -> RepoResult<IntegrityReport>
+    where
+        F: FnMut(IntegrityProgress) -> Fut,
+        Fut: Future<Output = RepoResult<()>>,
+    {
+        let files = if tag_ids.is_empty() {
+            self.file().all().await?
+        } else {
+            let tag_filters = tag_ids
+                .into_iter()
+                .map(|id| FilterProperty::TagId(Is(id)))
+                .collect();
+            let cds: Vec<Vec<u8>> = self
+                .file()
+                .find(vec![tag_filters])
+                .await?
+                .into_iter()
+                .map(|f| f.cd().to_vec())
+                .collect();
+            let mut files = Vec::new();
+
+            for chunk in cds.chunks(BATCH_SIZE) {
+                files.append(&mut self.file().all_by_cd(chunk.to_vec()).await?);
+            }
+            files
+        };
+        let mut contents: BTreeMap<i64, DamagedContent> = BTreeMap::new();
+
+        for file in files {
+            contents
+                .entry(file.cd_id())
+                .or_insert_with(|| DamagedContent {
+                    descriptor: file.cd().to_vec(),
+                    files: Vec::new(),
+                })
+                .files
+                .push(file);
+        }
+        let total = contents.len() as u64;
+        let mut report = IntegrityReport::default();
+
+        for content in contents.into_values() {
+            match self.main_storage.check_file(&content.descriptor).await? {
+                BlobState::Valid => {}
+                BlobState::Corrupt => report.corrupt.push(content),
+                BlobState::Missing => report.missing.push(content),
+            }
+            report.checked += 1;
+            on_progress(IntegrityProgress {
+                checked: report.checked,
+                total,
+                corrupt: report.corrupt.len() as u64,
+                missing: report.missing.len() as u64,
+            })
+            .await?;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::fs::file_hash_store::FileHashStore;
+
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddTagDto, FileDto};
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_reports_corrupt_and_missing_contents() {
+        let (repo, root) = test_repo("check-integrity").await;
+        let mut files = Vec::new();
+
+        for content in [vec![1], vec![2], vec![3]] {
+            files.push(add_file(&repo, content, "application/octet-stream", false).await);
+        }
+        let storage = FileHashStore::new(root.join("files"));
+        std::fs::write(storage.file_path(files[1].cd()), [4]).unwrap();
+        std::fs::remove_file(storage.file_path(files[2].cd())).unwrap();
+        let tag = repo
+            .tag()
+            .add_all(
+                vec![AddTagDto {
+                    namespace: None,
+                    name: String::from("checked"),
+                }],
+                &Default::default(),
+            )
+            .await
+            .unwrap()[0]
+            .id();
+        repo.add_tags_to_files(vec![files[0].id(), files[1].id()], vec![tag])
+            .await
+            .unwrap();
+
+        let mut progress = Vec::new();
+        let report = repo
+            .check_integrity(vec![tag], |p| {
+                progress.push((p.checked, p.total, p.corrupt, p.missing));
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(progress, vec![(1, 2, 0, 0), (2, 2, 1, 0)]);
+        assert_eq!(report.checked, 2);
+        assert!(report.missing.is_empty());
+        assert_eq!(report.corrupt.len(), 1);
+        assert_eq!(report.corrupt[0].descriptor, files[1].cd());
+        let corrupt_ids: Vec<i64> = report.corrupt[0].files.iter().map(FileDto::id).collect();
+        assert_eq!(corrupt_ids, vec![files[1].id()]);
+
+        let report = repo
+            .check_integrity(vec![], |_| async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.corrupt.len(), 1);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].descriptor, files[2].cd());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
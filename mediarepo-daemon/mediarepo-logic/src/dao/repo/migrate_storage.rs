@@ -0,0 +1,135 @@
+use mediarepo_core::content_descriptor::encode_content_descriptor;
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::fs::file_hash_store::FileHashStore;
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+
+/// The progress of a storage migration after a content was moved
+#[derive(Clone, Debug)]
+pub struct StorageMigrationProgress {
+    pub processed: u64,
+    pub total: u64,
+    pub descriptor: Vec<u8>,
+    /// If the content had already been moved by an earlier run
+    pub skipped: bool,
+}
+
+impl Repo {
+    /// Moves the contents of all files from one storage into another and returns the number
+    /// of moved contents and the number of contents that had already been moved.
+    /// Every copy is read again and compared with its content descriptor before the entry
+    /// in the old storage is removed. Contents that already have a valid copy in the new
+    /// storage are skipped so that an interrupted migration can be continued by running it again
+    #[tracing::instrument(level = "debug", skip(self, from, to, on_progress))]
+    pub async fn migrate_storage<F: FnMut(StorageMigrationProgress)>(
+        &self,
+        from: &FileHashStore,
+        to: &FileHashStore,
+        mut on_progress: F,
+    ) -> RepoResult<(u64, u64)> {
+        let mut descriptors = self.file().all_cds().await?;
+        descriptors.dedup();
+        let total = descriptors.len() as u64;
+        let mut migrated = 0;
+        let mut skipped = 0;
+
+        for (index, descriptor) in descriptors.into_iter().enumerate() {
+            let was_moved = migrate_storage_entry(from, to, &descriptor).await?;
+
+            if was_moved {
+                migrated += 1;
+            } else {
+                skipped += 1;
+            }
+            on_progress(StorageMigrationProgress {
+                processed: index as u64 + 1,
+                total,
+                descriptor,
+                skipped: !was_moved,
+            });
+        }
+
+        Ok((migrated, skipped))
+    }
+}
+
+/// Moves the content into the new storage. Returns false if a valid copy already exists
+async fn migrate_storage_entry(
+    from: &FileHashStore,
+    to: &FileHashStore,
+    descriptor: &[u8],
+) -> RepoResult<bool> {
+    if to.contains_file(descriptor).await? {
+        if to.verify_file(descriptor).await? {
+            from.delete_file(descriptor).await?;
+            return Ok(false);
+        }
+        tracing::warn!(
+            "removing invalid copy of file {} from the new storage",
+            encode_content_descriptor(descriptor)
+        );
+        to.delete_file(descriptor).await?;
+    }
+    if !from.contains_file(descriptor).await? {
+        return Err(RepoError::from(&*format!(
+            "file {} doesn't exist in either storage",
+            encode_content_descriptor(descriptor)
+        )));
+    }
+    from.copy_file_to(descriptor, to).await?;
+
+    if !to.verify_file(descriptor).await? {
+        to.delete_file(descriptor).await?;
+        return Err(RepoError::from(&*format!(
+            "the copy of file {} doesn't match its content descriptor",
+            encode_content_descriptor(descriptor)
+        )));
+    }
+    from.delete_file(descriptor).await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::fs::file_hash_store::FileHashStore;
+
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_resumes_storage_migrations() {
+        let (repo, root) = test_repo("move-storage").await;
+        let moved = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let broken = add_file(&repo, vec![4, 5, 6], "application/octet-stream", false).await;
+        add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        std::fs::create_dir_all(root.join("new-files")).unwrap();
+        let from = FileHashStore::new(root.join("files"));
+        let to = FileHashStore::new(root.join("new-files"));
+
+        from.copy_file_to(moved.cd(), &to).await.unwrap();
+        from.delete_file(moved.cd()).await.unwrap();
+        let broken_path = to.file_path(broken.cd());
+        std::fs::create_dir_all(broken_path.parent().unwrap()).unwrap();
+        std::fs::write(&broken_path, [7]).unwrap();
+
+        let mut progress = Vec::new();
+        let result = repo
+            .migrate_storage(&from, &to, |p| {
+                progress.push((p.processed, p.total, p.skipped))
+            })
+            .await
+            .unwrap();
+        assert_eq!(result, (1, 1));
+        assert_eq!(progress, vec![(1, 2, true), (2, 2, false)]);
+
+        for file in [&moved, &broken] {
+            assert!(!from.contains_file(file.cd()).await.unwrap());
+            assert!(to.verify_file(file.cd()).await.unwrap());
+        }
+        let result = repo.migrate_storage(&from, &to, |_| {}).await.unwrap();
+        assert_eq!(result, (0, 2));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
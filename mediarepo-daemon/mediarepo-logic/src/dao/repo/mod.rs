@@ -4,19 +4,44 @@ use std::path::PathBuf;
 
 use sea_orm::DatabaseConnection;
 
+use mediarepo_core::content_descriptor::HashAlgorithm;
 use mediarepo_core::error::RepoResult;
 use mediarepo_core::fs::file_hash_store::FileHashStore;
 use mediarepo_core::fs::thumbnail_store::ThumbnailStore;
+use mediarepo_core::mediarepo_api::types::filtering::SortKey;
+use mediarepo_core::settings::DatabaseSettings;
+use mediarepo_core::thumbnailer::ThumbnailSize;
 
 use crate::dao::{DaoContext, DaoProvider};
+use crate::dto::{FileDto, TagDto};
 use mediarepo_database::get_database;
-use mediarepo_database::queries::analysis::{get_all_counts, Counts};
+use mediarepo_database::queries::analysis::{get_all_counts, get_dedup_stats, Counts, DedupStats};
+
+pub mod bulk_rename;
+pub mod bulk_tags;
+pub mod contact_sheet;
+pub mod directory_import;
+pub mod duplicates;
+pub mod exif;
+pub mod export;
+pub mod identifying_tags;
+pub mod import;
+pub mod integrity;
+pub mod migrate_storage;
+pub mod orphaned_blobs;
+pub mod probe;
+pub mod resniff;
+pub mod similar;
+pub mod source;
+pub mod text_search;
+pub mod video_timeline;
 
 #[derive(Clone)]
 pub struct Repo {
     db: DatabaseConnection,
     main_storage: FileHashStore,
     thumbnail_storage: ThumbnailStore,
+    thumbnail_sizes: Vec<ThumbnailSize>,
 }
 
 impl DaoProvider for Repo {
@@ -25,6 +50,7 @@ impl DaoProvider for Repo {
             db: self.db.clone(),
             main_storage: self.main_storage.clone(),
             thumbnail_storage: self.thumbnail_storage.clone(),
+            thumbnail_sizes: self.thumbnail_sizes.clone(),
         }
     }
 }
@@ -39,20 +65,35 @@ impl Repo {
             db,
             main_storage: FileHashStore::new(file_store_path),
             thumbnail_storage: ThumbnailStore::new(thumb_store_path),
+            thumbnail_sizes: vec![ThumbnailSize::Medium],
         }
     }
 
-    /// Connects to the database with the given uri
+    /// Connects to the database with the given uri.
+    /// Passing `:memory:` creates a new migrated database that only lives in memory
     #[tracing::instrument(level = "debug")]
     pub async fn connect<S: AsRef<str> + Debug>(
         uri: S,
+        db_settings: &DatabaseSettings,
         file_store_path: PathBuf,
         thumb_store_path: PathBuf,
     ) -> RepoResult<Self> {
-        let db = get_database(uri).await?;
+        let db = get_database(uri, db_settings).await?;
         Ok(Self::new(db, file_store_path, thumb_store_path))
     }
 
+    /// Sets the hashing algorithm that is used for the content descriptors of new files
+    pub fn with_hash_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.main_storage = self.main_storage.with_algorithm(algorithm);
+        self
+    }
+
+    /// Sets the sizes of the thumbnails that are created for every file
+    pub fn with_thumbnail_sizes<I: IntoIterator<Item = ThumbnailSize>>(mut self, sizes: I) -> Self {
+        self.thumbnail_sizes = sizes.into_iter().collect();
+        self
+    }
+
     /// Returns the database of the repo for raw sql queries
     pub fn db(&self) -> &DatabaseConnection {
         &self.db
@@ -78,4 +119,66 @@ impl Repo {
     pub async fn get_counts(&self) -> RepoResult<Counts> {
         get_all_counts(&self.db).await
     }
+
+    /// Returns how much storage is saved by files sharing the same content
+    #[inline]
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn dedup_stats(&self) -> RepoResult<DedupStats> {
+        get_dedup_stats(&self.db).await
+    }
+
+    /// Returns all tags with the number of contents they are assigned to
+    /// including the tags that aren't assigned to any content
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn tag_counts(&self) -> RepoResult<Vec<(TagDto, u64)>> {
+        let tag_dao = self.tag();
+        let counts = tag_dao.all_counts().await?;
+        let tag_counts = tag_dao
+            .all()
+            .await?
+            .into_iter()
+            .map(|tag| {
+                let count = counts.get(&tag.id()).copied().unwrap_or(0);
+                (tag, count)
+            })
+            .collect();
+
+        Ok(tag_counts)
+    }
+
+    /// Returns a page of all files ordered by the sort keys together with
+    /// the total number of files
+    #[inline]
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn files_paginated(
+        &self,
+        offset: u64,
+        limit: u64,
+        sort: Vec<SortKey>,
+    ) -> RepoResult<(Vec<FileDto>, u64)> {
+        self.file()
+            .find_paginated(vec![], offset, limit, sort)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::fixtures::{add_file, add_file_sharing_content, test_repo};
+
+    #[tokio::test]
+    async fn it_counts_the_bytes_saved_by_shared_contents() {
+        let (repo, root) = test_repo("dedup-stats").await;
+        let shared = add_file(&repo, vec![1, 2, 3, 4], "application/octet-stream", false).await;
+        add_file(&repo, vec![5, 6], "application/octet-stream", false).await;
+
+        add_file_sharing_content(&repo, &shared).await;
+
+        let stats = repo.dedup_stats().await.unwrap();
+        assert_eq!(stats.file_count, 3);
+        assert_eq!(stats.blob_count, 2);
+        assert_eq!(stats.bytes_saved, 4);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
 }
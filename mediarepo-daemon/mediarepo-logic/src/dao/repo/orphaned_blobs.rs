@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_core::fs::file_hash_store::StoredBlob;
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+
+/// Blobs that were modified more recently are never reported as orphaned
+/// because their file might still be in the middle of being imported
+const ORPHAN_MIN_AGE: Duration = Duration::from_secs(60 * 60);
+
+impl Repo {
+    /// Returns all blobs of the main storage that don't belong to any file.
+    /// These are left behind when the daemon stops in the middle of an import
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_orphaned_blobs(&self) -> RepoResult<Vec<StoredBlob>> {
+        let referenced: HashSet<Vec<u8>> = self.file().all_cds().await?.into_iter().collect();
+        let now = SystemTime::now();
+
+        let orphaned = self
+            .main_storage
+            .blobs()
+            .await?
+            .into_iter()
+            .filter(|blob| {
+                now.duration_since(blob.modified)
+                    .map(|age| age >= ORPHAN_MIN_AGE)
+                    .unwrap_or(false)
+            })
+            .filter(|blob| match &blob.descriptor {
+                Some(descriptor) => !referenced.contains(descriptor),
+                None => true,
+            })
+            .collect();
+
+        Ok(orphaned)
+    }
+
+    /// Deletes all orphaned blobs and returns them.
+    /// With `dry_run` the blobs are only returned without being deleted
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn cleanup_orphaned_blobs(&self, dry_run: bool) -> RepoResult<Vec<StoredBlob>> {
+        let orphaned = self.find_orphaned_blobs().await?;
+
+        if !dry_run {
+            for blob in &orphaned {
+                self.main_storage.delete_blob(blob).await?;
+            }
+            tracing::info!("deleted {} orphaned blobs", orphaned.len());
+        }
+
+        Ok(orphaned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    use mediarepo_core::content_descriptor::{
+        create_content_descriptor, encode_content_descriptor,
+    };
+
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_finds_and_removes_orphaned_blobs() {
+        let (repo, root) = test_repo("orphaned-blobs").await;
+        let file = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let hour_ago = SystemTime::now() - Duration::from_secs(60 * 60 + 1);
+        let orphan = write_blob(&root, &[4, 5, 6], hour_ago);
+        let recent = write_blob(&root, &[7, 8, 9], SystemTime::now());
+        let staging = root.join("files").join(".staging-0-0");
+        std::fs::write(&staging, [1]).unwrap();
+        set_modified(&staging, hour_ago);
+
+        let mut orphaned: Vec<PathBuf> = repo
+            .cleanup_orphaned_blobs(true)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|b| b.path)
+            .collect();
+        orphaned.sort();
+        assert_eq!(orphaned, vec![staging.clone(), orphan.clone()]);
+        assert!(orphan.exists() && staging.exists());
+
+        repo.cleanup_orphaned_blobs(false).await.unwrap();
+        assert!(!orphan.exists() && !staging.exists());
+        assert!(recent.exists());
+        assert!(repo.find_orphaned_blobs().await.unwrap().is_empty());
+        assert_eq!(
+            repo.file().get_bytes(file.cd()).await.unwrap(),
+            vec![1, 2, 3]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Writes a blob into the file storage without adding a file for it
+    fn write_blob(root: &Path, content: &[u8], modified: SystemTime) -> PathBuf {
+        let encoded = encode_content_descriptor(&create_content_descriptor(content));
+        let folder = root
+            .join("files")
+            .join(&encoded[encoded.len() - 3..encoded.len() - 1]);
+        std::fs::create_dir_all(&folder).unwrap();
+        let path = folder.join(encoded);
+        std::fs::write(&path, content).unwrap();
+        set_modified(&path, modified);
+
+        path
+    }
+
+    fn set_modified(path: &Path, modified: SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs::File;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::image_processing::{
+    read_exif_fields, read_gps_location, read_image_dimensions,
+};
+
+use crate::dao::repo::Repo;
+use crate::dto::ProbedFileDto;
+
+impl Repo {
+    /// Reads the metadata of a file on the daemon's file system that would be stored
+    /// when importing it. Nothing is added to the repository
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn probe_file(&self, path: PathBuf) -> RepoResult<ProbedFileDto> {
+        let metadata = tokio::fs::metadata(&path).await?;
+
+        if !metadata.is_file() {
+            return Err(RepoError::from(&*format!("{:?} is not a file", path)));
+        }
+        let mime_type = mime_guess::from_path(&path)
+            .first_or_octet_stream()
+            .to_string();
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string());
+        let mut probed = ProbedFileDto {
+            name,
+            mime_type,
+            size: metadata.len(),
+            location: None,
+            dimensions: None,
+            exif: Vec::new(),
+        };
+
+        if probed.mime_type.starts_with("image/") {
+            let mime_type = probed.mime_type.clone();
+            let (location, dimensions, exif) = tokio::task::spawn_blocking(move || {
+                let mut reader = BufReader::new(File::open(path)?);
+                let exif = read_exif_fields(&mut reader);
+                reader.seek(SeekFrom::Start(0))?;
+                let location = read_gps_location(&mut reader);
+                reader.seek(SeekFrom::Start(0))?;
+                let dimensions = read_image_dimensions(reader, &mime_type);
+
+                RepoResult::Ok((location, dimensions, exif))
+            })
+            .await
+            .map_err(|e| RepoError::from(&*e.to_string()))??;
+            probed.location = location;
+            probed.dimensions = dimensions;
+            probed.exif = exif;
+        }
+
+        Ok(probed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_probes_files_without_importing_them() {
+        let (repo, root) = test_repo("probe").await;
+        let path = root.join("sample.png");
+        std::fs::write(&path, png_bytes(6, 4)).unwrap();
+
+        let probed = repo.probe_file(path).await.unwrap();
+        assert_eq!(probed.name.as_deref(), Some("sample.png"));
+        assert_eq!(probed.mime_type, "image/png");
+        assert_eq!(probed.dimensions, Some((6, 4)));
+        assert!(repo.file().all().await.unwrap().is_empty());
+        assert_eq!(std::fs::read_dir(root.join("files")).unwrap().count(), 0);
+        assert!(repo.probe_file(root.join("files")).await.is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
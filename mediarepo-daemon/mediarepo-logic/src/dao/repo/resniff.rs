@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_core::utils::{sniff_mime_type, MIME_SNIFF_LENGTH};
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+use crate::dto::{FileDto, MetadataField};
+
+impl Repo {
+    /// Detects the type of the file from the magic bytes of its content and stores it
+    /// as the mime type of the file if it differs. The thumbnails of the file are created
+    /// again for the new type. Returns the updated file or None if the type wasn't
+    /// recognized or didn't change
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn resniff_file(&self, file: &FileDto) -> RepoResult<Option<FileDto>> {
+        let bytes = self
+            .file()
+            .get_byte_range(file.cd(), 0, MIME_SNIFF_LENGTH)
+            .await?;
+        let mime_type = match sniff_mime_type(&bytes) {
+            Some(mime_type) if &mime_type != file.mime_type() => mime_type,
+            _ => return Ok(None),
+        };
+        tracing::debug!(
+            "changing the mime type of file {} from {} to {}",
+            file.id(),
+            file.mime_type(),
+            mime_type
+        );
+        let file = self.file().set_mime_type(file.id(), mime_type).await?;
+        self.file().clear_thumbnails(&file).await?;
+
+        if has_thumbnails(file.mime_type()) {
+            // the thumbnails get created again when requested so the type still gets stored
+            if let Err(e) = self.file().create_import_thumbnails(&file, true).await {
+                tracing::warn!("failed to create thumbnails for file {}: {}", file.id(), e);
+            }
+        }
+
+        Ok(Some(file))
+    }
+
+    /// Detects the types of all files that were stored without a known mime type
+    /// and returns the files whose type was recognized
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn resniff_all_unknown(&self) -> RepoResult<Vec<FileDto>> {
+        let files = self
+            .file()
+            .missing_metadata(vec![MetadataField::MimeType])
+            .await?;
+        let mut changed = Vec::new();
+
+        for file in files {
+            if let Some(file) = self.resniff_file(&file).await? {
+                changed.push(file);
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+/// Returns if thumbnails can be created for files of the mime type
+fn has_thumbnails(mime_type: &str) -> bool {
+    mime::Mime::from_str(mime_type)
+        .map(|m| m.type_() == mime::IMAGE || m.type_() == mime::VIDEO)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_resniffs_the_mime_types_of_unknown_files() {
+        let (repo, root) = test_repo("resniff").await;
+        let image = add_file(&repo, png_bytes(64, 64), "application/octet-stream", false).await;
+        let unknown = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+        let mislabeled = add_file(&repo, png_bytes(32, 32), "image/jpeg", false).await;
+
+        let changed = repo.resniff_all_unknown().await.unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].id(), image.id());
+        assert_eq!(changed[0].mime_type(), "image/png");
+        assert!(!repo
+            .file()
+            .thumbnails(image.encoded_cd())
+            .await
+            .unwrap()
+            .is_empty());
+        let stored = repo.file().by_id(unknown.id()).await.unwrap().unwrap();
+        assert_eq!(stored.mime_type(), "application/octet-stream");
+
+        let resniffed = repo.resniff_file(&mislabeled).await.unwrap().unwrap();
+        assert_eq!(resniffed.mime_type(), "image/png");
+        assert!(repo.resniff_file(&resniffed).await.unwrap().is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
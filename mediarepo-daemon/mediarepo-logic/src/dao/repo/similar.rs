@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use sea_orm::prelude::*;
+use sea_orm::{FromQueryResult, QuerySelect};
+
+use mediarepo_core::error::RepoResult;
+use mediarepo_core::image_processing::hamming_distance;
+use mediarepo_database::entities::file_metadata;
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+use crate::dto::FileDto;
+
+#[derive(Debug, FromQueryResult)]
+struct FilePhash {
+    file_id: i64,
+    phash: i64,
+}
+
+impl Repo {
+    /// Returns the images whose perceptual hash differs from the one of the given file
+    /// in at most `max_distance` bits together with that distance ordered by ascending distance.
+    /// The hash of the file is calculated if it doesn't have one yet. Files that aren't
+    /// images don't have similar files
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_similar_files(
+        &self,
+        file: &FileDto,
+        max_distance: u32,
+    ) -> RepoResult<Vec<(FileDto, u32)>> {
+        let file_dao = self.file();
+        let stored_phash = file_dao
+            .metadata(file.id())
+            .await?
+            .and_then(|metadata| metadata.phash());
+        let phash = match stored_phash {
+            Some(phash) => phash,
+            None => match file_dao.generate_phash(file).await? {
+                Some(phash) => phash,
+                None => return Ok(vec![]),
+            },
+        };
+        let distances: HashMap<i64, u32> = file_metadata::Entity::find()
+            .select_only()
+            .column(file_metadata::Column::FileId)
+            .column(file_metadata::Column::Phash)
+            .filter(file_metadata::Column::Phash.is_not_null())
+            .filter(file_metadata::Column::FileId.ne(file.id()))
+            .into_model::<FilePhash>()
+            .all(self.db())
+            .await?
+            .into_iter()
+            .map(|f| (f.file_id, hamming_distance(phash, f.phash as u64)))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .collect();
+        let mut files: Vec<(FileDto, u32)> = file_dao
+            .all_by_id(distances.keys().cloned().collect())
+            .await?
+            .into_iter()
+            .map(|f| {
+                let distance = distances[&f.id()];
+                (f, distance)
+            })
+            .collect();
+        files.sort_by_key(|(f, distance)| (*distance, f.id()));
+
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use sea_orm::sea_query::Expr;
+    use sea_orm::EntityTrait;
+
+    use mediarepo_core::image::{DynamicImage, GrayImage, ImageOutputFormat, Luma};
+    use mediarepo_database::entities::file_metadata;
+
+    use crate::dao::DaoProvider;
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_finds_similar_images_by_their_perceptual_hash() {
+        let (repo, root) = test_repo("similar-images").await;
+        let image = add_file(&repo, gradient_png(64, 64, false), "image/png", false).await;
+        let scaled = add_file(&repo, gradient_png(128, 96, false), "image/png", false).await;
+        let inverted = add_file(&repo, gradient_png(64, 64, true), "image/png", false).await;
+        let other = add_file(&repo, vec![1, 2, 3], "application/octet-stream", false).await;
+
+        let similar = repo.find_similar_files(&image, 8).await.unwrap();
+        let ids: Vec<(i64, u32)> = similar.iter().map(|(f, d)| (f.id(), *d)).collect();
+        assert_eq!(ids, vec![(scaled.id(), 0)]);
+
+        let similar = repo.find_similar_files(&image, 64).await.unwrap();
+        let ids: Vec<(i64, u32)> = similar.iter().map(|(f, d)| (f.id(), *d)).collect();
+        assert_eq!(ids, vec![(scaled.id(), 0), (inverted.id(), 64)]);
+
+        assert!(repo
+            .find_similar_files(&other, 64)
+            .await
+            .unwrap()
+            .is_empty());
+
+        file_metadata::Entity::update_many()
+            .col_expr(
+                file_metadata::Column::Phash,
+                Expr::value(Option::<i64>::None),
+            )
+            .exec(repo.db())
+            .await
+            .unwrap();
+        assert_eq!(repo.job().generate_phashes().await.unwrap(), 3);
+        assert!(repo.file().images_without_phash().await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Creates a png that gets brighter from left to right in the upper half
+    /// and darker in the lower half, or the other way around if inverted
+    fn gradient_png(width: u32, height: u32, inverted: bool) -> Vec<u8> {
+        let image = GrayImage::from_fn(width, height, |x, y| {
+            let value = (x * 255 / (width - 1)) as u8;
+            if (y < height / 2) != inverted {
+                Luma([value])
+            } else {
+                Luma([255 - value])
+            }
+        });
+        let mut buf = Cursor::new(Vec::new());
+        DynamicImage::ImageLuma8(image)
+            .write_to(&mut buf, ImageOutputFormat::Png)
+            .unwrap();
+
+        buf.into_inner()
+    }
+}
@@ -0,0 +1,176 @@
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::settings::TagSettings;
+
+use crate::dao::file::find::FilterProperty;
+use crate::dao::file::find::NegatableComparator::Is;
+use crate::dao::repo::Repo;
+use crate::dao::tag::by_name::TagByNameQuery;
+use crate::dao::DaoProvider;
+use crate::dto::FileDto;
+
+/// The namespace of the tags storing the urls files were imported from
+pub const SOURCE_NAMESPACE: &str = "source";
+
+impl Repo {
+    /// Returns all files with a `source:` tag matching the pattern.
+    /// Patterns containing `*` are matched against the whole url while other
+    /// patterns are domains matching the urls of the domain and all of its subdomains
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn files_from_source(
+        &self,
+        source_pattern: &str,
+        settings: &TagSettings,
+    ) -> RepoResult<Vec<FileDto>> {
+        let source_pattern = source_pattern.trim();
+        let tag_ids = if source_pattern.contains('*') {
+            let pattern = format!("{}:{}", SOURCE_NAMESPACE, source_pattern);
+            self.tag()
+                .wildcard_tags_to_ids(
+                    vec![pattern.clone()],
+                    settings.case_sensitive,
+                    settings.max_wildcard_matches,
+                )
+                .await?
+                .remove(&pattern)
+                .unwrap_or_default()
+        } else {
+            self.source_tag_ids_for_domain(source_pattern, settings)
+                .await?
+        };
+        if tag_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        self.file()
+            .find(vec![vec![FilterProperty::TagWildcardIds(Is(tag_ids))]])
+            .await
+    }
+
+    /// Returns the ids of all source tags with a url on the domain or one of its subdomains
+    async fn source_tag_ids_for_domain(
+        &self,
+        domain: &str,
+        settings: &TagSettings,
+    ) -> RepoResult<Vec<i64>> {
+        let domain = url_host(domain).to_lowercase();
+        if domain.is_empty() {
+            return Ok(vec![]);
+        }
+        // the candidates are narrowed down by the database and checked exactly afterwards
+        let candidates = self
+            .tag()
+            .all_by_name(
+                vec![TagByNameQuery {
+                    namespace: Some(String::from(SOURCE_NAMESPACE)),
+                    name: format!("*{}*", domain),
+                }],
+                false,
+            )
+            .await?;
+        let tag_ids: Vec<i64> = candidates
+            .into_iter()
+            .filter(|tag| is_on_domain(url_host(tag.name()), &domain))
+            .map(|tag| tag.id())
+            .collect();
+
+        if tag_ids.len() > settings.max_wildcard_matches {
+            Err(RepoError::TooManyWildcardMatches {
+                pattern: domain,
+                limit: settings.max_wildcard_matches,
+            })
+        } else {
+            Ok(tag_ids)
+        }
+    }
+}
+
+/// Returns the host of a url without the scheme, credentials, port and path.
+/// Values without a scheme are treated as urls starting with the host
+fn url_host(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let authority = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host_and_port = authority
+        .rsplit_once('@')
+        .map(|(_, host)| host)
+        .unwrap_or(authority);
+
+    host_and_port
+        .split_once(':')
+        .map(|(host, _)| host)
+        .unwrap_or(host_and_port)
+}
+
+/// Returns if the host is the domain or a subdomain of it
+fn is_on_domain(host: &str, domain: &str) -> bool {
+    let host = host.to_lowercase();
+
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .map(|subdomain| subdomain.ends_with('.'))
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddTagDto, FileDto};
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_finds_files_from_a_source() {
+        let (repo, root) = test_repo("files-from-source").await;
+        let mut files = Vec::new();
+        for size in 2..6 {
+            files.push(add_file(&repo, png_bytes(size, size), "image/png", false).await);
+        }
+        let sources = vec![
+            "https://example.com/a.png",
+            "https://cdn.Example.com:8080/b.png",
+            "https://notexample.com/c.png",
+            "https://other.org/?from=example.com",
+        ];
+        let tags = repo
+            .tag()
+            .add_all(
+                sources
+                    .into_iter()
+                    .map(|url| AddTagDto {
+                        namespace: Some(String::from("source")),
+                        name: String::from(url),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        for (file, tag) in files.iter().zip(tags.iter()) {
+            repo.tag()
+                .upsert_mappings(vec![file.cd_id()], vec![tag.id()])
+                .await
+                .unwrap();
+        }
+        let found_ids = |found: Vec<FileDto>| {
+            let mut ids: Vec<i64> = found.into_iter().map(|f| f.id()).collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        let by_domain = repo
+            .files_from_source("example.com", &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(found_ids(by_domain), vec![files[0].id(), files[1].id()]);
+
+        let by_pattern = repo
+            .files_from_source("*.org/*", &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(found_ids(by_pattern), vec![files[3].id()]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
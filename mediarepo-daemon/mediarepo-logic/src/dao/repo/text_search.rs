@@ -0,0 +1,148 @@
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::mediarepo_api::types::filtering::SortKey;
+
+use crate::dao::file::find::{FilterFileProperty, FilterProperty};
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+use crate::dto::FileDto;
+
+impl Repo {
+    /// Returns a page of the files whose name or comment contains the text ignoring the casing
+    /// ordered by the sort keys together with the number of all matching files.
+    /// Files that are excluded from searches are never returned
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn search_files_by_text(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+        sort: Vec<SortKey>,
+    ) -> RepoResult<(Vec<FileDto>, u64)> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(RepoError::InvalidFilter(String::from(
+                "the search text is empty",
+            )));
+        }
+        let filters = vec![
+            vec![FilterProperty::FileProperty(FilterFileProperty::Text(
+                query.to_string(),
+            ))],
+            vec![FilterProperty::FileProperty(FilterFileProperty::Indexed(
+                true,
+            ))],
+        ];
+
+        self.file()
+            .find_paginated(filters, offset, limit, sort)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::error::RepoError;
+    use mediarepo_core::mediarepo_api::types::filtering::{SortDirection, SortKey};
+
+    use crate::dao::file::find::NegatableComparator::Is;
+    use crate::dao::file::find::{FilterFileProperty, FilterProperty};
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddTagDto, FileDto, UpdateFileMetadataDto};
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_searches_file_names_and_comments() {
+        let (repo, root) = test_repo("text_search").await;
+        let texts = [
+            (Some("Beach.png"), None),
+            (None, Some("sunset at the BEACH")),
+            (Some("forest.png"), Some("no sand")),
+            (Some("beach2.png"), None),
+        ];
+        let mut files = Vec::new();
+
+        for (i, (name, comment)) in texts.iter().enumerate() {
+            let file = add_file(&repo, vec![i as u8], "application/octet-stream", false).await;
+            repo.file()
+                .update_metadata(UpdateFileMetadataDto {
+                    file_id: file.id(),
+                    name: Some(name.map(String::from)),
+                    comment: Some(comment.map(String::from)),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            files.push(file);
+        }
+        repo.file().set_indexed(files[3].id(), false).await.unwrap();
+        let cat = repo
+            .tag()
+            .add_all(
+                vec![AddTagDto {
+                    namespace: None,
+                    name: String::from("cat"),
+                }],
+                &Default::default(),
+            )
+            .await
+            .unwrap()[0]
+            .id();
+        repo.add_tags_to_files(vec![files[0].id(), files[2].id()], vec![cat])
+            .await
+            .unwrap();
+
+        let (found, total) = repo
+            .search_files_by_text(
+                " beach ",
+                0,
+                10,
+                vec![SortKey::FileName(SortDirection::Ascending)],
+            )
+            .await
+            .unwrap();
+        let found_ids: Vec<i64> = found.iter().map(FileDto::id).collect();
+        assert_eq!(found_ids, vec![files[1].id(), files[0].id()]);
+        assert_eq!(total, 2);
+
+        let filters = vec![
+            vec![FilterProperty::TagId(Is(cat))],
+            vec![FilterProperty::FileProperty(FilterFileProperty::Text(
+                String::from("BEACH"),
+            ))],
+        ];
+        assert_eq!(
+            repo.file().find_ids(filters, vec![]).await.unwrap(),
+            vec![files[0].id()]
+        );
+        assert!(matches!(
+            repo.search_files_by_text("  ", 0, 10, vec![]).await,
+            Err(RepoError::InvalidFilter(_))
+        ));
+
+        let search_ids = |text: &'static str| {
+            let repo = &repo;
+            async move {
+                let (found, _) = repo
+                    .search_files_by_text(text, 0, 10, vec![])
+                    .await
+                    .unwrap();
+                found.iter().map(FileDto::id).collect::<Vec<i64>>()
+            }
+        };
+        // texts shorter than a trigram are compared without the index
+        assert_eq!(search_ids("NO").await, vec![files[2].id()]);
+        assert!(search_ids("\"sand").await.is_empty());
+        repo.file()
+            .update_metadata(UpdateFileMetadataDto {
+                file_id: files[0].id(),
+                name: Some(Some(String::from("dune.png"))),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(search_ids("beach").await, vec![files[1].id()]);
+        assert_eq!(search_ids("dune").await, vec![files[0].id()]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
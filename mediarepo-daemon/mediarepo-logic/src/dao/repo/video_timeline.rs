@@ -0,0 +1,149 @@
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::fs::thumbnail_store::{Dimensions, ThumbnailCrop};
+use mediarepo_core::image::imageops::FilterType;
+use mediarepo_core::image::{self, ImageOutputFormat};
+use mediarepo_core::thumbnailer::ThumbnailSize;
+use mediarepo_core::video_processing::extract_video_frames;
+
+use crate::dao::repo::Repo;
+use crate::dto::{FileDto, ThumbnailDto};
+
+/// The maximum number of timeline thumbnails of a video
+const MAX_TIMELINE_THUMBNAILS: u32 = 50;
+
+/// The size the timeline thumbnails are fitted into
+const TIMELINE_THUMBNAIL_SIZE: ThumbnailSize = ThumbnailSize::Medium;
+
+impl Repo {
+    /// Creates `count` thumbnails of evenly spaced frames of a video and returns them
+    /// with their position as a fraction of the video's duration.
+    /// Previously created timeline thumbnails of the video are replaced
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn create_video_timeline_thumbnails(
+        &self,
+        file: &FileDto,
+        count: u32,
+    ) -> RepoResult<Vec<(f64, ThumbnailDto)>> {
+        if !file.mime_type().starts_with("video/") {
+            return Err(RepoError::from(
+                "timeline thumbnails can only be created for videos",
+            ));
+        }
+        if count == 0 || count > MAX_TIMELINE_THUMBNAILS {
+            return Err(RepoError::from(&*format!(
+                "the number of timeline thumbnails must be between 1 and {}",
+                MAX_TIMELINE_THUMBNAILS
+            )));
+        }
+        let video_path = self.main_storage.file_path(file.cd());
+
+        // ffmpeg is run for every frame which blocks for a while
+        let thumbnails = tokio::task::spawn_blocking(move || {
+            let (width, height) = TIMELINE_THUMBNAIL_SIZE.dimensions();
+            let mut thumbnails = Vec::with_capacity(count as usize);
+
+            for (position, frame) in extract_video_frames(&video_path, count)? {
+                let thumbnail = frame.resize(width, height, FilterType::Triangle);
+                let mut buf = Cursor::new(Vec::new());
+                thumbnail.write_to(&mut buf, ImageOutputFormat::Png)?;
+                thumbnails.push((
+                    position,
+                    Dimensions {
+                        height: thumbnail.height(),
+                        width: thumbnail.width(),
+                    },
+                    buf.into_inner(),
+                ));
+            }
+
+            RepoResult::Ok(thumbnails)
+        })
+        .await
+        .map_err(|e| RepoError::from(&*e.to_string()))??;
+        let sizes: Vec<Dimensions> = thumbnails.iter().map(|(_, s, _)| s.clone()).collect();
+        let stored = self
+            .thumbnail_storage
+            .set_timeline_thumbnails(
+                file.encoded_cd(),
+                thumbnails
+                    .into_iter()
+                    .map(|(position, _, data)| (position, data))
+                    .collect(),
+            )
+            .await?;
+
+        Ok(stored
+            .into_iter()
+            .zip(sizes)
+            .map(|((position, path), size)| (position, timeline_thumbnail_dto(file, path, size)))
+            .collect())
+    }
+
+    /// Returns the timeline thumbnail of a video that is closest to the position
+    /// given as a fraction of the video's duration
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn video_timeline_thumbnail(
+        &self,
+        file: &FileDto,
+        position: f64,
+    ) -> RepoResult<Option<ThumbnailDto>> {
+        let closest = self
+            .thumbnail_storage
+            .get_timeline_thumbnails(file.encoded_cd())
+            .await?
+            .into_iter()
+            .min_by(|(a, _), (b, _)| (a - position).abs().total_cmp(&(b - position).abs()));
+        let path = match closest {
+            Some((_, path)) => path,
+            None => return Ok(None),
+        };
+        let (width, height) = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || image::image_dimensions(path)
+        })
+        .await
+        .map_err(|e| RepoError::from(&*e.to_string()))??;
+
+        Ok(Some(timeline_thumbnail_dto(
+            file,
+            path,
+            Dimensions { height, width },
+        )))
+    }
+}
+
+fn timeline_thumbnail_dto(file: &FileDto, path: PathBuf, size: Dimensions) -> ThumbnailDto {
+    ThumbnailDto::new(
+        path,
+        file.encoded_cd(),
+        size,
+        ThumbnailCrop::Fit,
+        String::from("image/png"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::fixtures::{add_file, png_bytes, test_repo};
+
+    #[tokio::test]
+    async fn it_only_creates_timeline_thumbnails_for_videos() {
+        let (repo, root) = test_repo("timeline-thumbnails").await;
+        let file = add_file(&repo, png_bytes(4, 4), "image/png", false).await;
+
+        assert!(repo
+            .create_video_timeline_thumbnails(&file, 5)
+            .await
+            .is_err());
+        assert!(repo
+            .video_timeline_thumbnail(&file, 0.5)
+            .await
+            .unwrap()
+            .is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
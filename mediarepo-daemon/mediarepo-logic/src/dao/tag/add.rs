@@ -1,6 +1,8 @@
+use crate::dao::tag::alias::canonical_tags_for_aliases;
 use crate::dao::tag::{map_tag_dto, TagDao};
 use crate::dto::{AddTagDto, NamespaceDto, TagDto};
-use mediarepo_core::error::RepoResult;
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::settings::TagSettings;
 use mediarepo_database::entities::{namespace, tag};
 use sea_orm::prelude::*;
 use sea_orm::ActiveValue::Set;
@@ -8,54 +10,180 @@ use sea_orm::{Condition, DatabaseTransaction, TransactionTrait};
 use std::collections::HashMap;
 use std::iter::FromIterator;
 
+/// The maximum number of rows inserted or queried in a single statement.
+/// Every tag row binds up to two variables so this stays well below
+/// the lowest default variable limit (999) and the expression depth limit (1000) of sqlite.
+pub(crate) const BATCH_SIZE: usize = 400;
+
 impl TagDao {
+    /// Adds all tags that don't exist yet and returns all given tags.
+    /// Aliases are resolved to the tags they refer to instead of being created.
+    /// Fails without adding any tag if one of the tags is invalid
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn add_all(&self, mut tags: Vec<AddTagDto>) -> RepoResult<Vec<TagDto>> {
-        let namespaces = tags.iter().filter_map(|t| t.namespace.clone()).collect();
-        let trx = self.ctx.db.begin().await?;
-        let existing_tags = tags_by_name(&trx, tags.clone()).await?;
-
-        if existing_tags.len() == tags.len() {
-            return Ok(existing_tags);
-        }
-        let existing_tag_map: HashMap<String, TagDto> =
-            HashMap::from_iter(existing_tags.into_iter().map(|t| (t.normalized_name(), t)));
-
-        tags.retain(|dto| !existing_tag_map.contains_key(&dto.normalized_name()));
-        let namespace_map = add_or_get_all_namespaces(&trx, namespaces).await?;
+    pub async fn add_all(
+        &self,
+        tags: Vec<AddTagDto>,
+        settings: &TagSettings,
+    ) -> RepoResult<Vec<TagDto>> {
+        let tags = tags
+            .into_iter()
+            .map(|t| validate_tag(t, settings))
+            .collect::<RepoResult<Vec<AddTagDto>>>()?;
+        let mut tag_dtos: Vec<TagDto> = self.add_all_valid(tags).await?.into_values().collect();
+        // multiple aliases can resolve to the same tag
+        tag_dtos.sort_unstable_by_key(TagDto::id);
+        tag_dtos.dedup_by_key(|t| t.id());
 
-        if tags.is_empty() {
-            return Ok(existing_tag_map.into_values().collect());
-        }
+        Ok(tag_dtos)
+    }
 
-        let tag_models: Vec<tag::ActiveModel> = tags
+    /// Adds all valid tags and returns either the tag or the reason why it is invalid
+    /// for every given tag in the order of the input
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_all_checked(
+        &self,
+        tags: Vec<AddTagDto>,
+        settings: &TagSettings,
+    ) -> RepoResult<Vec<Result<TagDto, String>>> {
+        let validated_tags: Vec<RepoResult<AddTagDto>> = tags
+            .into_iter()
+            .map(|t| validate_tag(t, settings))
+            .collect();
+        let mut valid_tags: Vec<AddTagDto> = validated_tags
             .iter()
-            .map(|t| tag::ActiveModel {
-                name: Set(t.name.to_owned()),
-                namespace_id: Set(t
-                    .namespace
-                    .as_ref()
-                    .and_then(|n| namespace_map.get(n))
-                    .map(|n| n.id())),
-                ..Default::default()
+            .filter_map(|r| r.as_ref().ok())
+            .cloned()
+            .collect();
+        valid_tags.sort_unstable_by_key(AddTagDto::normalized_name);
+        valid_tags.dedup_by_key(|t| t.normalized_name());
+
+        let tag_map: HashMap<String, TagDto> = if valid_tags.is_empty() {
+            HashMap::new()
+        } else {
+            self.add_all_valid(valid_tags).await?
+        };
+        let results = validated_tags
+            .into_iter()
+            .map(|result| {
+                let tag = result.map_err(|e| e.to_string())?;
+                tag_map
+                    .get(&tag.normalized_name())
+                    .cloned()
+                    .ok_or_else(|| String::from("the tag could not be created"))
             })
             .collect();
-        tag::Entity::insert_many(tag_models).exec(&trx).await?;
-        let mut tag_dtos = tags_by_name(&trx, tags).await?;
+
+        Ok(results)
+    }
+
+    /// Returns the tags mapped by the normalized names they were requested with
+    async fn add_all_valid(&self, tags: Vec<AddTagDto>) -> RepoResult<HashMap<String, TagDto>> {
+        let trx = self.ctx.db.begin().await?;
+        let tag_map = add_or_get_valid_tags(&trx, tags).await?;
         trx.commit().await?;
-        tag_dtos.append(&mut existing_tag_map.into_values().collect());
 
-        Ok(tag_dtos)
+        Ok(tag_map)
     }
 }
 
-async fn add_or_get_all_namespaces(
+/// Adds the validated tags that don't exist yet and returns all of them
+/// mapped by the normalized names they were requested with
+pub(crate) async fn add_or_get_valid_tags(
+    trx: &DatabaseTransaction,
+    mut tags: Vec<AddTagDto>,
+) -> RepoResult<HashMap<String, TagDto>> {
+    let mut tag_map =
+        canonical_tags_for_aliases(trx, tags.iter().map(AddTagDto::normalized_name).collect())
+            .await?;
+    tags.retain(|dto| !tag_map.contains_key(&dto.normalized_name()));
+    let existing_tags = tags_by_name(trx, tags.clone()).await?;
+    tag_map.extend(existing_tags.into_iter().map(|t| (t.normalized_name(), t)));
+    tags.retain(|dto| !tag_map.contains_key(&dto.normalized_name()));
+
+    if tags.is_empty() {
+        return Ok(tag_map);
+    }
+    let namespaces = tags.iter().filter_map(|t| t.namespace.clone()).collect();
+    let namespace_map = add_or_get_all_namespaces(trx, namespaces).await?;
+    let tag_models: Vec<tag::ActiveModel> = tags
+        .iter()
+        .map(|t| tag::ActiveModel {
+            name: Set(t.name.to_owned()),
+            namespace_id: Set(t
+                .namespace
+                .as_ref()
+                .and_then(|n| namespace_map.get(n))
+                .map(|n| n.id())),
+            ..Default::default()
+        })
+        .collect();
+    for chunk in tag_models.chunks(BATCH_SIZE) {
+        tag::Entity::insert_many(chunk.to_vec()).exec(trx).await?;
+    }
+    let tag_dtos = tags_by_name(trx, tags).await?;
+    tag_map.extend(tag_dtos.into_iter().map(|t| (t.normalized_name(), t)));
+
+    Ok(tag_map)
+}
+
+/// Normalizes the tag according to the settings and checks if it can be stored
+pub(crate) fn validate_tag(mut tag: AddTagDto, settings: &TagSettings) -> RepoResult<AddTagDto> {
+    if settings.trim_whitespace {
+        tag.name = tag.name.trim().to_string();
+        tag.namespace = tag.namespace.map(|n| n.trim().to_string());
+    }
+    let invalid = |reason: String| RepoError::InvalidTag {
+        tag: tag.normalized_name(),
+        reason,
+    };
+
+    if tag.name.is_empty() {
+        return Err(invalid(String::from("the name is empty")));
+    }
+    if let Some(namespace) = &tag.namespace {
+        if namespace.is_empty() {
+            return Err(invalid(String::from("the namespace is empty")));
+        }
+        if namespace.chars().count() > settings.max_length {
+            return Err(invalid(format!(
+                "the namespace is longer than {} characters",
+                settings.max_length
+            )));
+        }
+    }
+    if tag.name.chars().count() > settings.max_length {
+        return Err(invalid(format!(
+            "the name is longer than {} characters",
+            settings.max_length
+        )));
+    }
+    let characters = tag
+        .namespace
+        .iter()
+        .flat_map(|n| n.chars())
+        .chain(tag.name.chars());
+
+    for c in characters {
+        if c.is_control() {
+            return Err(invalid(String::from("the tag contains control characters")));
+        }
+        if settings.disallowed_characters.contains(c) {
+            return Err(invalid(format!("the tag contains the character '{}'", c)));
+        }
+    }
+
+    Ok(tag)
+}
+
+pub(crate) async fn add_or_get_all_namespaces(
     trx: &DatabaseTransaction,
     mut namespaces: Vec<String>,
 ) -> RepoResult<HashMap<String, NamespaceDto>> {
     if namespaces.is_empty() {
         return Ok(HashMap::with_capacity(0));
     }
+    namespaces.sort_unstable();
+    namespaces.dedup();
     let existing_namespaces = namespaces_by_name(trx, namespaces.clone()).await?;
     let mut namespace_map = HashMap::from_iter(
         existing_namespaces
@@ -76,9 +204,11 @@ async fn add_or_get_all_namespaces(
             ..Default::default()
         })
         .collect();
-    namespace::Entity::insert_many(namespace_models)
-        .exec(trx)
-        .await?;
+    for chunk in namespace_models.chunks(BATCH_SIZE) {
+        namespace::Entity::insert_many(chunk.to_vec())
+            .exec(trx)
+            .await?;
+    }
     let additional_namespaces = namespaces_by_name(trx, namespaces.clone()).await?;
 
     for nsp in additional_namespaces {
@@ -92,38 +222,46 @@ async fn namespaces_by_name(
     trx: &DatabaseTransaction,
     names: Vec<String>,
 ) -> RepoResult<Vec<NamespaceDto>> {
-    if names.is_empty() {
-        return Ok(vec![]);
-    }
-    let namespaces: Vec<NamespaceDto> = namespace::Entity::find()
-        .filter(namespace::Column::Name.is_in(names))
-        .all(trx)
-        .await?
-        .into_iter()
-        .map(NamespaceDto::new)
-        .collect();
+    let mut namespaces = Vec::with_capacity(names.len());
+
+    for chunk in names.chunks(BATCH_SIZE) {
+        let mut chunk_namespaces = namespace::Entity::find()
+            .filter(namespace::Column::Name.is_in(chunk.to_vec()))
+            .all(trx)
+            .await?
+            .into_iter()
+            .map(NamespaceDto::new)
+            .collect();
+        namespaces.append(&mut chunk_namespaces);
+    }
 
     Ok(namespaces)
 }
 
-async fn tags_by_name(trx: &DatabaseTransaction, tags: Vec<AddTagDto>) -> RepoResult<Vec<TagDto>> {
-    if tags.is_empty() {
-        return Ok(vec![]);
-    }
-    let condition = tags
-        .into_iter()
-        .map(build_tag_condition)
-        .fold(Condition::any(), Condition::add);
-    let tags = tag::Entity::find()
-        .find_also_related(namespace::Entity)
-        .filter(condition)
-        .all(trx)
-        .await?
-        .into_iter()
-        .map(map_tag_dto)
-        .collect();
+pub(crate) async fn tags_by_name(
+    trx: &DatabaseTransaction,
+    tags: Vec<AddTagDto>,
+) -> RepoResult<Vec<TagDto>> {
+    let mut tag_dtos = Vec::with_capacity(tags.len());
+
+    for chunk in tags.chunks(BATCH_SIZE) {
+        let condition = chunk
+            .iter()
+            .cloned()
+            .map(build_tag_condition)
+            .fold(Condition::any(), Condition::add);
+        let mut chunk_tags = tag::Entity::find()
+            .find_also_related(namespace::Entity)
+            .filter(condition)
+            .all(trx)
+            .await?
+            .into_iter()
+            .map(map_tag_dto)
+            .collect();
+        tag_dtos.append(&mut chunk_tags);
+    }
 
-    Ok(tags)
+    Ok(tag_dtos)
 }
 
 fn build_tag_condition(tag: AddTagDto) -> Condition {
@@ -137,3 +275,549 @@ fn build_tag_condition(tag: AddTagDto) -> Condition {
             .add(tag::Column::NamespaceId.is_null())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::dto::AddTagDto;
+    use crate::testing::fixtures::{add_file, test_repo};
+    use mediarepo_core::error::RepoError;
+    use mediarepo_core::settings::TagSettings;
+
+    #[tokio::test]
+    async fn it_adds_tags_exceeding_the_variable_limit() {
+        let (repo, root) = test_repo("add_all_tags").await;
+        let tags: Vec<AddTagDto> = (0..50_000)
+            .map(|i| AddTagDto {
+                namespace: Some(format!("namespace{}", i % 1000)),
+                name: format!("tag{}", i),
+            })
+            .collect();
+
+        let added = repo
+            .tag()
+            .add_all(tags.clone(), &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(added.len(), 50_000);
+        assert_eq!(repo.tag().all_namespaces().await.unwrap().len(), 1000);
+
+        let existing = repo.tag().add_all(tags, &Default::default()).await.unwrap();
+        assert_eq!(existing.len(), 50_000);
+        assert_eq!(repo.tag().all().await.unwrap().len(), 50_000);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_reports_invalid_tags_in_input_order() {
+        let (repo, root) = test_repo("add_all_tags_checked").await;
+        let tag = |namespace: Option<&str>, name: &str| AddTagDto {
+            namespace: namespace.map(String::from),
+            name: name.to_string(),
+        };
+        let tags = vec![
+            tag(Some("creator"), "someone"),
+            tag(None, ""),
+            tag(None, &"a".repeat(256)),
+            tag(Some(""), "empty namespace"),
+            tag(Some("creator"), "someone"),
+            tag(None, "plain"),
+        ];
+
+        let results = repo
+            .tag()
+            .add_all_checked(tags, &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 6);
+        assert_eq!(
+            results[0].as_ref().unwrap().normalized_name(),
+            "creator:someone"
+        );
+        assert!(results[1].is_err());
+        assert!(results[2].is_err());
+        assert!(results[3].is_err());
+        assert_eq!(
+            results[4].as_ref().unwrap().id(),
+            results[0].as_ref().unwrap().id()
+        );
+        assert_eq!(results[5].as_ref().unwrap().normalized_name(), "plain");
+        assert_eq!(repo.tag().all().await.unwrap().len(), 2);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_validates_tags_with_the_configured_rules() {
+        let (repo, root) = test_repo("add_all_tags_validated").await;
+        let settings = TagSettings {
+            max_length: 5,
+            disallowed_characters: String::from("#"),
+            ..Default::default()
+        };
+        let tag = |name: &str| AddTagDto {
+            namespace: None,
+            name: name.to_string(),
+        };
+
+        let results = repo
+            .tag()
+            .add_all_checked(
+                vec![tag("  ok  "), tag("too long"), tag("a#b"), tag("a\u{7}b")],
+                &settings,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results[0].as_ref().unwrap().name(), "ok");
+        assert!(results[1].as_ref().unwrap_err().contains("longer than 5"));
+        assert!(results[2].as_ref().unwrap_err().contains("'#'"));
+        assert!(results[3].as_ref().unwrap_err().contains("control"));
+
+        let error = repo
+            .tag()
+            .add_all(vec![tag("fine"), tag("a#b")], &settings)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RepoError::InvalidTag { .. }));
+        assert_eq!(repo.tag().all().await.unwrap().len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_changes_and_pages_large_tag_lists_of_a_file() {
+        let (repo, root) = test_repo("large_tag_lists").await;
+        let file = add_file(&repo, b"tagged".to_vec(), "text/plain", false).await;
+        let tags: Vec<AddTagDto> = (0..5000)
+            .map(|i| AddTagDto {
+                namespace: None,
+                name: format!("tag{:04}", i),
+            })
+            .collect();
+        let tag_ids: Vec<i64> = repo
+            .tag()
+            .add_all(tags, &Default::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id())
+            .collect();
+
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], tag_ids[..3000].to_vec())
+            .await
+            .unwrap();
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], tag_ids.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.tag().tag_count_for_cd(file.cd_id()).await.unwrap(),
+            5000
+        );
+
+        let page = repo
+            .tag()
+            .tags_for_cd_paginated(file.cd_id(), 100, 50)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 50);
+        assert_eq!(page[0].name(), "tag0100");
+        assert_eq!(page[49].name(), "tag0149");
+
+        repo.tag()
+            .remove_mappings(vec![file.cd_id()], tag_ids[..4500].to_vec())
+            .await
+            .unwrap();
+        let remaining = repo.tag().tags_for_cd(file.cd_id()).await.unwrap();
+        assert_eq!(remaining.len(), 500);
+        assert_eq!(repo.tag().all().await.unwrap().len(), 500);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_finds_and_merges_similar_tags() {
+        let (repo, root) = test_repo("similar_tags").await;
+        let tag = |namespace: Option<&str>, name: &str| AddTagDto {
+            namespace: namespace.map(String::from),
+            name: name.to_string(),
+        };
+        repo.tag()
+            .add_all(
+                vec![
+                    tag(None, "big cat"),
+                    tag(None, "big  cat"),
+                    tag(None, "Big_Cat"),
+                    tag(None, "dog"),
+                    tag(Some("creator"), "someone"),
+                    tag(Some("Creator"), "Someone"),
+                    tag(Some("series"), "big cat"),
+                ],
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+
+        let similar = repo.tag().find_similar_tags().await.unwrap();
+        let names: Vec<Vec<String>> = similar
+            .iter()
+            .map(|group| group.iter().map(|t| t.normalized_name()).collect())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                vec!["big cat", "big  cat", "Big_Cat"],
+                vec!["creator:someone", "Creator:Someone"],
+            ]
+        );
+
+        let group = &similar[1];
+        let merged = repo
+            .tag()
+            .merge_tags(vec![group[1].id()], group[0].id())
+            .await
+            .unwrap();
+        assert_eq!(merged.normalized_name(), "creator:someone");
+        assert_eq!(repo.tag().find_similar_tags().await.unwrap().len(), 1);
+        assert_eq!(repo.tag().all_namespaces().await.unwrap().len(), 2);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_expands_wildcards_in_names_and_namespaces() {
+        let (repo, root) = test_repo("wildcard_tags").await;
+        let tag = |namespace: Option<&str>, name: &str| AddTagDto {
+            namespace: namespace.map(String::from),
+            name: name.to_string(),
+        };
+        let tags = repo
+            .tag()
+            .add_all(
+                vec![
+                    tag(None, "catgirl"),
+                    tag(None, "dog_girl"),
+                    tag(Some("character"), "smile"),
+                    tag(Some("emotion"), "smile"),
+                    tag(None, "smile"),
+                ],
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        let id = |name: &str| {
+            tags.iter()
+                .find(|t| t.normalized_name() == name)
+                .unwrap()
+                .id()
+        };
+        let patterns = vec!["*girl", "*:smile", "character:*", "cat_*"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let mut id_map = repo
+            .tag()
+            .wildcard_tags_to_ids(patterns, false, 10)
+            .await
+            .unwrap();
+        id_map.values_mut().for_each(|ids| ids.sort_unstable());
+        assert_eq!(id_map["*girl"], vec![id("catgirl"), id("dog_girl")]);
+        assert_eq!(
+            id_map["*:smile"],
+            vec![id("character:smile"), id("emotion:smile")]
+        );
+        assert_eq!(id_map["character:*"], vec![id("character:smile")]);
+        assert!(id_map["cat_*"].is_empty());
+
+        let result = repo
+            .tag()
+            .wildcard_tags_to_ids(vec![String::from("*:*")], false, 1)
+            .await;
+        assert!(matches!(
+            result,
+            Err(RepoError::TooManyWildcardMatches { limit: 1, .. })
+        ));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_resolves_aliases_and_reassigns_aliased_tags() {
+        let (repo, root) = test_repo("tag_aliases").await;
+        let file = add_file(&repo, b"aliased".to_vec(), "text/plain", false).await;
+        let tag = |name: &str| AddTagDto {
+            namespace: None,
+            name: name.to_string(),
+        };
+        let tags = repo
+            .tag()
+            .add_all(vec![tag("image"), tag("img")], &Default::default())
+            .await
+            .unwrap();
+        let (image, img) = if tags[0].name() == "image" {
+            (&tags[0], &tags[1])
+        } else {
+            (&tags[1], &tags[0])
+        };
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], vec![img.id()])
+            .await
+            .unwrap();
+
+        let alias = repo
+            .tag()
+            .add_alias(tag("img"), image.id(), &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(alias.tag_id(), image.id());
+        let file_tags = repo.tag().tags_for_cd(file.cd_id()).await.unwrap();
+        assert_eq!(file_tags.len(), 1);
+        assert_eq!(file_tags[0].id(), image.id());
+        assert_eq!(repo.tag().all().await.unwrap().len(), 1);
+
+        let resolved = repo
+            .tag()
+            .add_all_checked(vec![tag("img"), tag("image")], &Default::default())
+            .await
+            .unwrap();
+        assert!(resolved
+            .iter()
+            .all(|t| t.as_ref().unwrap().id() == image.id()));
+        let canonical_names = repo
+            .tag()
+            .canonical_names_for_aliases(vec![String::from("img"), String::from("image")])
+            .await
+            .unwrap();
+        assert_eq!(canonical_names.len(), 1);
+        assert_eq!(canonical_names["img"], "image");
+
+        let cycle = repo
+            .tag()
+            .add_alias(tag("image"), image.id(), &Default::default())
+            .await;
+        assert!(cycle.is_err());
+        assert_eq!(repo.tag().aliases_for(image.id()).await.unwrap().len(), 1);
+
+        repo.tag().remove_alias(alias.id()).await.unwrap();
+        assert!(repo.tag().all_aliases().await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_assigns_and_cleans_up_implied_tags() {
+        let (repo, root) = test_repo("tag_implications").await;
+        let mut cd_ids = Vec::new();
+
+        for content in [b"cat".to_vec(), b"cat and dog".to_vec()] {
+            let file = add_file(&repo, content, "text/plain", false).await;
+            cd_ids.push(file.cd_id());
+        }
+        let names = ["cat", "dog", "animal", "living"];
+        let tags = repo
+            .tag()
+            .add_all(
+                names
+                    .iter()
+                    .map(|name| AddTagDto {
+                        namespace: None,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        let id = |name: &str| tags.iter().find(|t| t.name() == name).unwrap().id();
+        let tag_dao = repo.tag();
+        tag_dao
+            .add_implication(id("cat"), id("animal"))
+            .await
+            .unwrap();
+        tag_dao
+            .add_implication(id("dog"), id("animal"))
+            .await
+            .unwrap();
+        tag_dao
+            .add_implication(id("animal"), id("living"))
+            .await
+            .unwrap();
+        assert!(tag_dao
+            .add_implication(id("living"), id("cat"))
+            .await
+            .is_err());
+        assert_eq!(tag_dao.implications_for(id("cat")).await.unwrap().len(), 2);
+        assert_eq!(
+            tag_dao
+                .resolve_implications(vec![id("cat"), id("dog")])
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+
+        tag_dao
+            .upsert_mappings(vec![cd_ids[0]], vec![id("cat")])
+            .await
+            .unwrap();
+        tag_dao
+            .upsert_mappings(vec![cd_ids[1]], vec![id("cat"), id("dog")])
+            .await
+            .unwrap();
+        assert_eq!(tag_dao.tags_for_cd(cd_ids[0]).await.unwrap().len(), 3);
+        assert_eq!(tag_dao.tags_for_cd(cd_ids[1]).await.unwrap().len(), 4);
+
+        tag_dao
+            .remove_implication(id("cat"), id("animal"))
+            .await
+            .unwrap();
+        let cat_tags = tag_dao.tags_for_cd(cd_ids[0]).await.unwrap();
+        assert_eq!(cat_tags.len(), 1);
+        assert_eq!(cat_tags[0].id(), id("cat"));
+        // the dog still implies the animal tag
+        assert_eq!(tag_dao.tags_for_cd(cd_ids[1]).await.unwrap().len(), 4);
+
+        tag_dao
+            .remove_mappings(vec![cd_ids[1]], vec![id("dog")])
+            .await
+            .unwrap();
+        assert_eq!(tag_dao.tags_for_cd(cd_ids[1]).await.unwrap().len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_counts_the_contents_of_tags() {
+        let (repo, root) = test_repo("tag_counts").await;
+        let mut cd_ids = Vec::new();
+
+        for content in [b"first".to_vec(), b"second".to_vec()] {
+            let file = add_file(&repo, content, "text/plain", false).await;
+            cd_ids.push(file.cd_id());
+        }
+        let tags = repo
+            .tag()
+            .add_all(
+                ["both", "one", "none"]
+                    .iter()
+                    .map(|name| AddTagDto {
+                        namespace: None,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        let id = |name: &str| tags.iter().find(|t| t.name() == name).unwrap().id();
+        repo.tag()
+            .upsert_mappings(cd_ids.clone(), vec![id("both")])
+            .await
+            .unwrap();
+        repo.tag()
+            .upsert_mappings(vec![cd_ids[0]], vec![id("one")])
+            .await
+            .unwrap();
+
+        let counts = repo
+            .tag()
+            .counts_for_tags(vec![id("both"), id("one"), id("none")])
+            .await
+            .unwrap();
+        assert_eq!(counts[&id("both")], 2);
+        assert_eq!(counts[&id("one")], 1);
+        assert_eq!(counts[&id("none")], 0);
+
+        let tag_counts = repo.tag_counts().await.unwrap();
+        assert_eq!(tag_counts.len(), 3);
+        assert!(tag_counts
+            .iter()
+            .all(|(tag, count)| counts[&tag.id()] == *count));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_autocompletes_tags_by_prefix() {
+        let (repo, root) = test_repo("tag_autocomplete").await;
+        let mut cd_ids = Vec::new();
+
+        for content in [b"first".to_vec(), b"second".to_vec()] {
+            let file = add_file(&repo, content, "text/plain", false).await;
+            cd_ids.push(file.cd_id());
+        }
+        let tags = repo
+            .tag()
+            .add_all(
+                [
+                    (Some("character"), "alice"),
+                    (Some("character"), "alina"),
+                    (Some("city"), "alton"),
+                    (None, "alpha"),
+                    (None, "100%_done"),
+                ]
+                .iter()
+                .map(|(namespace, name)| AddTagDto {
+                    namespace: namespace.map(String::from),
+                    name: name.to_string(),
+                })
+                .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        let id = |name: &str| tags.iter().find(|t| t.name() == name).unwrap().id();
+        repo.tag()
+            .upsert_mappings(cd_ids.clone(), vec![id("alice")])
+            .await
+            .unwrap();
+        repo.tag()
+            .upsert_mappings(vec![cd_ids[0]], vec![id("alina"), id("100%_done")])
+            .await
+            .unwrap();
+        let autocomplete = |prefix: &'static str, limit: u64| {
+            let tag_dao = repo.tag();
+            async move {
+                tag_dao
+                    .autocomplete(prefix, limit)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|(tag, count)| (tag.normalized_name(), count))
+                    .collect::<Vec<(String, u64)>>()
+            }
+        };
+
+        assert_eq!(
+            autocomplete("al", 10).await,
+            vec![
+                (String::from("character:alice"), 2),
+                (String::from("character:alina"), 1),
+                (String::from("alpha"), 0),
+                (String::from("city:alton"), 0),
+            ]
+        );
+        assert_eq!(
+            autocomplete("CHAR", 10).await,
+            vec![
+                (String::from("character:alice"), 2),
+                (String::from("character:alina"), 1),
+            ]
+        );
+        assert_eq!(
+            autocomplete("character:ali", 1).await,
+            vec![(String::from("character:alice"), 2)]
+        );
+        assert!(autocomplete("charact:ali", 10).await.is_empty());
+        assert_eq!(
+            autocomplete("100%", 10).await,
+            vec![(String::from("100%_done"), 1)]
+        );
+        assert!(autocomplete("100_", 10).await.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
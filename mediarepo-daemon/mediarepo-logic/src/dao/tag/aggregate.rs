@@ -0,0 +1,263 @@
+use crate::dao::file::find::escape_like_pattern;
+use crate::dao::tag::add::BATCH_SIZE;
+use crate::dao::tag::TagDao;
+use crate::dto::TagDto;
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::{content_descriptor_tag, file, tag};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{Condition, FromQueryResult, JoinType, QueryOrder, QuerySelect, QueryTrait, Select};
+use std::collections::HashMap;
+
+#[derive(Debug, FromQueryResult)]
+struct TagIdCount {
+    tag_id: i64,
+    count: i64,
+}
+
+impl TagDao {
+    /// Returns the tags assigned to any of the given files together with the number
+    /// of files they are assigned to and the number of files found.
+    /// If `intersection` is set only tags that are assigned to all files are returned
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn aggregate_tags_for_files(
+        &self,
+        file_ids: Vec<i64>,
+        intersection: bool,
+    ) -> RepoResult<(Vec<(TagDto, u64)>, u64)> {
+        let file_count = file::Entity::find()
+            .filter(file::Column::Id.is_in(file_ids.clone()))
+            .count(&self.ctx.db)
+            .await? as u64;
+        if file_count == 0 {
+            return Ok((vec![], 0));
+        }
+        let cd_ids = file::Entity::find()
+            .select_only()
+            .column(file::Column::CdId)
+            .filter(file::Column::Id.is_in(file_ids))
+            .into_query();
+
+        let mut query =
+            mapping_counts().filter(content_descriptor_tag::Column::CdId.in_subquery(cd_ids));
+        if intersection {
+            query = query.having(Expr::cust_with_values("count = ?", vec![file_count as i64]));
+        }
+        let tag_counts: Vec<TagIdCount> = query
+            .order_by_desc(Expr::cust("count"))
+            .order_by_asc(content_descriptor_tag::Column::TagId)
+            .into_model::<TagIdCount>()
+            .all(&self.ctx.db)
+            .await?;
+
+        let aggregate = self.tags_with_counts(tag_counts).await?;
+
+        Ok((aggregate, file_count))
+    }
+
+    /// Returns the tags assigned to any of the reference files but not to the given file
+    /// together with the number of reference files they are assigned to.
+    /// The tags are ordered by that number starting with the most common one
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn missing_tags_for_file(
+        &self,
+        file_id: i64,
+        reference_file_ids: Vec<i64>,
+    ) -> RepoResult<Vec<(TagDto, u64)>> {
+        let target_cd_id = file::Entity::find_by_id(file_id)
+            .one(&self.ctx.db)
+            .await?
+            .ok_or_else(|| RepoError::from("file not found"))?
+            .cd_id;
+        let reference_cd_ids = file::Entity::find()
+            .select_only()
+            .column(file::Column::CdId)
+            .filter(file::Column::Id.is_in(reference_file_ids))
+            .filter(file::Column::CdId.ne(target_cd_id))
+            .into_query();
+        let target_tag_ids = content_descriptor_tag::Entity::find()
+            .select_only()
+            .column(content_descriptor_tag::Column::TagId)
+            .filter(content_descriptor_tag::Column::CdId.eq(target_cd_id))
+            .into_query();
+
+        let tag_counts: Vec<TagIdCount> = mapping_counts()
+            .filter(content_descriptor_tag::Column::CdId.in_subquery(reference_cd_ids))
+            .filter(content_descriptor_tag::Column::TagId.not_in_subquery(target_tag_ids))
+            .order_by_desc(Expr::cust("count"))
+            .order_by_asc(content_descriptor_tag::Column::TagId)
+            .into_model::<TagIdCount>()
+            .all(&self.ctx.db)
+            .await?;
+
+        self.tags_with_counts(tag_counts).await
+    }
+
+    /// Returns the number of contents each of the given tags is assigned to.
+    /// Tags that aren't assigned to any content are returned with a count of 0
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn counts_for_tags(&self, tag_ids: Vec<i64>) -> RepoResult<HashMap<i64, u64>> {
+        let mut counts: HashMap<i64, u64> = tag_ids.iter().map(|id| (*id, 0)).collect();
+
+        for chunk in tag_ids.chunks(BATCH_SIZE) {
+            let tag_counts: Vec<TagIdCount> = mapping_counts()
+                .filter(content_descriptor_tag::Column::TagId.is_in(chunk.to_vec()))
+                .into_model::<TagIdCount>()
+                .all(&self.ctx.db)
+                .await?;
+            counts.extend(tag_counts.into_iter().map(|c| (c.tag_id, c.count as u64)));
+        }
+
+        Ok(counts)
+    }
+
+    /// Returns the number of contents each tag is assigned to.
+    /// Tags that aren't assigned to any content are omitted
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_counts(&self) -> RepoResult<HashMap<i64, u64>> {
+        let counts = mapping_counts()
+            .into_model::<TagIdCount>()
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(|c| (c.tag_id, c.count as u64))
+            .collect();
+
+        Ok(counts)
+    }
+
+    /// Returns the tags whose namespace or name starts with the prefix together with
+    /// the number of contents they are assigned to, most used first. A prefix of the form
+    /// `namespace:partial` only matches tags of that namespace whose name starts with
+    /// the partial name. The casing of ascii characters is ignored
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn autocomplete(&self, prefix: &str, limit: u64) -> RepoResult<Vec<(TagDto, u64)>> {
+        let condition = if let Some((namespace, partial)) = prefix.split_once(':') {
+            Condition::all()
+                .add(name_like("namespaces.name", escape_like_pattern(namespace)))
+                .add(name_like(
+                    "tags.name",
+                    format!("{}%", escape_like_pattern(partial)),
+                ))
+        } else {
+            let pattern = format!("{}%", escape_like_pattern(prefix));
+            Condition::any()
+                .add(name_like("tags.name", pattern.clone()))
+                .add(name_like("namespaces.name", pattern))
+        };
+        let tag_counts: Vec<TagIdCount> = tag::Entity::find()
+            .select_only()
+            .column_as(tag::Column::Id, "tag_id")
+            .column_as(content_descriptor_tag::Column::CdId.count(), "count")
+            .join(
+                JoinType::LeftJoin,
+                content_descriptor_tag::Relation::Tag.def().rev(),
+            )
+            .join(JoinType::LeftJoin, tag::Relation::Namespace.def())
+            .filter(condition)
+            .group_by(tag::Column::Id)
+            .order_by_desc(Expr::cust("count"))
+            .order_by_asc(tag::Column::Name)
+            .order_by_asc(tag::Column::Id)
+            .limit(limit)
+            .into_model::<TagIdCount>()
+            .all(&self.ctx.db)
+            .await?;
+
+        self.tags_with_counts(tag_counts).await
+    }
+
+    /// Loads the tags of the counts keeping the order of the counts
+    async fn tags_with_counts(
+        &self,
+        tag_counts: Vec<TagIdCount>,
+    ) -> RepoResult<Vec<(TagDto, u64)>> {
+        let tag_ids: Vec<i64> = tag_counts.iter().map(|c| c.tag_id).collect();
+        let mut tags: HashMap<i64, TagDto> = self
+            .all_by_ids(tag_ids)
+            .await?
+            .into_iter()
+            .map(|t| (t.id(), t))
+            .collect();
+
+        Ok(tag_counts
+            .into_iter()
+            .filter_map(|c| Some((tags.remove(&c.tag_id)?, c.count as u64)))
+            .collect())
+    }
+}
+
+/// Counts the mappings of every tag
+fn mapping_counts() -> Select<content_descriptor_tag::Entity> {
+    content_descriptor_tag::Entity::find()
+        .select_only()
+        .column(content_descriptor_tag::Column::TagId)
+        .column_as(content_descriptor_tag::Column::CdId.count(), "count")
+        .group_by(content_descriptor_tag::Column::TagId)
+}
+
+/// Matches the name column against the LIKE pattern.
+/// LIKE ignores the casing of ascii characters in sqlite and can use the nocase indices
+fn name_like(column: &str, pattern: String) -> SimpleExpr {
+    Expr::cust_with_values(&format!(r"{} LIKE ? ESCAPE '\'", column), vec![pattern])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::DaoProvider;
+    use crate::dto::AddTagDto;
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_lists_tags_of_reference_files_missing_on_a_file() {
+        let (repo, root) = test_repo("missing-tags").await;
+        let target = add_file(&repo, vec![1], "application/octet-stream", false).await;
+        let references = [
+            add_file(&repo, vec![2], "application/octet-stream", false).await,
+            add_file(&repo, vec![3], "application/octet-stream", false).await,
+            add_file(&repo, vec![4], "application/octet-stream", false).await,
+        ];
+        let tags = repo
+            .tag()
+            .add_all(
+                ["a", "b", "c"]
+                    .iter()
+                    .map(|name| AddTagDto {
+                        namespace: None,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        let (a, b, c) = (tags[0].id(), tags[1].id(), tags[2].id());
+        let tag_dao = repo.tag();
+        tag_dao
+            .upsert_mappings(vec![target.cd_id(), references[0].cd_id()], vec![a])
+            .await
+            .unwrap();
+        tag_dao
+            .upsert_mappings(references.iter().map(|f| f.cd_id()).collect(), vec![b])
+            .await
+            .unwrap();
+        tag_dao
+            .upsert_mappings(vec![references[0].cd_id(), references[1].cd_id()], vec![c])
+            .await
+            .unwrap();
+
+        let mut reference_ids: Vec<i64> = references.iter().map(|f| f.id()).collect();
+        reference_ids.push(target.id());
+        let missing: Vec<(i64, u64)> = tag_dao
+            .missing_tags_for_file(target.id(), reference_ids)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(tag, count)| (tag.id(), count))
+            .collect();
+        assert_eq!(missing, vec![(b, 3), (c, 2)]);
+        assert!(tag_dao.missing_tags_for_file(-1, vec![]).await.is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
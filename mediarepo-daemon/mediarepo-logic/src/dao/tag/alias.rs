@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DatabaseTransaction, QueryOrder, TransactionTrait};
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::settings::TagSettings;
+use mediarepo_database::entities::{namespace, tag, tag_alias};
+
+use crate::dao::tag::add::{tags_by_name, validate_tag, BATCH_SIZE};
+use crate::dao::tag::merge::{delete_empty_namespaces, merge_tag_mappings};
+use crate::dao::tag::{map_tag_dto, TagDao};
+use crate::dto::{AddTagDto, TagAliasDto, TagDto};
+
+impl TagDao {
+    /// Returns all aliases ordered by name
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_aliases(&self) -> RepoResult<Vec<TagAliasDto>> {
+        let aliases = tag_alias::Entity::find()
+            .order_by_asc(tag_alias::Column::Name)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(TagAliasDto::new)
+            .collect();
+
+        Ok(aliases)
+    }
+
+    /// Returns all aliases resolving to the given tag
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn aliases_for(&self, tag_id: i64) -> RepoResult<Vec<TagAliasDto>> {
+        let aliases = tag_alias::Entity::find()
+            .filter(tag_alias::Column::TagId.eq(tag_id))
+            .order_by_asc(tag_alias::Column::Name)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(TagAliasDto::new)
+            .collect();
+
+        Ok(aliases)
+    }
+
+    /// Adds an alias resolving to the given tag or changes the tag of an existing alias.
+    /// If a tag with the name of the alias exists, its files are assigned to the aliased tag
+    /// and the tag is deleted so that the alias is the only way to refer to the name
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_alias(
+        &self,
+        alias: AddTagDto,
+        tag_id: i64,
+        settings: &TagSettings,
+    ) -> RepoResult<TagAliasDto> {
+        let alias = validate_tag(alias, settings)?;
+        let alias_name = alias.normalized_name();
+        let trx = self.ctx.db.begin().await?;
+        let target = tag::Entity::find_by_id(tag_id)
+            .find_also_related(namespace::Entity)
+            .one(&trx)
+            .await?
+            .map(map_tag_dto)
+            .ok_or_else(|| RepoError::from("tag not found"))?;
+
+        if names_equal(
+            &alias_name,
+            &target.normalized_name(),
+            settings.case_sensitive,
+        ) {
+            return Err(RepoError::from("cannot alias a tag to itself"));
+        }
+        // aliases always resolve to existing tags so an alias named like the target
+        // would turn the two names into a cycle
+        tag_alias::Entity::delete_many()
+            .filter(tag_alias::Column::Name.eq(target.normalized_name()))
+            .exec(&trx)
+            .await?;
+
+        for existing_tag in tags_by_name(&trx, vec![alias]).await? {
+            merge_tag_mappings(&trx, existing_tag.id(), target.id()).await?;
+            tag::Entity::delete_by_id(existing_tag.id())
+                .exec(&trx)
+                .await?;
+        }
+        delete_empty_namespaces(&trx).await?;
+        let alias = upsert_alias(&trx, alias_name, target.id()).await?;
+        trx.commit().await?;
+
+        Ok(alias)
+    }
+
+    /// Removes an alias without changing the tag it resolves to
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_alias(&self, alias_id: i64) -> RepoResult<()> {
+        tag_alias::Entity::delete_by_id(alias_id)
+            .exec(&self.ctx.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Maps the given normalized names that are aliases to the normalized names
+    /// of the tags they resolve to. Names that aren't aliases are omitted
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn canonical_names_for_aliases(
+        &self,
+        names: Vec<String>,
+    ) -> RepoResult<HashMap<String, String>> {
+        let trx = self.ctx.db.begin().await?;
+        let tag_map = canonical_tags_for_aliases(&trx, names).await?;
+        trx.commit().await?;
+
+        Ok(tag_map
+            .into_iter()
+            .map(|(alias, tag)| (alias, tag.normalized_name()))
+            .collect())
+    }
+}
+
+/// Maps the given normalized names that are aliases to the tags they resolve to
+pub(crate) async fn canonical_tags_for_aliases(
+    trx: &DatabaseTransaction,
+    names: Vec<String>,
+) -> RepoResult<HashMap<String, TagDto>> {
+    let mut tag_map = HashMap::new();
+
+    for chunk in names.chunks(BATCH_SIZE) {
+        let aliases = tag_alias::Entity::find()
+            .find_also_related(tag::Entity)
+            .filter(tag_alias::Column::Name.is_in(chunk.to_vec()))
+            .all(trx)
+            .await?;
+        let tag_models: Vec<tag::Model> = aliases.iter().filter_map(|(_, t)| t.clone()).collect();
+        if tag_models.is_empty() {
+            continue;
+        }
+        let namespace_ids: Vec<i64> = tag_models.iter().filter_map(|t| t.namespace_id).collect();
+        let namespaces: HashMap<i64, namespace::Model> = namespace::Entity::find()
+            .filter(namespace::Column::Id.is_in(namespace_ids))
+            .all(trx)
+            .await?
+            .into_iter()
+            .map(|n| (n.id, n))
+            .collect();
+
+        for (alias, tag) in aliases {
+            if let Some(tag) = tag {
+                let namespace = tag.namespace_id.and_then(|id| namespaces.get(&id)).cloned();
+                tag_map.insert(alias.name, TagDto::new(tag, namespace));
+            }
+        }
+    }
+
+    Ok(tag_map)
+}
+
+async fn upsert_alias(
+    trx: &DatabaseTransaction,
+    name: String,
+    tag_id: i64,
+) -> RepoResult<TagAliasDto> {
+    let existing = tag_alias::Entity::find()
+        .filter(tag_alias::Column::Name.eq(name.clone()))
+        .one(trx)
+        .await?;
+    let model = if let Some(existing) = existing {
+        let mut active_model: tag_alias::ActiveModel = existing.into();
+        active_model.tag_id = Set(tag_id);
+        active_model.update(trx).await?
+    } else {
+        tag_alias::ActiveModel {
+            name: Set(name),
+            tag_id: Set(tag_id),
+            ..Default::default()
+        }
+        .insert(trx)
+        .await?
+    };
+
+    Ok(TagAliasDto::new(model))
+}
+
+fn names_equal(a: &str, b: &str, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        a == b
+    } else {
+        a.to_lowercase() == b.to_lowercase()
+    }
+}
@@ -1,10 +1,13 @@
+use std::collections::HashMap;
+
 use crate::dao::tag::{map_tag_dto, TagDao};
 use crate::dto::TagDto;
-use mediarepo_core::error::RepoResult;
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::utils::parse_namespace_and_tag;
 use mediarepo_database::entities::{namespace, tag};
 use sea_orm::prelude::*;
-use sea_orm::sea_query::Expr;
-use sea_orm::{Condition, QuerySelect};
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{Condition, QueryOrder, QuerySelect};
 
 #[derive(Clone, Debug)]
 pub struct TagByNameQuery {
@@ -15,12 +18,17 @@ pub struct TagByNameQuery {
 impl TagDao {
     /// Filters all tags by names
     /// wildcards are supported
+    /// Names are compared ignoring their casing unless `case_sensitive` is set
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn all_by_name(&self, names: Vec<TagByNameQuery>) -> RepoResult<Vec<TagDto>> {
+    pub async fn all_by_name(
+        &self,
+        names: Vec<TagByNameQuery>,
+        case_sensitive: bool,
+    ) -> RepoResult<Vec<TagDto>> {
         let mut condition_count = 0;
         let condition = names
             .into_iter()
-            .filter_map(name_query_to_condition)
+            .filter_map(|query| name_query_to_condition(query, case_sensitive))
             .inspect(|_| condition_count += 1)
             .fold(Condition::any(), Condition::add);
         if condition_count == 0 {
@@ -39,27 +47,187 @@ impl TagDao {
 
         Ok(tags)
     }
+
+    /// Returns all tags with the given name in any namespace including tags without a namespace.
+    /// Names are compared ignoring their casing unless `case_sensitive` is set
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_tag_variants(
+        &self,
+        name: String,
+        case_sensitive: bool,
+    ) -> RepoResult<Vec<TagDto>> {
+        let tags = tag::Entity::find()
+            .find_also_related(namespace::Entity)
+            .filter(name_eq_condition("tags.name", name, case_sensitive))
+            .order_by_asc(namespace::Column::Name)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(map_tag_dto)
+            .collect();
+
+        Ok(tags)
+    }
+
+    /// Resolves every wildcard pattern like `*:smile` or `char*` to the ids of the tags
+    /// matching it. Fails if a single pattern matches more than `limit` tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn wildcard_tags_to_ids(
+        &self,
+        patterns: Vec<String>,
+        case_sensitive: bool,
+        limit: usize,
+    ) -> RepoResult<HashMap<String, Vec<i64>>> {
+        let mut id_map = HashMap::new();
+
+        for pattern in patterns {
+            let (namespace, name) = parse_namespace_and_tag(pattern.clone(), case_sensitive);
+            let condition =
+                match name_query_to_condition(TagByNameQuery { namespace, name }, case_sensitive) {
+                    Some(condition) => condition,
+                    None => continue,
+                };
+            let ids: Vec<i64> = tag::Entity::find()
+                .find_also_related(namespace::Entity)
+                .filter(condition)
+                .limit(limit as u64 + 1)
+                .all(&self.ctx.db)
+                .await?
+                .into_iter()
+                .map(|(tag, _)| tag.id)
+                .collect();
+
+            if ids.len() > limit {
+                return Err(RepoError::TooManyWildcardMatches { pattern, limit });
+            }
+            id_map.insert(pattern, ids);
+        }
+
+        Ok(id_map)
+    }
 }
 
-fn name_query_to_condition(query: TagByNameQuery) -> Option<Condition> {
+fn name_query_to_condition(query: TagByNameQuery, case_sensitive: bool) -> Option<Condition> {
     let TagByNameQuery { namespace, name } = query;
     let mut condition = Condition::all();
 
     #[allow(clippy::question_mark)]
-    if !name.ends_with('*') {
-        condition = condition.add(tag::Column::Name.eq(name))
-    } else if name.len() > 1 {
-        condition =
-            condition.add(tag::Column::Name.like(&*format!("{}%", name.trim_end_matches('*'))))
+    if !name.contains('*') {
+        condition = condition.add(name_eq_condition("tags.name", name, case_sensitive))
+    } else if !is_match_all(&name) {
+        condition = condition.add(name_wildcard_condition("tags.name", &name, case_sensitive))
     } else if namespace.is_none() {
         return None;
     }
 
-    condition = if let Some(namespace) = namespace {
-        condition.add(namespace::Column::Name.eq(namespace))
-    } else {
-        condition.add(Expr::tbl(tag::Entity, tag::Column::NamespaceId).is_null())
+    condition = match namespace {
+        Some(namespace) if is_match_all(&namespace) => {
+            condition.add(Expr::tbl(tag::Entity, tag::Column::NamespaceId).is_not_null())
+        }
+        Some(namespace) if namespace.contains('*') => condition.add(name_wildcard_condition(
+            "namespaces.name",
+            &namespace,
+            case_sensitive,
+        )),
+        Some(namespace) => condition.add(name_eq_condition(
+            "namespaces.name",
+            namespace,
+            case_sensitive,
+        )),
+        None => condition.add(Expr::tbl(tag::Entity, tag::Column::NamespaceId).is_null()),
     };
 
     Some(condition)
 }
+
+fn name_eq_condition(column: &str, name: String, case_sensitive: bool) -> SimpleExpr {
+    if case_sensitive {
+        Expr::cust_with_values(&format!("{} = ?", column), vec![name])
+    } else {
+        // compared with the collation of the nocase indices on the names so that they can be used
+        Expr::cust_with_values(&format!("{} = ? COLLATE NOCASE", column), vec![name])
+    }
+}
+
+/// Matches the column against a pattern in which `*` matches any number of characters
+fn name_wildcard_condition(column: &str, pattern: &str, case_sensitive: bool) -> SimpleExpr {
+    if case_sensitive {
+        // GLOB already uses `*` as wildcard but treats `?` and `[` specially as well
+        let pattern = pattern.replace('[', "[[]").replace('?', "[?]");
+        Expr::cust_with_values(&format!("{} GLOB ?", column), vec![pattern])
+    } else {
+        // LIKE ignores the casing of ascii characters in sqlite
+        let pattern = pattern
+            .replace('\\', r"\\")
+            .replace('%', r"\%")
+            .replace('_', r"\_")
+            .replace('*', "%");
+        Expr::cust_with_values(&format!(r"{} LIKE ? ESCAPE '\'", column), vec![pattern])
+    }
+}
+
+/// Returns if the pattern consists only of wildcards
+fn is_match_all(pattern: &str) -> bool {
+    pattern.chars().all(|c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dao::tag::by_name::TagByNameQuery;
+    use crate::dao::DaoProvider;
+    use crate::dto::AddTagDto;
+    use crate::testing::fixtures::test_repo;
+
+    #[tokio::test]
+    async fn it_finds_tags_by_name_ignoring_the_casing() {
+        let (repo, root) = test_repo("tags-by-name").await;
+        repo.tag()
+            .add_all(
+                vec![
+                    AddTagDto {
+                        namespace: None,
+                        name: String::from("Cat"),
+                    },
+                    AddTagDto {
+                        namespace: Some(String::from("Animal")),
+                        name: String::from("cAT"),
+                    },
+                ],
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+
+        let variants = repo
+            .tag()
+            .find_tag_variants(String::from("CAT"), false)
+            .await
+            .unwrap();
+        assert_eq!(variants.len(), 2);
+        let variants = repo
+            .tag()
+            .find_tag_variants(String::from("Cat"), true)
+            .await
+            .unwrap();
+        assert_eq!(variants.len(), 1);
+        let query = TagByNameQuery {
+            namespace: Some(String::from("animal")),
+            name: String::from("cat"),
+        };
+        let tags = repo
+            .tag()
+            .all_by_name(vec![query.clone()], false)
+            .await
+            .unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name(), "cAT");
+        assert!(repo
+            .tag()
+            .all_by_name(vec![query], true)
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
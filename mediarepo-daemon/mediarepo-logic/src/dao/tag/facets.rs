@@ -0,0 +1,64 @@
+use crate::dao::file::find::{build_find_filter_conditions, FilterProperty};
+use crate::dao::tag::TagDao;
+use crate::dto::TagDto;
+use mediarepo_core::error::RepoResult;
+use mediarepo_database::entities::{content_descriptor, content_descriptor_tag, file};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::{FromQueryResult, JoinType, QueryOrder, QuerySelect, QueryTrait};
+use std::collections::HashMap;
+
+#[derive(Debug, FromQueryResult)]
+struct TagIdCount {
+    tag_id: i64,
+    count: i64,
+}
+
+impl TagDao {
+    /// Returns the tags that are assigned most often to the files matching the filters
+    /// together with the number of matching files they are assigned to
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn facets_for_filters(
+        &self,
+        filters: Vec<Vec<FilterProperty>>,
+        limit: u64,
+    ) -> RepoResult<Vec<(TagDto, u64)>> {
+        let result_set = content_descriptor::Entity::find()
+            .select_only()
+            .column(content_descriptor::Column::Id)
+            .join(
+                JoinType::LeftJoin,
+                file::Relation::ContentDescriptorId.def().rev(),
+            )
+            .filter(build_find_filter_conditions(filters))
+            .into_query();
+
+        let tag_counts: Vec<TagIdCount> = content_descriptor_tag::Entity::find()
+            .select_only()
+            .column(content_descriptor_tag::Column::TagId)
+            .column_as(content_descriptor_tag::Column::CdId.count(), "count")
+            .filter(content_descriptor_tag::Column::CdId.in_subquery(result_set))
+            .group_by(content_descriptor_tag::Column::TagId)
+            .order_by_desc(Expr::cust("count"))
+            .order_by_asc(content_descriptor_tag::Column::TagId)
+            .limit(limit)
+            .into_model::<TagIdCount>()
+            .all(&self.ctx.db)
+            .await?;
+
+        let tag_ids: Vec<i64> = tag_counts.iter().map(|c| c.tag_id).collect();
+        let mut tags: HashMap<i64, TagDto> = self
+            .all_by_ids(tag_ids)
+            .await?
+            .into_iter()
+            .map(|t| (t.id(), t))
+            .collect();
+
+        let facets = tag_counts
+            .into_iter()
+            .filter_map(|c| Some((tags.remove(&c.tag_id)?, c.count as u64)))
+            .collect();
+
+        Ok(facets)
+    }
+}
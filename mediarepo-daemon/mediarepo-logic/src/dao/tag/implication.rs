@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{ConnectionTrait, DatabaseTransaction, TransactionTrait};
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::{content_descriptor_tag, tag, tag_implication};
+
+use crate::dao::tag::add::BATCH_SIZE;
+use crate::dao::tag::mappings::insert_missing_mappings;
+use crate::dao::tag::TagDao;
+use crate::dto::TagDto;
+
+impl TagDao {
+    /// Returns all tags implied by the given tag including the tags
+    /// implied by those tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn implications_for(&self, tag_id: i64) -> RepoResult<Vec<TagDto>> {
+        let graph = ImplicationGraph::load(&self.ctx.db).await?;
+        let mut implied_ids = graph.closure(vec![tag_id]);
+        implied_ids.remove(&tag_id);
+
+        self.all_by_ids(implied_ids.into_iter().collect()).await
+    }
+
+    /// Returns the given tags together with all tags they imply
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn resolve_implications(&self, tag_ids: Vec<i64>) -> RepoResult<Vec<TagDto>> {
+        let graph = ImplicationGraph::load(&self.ctx.db).await?;
+        let tag_ids = graph.closure(tag_ids);
+
+        self.all_by_ids(tag_ids.into_iter().collect()).await
+    }
+
+    /// Adds an implication so that the implied tag is assigned together with the tag.
+    /// Contents that already have the tag get the implied tags assigned as well
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn add_implication(&self, tag_id: i64, implied_tag_id: i64) -> RepoResult<()> {
+        if tag_id == implied_tag_id {
+            return Err(RepoError::from("a tag cannot imply itself"));
+        }
+        let trx = self.ctx.db.begin().await?;
+        let tag_count = tag::Entity::find()
+            .filter(tag::Column::Id.is_in(vec![tag_id, implied_tag_id]))
+            .count(&trx)
+            .await?;
+
+        if tag_count != 2 {
+            return Err(RepoError::from("tag not found"));
+        }
+        let mut graph = ImplicationGraph::load(&trx).await?;
+
+        if graph.closure(vec![implied_tag_id]).contains(&tag_id) {
+            return Err(RepoError::from("the implication would create a cycle"));
+        }
+        if !graph.implies_directly(tag_id, implied_tag_id) {
+            tag_implication::ActiveModel {
+                tag_id: Set(tag_id),
+                implied_tag_id: Set(implied_tag_id),
+            }
+            .insert(&trx)
+            .await?;
+            graph.add(tag_id, implied_tag_id);
+        }
+        let implied_ids: Vec<i64> = graph.closure(vec![implied_tag_id]).into_iter().collect();
+        let mappings: Vec<(i64, i64)> = cd_ids_with_tag(&trx, tag_id)
+            .await?
+            .into_iter()
+            .flat_map(|cd_id| implied_ids.iter().map(move |id| (cd_id, *id)))
+            .collect();
+
+        for chunk in mappings.chunks(BATCH_SIZE) {
+            insert_missing_mappings(&trx, chunk, true).await?;
+        }
+        trx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Removes an implication. Tags that were only assigned because of the implication
+    /// are removed from contents unless another of their tags still implies them
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_implication(&self, tag_id: i64, implied_tag_id: i64) -> RepoResult<()> {
+        let trx = self.ctx.db.begin().await?;
+        let mut graph = ImplicationGraph::load(&trx).await?;
+        let affected_ids = graph.closure(vec![implied_tag_id]);
+
+        tag_implication::Entity::delete_many()
+            .filter(tag_implication::Column::TagId.eq(tag_id))
+            .filter(tag_implication::Column::ImpliedTagId.eq(implied_tag_id))
+            .exec(&trx)
+            .await?;
+        graph.remove(tag_id, implied_tag_id);
+        let cd_ids = cd_ids_with_tag(&trx, tag_id).await?;
+        remove_unrequired_implications(&trx, &graph, cd_ids, &affected_ids).await?;
+        trx.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// All implications between tags
+pub(crate) struct ImplicationGraph {
+    implications: HashMap<i64, Vec<i64>>,
+}
+
+impl ImplicationGraph {
+    pub async fn load<C: ConnectionTrait>(db: &C) -> RepoResult<Self> {
+        let mut implications: HashMap<i64, Vec<i64>> = HashMap::new();
+
+        for implication in tag_implication::Entity::find().all(db).await? {
+            implications
+                .entry(implication.tag_id)
+                .or_default()
+                .push(implication.implied_tag_id);
+        }
+
+        Ok(Self { implications })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.implications.is_empty()
+    }
+
+    /// Returns the given tags and all tags implied by them
+    pub fn closure(&self, tag_ids: Vec<i64>) -> HashSet<i64> {
+        let mut closure: HashSet<i64> = HashSet::new();
+        let mut pending = tag_ids;
+
+        while let Some(tag_id) = pending.pop() {
+            if closure.insert(tag_id) {
+                if let Some(implied) = self.implications.get(&tag_id) {
+                    pending.extend(implied);
+                }
+            }
+        }
+
+        closure
+    }
+
+    fn implies_directly(&self, tag_id: i64, implied_tag_id: i64) -> bool {
+        self.implications
+            .get(&tag_id)
+            .map(|implied| implied.contains(&implied_tag_id))
+            .unwrap_or(false)
+    }
+
+    fn add(&mut self, tag_id: i64, implied_tag_id: i64) {
+        self.implications
+            .entry(tag_id)
+            .or_default()
+            .push(implied_tag_id);
+    }
+
+    fn remove(&mut self, tag_id: i64, implied_tag_id: i64) {
+        if let Some(implied) = self.implications.get_mut(&tag_id) {
+            implied.retain(|id| *id != implied_tag_id);
+        }
+    }
+}
+
+/// Removes the implied mappings of the given tags from all contents
+/// whose remaining tags don't imply them anymore
+pub(crate) async fn remove_unrequired_implications(
+    trx: &DatabaseTransaction,
+    graph: &ImplicationGraph,
+    cd_ids: Vec<i64>,
+    tag_ids: &HashSet<i64>,
+) -> RepoResult<()> {
+    for chunk in cd_ids.chunks(BATCH_SIZE) {
+        let mut cd_mappings: HashMap<i64, Vec<content_descriptor_tag::Model>> = HashMap::new();
+
+        for mapping in content_descriptor_tag::Entity::find()
+            .filter(content_descriptor_tag::Column::CdId.is_in(chunk.to_vec()))
+            .all(trx)
+            .await?
+        {
+            cd_mappings.entry(mapping.cd_id).or_default().push(mapping);
+        }
+
+        for (cd_id, mappings) in cd_mappings {
+            let explicit_ids = mappings
+                .iter()
+                .filter(|m| !m.implied)
+                .map(|m| m.tag_id)
+                .collect();
+            let required_ids = graph.closure(explicit_ids);
+            let obsolete_ids: Vec<i64> = mappings
+                .iter()
+                .filter(|m| m.implied && tag_ids.contains(&m.tag_id))
+                .filter(|m| !required_ids.contains(&m.tag_id))
+                .map(|m| m.tag_id)
+                .collect();
+
+            if !obsolete_ids.is_empty() {
+                content_descriptor_tag::Entity::delete_many()
+                    .filter(content_descriptor_tag::Column::CdId.eq(cd_id))
+                    .filter(content_descriptor_tag::Column::TagId.is_in(obsolete_ids))
+                    .exec(trx)
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn cd_ids_with_tag(trx: &DatabaseTransaction, tag_id: i64) -> RepoResult<Vec<i64>> {
+    let cd_ids = content_descriptor_tag::Entity::find()
+        .filter(content_descriptor_tag::Column::TagId.eq(tag_id))
+        .all(trx)
+        .await?
+        .into_iter()
+        .map(|m| m.cd_id)
+        .collect();
+
+    Ok(cd_ids)
+}
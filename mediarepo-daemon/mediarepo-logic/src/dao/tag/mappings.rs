@@ -1,78 +1,181 @@
 use sea_orm::prelude::*;
 use sea_orm::sea_query::Query;
-use sea_orm::ActiveValue::Set;
-use sea_orm::{DatabaseTransaction, TransactionTrait};
+use sea_orm::{ConnectionTrait, DatabaseTransaction, DbBackend, Statement, TransactionTrait};
 
 use mediarepo_core::error::RepoResult;
-use mediarepo_database::entities::{content_descriptor_tag, namespace, tag};
+use mediarepo_database::entities::{
+    content_descriptor_tag, namespace, tag, tag_alias, tag_implication,
+};
 
+use crate::dao::tag::add::BATCH_SIZE;
+use crate::dao::tag::implication::{remove_unrequired_implications, ImplicationGraph};
 use crate::dao::tag::TagDao;
 
 impl TagDao {
+    /// Adds the mappings between all given contents and tags that don't exist yet
+    /// together with the mappings of all tags implied by the given tags.
+    /// Existing mappings are skipped by the database so they never get loaded
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn upsert_mappings(&self, cd_ids: Vec<i64>, tag_ids: Vec<i64>) -> RepoResult<()> {
+        if cd_ids.is_empty() || tag_ids.is_empty() {
+            return Ok(());
+        }
         let trx = self.ctx.db.begin().await?;
+        upsert_mappings(&trx, cd_ids, tag_ids).await?;
+        trx.commit().await?;
 
-        let existing_mappings = get_existing_mappings(&trx, &cd_ids, &tag_ids).await?;
+        Ok(())
+    }
 
-        let active_models: Vec<content_descriptor_tag::ActiveModel> = cd_ids
-            .into_iter()
-            .flat_map(|cd_id: i64| {
-                tag_ids
-                    .iter()
-                    .filter(|tag_id| !existing_mappings.contains(&(cd_id, **tag_id)))
-                    .map(move |tag_id| content_descriptor_tag::ActiveModel {
-                        cd_id: Set(cd_id),
-                        tag_id: Set(*tag_id),
-                    })
-                    .collect::<Vec<content_descriptor_tag::ActiveModel>>()
-            })
-            .collect();
+    /// Removes the mappings between the given contents and tags.
+    /// Implied tags are removed as well unless another tag of the content still implies them
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove_mappings(&self, cd_ids: Vec<i64>, tag_ids: Vec<i64>) -> RepoResult<()> {
+        let trx = self.ctx.db.begin().await?;
+        remove_mappings(&trx, cd_ids, tag_ids).await?;
+        trx.commit().await?;
 
-        if !active_models.is_empty() {
-            content_descriptor_tag::Entity::insert_many(active_models)
-                .exec(&trx)
+        Ok(())
+    }
+
+    /// Returns the existing (cd_id, tag_id) mappings between the given contents and tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn existing_mappings(
+        &self,
+        cd_ids: &[i64],
+        tag_ids: &[i64],
+    ) -> RepoResult<Vec<(i64, i64)>> {
+        let mut mappings = Vec::new();
+
+        if tag_ids.is_empty() {
+            return Ok(mappings);
+        }
+        for cd_ids in cd_ids.chunks(BATCH_SIZE) {
+            let chunk = content_descriptor_tag::Entity::find()
+                .filter(content_descriptor_tag::Column::CdId.is_in(cd_ids.to_vec()))
+                .filter(content_descriptor_tag::Column::TagId.is_in(tag_ids.to_vec()))
+                .all(&self.ctx.db)
                 .await?;
+            mappings.extend(chunk.into_iter().map(|m| (m.cd_id, m.tag_id)));
+        }
 
-            trx.commit().await?;
+        Ok(mappings)
+    }
+}
+
+/// Removes the mappings between the given contents and tags and the implied mappings
+/// that aren't required anymore. Returns the number of removed mappings of the given tags
+pub(crate) async fn remove_mappings(
+    trx: &DatabaseTransaction,
+    cd_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> RepoResult<u64> {
+    let mut removed = 0;
+
+    for cd_chunk in cd_ids.chunks(BATCH_SIZE) {
+        for tag_chunk in tag_ids.chunks(BATCH_SIZE) {
+            removed += content_descriptor_tag::Entity::delete_many()
+                .filter(content_descriptor_tag::Column::CdId.is_in(cd_chunk.to_vec()))
+                .filter(content_descriptor_tag::Column::TagId.is_in(tag_chunk.to_vec()))
+                .exec(trx)
+                .await?
+                .rows_affected;
         }
+    }
+    let graph = ImplicationGraph::load(trx).await?;
 
-        Ok(())
+    if !graph.is_empty() {
+        let implied_ids = graph.closure(tag_ids);
+        remove_unrequired_implications(trx, &graph, cd_ids, &implied_ids).await?;
     }
+    delete_orphans(trx).await?;
 
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn remove_mappings(&self, cd_ids: Vec<i64>, tag_ids: Vec<i64>) -> RepoResult<()> {
-        let trx = self.ctx.db.begin().await?;
-        content_descriptor_tag::Entity::delete_many()
-            .filter(content_descriptor_tag::Column::CdId.is_in(cd_ids))
-            .filter(content_descriptor_tag::Column::TagId.is_in(tag_ids))
-            .exec(&trx)
-            .await?;
-        delete_orphans(&trx).await?;
+    Ok(removed)
+}
 
-        trx.commit().await?;
+/// Adds the missing mappings between the contents and the tags and the tags implied by them
+pub(crate) async fn upsert_mappings(
+    trx: &DatabaseTransaction,
+    cd_ids: Vec<i64>,
+    tag_ids: Vec<i64>,
+) -> RepoResult<()> {
+    let implied_ids: Vec<i64> = ImplicationGraph::load(trx)
+        .await?
+        .closure(tag_ids.clone())
+        .into_iter()
+        .filter(|id| !tag_ids.contains(id))
+        .collect();
 
-        Ok(())
+    for (tag_ids, implied) in [(tag_ids, false), (implied_ids, true)] {
+        let mappings: Vec<(i64, i64)> = cd_ids
+            .iter()
+            .flat_map(|cd_id| tag_ids.iter().map(move |tag_id| (*cd_id, *tag_id)))
+            .collect();
+
+        for chunk in mappings.chunks(BATCH_SIZE) {
+            insert_missing_mappings(trx, chunk, implied).await?;
+        }
     }
+
+    Ok(())
 }
 
-async fn get_existing_mappings(
+/// Returns the number of existing mappings between the given contents and tags
+pub(crate) async fn count_mappings(
     trx: &DatabaseTransaction,
     cd_ids: &[i64],
     tag_ids: &[i64],
-) -> RepoResult<Vec<(i64, i64)>> {
-    let existing_mappings: Vec<(i64, i64)> = content_descriptor_tag::Entity::find()
-        .filter(content_descriptor_tag::Column::CdId.is_in(cd_ids.to_vec()))
-        .filter(content_descriptor_tag::Column::TagId.is_in(tag_ids.to_vec()))
-        .all(trx)
-        .await?
-        .into_iter()
-        .map(|model: content_descriptor_tag::Model| (model.cd_id, model.tag_id))
+) -> RepoResult<u64> {
+    let mut count = 0;
+
+    for cd_ids in cd_ids.chunks(BATCH_SIZE) {
+        count += content_descriptor_tag::Entity::find()
+            .filter(content_descriptor_tag::Column::CdId.is_in(cd_ids.to_vec()))
+            .filter(content_descriptor_tag::Column::TagId.is_in(tag_ids.to_vec()))
+            .count(trx)
+            .await? as u64;
+    }
+
+    Ok(count)
+}
+
+/// Inserts the given (cd_id, tag_id) mappings ignoring the ones that already exist.
+/// Explicitly inserted mappings replace existing implied mappings
+pub(crate) async fn insert_missing_mappings(
+    trx: &DatabaseTransaction,
+    mappings: &[(i64, i64)],
+    implied: bool,
+) -> RepoResult<()> {
+    let placeholders = (0..mappings.len())
+        .map(|i| format!("(${}, ${}, {})", i * 2 + 1, i * 2 + 2, implied))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let conflict_clause = if implied {
+        "DO NOTHING"
+    } else {
+        "DO UPDATE SET implied = FALSE"
+    };
+    let values: Vec<Value> = mappings
+        .iter()
+        .flat_map(|(cd_id, tag_id)| [(*cd_id).into(), (*tag_id).into()])
         .collect();
-    Ok(existing_mappings)
+
+    trx.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        &format!(
+            "INSERT INTO cd_tag_mappings (cd_id, tag_id, implied) VALUES {} ON CONFLICT (cd_id, tag_id) {}",
+            placeholders, conflict_clause
+        ),
+        values,
+    ))
+    .await?;
+
+    Ok(())
 }
 
-/// Deletes orphaned tag entries and namespaces from the database
+/// Deletes orphaned tag entries and namespaces from the database.
+/// Tags that are part of an implication or have aliases are kept so that
+/// the implication or aliases aren't lost
 async fn delete_orphans(trx: &DatabaseTransaction) -> RepoResult<()> {
     tag::Entity::delete_many()
         .filter(
@@ -84,6 +187,30 @@ async fn delete_orphans(trx: &DatabaseTransaction) -> RepoResult<()> {
                     .to_owned(),
             ),
         )
+        .filter(
+            tag::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(tag_implication::Column::TagId)
+                    .from(tag_implication::Entity)
+                    .to_owned(),
+            ),
+        )
+        .filter(
+            tag::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(tag_implication::Column::ImpliedTagId)
+                    .from(tag_implication::Entity)
+                    .to_owned(),
+            ),
+        )
+        .filter(
+            tag::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(tag_alias::Column::TagId)
+                    .from(tag_alias::Entity)
+                    .to_owned(),
+            ),
+        )
         .exec(trx)
         .await?;
 
@@ -102,3 +229,167 @@ async fn delete_orphans(trx: &DatabaseTransaction) -> RepoResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use crate::dao::DaoProvider;
+    use crate::dto::{AddFileDto, AddTagDto};
+    use crate::testing::fixtures::{add_file, test_repo};
+    use crate::testing::{in_memory_repo, temp_repo_path};
+
+    #[tokio::test]
+    async fn it_keeps_unmapped_tags_with_aliases() {
+        let root = temp_repo_path("orphans_with_aliases");
+        let repo = in_memory_repo(root.clone()).await.unwrap();
+        let now = Local::now().naive_local();
+        let file = repo
+            .file()
+            .add(AddFileDto {
+                content: vec![1, 2, 3],
+                mime_type: String::from("application/octet-stream"),
+                creation_time: now,
+                change_time: now,
+                name: None,
+                corrupt: false,
+                external_id: None,
+                generate_thumbnails: false,
+            })
+            .await
+            .unwrap();
+        let tag = repo
+            .tag()
+            .add_all(
+                vec![AddTagDto {
+                    namespace: None,
+                    name: String::from("cat"),
+                }],
+                &Default::default(),
+            )
+            .await
+            .unwrap()
+            .remove(0);
+        repo.tag()
+            .add_alias(
+                AddTagDto {
+                    namespace: None,
+                    name: String::from("kitty"),
+                },
+                tag.id(),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], vec![tag.id()])
+            .await
+            .unwrap();
+
+        repo.tag()
+            .remove_mappings(vec![file.cd_id()], vec![tag.id()])
+            .await
+            .unwrap();
+
+        let tags = repo.tag().all().await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name(), "cat");
+        let aliases = repo.tag().aliases_for(tag.id()).await.unwrap();
+        assert_eq!(aliases.len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_returns_the_existing_mappings() {
+        let (repo, root) = test_repo("existing_mappings").await;
+        let files = [
+            add_file(&repo, vec![1], "image/png", false).await,
+            add_file(&repo, vec![2], "image/png", false).await,
+        ];
+        let tag_ids: Vec<i64> = repo
+            .tag()
+            .add_all(
+                ["cat", "dog"]
+                    .iter()
+                    .map(|name| AddTagDto {
+                        namespace: None,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                &Default::default(),
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.id())
+            .collect();
+        repo.tag()
+            .upsert_mappings(vec![files[0].cd_id()], tag_ids.clone())
+            .await
+            .unwrap();
+        repo.tag()
+            .upsert_mappings(vec![files[1].cd_id()], vec![tag_ids[1]])
+            .await
+            .unwrap();
+
+        let cd_ids = [files[0].cd_id(), files[1].cd_id()];
+        let mut mappings = repo
+            .tag()
+            .existing_mappings(&cd_ids, &[tag_ids[0]])
+            .await
+            .unwrap();
+        assert_eq!(mappings, vec![(files[0].cd_id(), tag_ids[0])]);
+        mappings = repo
+            .tag()
+            .existing_mappings(&cd_ids, &tag_ids)
+            .await
+            .unwrap();
+        assert_eq!(mappings.len(), 3);
+        assert!(repo
+            .tag()
+            .existing_mappings(&cd_ids, &[])
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_removes_mappings_of_more_contents_than_the_variable_limit() {
+        let (repo, root) = test_repo("remove_many_mappings").await;
+        let file = add_file(&repo, vec![1], "image/png", false).await;
+        let tag_id = repo
+            .tag()
+            .add_all(
+                vec![AddTagDto {
+                    namespace: None,
+                    name: String::from("cat"),
+                }],
+                &Default::default(),
+            )
+            .await
+            .unwrap()[0]
+            .id();
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], vec![tag_id])
+            .await
+            .unwrap();
+
+        let mut cd_ids: Vec<i64> = (1_000_000..1_040_000).collect();
+        cd_ids.push(file.cd_id());
+        repo.tag()
+            .remove_mappings(cd_ids, vec![tag_id])
+            .await
+            .unwrap();
+        assert!(repo
+            .tag()
+            .tags_for_cd(file.cd_id())
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use sea_orm::prelude::*;
+use sea_orm::sea_query::{Expr, Query};
+use sea_orm::{
+    Condition, ConnectionTrait, DatabaseTransaction, DbBackend, Statement, TransactionTrait,
+};
+
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::settings::TagSettings;
+use mediarepo_database::entities::{
+    content_descriptor_tag, namespace, tag, tag_alias, tag_implication,
+};
+
+use crate::dao::tag::add::{add_or_get_all_namespaces, validate_tag};
+use crate::dao::tag::{map_tag_dto, TagDao};
+use crate::dto::{AddTagDto, NamespaceMergeDto, TagDto};
+
+impl TagDao {
+    /// Moves all tags of the namespace `from_id` into the namespace `into_id` and deletes
+    /// the emptied namespace. Tags that already exist in the target namespace are merged
+    /// by moving their mappings to the existing tag.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn merge_namespaces(
+        &self,
+        from_id: i64,
+        into_id: i64,
+    ) -> RepoResult<NamespaceMergeDto> {
+        if from_id == into_id {
+            return Err(RepoError::from("cannot merge a namespace into itself"));
+        }
+        let trx = self.ctx.db.begin().await?;
+        let namespace_count = namespace::Entity::find()
+            .filter(namespace::Column::Id.is_in(vec![from_id, into_id]))
+            .count(&trx)
+            .await?;
+
+        if namespace_count < 2 {
+            return Err(RepoError::from("namespace not found"));
+        }
+        let from_tags = tags_of_namespace(&trx, from_id).await?;
+        let into_tags: HashMap<String, i64> = tags_of_namespace(&trx, into_id)
+            .await?
+            .into_iter()
+            .map(|t| (t.name, t.id))
+            .collect();
+
+        let mut moved_tags = Vec::new();
+        let mut merged_tags = HashMap::new();
+
+        for from_tag in from_tags {
+            if let Some(into_tag_id) = into_tags.get(&from_tag.name) {
+                merge_tag_mappings(&trx, from_tag.id, *into_tag_id).await?;
+                merged_tags.insert(from_tag.id, *into_tag_id);
+            } else {
+                moved_tags.push(from_tag.id);
+            }
+        }
+
+        if !merged_tags.is_empty() {
+            tag::Entity::delete_many()
+                .filter(tag::Column::Id.is_in(merged_tags.keys().cloned().collect::<Vec<i64>>()))
+                .exec(&trx)
+                .await?;
+        }
+        if !moved_tags.is_empty() {
+            tag::Entity::update_many()
+                .col_expr(tag::Column::NamespaceId, Expr::value(into_id))
+                .filter(tag::Column::Id.is_in(moved_tags.clone()))
+                .exec(&trx)
+                .await?;
+        }
+        namespace::Entity::delete_many()
+            .filter(namespace::Column::Id.eq(from_id))
+            .exec(&trx)
+            .await?;
+
+        trx.commit().await?;
+
+        Ok(NamespaceMergeDto::new(moved_tags, merged_tags))
+    }
+
+    /// Merges the tags `from_ids` into the tag `into_id` by moving their mappings
+    /// to the target tag and deleting the merged tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn merge_tags(&self, from_ids: Vec<i64>, into_id: i64) -> RepoResult<TagDto> {
+        if from_ids.contains(&into_id) {
+            return Err(RepoError::from("cannot merge a tag into itself"));
+        }
+        let trx = self.ctx.db.begin().await?;
+        let into_tag = tag::Entity::find_by_id(into_id)
+            .find_also_related(namespace::Entity)
+            .one(&trx)
+            .await?
+            .map(map_tag_dto)
+            .ok_or_else(|| RepoError::from("tag not found"))?;
+
+        for from_id in &from_ids {
+            merge_tag_mappings(&trx, *from_id, into_id).await?;
+        }
+        tag::Entity::delete_many()
+            .filter(tag::Column::Id.is_in(from_ids))
+            .exec(&trx)
+            .await?;
+        // the namespaces of merged tags might not contain any tags anymore
+        delete_empty_namespaces(&trx).await?;
+        trx.commit().await?;
+
+        Ok(into_tag)
+    }
+
+    /// Moves the tag into the given namespace and creates the namespace if it doesn't exist.
+    /// The name of the tag is validated like the names of added tags.
+    /// If the tag already exists in the namespace, the mappings are moved to the existing tag
+    /// and the converted tag is deleted. The previous namespace is deleted if it became empty.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn convert_to_namespaced(
+        &self,
+        tag_id: i64,
+        namespace: String,
+        settings: &TagSettings,
+    ) -> RepoResult<TagDto> {
+        let trx = self.ctx.db.begin().await?;
+        let tag = tag::Entity::find_by_id(tag_id)
+            .one(&trx)
+            .await?
+            .ok_or_else(|| RepoError::from("tag not found"))?;
+        let target = validate_tag(
+            AddTagDto {
+                namespace: Some(namespace),
+                name: tag.name.clone(),
+            },
+            settings,
+        )?;
+        let namespace_name = target.namespace.unwrap();
+        let namespace = add_or_get_all_namespaces(&trx, vec![namespace_name.clone()])
+            .await?
+            .remove(&namespace_name)
+            .ok_or_else(|| RepoError::from("failed to create namespace"))?;
+        let existing_tag = tag::Entity::find()
+            .filter(tag::Column::NamespaceId.eq(namespace.id()))
+            .filter(tag::Column::Name.eq(target.name.clone()))
+            .one(&trx)
+            .await?;
+
+        let converted_id = match existing_tag {
+            Some(existing) if existing.id == tag.id => tag.id,
+            Some(existing) => {
+                merge_tag_mappings(&trx, tag.id, existing.id).await?;
+                tag::Entity::delete_many()
+                    .filter(tag::Column::Id.eq(tag.id))
+                    .exec(&trx)
+                    .await?;
+                existing.id
+            }
+            None => {
+                tag::Entity::update_many()
+                    .col_expr(tag::Column::NamespaceId, Expr::value(namespace.id()))
+                    .col_expr(tag::Column::Name, Expr::value(target.name))
+                    .filter(tag::Column::Id.eq(tag.id))
+                    .exec(&trx)
+                    .await?;
+                tag.id
+            }
+        };
+        // the previous namespace of the tag might not contain any tags anymore
+        delete_empty_namespaces(&trx).await?;
+        let converted_tag = tag::Entity::find_by_id(converted_id)
+            .find_also_related(namespace::Entity)
+            .one(&trx)
+            .await?
+            .map(map_tag_dto)
+            .ok_or_else(|| RepoError::from("tag not found"))?;
+        trx.commit().await?;
+
+        Ok(converted_tag)
+    }
+}
+
+async fn tags_of_namespace(
+    trx: &DatabaseTransaction,
+    namespace_id: i64,
+) -> RepoResult<Vec<tag::Model>> {
+    let tags = tag::Entity::find()
+        .filter(tag::Column::NamespaceId.eq(namespace_id))
+        .all(trx)
+        .await?;
+
+    Ok(tags)
+}
+
+/// Deletes all namespaces that don't contain any tags
+pub(crate) async fn delete_empty_namespaces(trx: &DatabaseTransaction) -> RepoResult<()> {
+    namespace::Entity::delete_many()
+        .filter(
+            namespace::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(tag::Column::NamespaceId)
+                    .from(tag::Entity)
+                    .and_where(tag::Column::NamespaceId.is_not_null())
+                    .to_owned(),
+            ),
+        )
+        .exec(trx)
+        .await?;
+
+    Ok(())
+}
+
+/// Moves all mappings, aliases and implications of one tag to another tag.
+/// Mappings that already exist for the target tag are dropped
+pub(crate) async fn merge_tag_mappings(
+    trx: &DatabaseTransaction,
+    from_id: i64,
+    into_id: i64,
+) -> RepoResult<()> {
+    // explicit mappings of the merged tag stay explicit for the target tag
+    trx.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "UPDATE cd_tag_mappings SET implied = FALSE WHERE tag_id = $1 AND cd_id IN \
+        (SELECT cd_id FROM cd_tag_mappings WHERE tag_id = $2 AND implied = FALSE)",
+        vec![into_id.into(), from_id.into()],
+    ))
+    .await?;
+    trx.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "UPDATE OR IGNORE cd_tag_mappings SET tag_id = $1 WHERE tag_id = $2",
+        vec![into_id.into(), from_id.into()],
+    ))
+    .await?;
+    content_descriptor_tag::Entity::delete_many()
+        .filter(content_descriptor_tag::Column::TagId.eq(from_id))
+        .exec(trx)
+        .await?;
+    tag_alias::Entity::update_many()
+        .col_expr(tag_alias::Column::TagId, Expr::value(into_id))
+        .filter(tag_alias::Column::TagId.eq(from_id))
+        .exec(trx)
+        .await?;
+    trx.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "UPDATE OR IGNORE tag_implications SET tag_id = $1 WHERE tag_id = $2",
+        vec![into_id.into(), from_id.into()],
+    ))
+    .await?;
+    trx.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "UPDATE OR IGNORE tag_implications SET implied_tag_id = $1 WHERE implied_tag_id = $2",
+        vec![into_id.into(), from_id.into()],
+    ))
+    .await?;
+    tag_implication::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(tag_implication::Column::TagId.eq(from_id))
+                .add(tag_implication::Column::ImpliedTagId.eq(from_id))
+                .add(
+                    Condition::all()
+                        .add(tag_implication::Column::TagId.eq(into_id))
+                        .add(tag_implication::Column::ImpliedTagId.eq(into_id)),
+                ),
+        )
+        .exec(trx)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mediarepo_core::settings::TagSettings;
+
+    use crate::dao::DaoProvider;
+    use crate::dto::AddTagDto;
+    use crate::testing::fixtures::{add_file, test_repo};
+
+    #[tokio::test]
+    async fn it_converts_tags_to_namespaced_tags_with_trimmed_names() {
+        let (repo, root) = test_repo("convert_to_namespaced").await;
+        let untrimmed_settings = TagSettings {
+            trim_whitespace: false,
+            ..Default::default()
+        };
+        let tag = |namespace: Option<&str>, name: &str| AddTagDto {
+            namespace: namespace.map(String::from),
+            name: name.to_string(),
+        };
+        let tags = repo
+            .tag()
+            .add_all(
+                vec![tag(Some("old"), " someone "), tag(None, "someone")],
+                &untrimmed_settings,
+            )
+            .await
+            .unwrap();
+        let file = add_file(&repo, vec![1], "image/png", false).await;
+        repo.add_tags_to_files(vec![file.id()], vec![tags[1].id()])
+            .await
+            .unwrap();
+
+        let converted = repo
+            .tag()
+            .convert_to_namespaced(tags[0].id(), String::from(" creator "), &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(converted.id(), tags[0].id());
+        assert_eq!(converted.normalized_name(), "creator:someone");
+        let namespaces = repo.tag().all_namespaces().await.unwrap();
+        assert_eq!(namespaces.len(), 1);
+        assert_eq!(namespaces[0].name(), "creator");
+
+        let merged = repo
+            .tag()
+            .convert_to_namespaced(tags[1].id(), String::from("creator"), &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(merged.id(), converted.id());
+        let file_tags = repo.tag().tags_for_cd(file.cd_id()).await.unwrap();
+        assert_eq!(file_tags.len(), 1);
+        assert_eq!(file_tags[0].id(), converted.id());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use sea_orm::prelude::*;
 use sea_orm::JoinType;
-use sea_orm::QuerySelect;
+use sea_orm::{QueryOrder, QuerySelect};
 use std::collections::HashMap;
 use std::iter::FromIterator;
 
@@ -14,10 +14,16 @@ use crate::dao_provider;
 use crate::dto::{NamespaceDto, TagDto};
 
 pub mod add;
+pub mod aggregate;
+pub mod alias;
 pub mod all_for_cds_map;
 pub mod by_name;
 pub mod cdids_with_namespaced_tags;
+pub mod facets;
+pub mod implication;
 pub mod mappings;
+pub mod merge;
+pub mod similar;
 
 dao_provider!(TagDao);
 
@@ -35,6 +41,20 @@ impl TagDao {
         Ok(tags)
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn all_by_ids(&self, ids: Vec<i64>) -> RepoResult<Vec<TagDto>> {
+        let tags = tag::Entity::find()
+            .find_also_related(namespace::Entity)
+            .filter(tag::Column::Id.is_in(ids))
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(map_tag_dto)
+            .collect();
+
+        Ok(tags)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn all_namespaces(&self) -> RepoResult<Vec<NamespaceDto>> {
         let namespaces = namespace::Entity::find()
@@ -92,22 +112,68 @@ impl TagDao {
         Ok(tags)
     }
 
+    /// Returns a page of the tags of a content descriptor ordered by namespace and name
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn tags_for_cd_paginated(
+        &self,
+        cd_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> RepoResult<Vec<TagDto>> {
+        let tags = tag::Entity::find()
+            .find_also_related(namespace::Entity)
+            .join(
+                JoinType::InnerJoin,
+                content_descriptor_tag::Relation::Tag.def().rev(),
+            )
+            .filter(content_descriptor_tag::Column::CdId.eq(cd_id))
+            .order_by_asc(namespace::Column::Name)
+            .order_by_asc(tag::Column::Name)
+            .order_by_asc(tag::Column::Id)
+            .offset(offset)
+            .limit(limit)
+            .all(&self.ctx.db)
+            .await?
+            .into_iter()
+            .map(map_tag_dto)
+            .collect();
+
+        Ok(tags)
+    }
+
+    /// Returns the number of tags assigned to a content descriptor
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn tag_count_for_cd(&self, cd_id: i64) -> RepoResult<u64> {
+        let count = content_descriptor_tag::Entity::find()
+            .filter(content_descriptor_tag::Column::CdId.eq(cd_id))
+            .count(&self.ctx.db)
+            .await?;
+
+        Ok(count as u64)
+    }
+
     /// Returns a map mapping tag names to ids
+    /// The names are lowercased unless `case_sensitive` is set
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn normalized_tags_to_ids(
         &self,
         names: Vec<String>,
+        case_sensitive: bool,
     ) -> RepoResult<HashMap<String, i64>> {
         let queries = names
             .into_iter()
-            .map(parse_namespace_and_tag)
+            .map(|name| parse_namespace_and_tag(name, case_sensitive))
             .map(|(namespace, name)| TagByNameQuery { namespace, name })
             .collect();
-        let tags = self.all_by_name(queries).await?;
-        let tag_map = HashMap::from_iter(
-            tags.into_iter()
-                .map(|tag| (tag.normalized_name(), tag.id())),
-        );
+        let tags = self.all_by_name(queries, case_sensitive).await?;
+        let tag_map = HashMap::from_iter(tags.into_iter().map(|tag| {
+            let name = if case_sensitive {
+                tag.normalized_name()
+            } else {
+                tag.normalized_name().to_lowercase()
+            };
+            (name, tag.id())
+        }));
 
         Ok(tag_map)
     }
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use crate::dao::tag::TagDao;
+use crate::dto::TagDto;
+use mediarepo_core::error::RepoResult;
+
+impl TagDao {
+    /// Returns groups of tags that only differ in their casing, their whitespace
+    /// or the use of underscores instead of spaces. Every group contains at least two tags
+    /// that are candidates for merging them into one
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_similar_tags(&self) -> RepoResult<Vec<Vec<TagDto>>> {
+        let mut groups: BTreeMap<(Option<String>, String), Vec<TagDto>> = BTreeMap::new();
+
+        for tag in self.all().await? {
+            let key = (
+                tag.namespace().map(|n| similarity_key(n.name())),
+                similarity_key(tag.name()),
+            );
+            groups.entry(key).or_default().push(tag);
+        }
+        let similar_tags = groups
+            .into_values()
+            .filter(|tags| tags.len() > 1)
+            .map(|mut tags| {
+                tags.sort_by_key(|t| t.id());
+                tags
+            })
+            .collect();
+
+        Ok(similar_tags)
+    }
+}
+
+/// Normalizes the name so that names differing only in casing, whitespace
+/// and underscores have the same key
+fn similarity_key(name: &str) -> String {
+    name.to_lowercase()
+        .replace('_', " ")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
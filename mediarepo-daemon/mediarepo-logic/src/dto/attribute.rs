@@ -0,0 +1,68 @@
+use mediarepo_database::entities::{attribute_field, file_attribute};
+
+pub use mediarepo_database::entities::attribute_field::AttributeValueType;
+
+#[derive(Clone, Debug)]
+pub struct AttributeFieldDto {
+    model: attribute_field::Model,
+}
+
+impl AttributeFieldDto {
+    pub(crate) fn new(model: attribute_field::Model) -> Self {
+        Self { model }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.model.id
+    }
+
+    pub fn name(&self) -> &String {
+        &self.model.name
+    }
+
+    pub fn value_type(&self) -> AttributeValueType {
+        self.model.value_type
+    }
+}
+
+/// The value of a custom attribute field assigned to a file
+#[derive(Clone, Debug)]
+pub struct FileAttributeDto {
+    model: file_attribute::Model,
+    field: AttributeFieldDto,
+}
+
+impl FileAttributeDto {
+    pub(crate) fn new(model: file_attribute::Model, field: attribute_field::Model) -> Self {
+        Self {
+            model,
+            field: AttributeFieldDto::new(field),
+        }
+    }
+
+    pub fn file_id(&self) -> i64 {
+        self.model.file_id
+    }
+
+    pub fn field(&self) -> &AttributeFieldDto {
+        &self.field
+    }
+
+    /// Returns the value in the normalized text form of its type
+    pub fn value(&self) -> &String {
+        &self.model.value
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AddAttributeFieldDto {
+    pub name: String,
+    pub value_type: AttributeValueType,
+}
+
+#[derive(Clone, Debug)]
+pub struct UpdateAttributeFieldDto {
+    pub id: i64,
+    pub name: Option<String>,
+    pub value_type: Option<AttributeValueType>,
+}
@@ -45,18 +45,26 @@ impl FileDto {
     }
 
     pub fn status(&self) -> FileStatus {
-        match self.model.status {
-            10 => FileStatus::Imported,
-            20 => FileStatus::Archived,
-            30 => FileStatus::Deleted,
-            _ => FileStatus::Imported,
-        }
+        FileStatus::from_value(self.model.status)
     }
 
     pub fn mime_type(&self) -> &String {
         &self.model.mime_type
     }
 
+    pub fn corrupt(&self) -> bool {
+        self.model.corrupt
+    }
+
+    /// Files that aren't indexed are excluded from searches
+    pub fn indexed(&self) -> bool {
+        self.model.indexed
+    }
+
+    pub fn external_id(&self) -> Option<&String> {
+        self.model.external_id.as_ref()
+    }
+
     pub fn metadata(&self) -> Option<&FileMetadataDto> {
         self.metadata.as_ref()
     }
@@ -66,6 +74,97 @@ impl FileDto {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct FileStorageInfoDto {
+    storage: String,
+    hash_algorithm: String,
+    cd: Vec<u8>,
+    size: u64,
+}
+
+impl FileStorageInfoDto {
+    pub(crate) fn new(storage: String, hash_algorithm: String, cd: Vec<u8>, size: u64) -> Self {
+        Self {
+            storage,
+            hash_algorithm,
+            cd,
+            size,
+        }
+    }
+
+    /// The name of the storage the file is stored in
+    pub fn storage(&self) -> &String {
+        &self.storage
+    }
+
+    /// The name of the hashing algorithm used for the content descriptor
+    pub fn hash_algorithm(&self) -> &String {
+        &self.hash_algorithm
+    }
+
+    pub fn cd(&self) -> &[u8] {
+        &self.cd
+    }
+
+    /// The size of the stored blob in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// What is removed when a file is deleted
+#[derive(Clone, Debug)]
+pub struct FileDeletionDto {
+    file_id: i64,
+    content_removed: bool,
+    tag_mappings: u64,
+    thumbnails: u64,
+    freed_bytes: u64,
+}
+
+impl FileDeletionDto {
+    pub(crate) fn new(
+        file_id: i64,
+        content_removed: bool,
+        tag_mappings: u64,
+        thumbnails: u64,
+        freed_bytes: u64,
+    ) -> Self {
+        Self {
+            file_id,
+            content_removed,
+            tag_mappings,
+            thumbnails,
+            freed_bytes,
+        }
+    }
+
+    pub fn file_id(&self) -> i64 {
+        self.file_id
+    }
+
+    /// If the content with its tags, thumbnails and stored blob is removed.
+    /// Contents that are shared with other files are kept
+    pub fn content_removed(&self) -> bool {
+        self.content_removed
+    }
+
+    /// The number of tags that are removed from the content
+    pub fn tag_mappings(&self) -> u64 {
+        self.tag_mappings
+    }
+
+    /// The number of thumbnails that are removed
+    pub fn thumbnails(&self) -> u64 {
+        self.thumbnails
+    }
+
+    /// The size of the removed blob in bytes
+    pub fn freed_bytes(&self) -> u64 {
+        self.freed_bytes
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct AddFileDto {
     pub content: Vec<u8>,
@@ -73,6 +172,50 @@ pub struct AddFileDto {
     pub creation_time: NaiveDateTime,
     pub change_time: NaiveDateTime,
     pub name: Option<String>,
+    pub corrupt: bool,
+    pub external_id: Option<String>,
+    pub generate_thumbnails: bool,
+}
+
+impl AddFileDto {
+    /// Splits the dto into the content and the properties of the file
+    pub(crate) fn into_parts(self) -> (Vec<u8>, AddFileInfoDto) {
+        let info = AddFileInfoDto {
+            mime_type: self.mime_type,
+            creation_time: self.creation_time,
+            change_time: self.change_time,
+            name: self.name,
+            corrupt: self.corrupt,
+            external_id: self.external_id,
+            generate_thumbnails: self.generate_thumbnails,
+        };
+
+        (self.content, info)
+    }
+}
+
+/// The properties of a file that is added by streaming its content from a reader
+#[derive(Clone, Debug)]
+pub struct AddFileInfoDto {
+    pub mime_type: String,
+    pub creation_time: NaiveDateTime,
+    pub change_time: NaiveDateTime,
+    pub name: Option<String>,
+    pub corrupt: bool,
+    pub external_id: Option<String>,
+    pub generate_thumbnails: bool,
+}
+
+/// The metadata a file would be imported with
+#[derive(Clone, Debug)]
+pub struct ProbedFileDto {
+    pub name: Option<String>,
+    pub mime_type: String,
+    pub size: u64,
+    pub location: Option<(f64, f64)>,
+    pub dimensions: Option<(u32, u32)>,
+    /// Pairs of exif tag names and their values
+    pub exif: Vec<(String, String)>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -81,6 +224,9 @@ pub struct UpdateFileDto {
     pub cd_id: Option<i64>,
     pub mime_type: Option<String>,
     pub status: Option<FileStatus>,
+    pub corrupt: Option<bool>,
+    pub external_id: Option<Option<String>>,
+    pub indexed: Option<bool>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -90,6 +236,24 @@ pub enum FileStatus {
     Deleted = 30,
 }
 
+impl FileStatus {
+    /// Converts the stored value of the status. Unknown values are treated as imported
+    pub(crate) fn from_value(value: i32) -> Self {
+        match value {
+            10 => FileStatus::Imported,
+            20 => FileStatus::Archived,
+            30 => FileStatus::Deleted,
+            _ => FileStatus::Imported,
+        }
+    }
+
+    /// Returns if a file with the status may get the new status.
+    /// Deleted files only get another status when they are undeleted explicitly
+    pub fn can_change_to(self, status: FileStatus, undelete: bool) -> bool {
+        !matches!(self, FileStatus::Deleted) || matches!(status, FileStatus::Deleted) || undelete
+    }
+}
+
 impl From<ApiFileStatus> for FileStatus {
     fn from(s: ApiFileStatus) -> Self {
         match s {
@@ -0,0 +1,44 @@
+use chrono::NaiveDateTime;
+
+use mediarepo_database::entities::file_exif;
+
+/// The capture metadata read from the exif data of an image when it was imported
+#[derive(Clone, Debug)]
+pub struct FileExifDto {
+    model: file_exif::Model,
+}
+
+impl FileExifDto {
+    pub(crate) fn new(model: file_exif::Model) -> Self {
+        Self { model }
+    }
+
+    pub fn file_id(&self) -> i64 {
+        self.model.file_id
+    }
+
+    pub fn camera_make(&self) -> Option<&String> {
+        self.model.camera_make.as_ref()
+    }
+
+    pub fn camera_model(&self) -> Option<&String> {
+        self.model.camera_model.as_ref()
+    }
+
+    pub fn capture_time(&self) -> Option<NaiveDateTime> {
+        self.model.capture_time
+    }
+
+    pub fn latitude(&self) -> Option<f64> {
+        self.model.latitude
+    }
+
+    pub fn longitude(&self) -> Option<f64> {
+        self.model.longitude
+    }
+
+    /// The exif orientation from 1 to 8
+    pub fn orientation(&self) -> Option<u32> {
+        self.model.orientation.map(|o| o as u32)
+    }
+}
@@ -1,5 +1,6 @@
 use chrono::NaiveDateTime;
 
+use mediarepo_core::mediarepo_api::types::files::FileMetadataField as ApiFileMetadataField;
 use mediarepo_database::entities::file_metadata;
 
 #[derive(Clone, Debug)]
@@ -39,6 +40,55 @@ impl FileMetadataDto {
     pub fn change_time(&self) -> NaiveDateTime {
         self.model.change_time
     }
+
+    pub fn view_count(&self) -> i64 {
+        self.model.view_count
+    }
+
+    pub fn access_time(&self) -> Option<NaiveDateTime> {
+        self.model.access_time
+    }
+
+    pub fn latitude(&self) -> Option<f64> {
+        self.model.latitude
+    }
+
+    pub fn longitude(&self) -> Option<f64> {
+        self.model.longitude
+    }
+
+    pub fn width(&self) -> Option<i64> {
+        self.model.width
+    }
+
+    pub fn height(&self) -> Option<i64> {
+        self.model.height
+    }
+
+    /// The perceptual hash of the image used to find similar images
+    pub fn phash(&self) -> Option<u64> {
+        self.model.phash.map(|phash| phash as u64)
+    }
+}
+
+/// A metadata field that can be missing on a file
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MetadataField {
+    Name,
+    Dimensions,
+    Size,
+    MimeType,
+}
+
+impl From<ApiFileMetadataField> for MetadataField {
+    fn from(f: ApiFileMetadataField) -> Self {
+        match f {
+            ApiFileMetadataField::Name => Self::Name,
+            ApiFileMetadataField::Dimensions => Self::Dimensions,
+            ApiFileMetadataField::Size => Self::Size,
+            ApiFileMetadataField::MimeType => Self::MimeType,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
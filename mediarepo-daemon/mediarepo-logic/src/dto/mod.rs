@@ -1,4 +1,6 @@
+pub use attribute::*;
 pub use file::*;
+pub use file_exif::*;
 pub use file_metadata::*;
 pub use job_state::*;
 pub use namespace::*;
@@ -6,7 +8,9 @@ pub use sorting_preset::*;
 pub use tag::*;
 pub use thumbnail::*;
 
+mod attribute;
 mod file;
+mod file_exif;
 mod file_metadata;
 mod job_state;
 mod namespace;
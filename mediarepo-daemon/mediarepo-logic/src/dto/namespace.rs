@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use mediarepo_database::entities::namespace;
 
 #[derive(Clone, Debug)]
@@ -18,3 +20,29 @@ impl NamespaceDto {
         &self.model.name
     }
 }
+
+/// The result of merging one namespace into another
+#[derive(Clone, Debug)]
+pub struct NamespaceMergeDto {
+    moved_tags: Vec<i64>,
+    merged_tags: HashMap<i64, i64>,
+}
+
+impl NamespaceMergeDto {
+    pub(crate) fn new(moved_tags: Vec<i64>, merged_tags: HashMap<i64, i64>) -> Self {
+        Self {
+            moved_tags,
+            merged_tags,
+        }
+    }
+
+    /// The ids of tags that were moved into the target namespace
+    pub fn moved_tags(&self) -> &Vec<i64> {
+        &self.moved_tags
+    }
+
+    /// Maps the ids of deleted tags to the ids of the tags they were merged into
+    pub fn merged_tags(&self) -> &HashMap<i64, i64> {
+        &self.merged_tags
+    }
+}
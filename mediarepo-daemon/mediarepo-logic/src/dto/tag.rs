@@ -1,5 +1,6 @@
 pub use mediarepo_database::entities::namespace;
 pub use mediarepo_database::entities::tag;
+use mediarepo_database::entities::tag_alias;
 
 use crate::dto::NamespaceDto;
 
@@ -60,3 +61,29 @@ impl AddTagDto {
         }
     }
 }
+
+/// An alternative name that resolves to a tag
+#[derive(Clone, Debug)]
+pub struct TagAliasDto {
+    model: tag_alias::Model,
+}
+
+impl TagAliasDto {
+    pub(crate) fn new(model: tag_alias::Model) -> Self {
+        Self { model }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.model.id
+    }
+
+    /// Returns the normalized name of the alias (namespace:tag)
+    pub fn name(&self) -> &String {
+        &self.model.name
+    }
+
+    /// Returns the id of the tag the alias resolves to
+    pub fn tag_id(&self) -> i64 {
+        self.model.tag_id
+    }
+}
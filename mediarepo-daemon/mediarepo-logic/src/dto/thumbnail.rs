@@ -5,22 +5,30 @@ use tokio::fs::{File, OpenOptions};
 use tokio::io::BufReader;
 
 use mediarepo_core::error::RepoResult;
-use mediarepo_core::fs::thumbnail_store::Dimensions;
+use mediarepo_core::fs::thumbnail_store::{Dimensions, ThumbnailCrop};
 
 #[derive(Clone, Debug)]
 pub struct ThumbnailDto {
     path: PathBuf,
     parent_cd: String,
     size: Dimensions,
+    crop: ThumbnailCrop,
     mime_type: String,
 }
 
 impl ThumbnailDto {
-    pub fn new(path: PathBuf, parent_cd: String, size: Dimensions, mime_type: String) -> Self {
+    pub fn new(
+        path: PathBuf,
+        parent_cd: String,
+        size: Dimensions,
+        crop: ThumbnailCrop,
+        mime_type: String,
+    ) -> Self {
         Self {
             path,
             parent_cd,
             size,
+            crop,
             mime_type,
         }
     }
@@ -33,10 +41,22 @@ impl ThumbnailDto {
         &self.size
     }
 
+    pub fn crop(&self) -> ThumbnailCrop {
+        self.crop
+    }
+
     pub fn mime_type(&self) -> &String {
         &self.mime_type
     }
 
+    /// Returns the size of the thumbnail file in bytes
+    #[tracing::instrument(level = "debug")]
+    pub async fn byte_size(&self) -> RepoResult<u64> {
+        let metadata = fs::metadata(&self.path).await?;
+
+        Ok(metadata.len())
+    }
+
     #[tracing::instrument(level = "debug")]
     pub async fn get_reader(&self) -> RepoResult<BufReader<File>> {
         let file = OpenOptions::new().read(true).open(&self.path).await?;
@@ -1,3 +1,5 @@
 pub mod dao;
 pub mod dto;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod type_keys;
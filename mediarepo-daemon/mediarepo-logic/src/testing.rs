@@ -0,0 +1,125 @@
+//! Helpers to set up repositories for tests
+
+use std::path::PathBuf;
+
+use chrono::Local;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use tokio::fs;
+
+use mediarepo_core::error::RepoResult;
+
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+use crate::dto::{AddFileDto, AddTagDto, FileDto};
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+/// The number of distinct tags the seeded files choose their tags from
+const TAG_POOL_SIZE: usize = 100;
+
+/// Creates a repository with an in-memory database.
+/// Files and thumbnails are stored in the given directory
+pub async fn in_memory_repo(storage_path: PathBuf) -> RepoResult<Repo> {
+    let file_store_path = storage_path.join("files");
+    let thumb_store_path = storage_path.join("thumbnails");
+    fs::create_dir_all(&file_store_path).await?;
+    fs::create_dir_all(&thumb_store_path).await?;
+
+    Repo::connect(
+        ":memory:",
+        &Default::default(),
+        file_store_path,
+        thumb_store_path,
+    )
+    .await
+}
+
+/// Inserts `file_count` files with random contents into the repository
+/// and assigns `tags_per_file` random tags to each of them
+pub async fn seed(
+    repo: &Repo,
+    file_count: usize,
+    tags_per_file: usize,
+) -> RepoResult<Vec<FileDto>> {
+    let mut rng = StdRng::from_entropy();
+    let tags = repo
+        .tag()
+        .add_all(
+            (0..TAG_POOL_SIZE.max(tags_per_file))
+                .map(|i| AddTagDto {
+                    namespace: (i % 3 == 0).then(|| format!("namespace{}", i % 10)),
+                    name: format!("tag{}", i),
+                })
+                .collect(),
+            &Default::default(),
+        )
+        .await?;
+    let tag_ids: Vec<i64> = tags.into_iter().map(|t| t.id()).collect();
+    let mut files = Vec::with_capacity(file_count);
+
+    for i in 0..file_count {
+        let mut content = (i as u64).to_be_bytes().to_vec();
+        content.extend((0..rng.gen_range(32..1024)).map(|_| rng.gen::<u8>()));
+        let now = Local::now().naive_local();
+
+        let file = repo
+            .file()
+            .add(AddFileDto {
+                content,
+                mime_type: String::from("application/octet-stream"),
+                creation_time: now,
+                change_time: now,
+                name: Some(format!("file{}", i)),
+                corrupt: false,
+                external_id: None,
+                generate_thumbnails: false,
+            })
+            .await?;
+        let file_tag_ids = tag_ids
+            .choose_multiple(&mut rng, tags_per_file)
+            .cloned()
+            .collect();
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], file_tag_ids)
+            .await?;
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+/// Returns a new directory in the temp dir of the system that is unique
+/// for the given name and the running process
+pub fn temp_repo_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("mediarepo-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&path).unwrap();
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_seeds_files_with_tags() {
+        let root = temp_repo_path("seed");
+        let repo = in_memory_repo(root.clone()).await.unwrap();
+
+        let files = seed(&repo, 20, 5).await.unwrap();
+        assert_eq!(files.len(), 20);
+        assert_eq!(repo.file().all().await.unwrap().len(), 20);
+        assert_eq!(repo.tag().all().await.unwrap().len(), TAG_POOL_SIZE);
+
+        let cds: Vec<Vec<u8>> = files.iter().map(|f| f.cd().to_vec()).collect();
+        let mappings = repo.tag().all_for_cds_map(cds.clone()).await.unwrap();
+        assert!(cds
+            .iter()
+            .all(|cd| mappings.get(cd).map(|t| t.len()) == Some(5)));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
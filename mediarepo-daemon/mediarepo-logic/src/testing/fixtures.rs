@@ -0,0 +1,96 @@
+//! Fixtures shared by the unit tests of the crate
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use sea_orm::ActiveModelTrait;
+use sea_orm::ActiveValue::Set;
+use sea_orm::TransactionTrait;
+
+use mediarepo_core::image::{DynamicImage, ImageOutputFormat};
+use mediarepo_database::entities::file;
+
+use crate::dao::file::add::add_file_metadata;
+use crate::dao::repo::Repo;
+use crate::dao::DaoProvider;
+use crate::dto::{AddFileDto, FileDto};
+use crate::testing::{in_memory_repo, temp_repo_path};
+
+/// Creates an in-memory repository storing its files in a new temp directory
+/// and returns it together with the directory
+pub(crate) async fn test_repo(name: &str) -> (Repo, PathBuf) {
+    let root = temp_repo_path(name);
+    let repo = in_memory_repo(root.clone()).await.unwrap();
+
+    (repo, root)
+}
+
+/// Connects to a repository with the database file of the given name in the directory.
+/// Needed by tests for sqlite operations that behave differently on in-memory databases
+pub(crate) async fn file_repo(root: &Path, db_name: &str) -> Repo {
+    Repo::connect(
+        format!("sqlite://{}", root.join(db_name).to_string_lossy()),
+        &Default::default(),
+        root.join("files"),
+        root.join("thumbnails"),
+    )
+    .await
+    .unwrap()
+}
+
+/// Adds a file with the given content that was created and changed just now
+pub(crate) async fn add_file(
+    repo: &Repo,
+    content: Vec<u8>,
+    mime_type: &str,
+    generate_thumbnails: bool,
+) -> FileDto {
+    let now = Local::now().naive_local();
+
+    repo.file()
+        .add(AddFileDto {
+            content,
+            mime_type: mime_type.to_string(),
+            creation_time: now,
+            change_time: now,
+            name: None,
+            corrupt: false,
+            external_id: None,
+            generate_thumbnails,
+        })
+        .await
+        .unwrap()
+}
+
+/// Adds a second file entry for the content of the given file.
+/// The import deduplicates contents so this can only happen on older repositories
+pub(crate) async fn add_file_sharing_content(repo: &Repo, shared: &FileDto) -> FileDto {
+    let trx = repo.db().begin().await.unwrap();
+    let duplicate = file::ActiveModel {
+        cd_id: Set(shared.cd_id()),
+        mime_type: Set(shared.mime_type().to_owned()),
+        ..Default::default()
+    }
+    .insert(&trx)
+    .await
+    .unwrap();
+    let size = repo.file().get_bytes(shared.cd()).await.unwrap().len() as i64;
+    let now = Local::now().naive_local();
+    add_file_metadata(&trx, duplicate.id, size, now, now, None, Default::default())
+        .await
+        .unwrap();
+    trx.commit().await.unwrap();
+
+    repo.file().by_id(duplicate.id).await.unwrap().unwrap()
+}
+
+/// Creates a black png image with the given dimensions
+pub(crate) fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+    let mut buf = Cursor::new(Vec::new());
+    DynamicImage::new_rgb8(width, height)
+        .write_to(&mut buf, ImageOutputFormat::Png)
+        .unwrap();
+
+    buf.into_inner()
+}
@@ -1,14 +1,33 @@
+use mediarepo_core::content_descriptor::encode_content_descriptor_as;
+use mediarepo_core::fs::file_hash_store::StoredBlob;
+use mediarepo_core::fs::thumbnail_store::ThumbnailCrop as ThumbnailCropModel;
+use mediarepo_core::mediarepo_api::types::attributes::{
+    AttributeFieldResponse, AttributeValueType, FileAttributeResponse,
+};
 use mediarepo_core::mediarepo_api::types::files::{
-    FileBasicDataResponse, FileMetadataResponse, FileStatus, ThumbnailMetadataResponse,
+    FileBasicDataResponse, FileDeletionResponse, FileMetadataResponse, FileStatus,
+    FileStorageInfoResponse, ProbedFileResponse, ThumbnailCrop, ThumbnailMetadataResponse,
 };
 use mediarepo_core::mediarepo_api::types::filtering::{
     SortDirection, SortKey, SortNamespace, SortingPreset,
 };
-use mediarepo_core::mediarepo_api::types::tags::{NamespaceResponse, TagResponse};
+use mediarepo_core::mediarepo_api::types::jobs::JobState;
+use mediarepo_core::mediarepo_api::types::repo::{
+    DamagedContentResponse, IntegrityProgressResponse, IntegrityReportResponse,
+    OrphanedBlobResponse,
+};
+use mediarepo_core::mediarepo_api::types::tags::{
+    NamespaceMergeResponse, NamespaceResponse, TagAliasResponse, TagResponse,
+};
+use mediarepo_core::settings::ContentDescriptorEncoding;
+use mediarepo_logic::dao::repo::integrity::{DamagedContent, IntegrityProgress, IntegrityReport};
 use mediarepo_logic::dto::{
-    FileDto, FileMetadataDto, FileStatus as FileStatusModel, KeyType, NamespaceDto, SortKeyDto,
-    SortingPresetDto, TagDto, ThumbnailDto,
+    AttributeFieldDto, AttributeValueType as AttributeValueTypeModel, FileAttributeDto,
+    FileDeletionDto, FileDto, FileMetadataDto, FileStatus as FileStatusModel, FileStorageInfoDto,
+    KeyType, NamespaceDto, NamespaceMergeDto, ProbedFileDto, SortKeyDto, SortingPresetDto,
+    TagAliasDto, TagDto, ThumbnailDto,
 };
+use mediarepo_worker::handle::JobState as JobStateModel;
 
 pub trait FromModel<M> {
     fn from_model(model: M) -> Self;
@@ -24,17 +43,73 @@ impl FromModel<FileMetadataDto> for FileMetadataResponse {
             change_time: model.change_time().to_owned(),
             import_time: model.import_time().to_owned(),
             size: model.size() as u64,
+            view_count: model.view_count() as u64,
+            access_time: model.access_time(),
+            latitude: model.latitude(),
+            longitude: model.longitude(),
+            width: model.width().map(|w| w as u32),
+            height: model.height().map(|h| h as u32),
         }
     }
 }
 
-impl FromModel<FileDto> for FileBasicDataResponse {
-    fn from_model(model: FileDto) -> Self {
+impl FromModel<(FileDto, ContentDescriptorEncoding)> for FileBasicDataResponse {
+    fn from_model((model, encoding): (FileDto, ContentDescriptorEncoding)) -> Self {
         FileBasicDataResponse {
             id: model.id(),
             status: FileStatus::from_model(model.status()),
-            cd: model.encoded_cd(),
+            cd: encode_content_descriptor_as(model.cd(), encoding),
             mime_type: model.mime_type().to_owned(),
+            corrupt: model.corrupt(),
+            external_id: model.external_id().cloned(),
+            indexed: model.indexed(),
+        }
+    }
+}
+
+impl FromModel<(FileStorageInfoDto, ContentDescriptorEncoding)> for FileStorageInfoResponse {
+    fn from_model((model, encoding): (FileStorageInfoDto, ContentDescriptorEncoding)) -> Self {
+        Self {
+            storage: model.storage().to_owned(),
+            hash_algorithm: model.hash_algorithm().to_owned(),
+            cd: encode_content_descriptor_as(model.cd(), encoding),
+            size: model.size(),
+        }
+    }
+}
+
+impl FromModel<FileDeletionDto> for FileDeletionResponse {
+    fn from_model(model: FileDeletionDto) -> Self {
+        Self {
+            file_id: model.file_id(),
+            content_removed: model.content_removed(),
+            tag_mappings: model.tag_mappings(),
+            thumbnails: model.thumbnails(),
+            freed_bytes: model.freed_bytes(),
+        }
+    }
+}
+
+impl FromModel<ThumbnailCropModel> for ThumbnailCrop {
+    fn from_model(model: ThumbnailCropModel) -> Self {
+        match model {
+            ThumbnailCropModel::Fit => Self::Fit,
+            ThumbnailCropModel::CenterCrop => Self::CenterCrop,
+        }
+    }
+}
+
+impl FromModel<ProbedFileDto> for ProbedFileResponse {
+    fn from_model(model: ProbedFileDto) -> Self {
+        Self {
+            name: model.name,
+            mime_type: model.mime_type,
+            size: model.size,
+            latitude: model.location.map(|(lat, _)| lat),
+            longitude: model.location.map(|(_, lon)| lon),
+            width: model.dimensions.map(|(width, _)| width),
+            height: model.dimensions.map(|(_, height)| height),
+            exif: model.exif,
         }
     }
 }
@@ -49,6 +124,18 @@ impl FromModel<FileStatusModel> for FileStatus {
     }
 }
 
+impl FromModel<JobStateModel> for JobState {
+    fn from_model(state: JobStateModel) -> Self {
+        match state {
+            JobStateModel::Queued => JobState::Queued,
+            JobStateModel::Scheduled => JobState::Scheduled,
+            JobStateModel::Running => JobState::Running,
+            JobStateModel::Paused => JobState::Paused,
+            JobStateModel::Finished => JobState::Finished,
+        }
+    }
+}
+
 impl FromModel<TagDto> for TagResponse {
     fn from_model(model: TagDto) -> Self {
         Self {
@@ -59,12 +146,23 @@ impl FromModel<TagDto> for TagResponse {
     }
 }
 
+impl FromModel<TagAliasDto> for TagAliasResponse {
+    fn from_model(model: TagAliasDto) -> Self {
+        Self {
+            id: model.id(),
+            name: model.name().to_owned(),
+            tag_id: model.tag_id(),
+        }
+    }
+}
+
 impl FromModel<ThumbnailDto> for ThumbnailMetadataResponse {
     fn from_model(model: ThumbnailDto) -> Self {
         Self {
             file_hash: model.parent_cd().to_owned(),
             height: model.size().height,
             width: model.size().width,
+            crop: ThumbnailCrop::from_model(model.crop()),
             mime_type: model.mime_type().to_owned(),
         }
     }
@@ -79,6 +177,15 @@ impl FromModel<NamespaceDto> for NamespaceResponse {
     }
 }
 
+impl FromModel<NamespaceMergeDto> for NamespaceMergeResponse {
+    fn from_model(model: NamespaceMergeDto) -> Self {
+        Self {
+            moved_tags: model.moved_tags().to_owned(),
+            merged_tags: model.merged_tags().to_owned(),
+        }
+    }
+}
+
 impl FromModel<SortingPresetDto> for SortingPreset {
     fn from_model(model: SortingPresetDto) -> Self {
         SortingPreset {
@@ -117,3 +224,87 @@ fn map_direction(ascending: bool) -> SortDirection {
         SortDirection::Descending
     }
 }
+
+impl FromModel<(StoredBlob, ContentDescriptorEncoding)> for OrphanedBlobResponse {
+    fn from_model((model, encoding): (StoredBlob, ContentDescriptorEncoding)) -> Self {
+        Self {
+            path: model.path.to_string_lossy().to_string(),
+            cd: model
+                .descriptor
+                .map(|cd| encode_content_descriptor_as(&cd, encoding)),
+            size: model.size,
+        }
+    }
+}
+
+impl FromModel<IntegrityProgress> for IntegrityProgressResponse {
+    fn from_model(model: IntegrityProgress) -> Self {
+        Self {
+            checked: model.checked,
+            total: model.total,
+            corrupt: model.corrupt,
+            missing: model.missing,
+        }
+    }
+}
+
+impl FromModel<(IntegrityReport, ContentDescriptorEncoding)> for IntegrityReportResponse {
+    fn from_model((model, encoding): (IntegrityReport, ContentDescriptorEncoding)) -> Self {
+        let map_contents = |contents: Vec<DamagedContent>| {
+            contents
+                .into_iter()
+                .map(|c| DamagedContentResponse::from_model((c, encoding)))
+                .collect()
+        };
+
+        Self {
+            checked: model.checked,
+            corrupt: map_contents(model.corrupt),
+            missing: map_contents(model.missing),
+        }
+    }
+}
+
+impl FromModel<(DamagedContent, ContentDescriptorEncoding)> for DamagedContentResponse {
+    fn from_model((model, encoding): (DamagedContent, ContentDescriptorEncoding)) -> Self {
+        Self {
+            cd: encode_content_descriptor_as(&model.descriptor, encoding),
+            files: model
+                .files
+                .into_iter()
+                .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+                .collect(),
+        }
+    }
+}
+
+impl FromModel<AttributeValueTypeModel> for AttributeValueType {
+    fn from_model(model: AttributeValueTypeModel) -> Self {
+        match model {
+            AttributeValueTypeModel::Text => Self::Text,
+            AttributeValueTypeModel::Number => Self::Number,
+            AttributeValueTypeModel::Boolean => Self::Boolean,
+            AttributeValueTypeModel::Date => Self::Date,
+        }
+    }
+}
+
+impl FromModel<AttributeFieldDto> for AttributeFieldResponse {
+    fn from_model(model: AttributeFieldDto) -> Self {
+        Self {
+            id: model.id(),
+            name: model.name().to_owned(),
+            value_type: AttributeValueType::from_model(model.value_type()),
+        }
+    }
+}
+
+impl FromModel<FileAttributeDto> for FileAttributeResponse {
+    fn from_model(model: FileAttributeDto) -> Self {
+        Self {
+            file_id: model.file_id(),
+            field: AttributeFieldResponse::from_model(model.field().clone()),
+            value: model.value().to_owned(),
+        }
+    }
+}
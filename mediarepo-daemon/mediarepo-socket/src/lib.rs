@@ -6,14 +6,20 @@ use tokio::task::JoinHandle;
 use crate::encrypted::EncryptedListener;
 use mediarepo_core::bromine::prelude::*;
 use mediarepo_core::error::{RepoError, RepoResult};
-use mediarepo_core::mediarepo_api::types::misc::InfoResponse;
+use mediarepo_core::image_processing::AVIF_SUPPORTED;
+use mediarepo_core::mediarepo_api::types::misc::{CapabilitiesResponse, InfoResponse};
 use mediarepo_core::settings::{PortSetting, Settings};
 use mediarepo_core::tokio_graceful_shutdown::SubsystemHandle;
 use mediarepo_core::trait_bound_typemap::{SendSyncTypeMap, TypeMap};
 use mediarepo_core::type_keys::{SizeMetadataKey, SubsystemKey};
+use mediarepo_core::utils::is_ffmpeg_installed;
+
+use crate::type_keys::{CancellationFlagsKey, PendingDeletionsKey, TagChangeHistoryKey};
+use crate::utils::get_settings_from_context;
 
 mod from_model;
 mod namespaces;
+mod type_keys;
 mod utils;
 
 #[tracing::instrument(skip_all)]
@@ -45,6 +51,9 @@ pub fn start_tcp_server(
                 .insert::<SubsystemKey>(subsystem)
                 .insert_all(shared_data)
                 .insert::<SizeMetadataKey>(Default::default())
+                .insert::<TagChangeHistoryKey>(Default::default())
+                .insert::<PendingDeletionsKey>(Default::default())
+                .insert::<CancellationFlagsKey>(Default::default())
                 .build_server()
                 .await
                 .expect("Failed to start tcp server")
@@ -73,6 +82,9 @@ pub fn create_unix_socket(
                 .insert::<SubsystemKey>(subsystem)
                 .insert_all(shared_data)
                 .insert::<SizeMetadataKey>(Default::default())
+                .insert::<TagChangeHistoryKey>(Default::default())
+                .insert::<PendingDeletionsKey>(Default::default())
+                .insert::<CancellationFlagsKey>(Default::default())
                 .build_server()
                 .await
                 .expect("Failed to create unix domain socket");
@@ -84,19 +96,41 @@ pub fn create_unix_socket(
 fn get_builder<L: AsyncStreamProtocolListener>(address: L::AddressType) -> IPCBuilder<L> {
     namespaces::build_namespaces(IPCBuilder::new().address(address))
         .on("info", callback!(info))
+        .on("capabilities", callback!(capabilities))
         .on("shutdown", callback!(shutdown))
 }
 
 #[tracing::instrument(skip_all)]
 async fn info(ctx: &Context, _: Event) -> IPCResult<Response> {
+    let settings = get_settings_from_context(ctx).await;
     let response = InfoResponse::new(
         env!("CARGO_PKG_NAME").to_string(),
         env!("CARGO_PKG_VERSION").to_string(),
+        settings.import.auto_generate_thumbnails,
     );
 
     ctx.response(response)
 }
 
+/// Returns the features that are supported by the daemon with its current settings
+#[tracing::instrument(skip_all)]
+async fn capabilities(ctx: &Context, _: Event) -> IPCResult<Response> {
+    let settings = get_settings_from_context(ctx).await;
+    let video_thumbnails = tokio::task::spawn_blocking(is_ffmpeg_installed)
+        .await
+        .unwrap_or(false);
+    let response = CapabilitiesResponse {
+        video_thumbnails,
+        avif_decoding: AVIF_SUPPORTED,
+        auto_generate_thumbnails: settings.import.auto_generate_thumbnails,
+        image_validation: settings.import.validate_images,
+        case_sensitive_tags: settings.tags.case_sensitive,
+        max_message_size: Some(settings.server.max_message_size),
+    };
+
+    ctx.response(response)
+}
+
 #[tracing::instrument(skip_all)]
 async fn shutdown(ctx: &Context, _: Event) -> IPCResult<Response> {
     ctx.clone().stop().await?;
@@ -0,0 +1,138 @@
+use crate::from_model::FromModel;
+use crate::utils::{file_by_identifier, get_repo_from_context};
+use mediarepo_core::bromine::prelude::*;
+use mediarepo_core::mediarepo_api::types::attributes::{
+    AddAttributeFieldRequest, AttributeFieldResponse, AttributeValueType, FileAttributeResponse,
+    RemoveFileAttributeRequest, SetFileAttributeRequest, UpdateAttributeFieldRequest,
+};
+use mediarepo_core::mediarepo_api::types::identifier::FileIdentifier;
+use mediarepo_logic::dao::DaoProvider;
+use mediarepo_logic::dto::{
+    AddAttributeFieldDto, AttributeValueType as AttributeValueTypeModel, UpdateAttributeFieldDto,
+};
+
+pub struct AttributesNamespace;
+
+impl NamespaceProvider for AttributesNamespace {
+    fn name() -> &'static str {
+        "attributes"
+    }
+
+    fn register(handler: &mut EventHandler) {
+        events!(handler,
+            "all_attribute_fields" => Self::all_attribute_fields,
+            "add_attribute_field" => Self::add_attribute_field,
+            "update_attribute_field" => Self::update_attribute_field,
+            "delete_attribute_field" => Self::delete_attribute_field,
+            "get_file_attributes" => Self::get_file_attributes,
+            "set_file_attribute" => Self::set_file_attribute,
+            "remove_file_attribute" => Self::remove_file_attribute
+        );
+    }
+}
+
+impl AttributesNamespace {
+    #[tracing::instrument(skip_all)]
+    pub async fn all_attribute_fields(ctx: &Context, _: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let fields: Vec<AttributeFieldResponse> = repo
+            .attribute()
+            .all_fields()
+            .await?
+            .into_iter()
+            .map(AttributeFieldResponse::from_model)
+            .collect();
+
+        ctx.response(fields)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn add_attribute_field(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<AddAttributeFieldRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let field = repo
+            .attribute()
+            .add_field(AddAttributeFieldDto {
+                name: request.name,
+                value_type: map_value_type(request.value_type),
+            })
+            .await?;
+
+        ctx.response(AttributeFieldResponse::from_model(field))
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn update_attribute_field(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<UpdateAttributeFieldRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let field = repo
+            .attribute()
+            .update_field(UpdateAttributeFieldDto {
+                id: request.id,
+                name: request.name,
+                value_type: request.value_type.map(map_value_type),
+            })
+            .await?;
+
+        ctx.response(AttributeFieldResponse::from_model(field))
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn delete_attribute_field(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let id = event.payload::<i64>()?;
+        let repo = get_repo_from_context(ctx).await;
+        repo.attribute().delete_field(id).await?;
+
+        Ok(Response::empty())
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn get_file_attributes(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let id = event.payload::<FileIdentifier>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let file = file_by_identifier(id, &repo).await?;
+        let attributes: Vec<FileAttributeResponse> = repo
+            .attribute()
+            .file_attributes(file.id())
+            .await?
+            .into_iter()
+            .map(FileAttributeResponse::from_model)
+            .collect();
+
+        ctx.response(attributes)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn set_file_attribute(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<SetFileAttributeRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let file = file_by_identifier(request.file_id, &repo).await?;
+        let attribute = repo
+            .attribute()
+            .set_file_attribute(file.id(), request.field_id, request.value)
+            .await?;
+
+        ctx.response(FileAttributeResponse::from_model(attribute))
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn remove_file_attribute(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<RemoveFileAttributeRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let file = file_by_identifier(request.file_id, &repo).await?;
+        repo.attribute()
+            .remove_file_attribute(file.id(), request.field_id)
+            .await?;
+
+        Ok(Response::empty())
+    }
+}
+
+fn map_value_type(value_type: AttributeValueType) -> AttributeValueTypeModel {
+    match value_type {
+        AttributeValueType::Text => AttributeValueTypeModel::Text,
+        AttributeValueType::Number => AttributeValueTypeModel::Number,
+        AttributeValueType::Boolean => AttributeValueTypeModel::Boolean,
+        AttributeValueType::Date => AttributeValueTypeModel::Date,
+    }
+}
@@ -1,30 +1,81 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use tokio::io::AsyncReadExt;
 
 use mediarepo_core::bromine::prelude::*;
-use mediarepo_core::content_descriptor::{create_content_descriptor, encode_content_descriptor};
-use mediarepo_core::error::RepoError;
-use mediarepo_core::fs::thumbnail_store::Dimensions;
+use mediarepo_core::content_descriptor::{
+    create_content_descriptor_with, encode_content_descriptor, encode_content_descriptor_as,
+};
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::fs::thumbnail_store::{Dimensions, ThumbnailCrop};
+use mediarepo_core::image_processing::validate_image_blocking;
 use mediarepo_core::itertools::Itertools;
 use mediarepo_core::mediarepo_api::types::files::{
-    AddFileRequestHeader, FileBasicDataResponse, FileMetadataResponse,
-    GetFileThumbnailOfSizeRequest, GetFileThumbnailsRequest, ReadFileRequest,
-    ThumbnailMetadataResponse, UpdateFileNameRequest, UpdateFileStatusRequest,
+    AddFileRequestHeader, BulkRenameFilesRequest, ChangeFilesStatusRequest,
+    CreateTimelineThumbnailsRequest, DeleteFileRequest, DeleteFilesPreviewResponse,
+    DeleteFilesRequest, DirectoryImportProgressResponse, ExportFilesEvent, ExportFilesRequest,
+    ExportProgressResponse, FileBasicDataResponse, FileDeletionResponse, FileMetadataField,
+    FileMetadataResponse, FileOSMetadata, FileStatus as ApiFileStatus, FileStorageInfoResponse,
+    FilesPageResponse, FindFilesNearRequest, FindSimilarFilesRequest, GetContactSheetRequest,
+    GetFileThumbnailOfSizeRequest, GetFileThumbnailsRequest, GetFilesPageRequest,
+    GetInlineThumbnailsForCdsRequest, GetThumbnailsForCdsRequest, ImportDirectoryEvent,
+    ImportDirectoryRequest, ImportDirectoryResponse, ImportFailure, ImportFileEntry,
+    ImportFilesEvent, ImportFilesRequest, ImportFilesResponse, ImportProgressResponse,
+    InlineThumbnailResponse, PathTags as ApiPathTags, PrepareDeleteFilesRequest, ProbeFileRequest,
+    ProbedFileResponse, ReadFileRangeRequest, ReadFileRequest, RenamedFileResponse,
+    SetFileIndexedRequest, SimilarFileResponse, ThumbnailCrop as ApiThumbnailCrop,
+    ThumbnailMetadataResponse, UpdateFileCommentRequest, UpdateFileMetadataRequest,
+    UpdateFileMimeTypeRequest, UpdateFileNameRequest, UpdateFileStatusRequest,
+};
+use mediarepo_core::mediarepo_api::types::filtering::{
+    FilterExpression, FindFileIdsResponse, FindFilesRequest, FindFilesResponse,
+    SearchFilesByTextRequest, StreamFilesRequest,
 };
-use mediarepo_core::mediarepo_api::types::filtering::FindFilesRequest;
 use mediarepo_core::mediarepo_api::types::identifier::FileIdentifier;
+use mediarepo_core::settings::Settings;
 use mediarepo_core::thumbnailer::ThumbnailSize;
-use mediarepo_core::utils::parse_namespace_and_tag;
+use mediarepo_core::utils::{parse_namespace_and_tag, sniff_mime_type};
+use mediarepo_logic::dao::file::find::{FilterFileProperty, FilterProperty};
+use mediarepo_logic::dao::repo::directory_import::{DirectoryImportOptions, PathTags};
+use mediarepo_logic::dao::repo::export::ExportOptions;
+use mediarepo_logic::dao::repo::Repo;
 use mediarepo_logic::dao::DaoProvider;
-use mediarepo_logic::dto::{AddFileDto, AddTagDto, UpdateFileDto, UpdateFileMetadataDto};
+use mediarepo_logic::dto::{
+    AddFileDto, AddTagDto, FileDto, FileStatus, MetadataField, ThumbnailDto, UpdateFileDto,
+    UpdateFileMetadataDto,
+};
 
 use crate::from_model::FromModel;
-use crate::namespaces::files::searching::find_files_for_filters;
+use crate::namespaces::files::searching::{filters_for_expressions, status_filter};
 use crate::namespaces::files::sorting::sort_files_by_properties;
-use crate::utils::{cd_by_identifier, file_by_identifier, get_repo_from_context};
+use crate::type_keys::PendingDeletionsKey;
+use crate::utils::{
+    cancel_operation, cd_by_identifier, check_message_size, file_by_identifier,
+    get_cd_encoding_from_context, get_repo_from_context, get_settings_from_context,
+    register_cancellation_flag, remove_cancellation_flag,
+};
 
-mod searching;
+pub(crate) mod searching;
 mod sorting;
 
+/// The number of content descriptors sent per chunk by `stream_all_hashes`
+const HASH_CHUNK_SIZE: usize = 1000;
+
+/// The maximum number of files included in the preview of a deletion
+const DELETE_PREVIEW_SAMPLE_SIZE: usize = 10;
+
+/// The number of timeline thumbnails created for a video when a thumbnail
+/// at a position is requested before any were created
+const DEFAULT_TIMELINE_THUMBNAIL_COUNT: u32 = 10;
+
+/// The maximum size of a thumbnail in bytes that is sent inline with its metadata
+const MAX_INLINE_THUMBNAIL_SIZE: u64 = 64 * 1024;
+
+/// The page size of searches that only specify an offset. Sqlite limits are signed integers
+const UNLIMITED_PAGE_SIZE: u64 = i64::MAX as u64;
+
 pub struct FilesNamespace;
 
 impl NamespaceProvider for FilesNamespace {
@@ -35,44 +86,313 @@ impl NamespaceProvider for FilesNamespace {
     fn register(handler: &mut EventHandler) {
         events!(handler,
             "all_files" => Self::all_files,
+            "all_files_paginated" => Self::all_files_paginated,
+            "corrupt_files" => Self::corrupt_files,
+            "find_duplicate_files" => Self::find_duplicate_files,
+            "find_similar_files" => Self::find_similar_files,
+            "most_viewed_files" => Self::most_viewed_files,
+            "files_missing_metadata" => Self::files_missing_metadata,
+            "find_files_by_name" => Self::find_files_by_name,
+            "find_files_near" => Self::find_files_near,
+            "find_files_from_source" => Self::find_files_from_source,
+            "file_by_external_id" => Self::file_by_external_id,
+            "stream_all_hashes" => Self::stream_all_hashes,
             "get_file" => Self::get_file,
             "get_file_metadata" => Self::get_file_metadata,
+            "get_file_storage_info" => Self::get_file_storage_info,
             "get_files" => Self::get_files,
+            "get_cds_for_ids" => Self::get_cds_for_ids,
             "find_files" => Self::find_files,
+            "search_files_by_text" => Self::search_files_by_text,
+            "stream_files" => Self::stream_files,
+            "stop_search" => Self::stop_search,
             "add_file" => Self::add_file,
+            "import_files" => Self::import_files,
+            "import_directory" => Self::import_directory,
+            "cancel_import" => Self::cancel_import,
+            "export_files" => Self::export_files,
             "read_file" => Self::read_file,
+            "read_file_range" => Self::read_file_range,
             "get_thumbnails" => Self::thumbnails,
             "get_thumbnail_of_size" => Self::get_thumbnail_of_size,
+            "get_thumbnails_for_cds" => Self::get_thumbnails_for_cds,
+            "get_inline_thumbnails_for_cds" => Self::get_inline_thumbnails_for_cds,
+            "get_contact_sheet" => Self::get_contact_sheet,
+            "create_timeline_thumbnails" => Self::create_timeline_thumbnails,
             "update_file_name" => Self::update_file_name,
+            "update_file_comment" => Self::update_file_comment,
+            "update_file_metadata" => Self::update_file_metadata,
             "delete_thumbnails" => Self::delete_thumbnails,
+            "clear_all_thumbnails" => Self::clear_all_thumbnails,
             "update_file_status" => Self::update_status,
-            "delete_file" => Self::delete_file
+            "archive_files" => Self::archive_files,
+            "unarchive_files" => Self::unarchive_files,
+            "restore_files" => Self::restore_files,
+            "set_file_indexed" => Self::set_indexed,
+            "update_file_mime_type" => Self::update_mime_type,
+            "delete_file" => Self::delete_file,
+            "probe_file" => Self::probe_file,
+            "prepare_delete_files" => Self::prepare_delete_files,
+            "delete_files" => Self::delete_files,
+            "bulk_rename_files" => Self::bulk_rename_files,
+            "touch_file" => Self::touch_file
         );
     }
 }
 
 impl FilesNamespace {
-    /// Returns a list of all files
+    /// Returns a list of all files sorted by the configured default sort keys
     #[tracing::instrument(skip_all)]
     async fn all_files(ctx: &Context, _event: Event) -> IPCResult<Response> {
         let repo = get_repo_from_context(ctx).await;
-        let files = repo.file().all().await?;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let sort_keys = get_settings_from_context(ctx)
+            .await
+            .sorting
+            .default_sort_keys();
+        let mut files = repo.file().all().await?;
+
+        if !sort_keys.is_empty() {
+            sort_files_by_properties(&repo, sort_keys, &mut files).await?;
+        }
+
+        let responses: Vec<FileBasicDataResponse> = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns a page of all files sorted by the default sort keys
+    /// together with the total number of files
+    #[tracing::instrument(skip_all)]
+    async fn all_files_paginated(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<GetFilesPageRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let sort_keys = get_settings_from_context(ctx)
+            .await
+            .sorting
+            .default_sort_keys();
+        let (files, total) = repo
+            .files_paginated(request.offset, request.limit, sort_keys)
+            .await?;
+        let files = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(FilesPageResponse { files, total })
+    }
+
+    /// Returns a list of all files that were flagged as corrupt on import
+    #[tracing::instrument(skip_all)]
+    async fn corrupt_files(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let files = repo.file().all_corrupt().await?;
+
+        let responses: Vec<FileBasicDataResponse> = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns groups of files that share the same content
+    #[tracing::instrument(skip_all)]
+    async fn find_duplicate_files(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let groups = repo.find_duplicate_files().await?;
+
+        let responses: Vec<Vec<FileBasicDataResponse>> = groups
+            .into_iter()
+            .map(|group| {
+                group
+                    .into_iter()
+                    .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+                    .collect()
+            })
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns the images with a perceptual hash close to the one of the given file
+    /// ordered by ascending distance
+    #[tracing::instrument(skip_all)]
+    async fn find_similar_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<FindSimilarFilesRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let files = repo.find_similar_files(&file, request.max_distance).await?;
+
+        let responses: Vec<SimilarFileResponse> = files
+            .into_iter()
+            .map(|(f, distance)| SimilarFileResponse {
+                file: FileBasicDataResponse::from_model((f, encoding)),
+                distance,
+            })
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns all files that miss any of the given metadata fields
+    #[tracing::instrument(skip_all)]
+    async fn files_missing_metadata(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let fields = event.payload::<Vec<FileMetadataField>>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let files = repo
+            .file()
+            .missing_metadata(fields.into_iter().map(MetadataField::from).collect())
+            .await?;
+
+        let responses: Vec<FileBasicDataResponse> = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns the files with the highest view count
+    #[tracing::instrument(skip_all)]
+    async fn most_viewed_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let limit = event.payload::<u64>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let files = repo.file().most_viewed(limit).await?;
+
+        let responses: Vec<FileBasicDataResponse> = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns all files whose name contains the given pattern
+    #[tracing::instrument(skip_all)]
+    async fn find_files_by_name(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let pattern = event.payload::<String>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let files = repo.file().find_by_name(pattern).await?;
+
+        let responses: Vec<FileBasicDataResponse> = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns all files located within a radius around the given coordinates
+    #[tracing::instrument(skip_all)]
+    async fn find_files_near(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<FindFilesNearRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let files = repo
+            .file()
+            .find_near(request.latitude, request.longitude, request.radius_km)
+            .await?;
+
+        let responses: Vec<FileBasicDataResponse> = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Returns all files with a source tag on the given domain or matching the given pattern
+    #[tracing::instrument(skip_all)]
+    async fn find_files_from_source(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let source_pattern = event.payload::<String>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await;
+        let files = repo
+            .files_from_source(&source_pattern, &settings.tags)
+            .await?;
 
         let responses: Vec<FileBasicDataResponse> = files
             .into_iter()
-            .map(FileBasicDataResponse::from_model)
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
             .collect();
 
         ctx.response(responses)
     }
 
+    /// Returns the file that was imported with the given external id
+    #[tracing::instrument(skip_all)]
+    async fn file_by_external_id(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let external_id = event.payload::<String>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let file = repo
+            .file()
+            .by_external_id(external_id)
+            .await?
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)));
+
+        ctx.response(file)
+    }
+
+    /// Streams the encoded content descriptors of all files in chunks
+    /// The last chunk is sent as the response to the event
+    #[tracing::instrument(skip_all)]
+    async fn stream_all_hashes(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let load_chunk = |offset: u64| {
+            let repo = &repo;
+            async move {
+                let cds = repo
+                    .file()
+                    .all_cds_paginated(offset, HASH_CHUNK_SIZE as u64)
+                    .await?;
+                let chunk: Vec<String> = cds
+                    .iter()
+                    .map(|cd| encode_content_descriptor_as(cd, encoding))
+                    .collect();
+
+                RepoResult::Ok(chunk)
+            }
+        };
+        let mut offset = 0;
+        let mut chunk = load_chunk(offset).await?;
+
+        // the descriptors are loaded one chunk at a time so that they never all are in memory
+        while chunk.len() == HASH_CHUNK_SIZE {
+            offset += chunk.len() as u64;
+            let next_chunk = load_chunk(offset).await?;
+
+            if next_chunk.is_empty() {
+                break;
+            }
+            ctx.emit("hashes", chunk).await?;
+            chunk = next_chunk;
+        }
+
+        ctx.response(chunk)
+    }
+
     /// Returns a file by id
     #[tracing::instrument(skip_all)]
     async fn get_file(ctx: &Context, event: Event) -> IPCResult<Response> {
         let id = event.payload::<FileIdentifier>()?;
         let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
         let file = file_by_identifier(id, &repo).await?;
-        let response = FileBasicDataResponse::from_model(file);
+        let response = FileBasicDataResponse::from_model((file, encoding));
 
         ctx.response(response)
     }
@@ -97,89 +417,349 @@ impl FilesNamespace {
         ctx.response(FileMetadataResponse::from_model(metadata))
     }
 
+    /// Returns the storage location and hash details of a file
+    #[tracing::instrument(skip_all)]
+    async fn get_file_storage_info(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let id = event.payload::<FileIdentifier>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let file = file_by_identifier(id, &repo).await?;
+        let storage_info = repo.file().storage_info(&file).await?;
+
+        ctx.response(FileStorageInfoResponse::from_model((
+            storage_info,
+            encoding,
+        )))
+    }
+
     /// Returns a list of files by identifier
     #[tracing::instrument(skip_all)]
     async fn get_files(ctx: &Context, event: Event) -> IPCResult<Response> {
         let ids = event.payload::<Vec<FileIdentifier>>()?;
         let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
         let mut responses = Vec::new();
 
         for id in ids {
             responses.push(
                 file_by_identifier(id, &repo)
                     .await
-                    .map(FileBasicDataResponse::from_model)?,
+                    .map(|f| FileBasicDataResponse::from_model((f, encoding)))?,
             );
         }
 
         ctx.response(responses)
     }
 
-    /// Searches for files by tags
+    /// Returns the encoded content descriptors of the files with the given ids
+    #[tracing::instrument(skip_all)]
+    async fn get_cds_for_ids(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let ids = event.payload::<Vec<i64>>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let cds: HashMap<i64, String> = repo
+            .file()
+            .cds_by_id(ids)
+            .await?
+            .into_iter()
+            .map(|(id, cd)| (id, encode_content_descriptor_as(&cd, encoding)))
+            .collect();
+
+        ctx.response(cds)
+    }
+
+    /// Searches for files by tags.
+    /// Files are sorted by the configured default sort keys if the request doesn't contain any.
+    /// Responds with only the ids of the files if requested
     #[tracing::instrument(skip_all)]
     async fn find_files(ctx: &Context, event: Event) -> IPCResult<Response> {
         let req = event.payload::<FindFilesRequest>()?;
         let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await;
+        let sort_keys = if req.sort_expression.is_empty() {
+            settings.sorting.default_sort_keys()
+        } else {
+            req.sort_expression
+        };
+        let filters = search_filters(
+            &repo,
+            req.filters,
+            req.include_unindexed,
+            req.include_statuses,
+            &settings,
+        )
+        .await?;
 
-        let mut files = find_files_for_filters(&repo, req.filters).await?;
-        sort_files_by_properties(&repo, req.sort_expression, &mut files).await?;
+        if req.ids_only && req.offset.is_none() && req.limit.is_none() {
+            // the ids are sorted by the database so the files don't need to be loaded at all
+            let mut ids = repo.file().find_ids(filters, sort_keys).await?;
+            let truncated = truncate_results(&mut ids, req.max_results);
 
-        let responses: Vec<FileBasicDataResponse> = files
+            return ctx.response(FindFileIdsResponse {
+                ids,
+                truncated,
+                total: None,
+            });
+        }
+
+        if req.offset.is_some() || req.limit.is_some() || req.max_results.is_some() {
+            // pages are sorted and cut by the database so only the files of the page are loaded
+            let offset = req.offset.unwrap_or(0);
+            let limit = req.limit.or(req.max_results).unwrap_or(UNLIMITED_PAGE_SIZE);
+            let (files, total) = repo
+                .file()
+                .find_paginated(filters, offset, limit, sort_keys)
+                .await?;
+            let truncated = offset.saturating_add(files.len() as u64) < total;
+            let total = Some(total);
+
+            if req.ids_only {
+                let ids = files.into_iter().map(|f| f.id()).collect();
+
+                return ctx.response(FindFileIdsResponse {
+                    ids,
+                    truncated,
+                    total,
+                });
+            }
+            let files = files
+                .into_iter()
+                .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+                .collect();
+
+            return ctx.response(FindFilesResponse {
+                files,
+                truncated,
+                total,
+            });
+        }
+        let mut files = repo.file().find(filters).await?;
+        if !sort_keys.is_empty() {
+            sort_files_by_properties(&repo, sort_keys, &mut files).await?;
+        }
+        let files = files
             .into_iter()
-            .map(FileBasicDataResponse::from_model)
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
             .collect();
 
-        ctx.response(responses)
+        ctx.response(FindFilesResponse {
+            files,
+            truncated: false,
+            total: None,
+        })
+    }
+
+    /// Searches for files like `find_files` but sends the results in pages so that the
+    /// first files arrive before the search has finished. The last page is sent as the response.
+    /// Stops after the current page when the search is stopped
+    #[tracing::instrument(skip_all)]
+    async fn stream_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let req = event.payload::<StreamFilesRequest>()?;
+        let search_id = req.search_id.clone();
+        let stopped = register_cancellation_flag(ctx, &search_id).await?;
+        let result = stream_file_pages(ctx, req, &stopped).await;
+        remove_cancellation_flag(ctx, &search_id).await;
+
+        ctx.response(result?)
+    }
+
+    /// Stops a streamed search and responds with whether a search with the id was running
+    #[tracing::instrument(skip_all)]
+    async fn stop_search(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let search_id = event.payload::<String>()?;
+        let stopped = cancel_operation(ctx, &search_id).await;
+
+        ctx.response(stopped)
+    }
+
+    /// Searches for a page of the files whose name or comment contains the text.
+    /// Files are sorted by the configured default sort keys if the request doesn't contain any
+    #[tracing::instrument(skip_all)]
+    async fn search_files_by_text(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let req = event.payload::<SearchFilesByTextRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let sort_keys = if req.sort_expression.is_empty() {
+            get_settings_from_context(ctx)
+                .await
+                .sorting
+                .default_sort_keys()
+        } else {
+            req.sort_expression
+        };
+        let offset = req.offset.unwrap_or(0);
+        let limit = req.limit.unwrap_or(UNLIMITED_PAGE_SIZE);
+        let (files, total) = repo
+            .search_files_by_text(&req.query, offset, limit, sort_keys)
+            .await?;
+        let truncated = offset.saturating_add(files.len() as u64) < total;
+        let files = files
+            .into_iter()
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        ctx.response(FindFilesResponse {
+            files,
+            truncated,
+            total: Some(total),
+        })
     }
 
     /// Adds a file to the repository
     #[tracing::instrument(skip_all)]
     async fn add_file(ctx: &Context, event: Event) -> IPCResult<Response> {
+        check_message_size(ctx, event.data_raw().len()).await?;
         let (request, bytes) = event
             .payload::<TandemPayload<AddFileRequestHeader, BytePayload>>()?
             .into_inner();
-        let AddFileRequestHeader { metadata, tags } = request;
+        let AddFileRequestHeader {
+            metadata,
+            tags,
+            external_id,
+        } = request;
         let repo = get_repo_from_context(ctx).await;
-        let bytes = bytes.into_inner();
-        let cd = create_content_descriptor(&bytes);
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await;
+        let file = add_file_to_repo(
+            &repo,
+            &settings,
+            metadata,
+            tags,
+            external_id,
+            bytes.into_inner(),
+        )
+        .await?;
 
-        let file = if let Some(file) = repo.file().by_cd(cd).await? {
-            tracing::debug!("Inserted file already exists");
-            file
-        } else {
-            let add_dto = AddFileDto {
-                content: bytes,
-                mime_type: metadata
-                    .mime_type
-                    .unwrap_or_else(|| String::from("application/octet-stream")),
-                creation_time: metadata.creation_time,
-                change_time: metadata.change_time,
-                name: Some(metadata.name),
-            };
-            repo.file().add(add_dto).await?
+        ctx.response(FileBasicDataResponse::from_model((file, encoding)))
+    }
+
+    /// Imports files from paths on the machine of the daemon and sends the progress
+    /// after each file. Files that fail to import don't stop the import but are
+    /// reported with the result. Stops after the current file when the import is cancelled
+    #[tracing::instrument(skip_all)]
+    async fn import_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<ImportFilesRequest>()?;
+        let cancelled = register_cancellation_flag(ctx, &request.import_id).await?;
+        let result = import_files_from_paths(ctx, request.files, &cancelled).await;
+        remove_cancellation_flag(ctx, &request.import_id).await;
+
+        ctx.response(ImportFilesEvent::Finished(result?))
+    }
+
+    /// Imports the files of a folder and its subfolders on the machine of the daemon and
+    /// sends the progress after each path. Can be cancelled like an import of files
+    #[tracing::instrument(skip_all)]
+    async fn import_directory(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let ImportDirectoryRequest {
+            import_id,
+            path,
+            options,
+        } = event.payload()?;
+        let repo = get_repo_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await;
+        let options = DirectoryImportOptions {
+            pattern: options.pattern,
+            extensions: options.extensions,
+            path_tags: options.path_tags.map(|mode| match mode {
+                ApiPathTags::Tags => PathTags::Tags,
+                ApiPathTags::Namespace(namespace) => PathTags::Namespace(namespace),
+            }),
+            follow_symlinks: options.follow_symlinks,
+            include_hidden: options.include_hidden,
         };
+        let cancelled = register_cancellation_flag(ctx, &import_id).await?;
+        let result = repo
+            .import_directory(
+                &PathBuf::from(path),
+                options,
+                &settings.import,
+                &settings.tags,
+                &cancelled,
+                |progress| async move {
+                    let progress = DirectoryImportProgressResponse {
+                        path: progress.path.to_string_lossy().to_string(),
+                        imported: progress.imported,
+                        skipped: progress.skipped,
+                        failed: progress.failed,
+                        error: progress.error,
+                    };
+                    ctx.emit(
+                        "directory_import_progress",
+                        ImportDirectoryEvent::Progress(progress),
+                    )
+                    .await?;
+                    Ok(())
+                },
+            )
+            .await;
+        remove_cancellation_flag(ctx, &import_id).await;
+        let summary = result?;
+        let failures = summary
+            .failures
+            .into_iter()
+            .map(|(path, error)| ImportFailure {
+                path: path.to_string_lossy().to_string(),
+                error,
+            })
+            .collect();
+
+        ctx.response(ImportDirectoryEvent::Finished(ImportDirectoryResponse {
+            imported: summary.imported,
+            skipped: summary.skipped,
+            failures,
+            cancelled: summary.cancelled,
+        }))
+    }
 
-        let tags = repo
-            .tag()
-            .add_all(
-                tags.into_iter()
-                    .map(parse_namespace_and_tag)
-                    .map(AddTagDto::from_tuple)
-                    .collect(),
+    /// Cancels a running import and responds with whether an import with the id was running
+    #[tracing::instrument(skip_all)]
+    async fn cancel_import(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let import_id = event.payload::<String>()?;
+        let cancelled = cancel_operation(ctx, &import_id).await;
+
+        ctx.response(cancelled)
+    }
+
+    /// Writes the files into a folder on the machine of the daemon and sends the progress
+    /// after each file. Responds with the paths of the written files
+    #[tracing::instrument(skip_all)]
+    async fn export_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<ExportFilesRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let options = ExportOptions {
+            write_tag_sidecars: request.write_tag_sidecars,
+            group_by_namespace: request.group_by_namespace,
+        };
+        let paths = repo
+            .export_files(
+                request.ids,
+                &PathBuf::from(request.target_dir),
+                options,
+                |progress| async move {
+                    let progress = ExportProgressResponse {
+                        exported: progress.exported,
+                        total: progress.total,
+                        path: progress.path.to_string_lossy().to_string(),
+                    };
+                    ctx.emit("export_progress", ExportFilesEvent::Progress(progress))
+                        .await?;
+                    Ok(())
+                },
             )
-            .await?;
-        let tag_ids: Vec<i64> = tags.into_iter().map(|t| t.id()).unique().collect();
-        repo.tag()
-            .upsert_mappings(vec![file.cd_id()], tag_ids)
-            .await?;
+            .await?
+            .into_iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
 
-        ctx.response(FileBasicDataResponse::from_model(file))
+        ctx.response(ExportFilesEvent::Finished(paths))
     }
 
     #[tracing::instrument(skip_all)]
     async fn update_status(ctx: &Context, event: Event) -> IPCResult<Response> {
         let request = event.payload::<UpdateFileStatusRequest>()?;
         let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
         let mut file = file_by_identifier(request.file_id, &repo).await?;
         file = repo
             .file()
@@ -190,7 +770,52 @@ impl FilesNamespace {
             })
             .await?;
 
-        ctx.response(FileBasicDataResponse::from_model(file))
+        ctx.response(FileBasicDataResponse::from_model((file, encoding)))
+    }
+
+    /// Archives the imported files and responds with the archived ones
+    #[tracing::instrument(skip_all)]
+    async fn archive_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        change_files_status(ctx, event, FileStatus::Imported, FileStatus::Archived).await
+    }
+
+    /// Moves the archived files back to the imported files and responds with them
+    #[tracing::instrument(skip_all)]
+    async fn unarchive_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        change_files_status(ctx, event, FileStatus::Archived, FileStatus::Imported).await
+    }
+
+    /// Restores the deleted files as imported files and responds with them
+    #[tracing::instrument(skip_all)]
+    async fn restore_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        change_files_status(ctx, event, FileStatus::Deleted, FileStatus::Imported).await
+    }
+
+    /// Sets if a file is included in searches
+    #[tracing::instrument(skip_all)]
+    async fn set_indexed(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<SetFileIndexedRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let file = file_by_identifier(request.file_id, &repo).await?;
+        let file = repo.file().set_indexed(file.id(), request.indexed).await?;
+
+        ctx.response(FileBasicDataResponse::from_model((file, encoding)))
+    }
+
+    /// Overrides the mime type of a file
+    #[tracing::instrument(skip_all)]
+    async fn update_mime_type(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<UpdateFileMimeTypeRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let file = file_by_identifier(request.file_id, &repo).await?;
+        let file = repo
+            .file()
+            .set_mime_type(file.id(), request.mime_type)
+            .await?;
+
+        ctx.response(FileBasicDataResponse::from_model((file, encoding)))
     }
 
     /// Reads the binary contents of a file
@@ -199,23 +824,150 @@ impl FilesNamespace {
         let request = event.payload::<ReadFileRequest>()?;
         let repo = get_repo_from_context(ctx).await;
         let file = file_by_identifier(request.id, &repo).await?;
+        // the stored size is checked first so that files over the limit aren't loaded
+        check_message_size(ctx, stored_size(&repo, &file).await? as usize).await?;
         let bytes = repo.file().get_bytes(file.cd()).await?;
 
         ctx.response(BytePayload::new(bytes))
     }
 
-    /// Deletes a file
+    /// Reads a range of bytes of a file
+    #[tracing::instrument(skip_all)]
+    async fn read_file_range(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<ReadFileRangeRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let length = stored_size(&repo, &file)
+            .await?
+            .saturating_sub(request.offset)
+            .min(request.length);
+        check_message_size(ctx, length as usize).await?;
+        let bytes = repo
+            .file()
+            .get_byte_range(file.cd(), request.offset, request.length)
+            .await?;
+
+        ctx.response(BytePayload::new(bytes))
+    }
+
+    /// Deletes a file and returns what was removed or only returns the report for a dry run
     #[tracing::instrument(skip_all)]
     async fn delete_file(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<DeleteFileRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let deletion = repo.file().delete(file, request.dry_run).await?;
+
+        ctx.response(FileDeletionResponse::from_model(deletion))
+    }
+
+    /// Returns the metadata a file on the daemon's file system would be imported with
+    #[tracing::instrument(skip_all)]
+    async fn probe_file(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<ProbeFileRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let probed = repo.probe_file(PathBuf::from(request.path)).await?;
+
+        ctx.response(ProbedFileResponse::from_model(probed))
+    }
+
+    /// Returns a preview of the files that would be deleted together with a token
+    /// that needs to be presented to `delete_files` to actually delete them
+    #[tracing::instrument(skip_all)]
+    async fn prepare_delete_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<PrepareDeleteFilesRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let files = repo.file().all_by_id(request.ids).await?;
+        let file_ids: Vec<i64> = files.iter().map(|f| f.id()).collect();
+        let total_size = repo
+            .file()
+            .all_metadata(file_ids.clone())
+            .await?
+            .into_iter()
+            .map(|m| m.size() as u64)
+            .sum();
+        let file_count = files.len() as u64;
+        let sample = files
+            .into_iter()
+            .take(DELETE_PREVIEW_SAMPLE_SIZE)
+            .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+            .collect();
+
+        let token = {
+            let mut data = ctx.data.write().await;
+            data.get_mut::<PendingDeletionsKey>()
+                .unwrap()
+                .prepare(file_ids)
+        };
+
+        ctx.response(DeleteFilesPreviewResponse {
+            token,
+            file_count,
+            total_size,
+            sample,
+        })
+    }
+
+    /// Deletes the files of a previously prepared deletion and returns the number of deleted files.
+    /// The token can only be used once. If the deletion fails the token stays valid
+    /// so that the remaining files can be deleted with it
+    #[tracing::instrument(skip_all)]
+    async fn delete_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<DeleteFilesRequest>()?;
+        let pending = {
+            let mut data = ctx.data.write().await;
+            data.get_mut::<PendingDeletionsKey>()
+                .unwrap()
+                .take(&request.token)
+        };
+        let pending = match pending {
+            Some(pending) => pending,
+            None => return Err(RepoError::from("invalid or expired confirmation token").into()),
+        };
+        let repo = get_repo_from_context(ctx).await;
+
+        match delete_files_by_id(&repo, pending.file_ids.clone()).await {
+            Ok(count) => ctx.response(count),
+            Err(e) => {
+                let mut data = ctx.data.write().await;
+                data.get_mut::<PendingDeletionsKey>()
+                    .unwrap()
+                    .restore(request.token, pending);
+
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Renames the given files by a pattern or only returns the new names for a dry run
+    #[tracing::instrument(skip_all)]
+    async fn bulk_rename_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<BulkRenameFilesRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let renamed: Vec<RenamedFileResponse> = repo
+            .bulk_rename(request.ids, &request.pattern, request.dry_run)
+            .await?
+            .into_iter()
+            .map(|(id, name)| RenamedFileResponse { id, name })
+            .collect();
+
+        ctx.response(renamed)
+    }
+
+    /// Increments the view count of a file and updates its access time
+    #[tracing::instrument(skip_all)]
+    async fn touch_file(ctx: &Context, event: Event) -> IPCResult<Response> {
         let id = event.payload::<FileIdentifier>()?;
         let repo = get_repo_from_context(ctx).await;
         let file = file_by_identifier(id, &repo).await?;
-        repo.file().delete(file).await?;
+        let metadata = repo.file().touch(file.id()).await?;
 
-        Ok(Response::empty())
+        ctx.response(FileMetadataResponse::from_model(metadata))
     }
 
-    /// Returns a list of available thumbnails of a file
+    /// Returns a list of available thumbnails of a file ordered by their area
+    /// starting with the smallest thumbnail
     #[tracing::instrument(skip_all)]
     async fn thumbnails(ctx: &Context, event: Event) -> IPCResult<Response> {
         let request = event.payload::<GetFileThumbnailsRequest>()?;
@@ -229,10 +981,7 @@ impl FilesNamespace {
         if thumbnails.is_empty() {
             tracing::debug!("No thumbnails for file found. Creating thumbnails...");
             let file = file_by_identifier(request.id, &repo).await?;
-            thumbnails = repo
-                .file()
-                .create_thumbnails(&file, vec![ThumbnailSize::Medium])
-                .await?;
+            thumbnails = repo.file().create_configured_thumbnails(&file).await?;
             tracing::debug!("Thumbnails for file created.");
         }
 
@@ -252,17 +1001,36 @@ impl FilesNamespace {
         let file_cd = cd_by_identifier(request.id.clone(), &repo).await?;
         let min_size = request.min_size;
         let max_size = request.max_size;
+        let crop = thumbnail_crop_for_request(ctx, request.crop).await;
+
+        if let Some(position) = request.position {
+            let file = file_by_identifier(request.id.clone(), &repo).await?;
+
+            // the position is ignored for files that aren't videos
+            if file.mime_type().starts_with("video/") {
+                let thumbnail = match repo.video_timeline_thumbnail(&file, position).await? {
+                    Some(thumbnail) => thumbnail,
+                    None => {
+                        repo.create_video_timeline_thumbnails(
+                            &file,
+                            DEFAULT_TIMELINE_THUMBNAIL_COUNT,
+                        )
+                        .await?;
+                        repo.video_timeline_thumbnail(&file, position)
+                            .await?
+                            .ok_or_else(|| RepoError::from("thumbnail could not be created"))?
+                    }
+                };
+
+                return thumbnail_response(ctx, thumbnail).await;
+            }
+        }
         let thumbnails = repo
             .file()
             .thumbnails(encode_content_descriptor(&file_cd))
             .await?;
 
-        let found_thumbnail = thumbnails.into_iter().find(|thumb| {
-            let Dimensions { height, width } = thumb.size();
-
-            (*height <= max_size.0 && *width <= max_size.1)
-                && (*width >= min_size.1 || *height >= min_size.0)
-        });
+        let found_thumbnail = nearest_thumbnail_in_size_range(thumbnails, min_size, max_size, crop);
 
         let thumbnail = if let Some(thumbnail) = found_thumbnail {
             thumbnail
@@ -271,7 +1039,7 @@ impl FilesNamespace {
             let middle_size = ((max_size.0 + min_size.0) / 2, (max_size.1 + min_size.1) / 2);
             let thumbnail = repo
                 .file()
-                .create_thumbnails(&file, vec![ThumbnailSize::Custom(middle_size)])
+                .create_thumbnails_with_crop(&file, vec![ThumbnailSize::Custom(middle_size)], crop)
                 .await?;
 
             thumbnail
@@ -279,12 +1047,109 @@ impl FilesNamespace {
                 .next()
                 .ok_or_else(|| RepoError::from("thumbnail could not be created"))?
         };
-        let mut buf = Vec::new();
-        thumbnail.get_reader().await?.read_to_end(&mut buf).await?;
-        let byte_payload = BytePayload::new(buf);
-        let thumb_payload = ThumbnailMetadataResponse::from_model(thumbnail);
 
-        ctx.response(TandemPayload::new(thumb_payload, byte_payload))
+        thumbnail_response(ctx, thumbnail).await
+    }
+
+    /// Returns the contact sheet of a video and creates it if it doesn't exist yet
+    #[tracing::instrument(skip_all)]
+    async fn get_contact_sheet(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<GetContactSheetRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let sheet = match repo
+            .contact_sheet(&file, request.rows, request.cols)
+            .await?
+        {
+            Some(sheet) => sheet,
+            None => {
+                repo.create_contact_sheet(&file, request.rows, request.cols)
+                    .await?
+            }
+        };
+
+        thumbnail_response(ctx, sheet).await
+    }
+
+    /// Creates thumbnails of evenly spaced frames of a video that can be requested
+    /// with a position by `get_thumbnail_of_size`
+    #[tracing::instrument(skip_all)]
+    async fn create_timeline_thumbnails(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<CreateTimelineThumbnailsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let thumbnails: Vec<(f64, ThumbnailMetadataResponse)> = repo
+            .create_video_timeline_thumbnails(&file, request.count)
+            .await?
+            .into_iter()
+            .map(|(position, t)| (position, ThumbnailMetadataResponse::from_model(t)))
+            .collect();
+
+        ctx.response(thumbnails)
+    }
+
+    /// Returns the metadata of thumbnails within the requested size range
+    /// for several content descriptors
+    #[tracing::instrument(skip_all)]
+    async fn get_thumbnails_for_cds(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let GetThumbnailsForCdsRequest {
+            cd_ids,
+            min_size,
+            max_size,
+            crop,
+        } = event.payload::<GetThumbnailsForCdsRequest>()?;
+        let crop = thumbnail_crop_for_request(ctx, crop).await;
+        let response: HashMap<i64, ThumbnailMetadataResponse> =
+            thumbnails_for_cds_in_size_range(ctx, cd_ids, min_size, max_size, crop)
+                .await?
+                .into_iter()
+                .map(|(cd_id, t)| (cd_id, ThumbnailMetadataResponse::from_model(t)))
+                .collect();
+
+        ctx.response(response)
+    }
+
+    /// Returns the metadata of thumbnails within the requested size range
+    /// for several content descriptors. The contents of thumbnails that are small
+    /// enough are sent inline so that they don't have to be requested one by one
+    #[tracing::instrument(skip_all)]
+    async fn get_inline_thumbnails_for_cds(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let GetInlineThumbnailsForCdsRequest {
+            cd_ids,
+            min_size,
+            max_size,
+            crop,
+            max_inline_size,
+        } = event.payload::<GetInlineThumbnailsForCdsRequest>()?;
+        let crop = thumbnail_crop_for_request(ctx, crop).await;
+        let max_inline_size = max_inline_size.min(MAX_INLINE_THUMBNAIL_SIZE);
+        let thumbnails =
+            thumbnails_for_cds_in_size_range(ctx, cd_ids, min_size, max_size, crop).await?;
+
+        // thumbnails that don't fit into the message anymore are sent without contents
+        let mut remaining_size = get_settings_from_context(ctx).await.server.max_message_size;
+        let mut response = HashMap::with_capacity(thumbnails.len());
+
+        for (cd_id, thumbnail) in thumbnails {
+            let byte_size = thumbnail.byte_size().await?;
+            let data = if byte_size <= max_inline_size && byte_size <= remaining_size {
+                let mut buf = Vec::with_capacity(byte_size as usize);
+                thumbnail.get_reader().await?.read_to_end(&mut buf).await?;
+                remaining_size = remaining_size.saturating_sub(buf.len() as u64);
+                Some(buf)
+            } else {
+                None
+            };
+            response.insert(
+                cd_id,
+                InlineThumbnailResponse {
+                    metadata: ThumbnailMetadataResponse::from_model(thumbnail),
+                    data,
+                },
+            );
+        }
+
+        ctx.response(response)
     }
 
     /// Updates the name of a file
@@ -306,18 +1171,430 @@ impl FilesNamespace {
         ctx.response(FileMetadataResponse::from_model(metadata))
     }
 
-    /// Deletes all thumbnails of a file
+    /// Updates the comment of a file
+    #[tracing::instrument(skip_all)]
+    async fn update_file_comment(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let request = event.payload::<UpdateFileCommentRequest>()?;
+        let file = file_by_identifier(request.file_id, &repo).await?;
+
+        let metadata = repo
+            .file()
+            .update_metadata(UpdateFileMetadataDto {
+                file_id: file.id(),
+                comment: Some(request.comment),
+                ..Default::default()
+            })
+            .await?;
+
+        ctx.response(FileMetadataResponse::from_model(metadata))
+    }
+
+    /// Updates the name, comment and status of a file at once
+    #[tracing::instrument(skip_all)]
+    async fn update_file_metadata(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let request = event.payload::<UpdateFileMetadataRequest>()?;
+        let file = file_by_identifier(request.file_id, &repo).await?;
+
+        let metadata = repo
+            .file()
+            .update_metadata_and_status(
+                UpdateFileMetadataDto {
+                    file_id: file.id(),
+                    name: request.name.map(Some),
+                    comment: request.comment.map(Some),
+                    ..Default::default()
+                },
+                request.status.map(Into::into),
+                request.undelete,
+            )
+            .await?;
+
+        ctx.response(FileMetadataResponse::from_model(metadata))
+    }
+
+    /// Deletes all thumbnails of a file and returns the number of bytes freed
     #[tracing::instrument(skip_all)]
     async fn delete_thumbnails(ctx: &Context, event: Event) -> IPCResult<Response> {
         let repo = get_repo_from_context(ctx).await;
         let id = event.payload::<FileIdentifier>()?;
         let file = file_by_identifier(id, &repo).await?;
-        let thumbnails = repo.file().thumbnails(file.encoded_cd()).await?;
+        let freed_bytes = repo.file().clear_thumbnails(&file).await?;
+
+        ctx.response(freed_bytes)
+    }
+
+    /// Deletes the thumbnails of all files and returns the number of bytes freed
+    #[tracing::instrument(skip_all)]
+    async fn clear_all_thumbnails(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let freed_bytes = repo.file().clear_all_thumbnails().await?;
+
+        ctx.response(freed_bytes)
+    }
+}
+
+/// Changes the status of the requested files that have the `from` status
+/// and responds with the changed files
+async fn change_files_status(
+    ctx: &Context,
+    event: Event,
+    from: FileStatus,
+    status: FileStatus,
+) -> IPCResult<Response> {
+    let request = event.payload::<ChangeFilesStatusRequest>()?;
+    let repo = get_repo_from_context(ctx).await;
+    let encoding = get_cd_encoding_from_context(ctx).await;
+    let files: Vec<FileBasicDataResponse> = repo
+        .file()
+        .set_status(request.ids, from, status)
+        .await?
+        .into_iter()
+        .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+        .collect();
+
+    ctx.response(files)
+}
+
+/// Deletes the files with the given ids and returns the number of deleted files.
+/// Files that don't exist anymore are skipped
+async fn delete_files_by_id(repo: &Repo, file_ids: Vec<i64>) -> RepoResult<u64> {
+    let files = repo.file().all_by_id(file_ids).await?;
+    let count = files.len() as u64;
+
+    for file in files {
+        repo.file().delete(file, false).await?;
+    }
+
+    Ok(count)
+}
+
+/// Converts the filter expressions of a search into filters.
+/// Files that are excluded from searches are filtered out unless they are requested.
+/// Archived files are filtered out unless the statuses or the expressions include them
+async fn search_filters(
+    repo: &Repo,
+    expressions: Vec<FilterExpression>,
+    include_unindexed: bool,
+    include_statuses: Option<Vec<ApiFileStatus>>,
+    settings: &Settings,
+) -> RepoResult<Vec<Vec<FilterProperty>>> {
+    let status_filter = status_filter(include_statuses, &expressions)?;
+    let mut filters = filters_for_expressions(repo, expressions, &settings.tags).await?;
+    filters.extend(status_filter);
+    if !include_unindexed {
+        filters.push(vec![FilterProperty::FileProperty(
+            FilterFileProperty::Indexed(true),
+        )]);
+    }
+
+    Ok(filters)
+}
+
+/// Loads the files of the search page by page and sends every page except the last one
+/// which is returned
+async fn stream_file_pages(
+    ctx: &Context,
+    req: StreamFilesRequest,
+    stopped: &AtomicBool,
+) -> IPCResult<FindFilesResponse> {
+    let repo = get_repo_from_context(ctx).await;
+    let encoding = get_cd_encoding_from_context(ctx).await;
+    let settings = get_settings_from_context(ctx).await;
+    let sort_keys = if req.sort_expression.is_empty() {
+        settings.sorting.default_sort_keys()
+    } else {
+        req.sort_expression
+    };
+    let filters = search_filters(
+        &repo,
+        req.filters,
+        req.include_unindexed,
+        req.include_statuses,
+        &settings,
+    )
+    .await?;
+    let page_size = req.page_size.max(1);
+    let max_results = req.max_results.unwrap_or(UNLIMITED_PAGE_SIZE);
+    let mut offset = 0;
+
+    loop {
+        let limit = page_size.min(max_results - offset);
+        let (files, total) = repo
+            .file()
+            .find_paginated(filters.clone(), offset, limit, sort_keys.clone())
+            .await?;
+        let last_page = files.is_empty();
+        offset += files.len() as u64;
+        let page = FindFilesResponse {
+            files: files
+                .into_iter()
+                .map(|f| FileBasicDataResponse::from_model((f, encoding)))
+                .collect(),
+            truncated: offset < total,
+            total: Some(total),
+        };
+
+        if last_page || offset >= total.min(max_results) || stopped.load(Ordering::SeqCst) {
+            return Ok(page);
+        }
+        ctx.emit("files_page", page).await?;
+    }
+}
+
+/// Adds a file with its tags or assigns the tags to the file with the same content
+async fn add_file_to_repo(
+    repo: &Repo,
+    settings: &Settings,
+    metadata: FileOSMetadata,
+    tags: Vec<String>,
+    external_id: Option<String>,
+    bytes: Vec<u8>,
+) -> RepoResult<FileDto> {
+    let cd = create_content_descriptor_with(&bytes, settings.import.hash_algorithm);
+    let tags: Vec<AddTagDto> = tags
+        .into_iter()
+        .map(|tag| parse_namespace_and_tag(tag, settings.tags.case_sensitive))
+        .map(AddTagDto::from_tuple)
+        .collect();
+
+    let file = if let Some(file) = repo.file().by_cd(cd).await? {
+        tracing::debug!("Inserted file already exists");
+
+        let file = if external_id.is_some() && file.external_id() != external_id.as_ref() {
+            repo.file()
+                .update(UpdateFileDto {
+                    id: file.id(),
+                    external_id: Some(external_id),
+                    ..Default::default()
+                })
+                .await?
+        } else {
+            file
+        };
+        let tags = repo.tag().add_all(tags, &settings.tags).await?;
+        let tag_ids: Vec<i64> = tags.into_iter().map(|t| t.id()).unique().collect();
+        repo.tag()
+            .upsert_mappings(vec![file.cd_id()], tag_ids)
+            .await?;
+
+        file
+    } else {
+        let mime_type = metadata
+            .mime_type
+            .or_else(|| sniff_mime_type(&bytes))
+            .unwrap_or_else(|| String::from("application/octet-stream"));
+        let (bytes, corrupt) = if settings.import.validate_images {
+            match validate_image_blocking(bytes, mime_type.clone()).await? {
+                (bytes, Ok(_)) => (bytes, false),
+                (bytes, Err(e)) => {
+                    tracing::warn!("imported file {} is corrupt: {}", metadata.name, e);
+                    (bytes, true)
+                }
+            }
+        } else {
+            (bytes, false)
+        };
+        let add_dto = AddFileDto {
+            content: bytes,
+            mime_type,
+            creation_time: metadata.creation_time,
+            change_time: metadata.change_time,
+            name: Some(metadata.name),
+            corrupt,
+            external_id,
+            generate_thumbnails: settings.import.auto_generate_thumbnails,
+        };
+        // new files are stored together with their tags so they are never untagged
+        let (file, _) = repo
+            .add_file_with_tags(add_dto, tags, &settings.tags)
+            .await?;
+
+        file
+    };
 
-        for thumb in thumbnails {
-            thumb.delete().await?;
+    Ok(file)
+}
+
+/// Imports the files one after the other and sends the progress after each file
+async fn import_files_from_paths(
+    ctx: &Context,
+    entries: Vec<ImportFileEntry>,
+    cancelled: &AtomicBool,
+) -> IPCResult<ImportFilesResponse> {
+    let repo = get_repo_from_context(ctx).await;
+    let encoding = get_cd_encoding_from_context(ctx).await;
+    let settings = get_settings_from_context(ctx).await;
+    let total = entries.len() as u64;
+    let mut processed = 0;
+    let mut files = Vec::new();
+    let mut failures = Vec::new();
+
+    for ImportFileEntry { metadata, tags } in entries {
+        if cancelled.load(Ordering::SeqCst) {
+            tracing::info!("import cancelled after {} of {} files", processed, total);
+            break;
         }
+        let path = metadata.path.clone();
+        let result = match tokio::fs::read(&path).await {
+            Ok(bytes) => add_file_to_repo(&repo, &settings, metadata, tags, None, bytes).await,
+            Err(e) => Err(e.into()),
+        };
+        let error = match result {
+            Ok(file) => {
+                files.push(FileBasicDataResponse::from_model((file, encoding)));
+                None
+            }
+            Err(e) => {
+                tracing::warn!("failed to import {}: {}", path, e);
+                failures.push(ImportFailure {
+                    path: path.clone(),
+                    error: e.to_string(),
+                });
+                Some(e.to_string())
+            }
+        };
+        processed += 1;
+        let progress = ImportProgressResponse {
+            processed,
+            total,
+            current_file: path,
+            failed: failures.len() as u64,
+            error,
+        };
+        ctx.emit("import_progress", ImportFilesEvent::Progress(progress))
+            .await?;
+    }
+
+    Ok(ImportFilesResponse {
+        files,
+        failures,
+        cancelled: processed < total,
+    })
+}
+
+/// Returns the size of the stored content of the file from its metadata
+async fn stored_size(repo: &Repo, file: &FileDto) -> RepoResult<u64> {
+    let metadata = repo
+        .file()
+        .metadata(file.id())
+        .await?
+        .ok_or_else(|| RepoError::from("file metadata not found"))?;
+
+    Ok(metadata.size() as u64)
+}
+
+/// Responds with the metadata and the contents of the thumbnail
+async fn thumbnail_response(ctx: &Context, thumbnail: ThumbnailDto) -> IPCResult<Response> {
+    let mut buf = Vec::new();
+    thumbnail.get_reader().await?.read_to_end(&mut buf).await?;
+    check_message_size(ctx, buf.len()).await?;
+    let byte_payload = BytePayload::new(buf);
+    let thumb_payload = ThumbnailMetadataResponse::from_model(thumbnail);
+
+    ctx.response(TandemPayload::new(thumb_payload, byte_payload))
+}
+
+/// Returns the thumbnail with the crop within the size range for every content descriptor.
+/// Missing thumbnails are created for images and videos
+async fn thumbnails_for_cds_in_size_range(
+    ctx: &Context,
+    cd_ids: Vec<i64>,
+    min_size: (u32, u32),
+    max_size: (u32, u32),
+    crop: ThumbnailCrop,
+) -> RepoResult<Vec<(i64, ThumbnailDto)>> {
+    let repo = get_repo_from_context(ctx).await;
+    let thumbnail_map = repo.file().thumbnails_by_cd_ids(cd_ids).await?;
+    let mut found_thumbnails = Vec::with_capacity(thumbnail_map.len());
+    let mut missing_cd_ids = Vec::new();
+
+    for (cd_id, thumbnails) in thumbnail_map {
+        match nearest_thumbnail_in_size_range(thumbnails, min_size, max_size, crop) {
+            Some(thumbnail) => found_thumbnails.push((cd_id, thumbnail)),
+            None => missing_cd_ids.push(cd_id),
+        }
+    }
+    let mut missing_files = HashMap::new();
+
+    for file in repo.file().all_by_cd_id(missing_cd_ids).await? {
+        let mime_type = file.mime_type();
+
+        if mime_type.starts_with("image/") || mime_type.starts_with("video/") {
+            missing_files.entry(file.cd_id()).or_insert(file);
+        }
+    }
+    let middle_size = ((max_size.0 + min_size.0) / 2, (max_size.1 + min_size.1) / 2);
+
+    for (cd_id, file) in missing_files {
+        // a file that can't be thumbnailed shouldn't fail the thumbnails of the others
+        match repo
+            .file()
+            .create_thumbnails_with_crop(&file, vec![ThumbnailSize::Custom(middle_size)], crop)
+            .await
+        {
+            Ok(thumbnails) => found_thumbnails.extend(thumbnails.into_iter().map(|t| (cd_id, t))),
+            Err(e) => tracing::warn!("failed to create thumbnail for file {}: {}", file.id(), e),
+        }
+    }
+
+    Ok(found_thumbnails)
+}
+
+/// Returns the thumbnail with the given crop within the size range that is the closest
+/// to the middle of the range so that the best fitting of multiple stored sizes is used
+fn nearest_thumbnail_in_size_range(
+    thumbnails: Vec<ThumbnailDto>,
+    min_size: (u32, u32),
+    max_size: (u32, u32),
+    crop: ThumbnailCrop,
+) -> Option<ThumbnailDto> {
+    let middle_height = (min_size.0 + max_size.0) / 2;
+    let middle_width = (min_size.1 + max_size.1) / 2;
+
+    thumbnails
+        .into_iter()
+        .filter(|t| t.crop() == crop && is_thumbnail_in_size_range(t, min_size, max_size))
+        .min_by_key(|t| {
+            let Dimensions { height, width } = t.size();
+            height
+                .abs_diff(middle_height)
+                .min(width.abs_diff(middle_width))
+        })
+}
+
+/// Returns if the thumbnail fits into the given (height, width) ranges
+fn is_thumbnail_in_size_range(
+    thumbnail: &ThumbnailDto,
+    min_size: (u32, u32),
+    max_size: (u32, u32),
+) -> bool {
+    let Dimensions { height, width } = thumbnail.size();
+
+    (*height <= max_size.0 && *width <= max_size.1)
+        && (*width >= min_size.1 || *height >= min_size.0)
+}
+
+/// Returns the requested crop or the configured one if the request doesn't specify it
+async fn thumbnail_crop_for_request(
+    ctx: &Context,
+    crop: Option<ApiThumbnailCrop>,
+) -> ThumbnailCrop {
+    match crop {
+        Some(ApiThumbnailCrop::Fit) => ThumbnailCrop::Fit,
+        Some(ApiThumbnailCrop::CenterCrop) => ThumbnailCrop::CenterCrop,
+        None => get_settings_from_context(ctx).await.thumbnails.crop,
+    }
+}
 
-        Ok(Response::empty())
+/// Truncates the results to the maximum and returns if results were removed
+fn truncate_results<T>(results: &mut Vec<T>, max_results: Option<u64>) -> bool {
+    if let Some(max_results) = max_results {
+        let truncated = results.len() as u64 > max_results;
+        results.truncate(max_results as usize);
+        truncated
+    } else {
+        false
     }
 }
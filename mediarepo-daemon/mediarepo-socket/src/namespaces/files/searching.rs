@@ -1,46 +1,166 @@
 use std::collections::HashMap;
 
 use mediarepo_core::content_descriptor::decode_content_descriptor;
-use mediarepo_core::error::RepoResult;
+use mediarepo_core::error::{RepoError, RepoResult};
 use mediarepo_core::mediarepo_api::types::files::FileStatus as ApiFileStatus;
 use mediarepo_core::mediarepo_api::types::filtering::{
-    FilterExpression, FilterQuery, PropertyQuery, TagQuery, ValueComparator,
+    FilterExpression, FilterQuery, Orientation as ApiOrientation, PropertyQuery, TagQuery,
+    ValueComparator,
 };
+use mediarepo_core::settings::TagSettings;
 use mediarepo_logic::dao::file::find::NegatableComparator::{Is, IsNot};
-use mediarepo_logic::dao::file::find::{FilterFileProperty, FilterProperty, OrderingComparator};
+use mediarepo_logic::dao::file::find::{
+    FilterFileProperty, FilterProperty, NamespaceFilter, OrderingComparator, Orientation,
+};
 use mediarepo_logic::dao::repo::Repo;
 use mediarepo_logic::dao::DaoProvider;
-use mediarepo_logic::dto::{FileDto, FileStatus};
+use mediarepo_logic::dto::FileStatus;
 
+/// Resolves the tags of the filter expressions and converts them into filter properties
 #[tracing::instrument(level = "debug", skip(repo))]
-pub async fn find_files_for_filters(
+pub async fn filters_for_expressions(
     repo: &Repo,
-    expressions: Vec<FilterExpression>,
-) -> RepoResult<Vec<FileDto>> {
-    let tag_names = get_tag_names_from_expressions(&expressions);
-    let tag_id_map = repo.tag().normalized_tags_to_ids(tag_names).await?;
-    let filters = build_filters_from_expressions(expressions, &tag_id_map);
+    mut expressions: Vec<FilterExpression>,
+    settings: &TagSettings,
+) -> RepoResult<Vec<Vec<FilterProperty>>> {
+    if expressions
+        .iter()
+        .any(|e| matches!(e, FilterExpression::OrExpression(queries) if queries.is_empty()))
+    {
+        return Err(RepoError::InvalidFilter(String::from(
+            "an OR group needs at least one query",
+        )));
+    }
+    let case_sensitive = settings.case_sensitive;
+    if !case_sensitive {
+        lowercase_tag_queries(&mut expressions);
+    }
+    resolve_tag_aliases(repo, &mut expressions, case_sensitive).await?;
+    let (wildcards, tag_names): (Vec<String>, Vec<String>) =
+        get_tag_names_from_expressions(&expressions)
+            .into_iter()
+            .partition(|name| name.contains('*'));
+    let tag_id_map = repo
+        .tag()
+        .normalized_tags_to_ids(tag_names, case_sensitive)
+        .await?;
+    let wildcard_id_map = repo
+        .tag()
+        .wildcard_tags_to_ids(wildcards, case_sensitive, settings.max_wildcard_matches)
+        .await?;
+
+    Ok(build_filters_from_expressions(
+        expressions,
+        &tag_id_map,
+        &wildcard_id_map,
+        case_sensitive,
+    ))
+}
+
+/// Lowercases all tag queries so that they match the keys of the tag id map
+fn lowercase_tag_queries(expressions: &mut [FilterExpression]) {
+    let queries = expressions.iter_mut().flat_map(|e| match e {
+        FilterExpression::OrExpression(queries) => queries.iter_mut().collect(),
+        FilterExpression::Query(q) => vec![q],
+    });
+
+    for query in queries {
+        if let FilterQuery::Tag(tag) = query {
+            tag.tag = tag.tag.to_lowercase();
+        }
+    }
+}
+
+/// Returns the filter that limits a search to the statuses. Archived files are left out
+/// if no statuses are given and the expressions don't query the status themselves
+pub fn status_filter(
+    statuses: Option<Vec<ApiFileStatus>>,
+    expressions: &[FilterExpression],
+) -> RepoResult<Option<Vec<FilterProperty>>> {
+    match statuses {
+        Some(statuses) if statuses.is_empty() => Err(RepoError::InvalidFilter(String::from(
+            "at least one status needs to be included",
+        ))),
+        Some(statuses) => Ok(Some(
+            statuses
+                .into_iter()
+                .map(|s| {
+                    FilterProperty::FileProperty(FilterFileProperty::Status(Is(
+                        file_status_to_number(s),
+                    )))
+                })
+                .collect(),
+        )),
+        None if queries_status(expressions) => Ok(None),
+        None => Ok(Some(vec![FilterProperty::FileProperty(
+            FilterFileProperty::Status(IsNot(FileStatus::Archived as i64)),
+        )])),
+    }
+}
+
+fn queries_status(expressions: &[FilterExpression]) -> bool {
+    expressions
+        .iter()
+        .flat_map(|e| match e {
+            FilterExpression::OrExpression(queries) => queries.iter().collect(),
+            FilterExpression::Query(q) => vec![q],
+        })
+        .any(|q| matches!(q, FilterQuery::Property(PropertyQuery::Status(_))))
+}
+
+/// Replaces tag queries for aliases with queries for the tags the aliases resolve to
+async fn resolve_tag_aliases(
+    repo: &Repo,
+    expressions: &mut [FilterExpression],
+    case_sensitive: bool,
+) -> RepoResult<()> {
+    let names = get_tag_names_from_expressions(expressions)
+        .into_iter()
+        .filter(|name| !name.contains('*'))
+        .collect();
+    let canonical_names = repo.tag().canonical_names_for_aliases(names).await?;
+    if canonical_names.is_empty() {
+        return Ok(());
+    }
+    let queries = expressions.iter_mut().flat_map(|e| match e {
+        FilterExpression::OrExpression(queries) => queries.iter_mut().collect(),
+        FilterExpression::Query(q) => vec![q],
+    });
+
+    for query in queries {
+        if let FilterQuery::Tag(tag) = query {
+            if let Some(name) = canonical_names.get(&tag.tag) {
+                tag.tag = if case_sensitive {
+                    name.to_owned()
+                } else {
+                    name.to_lowercase()
+                };
+            }
+        }
+    }
 
-    repo.file().find(filters).await
+    Ok(())
 }
 
 #[tracing::instrument(level = "debug")]
-fn get_tag_names_from_expressions(expressions: &Vec<FilterExpression>) -> Vec<String> {
+fn get_tag_names_from_expressions(expressions: &[FilterExpression]) -> Vec<String> {
     expressions
         .iter()
         .flat_map(|f| match f {
             FilterExpression::OrExpression(queries) => queries
                 .iter()
                 .filter_map(|q| match q {
-                    FilterQuery::Tag(tag) => Some(tag.tag.to_owned()),
+                    FilterQuery::Tag(tag) if tag.wildcard_namespace().is_none() => {
+                        Some(tag.tag.to_owned())
+                    }
                     _ => None,
                 })
                 .collect::<Vec<String>>(),
             FilterExpression::Query(q) => match q {
-                FilterQuery::Tag(tag) => {
+                FilterQuery::Tag(tag) if tag.wildcard_namespace().is_none() => {
                     vec![tag.tag.to_owned()]
                 }
-                FilterQuery::Property(_) => {
+                _ => {
                     vec![]
                 }
             },
@@ -48,32 +168,27 @@ fn get_tag_names_from_expressions(expressions: &Vec<FilterExpression>) -> Vec<St
         .collect::<Vec<String>>()
 }
 
+/// Converts the expressions into groups of alternative filters that all have to match.
+/// Queries without a filter match all files so that a group containing such a query
+/// matches all files as well and is omitted
 #[tracing::instrument(level = "debug")]
 fn build_filters_from_expressions(
     expressions: Vec<FilterExpression>,
     tag_id_map: &HashMap<String, i64>,
+    wildcard_id_map: &HashMap<String, Vec<i64>>,
+    case_sensitive: bool,
 ) -> Vec<Vec<FilterProperty>> {
     expressions
         .into_iter()
         .filter_map(|e| {
-            let filters = match e {
-                FilterExpression::OrExpression(queries) => queries
-                    .into_iter()
-                    .filter_map(|q| map_query_to_filter(q, tag_id_map))
-                    .collect(),
-                FilterExpression::Query(q) => {
-                    if let Some(filter) = map_query_to_filter(q, tag_id_map) {
-                        vec![filter]
-                    } else {
-                        vec![]
-                    }
-                }
+            let queries = match e {
+                FilterExpression::OrExpression(queries) => queries,
+                FilterExpression::Query(q) => vec![q],
             };
-            if filters.is_empty() {
-                None
-            } else {
-                Some(filters)
-            }
+            queries
+                .into_iter()
+                .map(|q| map_query_to_filter(q, tag_id_map, wildcard_id_map, case_sensitive))
+                .collect::<Option<Vec<FilterProperty>>>()
         })
         .collect()
 }
@@ -81,9 +196,13 @@ fn build_filters_from_expressions(
 fn map_query_to_filter(
     query: FilterQuery,
     tag_id_map: &HashMap<String, i64>,
+    wildcard_id_map: &HashMap<String, Vec<i64>>,
+    case_sensitive: bool,
 ) -> Option<FilterProperty> {
     match query {
-        FilterQuery::Tag(tag_query) => map_tag_query_to_filter(tag_query, tag_id_map),
+        FilterQuery::Tag(tag_query) => {
+            map_tag_query_to_filter(tag_query, tag_id_map, wildcard_id_map, case_sensitive)
+        }
         FilterQuery::Property(property) => map_property_query_to_filter(property),
     }
 }
@@ -91,9 +210,17 @@ fn map_query_to_filter(
 fn map_tag_query_to_filter(
     query: TagQuery,
     tag_id_map: &HashMap<String, i64>,
+    wildcard_id_map: &HashMap<String, Vec<i64>>,
+    case_sensitive: bool,
 ) -> Option<FilterProperty> {
-    if query.tag.ends_with('*') {
-        map_wildcard_tag_to_filter(query, tag_id_map)
+    if let Some(namespace) = query.wildcard_namespace() {
+        Some(map_namespace_to_filter(
+            namespace.to_owned(),
+            query.negate,
+            case_sensitive,
+        ))
+    } else if query.is_wildcard() {
+        map_wildcard_tag_to_filter(query, wildcard_id_map)
     } else {
         map_tag_to_filter(query, tag_id_map)
     }
@@ -101,37 +228,47 @@ fn map_tag_query_to_filter(
 
 fn map_wildcard_tag_to_filter(
     query: TagQuery,
-    tag_id_map: &HashMap<String, i64>,
+    wildcard_id_map: &HashMap<String, Vec<i64>>,
 ) -> Option<FilterProperty> {
-    let filter_tag = query.tag.trim_end_matches('*');
-    let relevant_ids = tag_id_map
-        .iter()
-        .filter_map(|(name, id)| {
-            if name.starts_with(filter_tag) {
-                Some(*id)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<i64>>();
+    let relevant_ids = wildcard_id_map.get(&query.tag).cloned().unwrap_or_default();
 
-    if relevant_ids.is_empty() {
-        None
-    } else {
-        let comparator = if query.negate {
-            IsNot(relevant_ids)
+    if query.negate {
+        if relevant_ids.is_empty() {
+            None
         } else {
-            Is(relevant_ids)
-        };
-        Some(FilterProperty::TagWildcardIds(comparator))
+            Some(FilterProperty::TagWildcardIds(IsNot(relevant_ids)))
+        }
+    } else {
+        // without matching tags the filter matches no file
+        Some(FilterProperty::TagWildcardIds(Is(relevant_ids)))
     }
 }
 
+/// Translates a query for all tags of a namespace (`namespace:*`) into a filter
+/// that doesn't depend on the ids of the tags in the namespace
+fn map_namespace_to_filter(name: String, negate: bool, case_sensitive: bool) -> FilterProperty {
+    let namespace = NamespaceFilter {
+        name,
+        case_sensitive,
+    };
+    let comparator = if negate {
+        IsNot(namespace)
+    } else {
+        Is(namespace)
+    };
+
+    FilterProperty::TagNamespace(comparator)
+}
+
+/// Tags that don't exist are never assigned so a query for them matches no file
+/// and a negated query matches all files
 fn map_tag_to_filter(query: TagQuery, tag_id_map: &HashMap<String, i64>) -> Option<FilterProperty> {
-    tag_id_map.get(&query.tag).map(|id| {
-        let comparator = if query.negate { IsNot(*id) } else { Is(*id) };
-        FilterProperty::TagId(comparator)
-    })
+    match (tag_id_map.get(&query.tag), query.negate) {
+        (Some(id), false) => Some(FilterProperty::TagId(Is(*id))),
+        (Some(id), true) => Some(FilterProperty::TagId(IsNot(*id))),
+        (None, false) => Some(FilterProperty::TagWildcardIds(Is(vec![]))),
+        (None, true) => None,
+    }
 }
 
 fn map_property_query_to_filter(query: PropertyQuery) -> Option<FilterProperty> {
@@ -160,6 +297,31 @@ fn map_property_query_to_filter(query: PropertyQuery) -> Option<FilterProperty>
             .ok()
             .map(|cd| FilterProperty::ContentDescriptor(Is(cd))),
         PropertyQuery::Id(id) => Some(FilterProperty::FileProperty(FilterFileProperty::Id(Is(id)))),
+        PropertyQuery::Width(w) => Some(FilterProperty::FileProperty(FilterFileProperty::Width(
+            val_comparator_to_order(w, |v| v as i64),
+        ))),
+        PropertyQuery::Height(h) => Some(FilterProperty::FileProperty(FilterFileProperty::Height(
+            val_comparator_to_order(h, |v| v as i64),
+        ))),
+        PropertyQuery::AspectRatio(r) => Some(FilterProperty::FileProperty(
+            FilterFileProperty::AspectRatio(val_comparator_to_order(r, |r| r)),
+        )),
+        PropertyQuery::Orientation(o) => Some(FilterProperty::FileProperty(
+            FilterFileProperty::Orientation(map_orientation(o)),
+        )),
+        // an empty text is contained in every name so it doesn't restrict the search
+        PropertyQuery::Text(text) if text.trim().is_empty() => None,
+        PropertyQuery::Text(text) => Some(FilterProperty::FileProperty(FilterFileProperty::Text(
+            text.trim().to_string(),
+        ))),
+    }
+}
+
+fn map_orientation(orientation: ApiOrientation) -> Orientation {
+    match orientation {
+        ApiOrientation::Portrait => Orientation::Portrait,
+        ApiOrientation::Landscape => Orientation::Landscape,
+        ApiOrientation::Square => Orientation::Square,
     }
 }
 
@@ -1,15 +1,26 @@
 use crate::TypeMap;
 use mediarepo_core::bromine::prelude::*;
-use mediarepo_core::error::RepoResult;
-use mediarepo_core::mediarepo_api::types::jobs::{JobType, RunJobRequest};
+use mediarepo_core::content_descriptor::HashAlgorithm;
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_core::mediarepo_api::types::jobs::{
+    DatabaseBackupResponse, HashAlgorithm as ApiHashAlgorithm, JobInfoResponse,
+    JobProgressResponse, JobState as ApiJobState, JobType, MigrateStorageRequest, RunJobRequest,
+};
 use mediarepo_core::type_keys::{RepoPathKey, SettingsKey, SizeMetadataKey};
 use mediarepo_worker::handle::JobState;
 use mediarepo_worker::job_dispatcher::JobDispatcher;
 use mediarepo_worker::jobs::{
-    CalculateSizesJob, CheckIntegrityJob, GenerateMissingThumbsJob, Job, MigrateCDsJob, VacuumJob,
+    BackupDatabaseJob, CalculateSizesJob, CheckIntegrityJob, GenerateMissingThumbsJob,
+    GeneratePhashesJob, Job, MigrateCDsJob, MigrateStorageJob, ResniffMimeTypesJob, VacuumJob,
 };
+use mediarepo_worker::status_utils::SimpleProgress;
+use std::time::Duration;
+
+use crate::from_model::FromModel;
+use crate::utils::{get_job_dispatcher_from_context, get_settings_from_context};
 
-use crate::utils::get_job_dispatcher_from_context;
+/// The interval in which the progress of running jobs is sent to the client
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
 
 pub struct JobsNamespace;
 
@@ -21,7 +32,15 @@ impl NamespaceProvider for JobsNamespace {
     fn register(handler: &mut EventHandler) {
         events!(handler,
             "run_job" => Self::run_job,
-            "is_job_running" => Self::is_job_running
+            "is_job_running" => Self::is_job_running,
+            "cancel_job" => Self::cancel_job,
+            "pause_job" => Self::pause_job,
+            "resume_job" => Self::resume_job,
+            "list_jobs" => Self::list_jobs,
+            "job_progress" => Self::job_progress,
+            "generate_missing_thumbnails" => Self::generate_missing_thumbnails,
+            "migrate_storage" => Self::migrate_storage,
+            "backup_now" => Self::backup_now
         );
     }
 }
@@ -56,6 +75,20 @@ impl JobsNamespace {
                 )
                 .await?
             }
+            JobType::MigrateStorage => {
+                dispatch_job(&dispatcher, MigrateStorageJob::default(), run_request.sync).await?
+            }
+            JobType::GeneratePhashes => {
+                dispatch_job(&dispatcher, GeneratePhashesJob::default(), run_request.sync).await?
+            }
+            JobType::ResniffMimeTypes => {
+                dispatch_job(
+                    &dispatcher,
+                    ResniffMimeTypesJob::default(),
+                    run_request.sync,
+                )
+                .await?
+            }
         }
 
         Ok(Response::empty())
@@ -76,10 +109,229 @@ impl JobsNamespace {
             }
             JobType::CheckIntegrity => is_job_running::<CheckIntegrityJob>(&dispatcher).await,
             JobType::Vacuum => is_job_running::<VacuumJob>(&dispatcher).await,
+            JobType::MigrateStorage => is_job_running::<MigrateStorageJob>(&dispatcher).await,
+            JobType::GeneratePhashes => is_job_running::<GeneratePhashesJob>(&dispatcher).await,
+            JobType::ResniffMimeTypes => is_job_running::<ResniffMimeTypesJob>(&dispatcher).await,
         };
 
         Response::payload(ctx, running)
     }
+
+    /// Cancels the current run of a job and returns if the job was running
+    #[tracing::instrument(skip_all)]
+    pub async fn cancel_job(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let job_type = event.payload::<JobType>()?;
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+
+        let cancelled = match job_type {
+            JobType::MigrateContentDescriptors => cancel_job::<MigrateCDsJob>(&dispatcher).await,
+            JobType::CalculateSizes => cancel_job::<CalculateSizesJob>(&dispatcher).await,
+            JobType::GenerateThumbnails => {
+                cancel_job::<GenerateMissingThumbsJob>(&dispatcher).await
+            }
+            JobType::CheckIntegrity => cancel_job::<CheckIntegrityJob>(&dispatcher).await,
+            JobType::Vacuum => cancel_job::<VacuumJob>(&dispatcher).await,
+            JobType::MigrateStorage => cancel_job::<MigrateStorageJob>(&dispatcher).await,
+            JobType::GeneratePhashes => cancel_job::<GeneratePhashesJob>(&dispatcher).await,
+            JobType::ResniffMimeTypes => cancel_job::<ResniffMimeTypesJob>(&dispatcher).await,
+        };
+
+        Response::payload(ctx, cancelled)
+    }
+
+    /// Pauses a running job at its next checkpoint and returns if the job was running
+    #[tracing::instrument(skip_all)]
+    pub async fn pause_job(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let job_type = event.payload::<JobType>()?;
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+
+        let paused = match job_type {
+            JobType::MigrateContentDescriptors => pause_job::<MigrateCDsJob>(&dispatcher).await,
+            JobType::CalculateSizes => pause_job::<CalculateSizesJob>(&dispatcher).await,
+            JobType::GenerateThumbnails => pause_job::<GenerateMissingThumbsJob>(&dispatcher).await,
+            JobType::CheckIntegrity => pause_job::<CheckIntegrityJob>(&dispatcher).await,
+            JobType::Vacuum => pause_job::<VacuumJob>(&dispatcher).await,
+            JobType::MigrateStorage => pause_job::<MigrateStorageJob>(&dispatcher).await,
+            JobType::GeneratePhashes => pause_job::<GeneratePhashesJob>(&dispatcher).await,
+            JobType::ResniffMimeTypes => pause_job::<ResniffMimeTypesJob>(&dispatcher).await,
+        };
+
+        Response::payload(ctx, paused)
+    }
+
+    /// Resumes a paused job and returns if the job was paused
+    #[tracing::instrument(skip_all)]
+    pub async fn resume_job(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let job_type = event.payload::<JobType>()?;
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+
+        let resumed = match job_type {
+            JobType::MigrateContentDescriptors => resume_job::<MigrateCDsJob>(&dispatcher).await,
+            JobType::CalculateSizes => resume_job::<CalculateSizesJob>(&dispatcher).await,
+            JobType::GenerateThumbnails => {
+                resume_job::<GenerateMissingThumbsJob>(&dispatcher).await
+            }
+            JobType::CheckIntegrity => resume_job::<CheckIntegrityJob>(&dispatcher).await,
+            JobType::Vacuum => resume_job::<VacuumJob>(&dispatcher).await,
+            JobType::MigrateStorage => resume_job::<MigrateStorageJob>(&dispatcher).await,
+            JobType::GeneratePhashes => resume_job::<GeneratePhashesJob>(&dispatcher).await,
+            JobType::ResniffMimeTypes => resume_job::<ResniffMimeTypesJob>(&dispatcher).await,
+        };
+
+        Response::payload(ctx, resumed)
+    }
+
+    /// Returns the state and progress of all jobs that have been started
+    #[tracing::instrument(skip_all)]
+    pub async fn list_jobs(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+
+        let jobs: Vec<JobInfoResponse> = vec![
+            job_info::<MigrateCDsJob>(&dispatcher, JobType::MigrateContentDescriptors, |p| {
+                Some(progress_response(p))
+            })
+            .await,
+            job_info::<CalculateSizesJob>(&dispatcher, JobType::CalculateSizes, |s| {
+                Some(progress_response(&s.progress))
+            })
+            .await,
+            job_info::<GenerateMissingThumbsJob>(&dispatcher, JobType::GenerateThumbnails, |p| {
+                Some(progress_response(p))
+            })
+            .await,
+            job_info::<CheckIntegrityJob>(&dispatcher, JobType::CheckIntegrity, |p| {
+                Some(progress_response(p))
+            })
+            .await,
+            job_info::<VacuumJob>(&dispatcher, JobType::Vacuum, |_| None).await,
+            job_info::<MigrateStorageJob>(&dispatcher, JobType::MigrateStorage, |p| {
+                Some(progress_response(p))
+            })
+            .await,
+            job_info::<GeneratePhashesJob>(&dispatcher, JobType::GeneratePhashes, |p| {
+                Some(progress_response(p))
+            })
+            .await,
+            job_info::<ResniffMimeTypesJob>(&dispatcher, JobType::ResniffMimeTypes, |p| {
+                Some(progress_response(p))
+            })
+            .await,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Response::payload(ctx, jobs)
+    }
+
+    /// Returns the progress of the current or last run of a job
+    /// if the job reports its progress
+    #[tracing::instrument(skip_all)]
+    pub async fn job_progress(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let job_type = event.payload::<JobType>()?;
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+
+        let progress = match job_type {
+            JobType::MigrateContentDescriptors => job_progress::<MigrateCDsJob>(&dispatcher).await,
+            JobType::GenerateThumbnails => {
+                job_progress::<GenerateMissingThumbsJob>(&dispatcher).await
+            }
+            JobType::CheckIntegrity => job_progress::<CheckIntegrityJob>(&dispatcher).await,
+            JobType::MigrateStorage => job_progress::<MigrateStorageJob>(&dispatcher).await,
+            JobType::GeneratePhashes => job_progress::<GeneratePhashesJob>(&dispatcher).await,
+            JobType::ResniffMimeTypes => job_progress::<ResniffMimeTypesJob>(&dispatcher).await,
+            JobType::CalculateSizes | JobType::Vacuum => None,
+        };
+
+        Response::payload(ctx, progress)
+    }
+
+    /// Starts generating thumbnails for all files without one and sends the progress
+    /// until the job has finished. Joins the job if it is already running
+    #[tracing::instrument(skip_all)]
+    pub async fn generate_missing_thumbnails(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+        let handle = match dispatcher.get_handle::<GenerateMissingThumbsJob>().await {
+            Some(handle) if handle.state().await.is_active() => handle,
+            _ => {
+                dispatcher
+                    .dispatch(GenerateMissingThumbsJob::default())
+                    .await
+            }
+        };
+
+        while handle.state().await != JobState::Finished {
+            tokio::time::sleep(PROGRESS_INTERVAL).await;
+            let progress = progress_response(&*handle.status().read().await);
+            ctx.emit("job_progress", progress).await?;
+        }
+        let progress = progress_response(&*handle.status().read().await);
+
+        ctx.response(progress)
+    }
+
+    /// Starts storing all files under content descriptors of the given hashing algorithm
+    /// and sends the progress until all files have been migrated
+    #[tracing::instrument(skip_all)]
+    pub async fn migrate_storage(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<MigrateStorageRequest>()?;
+        let algorithm = map_hash_algorithm(request.algorithm);
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await;
+
+        if is_job_running::<MigrateStorageJob>(&dispatcher).await {
+            return Err(RepoError::from("a storage migration is already running").into());
+        }
+        if settings.import.hash_algorithm != algorithm {
+            tracing::warn!(
+                "migrating the storage to {:?} while new files are stored with {:?}",
+                algorithm,
+                settings.import.hash_algorithm
+            );
+        }
+        let mut handle = dispatcher.dispatch(MigrateStorageJob::new(algorithm)).await;
+
+        while handle.state().await != JobState::Finished {
+            tokio::time::sleep(PROGRESS_INTERVAL).await;
+            let progress = progress_response(&*handle.status().read().await);
+            ctx.emit("job_progress", progress).await?;
+        }
+        if let Some(result) = handle.take_result().await {
+            result?;
+        }
+        let progress = progress_response(&*handle.status().read().await);
+
+        ctx.response(progress)
+    }
+
+    /// Creates a backup of the database in the configured backup directory
+    /// and returns where it was stored
+    #[tracing::instrument(skip_all)]
+    pub async fn backup_now(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let (repo_path, settings) = {
+            let data = ctx.data.read().await;
+            (
+                data.get::<RepoPathKey>().unwrap().clone(),
+                data.get::<SettingsKey>().unwrap().clone(),
+            )
+        };
+        let job =
+            BackupDatabaseJob::new(settings.backup.backup_dir(&repo_path), settings.backup.keep);
+        let dispatcher = get_job_dispatcher_from_context(ctx).await;
+        let mut handle = dispatcher.dispatch(job).await;
+        let backup = handle
+            .take_result()
+            .await
+            .ok_or_else(|| RepoError::from("the backup didn't finish"))??;
+
+        Response::payload(
+            ctx,
+            DatabaseBackupResponse {
+                path: backup.path.to_string_lossy().to_string(),
+                size: backup.size,
+            },
+        )
+    }
 }
 
 async fn dispatch_job<J: 'static + Job>(
@@ -88,7 +340,7 @@ async fn dispatch_job<J: 'static + Job>(
     sync: bool,
 ) -> RepoResult<()> {
     let mut handle = if let Some(handle) = dispatcher.get_handle::<J>().await {
-        if handle.state().await == JobState::Running {
+        if handle.state().await.is_active() {
             handle
         } else {
             dispatcher.dispatch(job).await
@@ -129,10 +381,78 @@ async fn calculate_all_sizes(ctx: &Context) -> RepoResult<()> {
     Ok(())
 }
 
+async fn cancel_job<T: 'static + Job>(dispatcher: &JobDispatcher) -> bool {
+    if let Some(handle) = dispatcher.get_handle::<T>().await {
+        handle.cancel().await
+    } else {
+        false
+    }
+}
+
+async fn pause_job<T: 'static + Job>(dispatcher: &JobDispatcher) -> bool {
+    if let Some(handle) = dispatcher.get_handle::<T>().await {
+        handle.pause().await
+    } else {
+        false
+    }
+}
+
+async fn resume_job<T: 'static + Job>(dispatcher: &JobDispatcher) -> bool {
+    if let Some(handle) = dispatcher.get_handle::<T>().await {
+        handle.resume().await
+    } else {
+        false
+    }
+}
+
+async fn job_info<T: 'static + Job>(
+    dispatcher: &JobDispatcher,
+    job_type: JobType,
+    progress: fn(&T::JobStatus) -> Option<JobProgressResponse>,
+) -> Option<JobInfoResponse> {
+    let handle = dispatcher.get_handle::<T>().await?;
+    let state = ApiJobState::from_model(handle.state().await);
+    let progress = progress(&*handle.status().read().await);
+
+    Some(JobInfoResponse {
+        job_type,
+        state,
+        progress,
+    })
+}
+
+async fn job_progress<T: 'static + Job<JobStatus = SimpleProgress>>(
+    dispatcher: &JobDispatcher,
+) -> Option<JobProgressResponse> {
+    let handle = dispatcher.get_handle::<T>().await?;
+    let progress = handle.status().read().await;
+
+    Some(progress_response(&progress))
+}
+
+fn progress_response(progress: &SimpleProgress) -> JobProgressResponse {
+    JobProgressResponse {
+        current: progress.current,
+        total: progress.total,
+    }
+}
+
+fn map_hash_algorithm(algorithm: ApiHashAlgorithm) -> HashAlgorithm {
+    match algorithm {
+        ApiHashAlgorithm::Sha2_256 => HashAlgorithm::Sha2_256,
+        ApiHashAlgorithm::Sha2_512 => HashAlgorithm::Sha2_512,
+        ApiHashAlgorithm::Sha3_256 => HashAlgorithm::Sha3_256,
+        ApiHashAlgorithm::Sha3_512 => HashAlgorithm::Sha3_512,
+        ApiHashAlgorithm::Blake2b256 => HashAlgorithm::Blake2b256,
+        ApiHashAlgorithm::Blake2b512 => HashAlgorithm::Blake2b512,
+        ApiHashAlgorithm::Blake3 => HashAlgorithm::Blake3,
+    }
+}
+
+/// Returns if the job is in the middle of a run, which includes paused jobs
 async fn is_job_running<T: 'static + Job>(dispatcher: &JobDispatcher) -> bool {
     if let Some(handle) = dispatcher.get_handle::<T>().await {
-        let state = handle.state().await;
-        state == JobState::Running
+        handle.state().await.is_active()
     } else {
         false
     }
@@ -1,6 +1,7 @@
 use mediarepo_core::bromine::prelude::AsyncStreamProtocolListener;
 use mediarepo_core::bromine::{namespace, namespace::Namespace, IPCBuilder};
 
+pub mod attributes;
 pub mod files;
 pub mod jobs;
 pub mod presets;
@@ -14,4 +15,5 @@ pub fn build_namespaces<L: AsyncStreamProtocolListener>(builder: IPCBuilder<L>)
         .add_namespace(namespace!(repo::RepoNamespace))
         .add_namespace(namespace!(jobs::JobsNamespace))
         .add_namespace(namespace!(presets::PresetsNamespace))
+        .add_namespace(namespace!(attributes::AttributesNamespace))
 }
@@ -5,11 +5,14 @@ use tokio::fs;
 use crate::TypeMap;
 use mediarepo_core::bromine::prelude::*;
 use mediarepo_core::mediarepo_api::types::repo::{
-    FrontendState, RepositoryMetadata, SizeMetadata, SizeType,
+    CheckIntegrityEvent, CheckIntegrityRequest, CleanupOrphanedBlobsRequest, DedupStatsResponse,
+    FrontendState, IntegrityProgressResponse, IntegrityReportResponse, OrphanedBlobResponse,
+    RepositoryMetadata, SizeMetadata, SizeType, StorageResponse,
 };
 use mediarepo_core::type_keys::{RepoPathKey, SettingsKey, SizeMetadataKey};
 
-use crate::utils::get_repo_from_context;
+use crate::from_model::FromModel;
+use crate::utils::{get_cd_encoding_from_context, get_repo_from_context};
 
 pub struct RepoNamespace;
 
@@ -22,6 +25,11 @@ impl NamespaceProvider for RepoNamespace {
         events!(handler,
             "repository_metadata" => Self::get_metadata,
             "size_metadata" => Self::get_size_metadata,
+            "dedup_stats" => Self::get_dedup_stats,
+            "list_storages" => Self::list_storages,
+            "find_orphaned_blobs" => Self::find_orphaned_blobs,
+            "cleanup_orphaned_blobs" => Self::cleanup_orphaned_blobs,
+            "check_integrity" => Self::check_integrity,
             "frontend_state" => Self::frontend_state,
             "set_frontend_state" => Self::set_frontend_state
         );
@@ -63,6 +71,105 @@ impl RepoNamespace {
         ctx.response(SizeMetadata { size, size_type })
     }
 
+    /// Returns the main and thumbnail storage of the repository
+    #[tracing::instrument(skip_all)]
+    async fn list_storages(ctx: &Context, _: Event) -> IPCResult<Response> {
+        let data = ctx.data.read().await;
+        let settings = data.get::<SettingsKey>().unwrap();
+        let repo_path = data.get::<RepoPathKey>().unwrap();
+
+        let storages = vec![
+            StorageResponse {
+                name: String::from("main"),
+                path: settings
+                    .paths
+                    .files_dir(repo_path)
+                    .to_string_lossy()
+                    .to_string(),
+            },
+            StorageResponse {
+                name: String::from("thumbnails"),
+                path: settings
+                    .paths
+                    .thumbs_dir(repo_path)
+                    .to_string_lossy()
+                    .to_string(),
+            },
+        ];
+
+        ctx.response(storages)
+    }
+
+    /// Returns the number of files and stored contents and how much space is saved
+    /// by files sharing their content
+    #[tracing::instrument(skip_all)]
+    async fn get_dedup_stats(ctx: &Context, _: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let stats = repo.dedup_stats().await?;
+
+        ctx.response(DedupStatsResponse {
+            file_count: stats.file_count as u64,
+            blob_count: stats.blob_count as u64,
+            bytes_saved: stats.bytes_saved as u64,
+        })
+    }
+
+    /// Returns the blobs of the file storage that don't belong to any file
+    #[tracing::instrument(skip_all)]
+    async fn find_orphaned_blobs(ctx: &Context, _: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let blobs: Vec<OrphanedBlobResponse> = repo
+            .find_orphaned_blobs()
+            .await?
+            .into_iter()
+            .map(|b| OrphanedBlobResponse::from_model((b, encoding)))
+            .collect();
+
+        ctx.response(blobs)
+    }
+
+    /// Deletes the blobs of the file storage that don't belong to any file
+    /// and returns them. Nothing is deleted for dry runs
+    #[tracing::instrument(skip_all)]
+    async fn cleanup_orphaned_blobs(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<CleanupOrphanedBlobsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let blobs: Vec<OrphanedBlobResponse> = repo
+            .cleanup_orphaned_blobs(request.dry_run)
+            .await?
+            .into_iter()
+            .map(|b| OrphanedBlobResponse::from_model((b, encoding)))
+            .collect();
+
+        ctx.response(blobs)
+    }
+
+    /// Checks the stored files against their content descriptors. The progress is emitted
+    /// after each content and the response contains the contents that are corrupt or missing
+    #[tracing::instrument(skip_all)]
+    async fn check_integrity(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<CheckIntegrityRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
+        let report = repo
+            .check_integrity(request.tag_ids, |progress| async move {
+                let progress = IntegrityProgressResponse::from_model(progress);
+                ctx.emit(
+                    "integrity_progress",
+                    CheckIntegrityEvent::Progress(progress),
+                )
+                .await?;
+                Ok(())
+            })
+            .await?;
+
+        ctx.response(CheckIntegrityEvent::Finished(
+            IntegrityReportResponse::from_model((report, encoding)),
+        ))
+    }
+
     #[tracing::instrument(skip_all)]
     async fn frontend_state(ctx: &Context, _: Event) -> IPCResult<Response> {
         let path = get_frontend_state_path(ctx).await?;
@@ -1,20 +1,33 @@
+use chrono::Local;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use mediarepo_core::bromine::prelude::*;
-use mediarepo_core::content_descriptor::{decode_content_descriptor, encode_content_descriptor};
+use mediarepo_core::content_descriptor::{decode_content_descriptor, encode_content_descriptor_as};
+use mediarepo_core::error::RepoResult;
+use mediarepo_core::itertools::Itertools;
 use mediarepo_core::mediarepo_api::types::files::{
-    GetFileTagMapRequest, GetFileTagsRequest, GetFilesTagsRequest,
+    GetFileTagMapRequest, GetFileTagsPageRequest, GetFileTagsRequest, GetFilesTagsRequest,
 };
 use mediarepo_core::mediarepo_api::types::tags::{
-    ChangeFileTagsRequest, NamespaceResponse, TagResponse,
+    AddTagAliasRequest, AggregateTagsRequest, AutocompleteTagsRequest, ChangeFileTagsRequest,
+    ConvertTagToNamespacedRequest, FileTagsPageResponse, GetTagCountsRequest,
+    IdentifyingTagsResponse, MergeNamespacesRequest, MergeTagsRequest, MissingTagsRequest,
+    NamespaceMergeResponse, NamespaceResponse, TagAggregateResponse, TagAliasResponse,
+    TagChangeResponse, TagFacetsRequest, TagFilesRequest, TagImplicationRequest, TagResponse,
 };
 use mediarepo_core::utils::parse_namespace_and_tag;
+use mediarepo_logic::dao::repo::Repo;
 use mediarepo_logic::dao::DaoProvider;
 use mediarepo_logic::dto::AddTagDto;
 
 use crate::from_model::FromModel;
-use crate::utils::{file_by_identifier, get_repo_from_context};
+use crate::namespaces::files::searching::filters_for_expressions;
+use crate::type_keys::{TagChange, TagChangeHistoryKey};
+use crate::utils::{
+    file_by_identifier, get_cd_encoding_from_context, get_repo_from_context,
+    get_settings_from_context,
+};
 
 pub struct TagsNamespace;
 
@@ -28,10 +41,33 @@ impl NamespaceProvider for TagsNamespace {
             "all_tags" => Self::all_tags,
             "all_namespaces" => Self::all_namespaces,
             "tags_for_file" => Self::tags_for_file,
+            "tags_for_file_paginated" => Self::tags_for_file_paginated,
             "tags_for_files" => Self::tags_for_files,
             "file_tag_map" => Self::tag_cd_map_for_files,
             "create_tags" => Self::create_tags,
-            "change_file_tags" => Self::change_file_tags
+            "change_file_tags" => Self::change_file_tags,
+            "add_tags_to_files" => Self::add_tags_to_files,
+            "remove_tags_from_files" => Self::remove_tags_from_files,
+            "tag_facets" => Self::tag_facets,
+            "recent_tag_changes_for_file" => Self::recent_tag_changes_for_file,
+            "merge_namespaces" => Self::merge_namespaces,
+            "convert_tag_to_namespaced" => Self::convert_tag_to_namespaced,
+            "find_tag_variants" => Self::find_tag_variants,
+            "find_similar_tags" => Self::find_similar_tags,
+            "merge_tags" => Self::merge_tags,
+            "all_tag_aliases" => Self::all_tag_aliases,
+            "tag_aliases_for_tag" => Self::tag_aliases_for_tag,
+            "add_tag_alias" => Self::add_tag_alias,
+            "remove_tag_alias" => Self::remove_tag_alias,
+            "tag_implications" => Self::tag_implications,
+            "add_tag_implication" => Self::add_tag_implication,
+            "remove_tag_implication" => Self::remove_tag_implication,
+            "resolve_implications" => Self::resolve_implications,
+            "aggregate_tags_for_files" => Self::aggregate_tags_for_files,
+            "get_tag_counts" => Self::get_tag_counts,
+            "autocomplete_tags" => Self::autocomplete_tags,
+            "missing_tags_for_file" => Self::missing_tags_for_file,
+            "minimal_identifying_tags" => Self::minimal_identifying_tags
         );
     }
 }
@@ -79,6 +115,24 @@ impl TagsNamespace {
         ctx.response(responses)
     }
 
+    /// Returns a page of the tags of a single file with the number of all its tags
+    #[tracing::instrument(skip_all)]
+    async fn tags_for_file_paginated(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let request = event.payload::<GetFileTagsPageRequest>()?;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let tags = repo
+            .tag()
+            .tags_for_cd_paginated(file.cd_id(), request.offset, request.limit)
+            .await?;
+        let total = repo.tag().tag_count_for_cd(file.cd_id()).await?;
+
+        ctx.response(FileTagsPageResponse {
+            tags: tags.into_iter().map(TagResponse::from_model).collect(),
+            total,
+        })
+    }
+
     /// Returns all tags for a given list of file hashes
     #[tracing::instrument(skip_all)]
     async fn tags_for_files(ctx: &Context, event: Event) -> IPCResult<Response> {
@@ -106,6 +160,7 @@ impl TagsNamespace {
     async fn tag_cd_map_for_files(ctx: &Context, event: Event) -> IPCResult<Response> {
         let request = event.payload::<GetFileTagMapRequest>()?;
         let repo = get_repo_from_context(ctx).await;
+        let encoding = get_cd_encoding_from_context(ctx).await;
         let cds = request
             .cds
             .into_iter()
@@ -117,7 +172,7 @@ impl TagsNamespace {
             .all_for_cds_map(cds)
             .await?
             .into_iter()
-            .map(|(cd, tags)| (encode_content_descriptor(&cd), tags))
+            .map(|(cd, tags)| (encode_content_descriptor_as(&cd, encoding), tags))
             .map(|(cd, tags)| {
                 (
                     cd,
@@ -131,22 +186,77 @@ impl TagsNamespace {
         ctx.response(mappings)
     }
 
-    /// Creates all tags given as input or returns the existing tags
+    /// Creates all valid tags given as input or returns the existing tags.
+    /// Returns the reason why a tag couldn't be created for each invalid tag
     #[tracing::instrument(skip_all)]
     async fn create_tags(ctx: &Context, event: Event) -> IPCResult<Response> {
         let repo = get_repo_from_context(ctx).await;
         let tags = event.payload::<Vec<String>>()?;
+        let settings = get_settings_from_context(ctx).await.tags;
         let created_tags = repo
             .tag()
-            .add_all(
+            .add_all_checked(
                 tags.into_iter()
-                    .map(parse_namespace_and_tag)
+                    .map(|tag| parse_namespace_and_tag(tag, settings.case_sensitive))
                     .map(AddTagDto::from_tuple)
                     .collect(),
+                &settings,
             )
             .await?;
 
-        let responses: Vec<TagResponse> = created_tags
+        let responses: Vec<Result<TagResponse, String>> = created_tags
+            .into_iter()
+            .map(|result| result.map(TagResponse::from_model))
+            .collect();
+
+        ctx.response(responses)
+    }
+
+    /// Moves all tags of one namespace into another one and deletes the emptied namespace
+    #[tracing::instrument(skip_all)]
+    async fn merge_namespaces(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<MergeNamespacesRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let merge = repo
+            .tag()
+            .merge_namespaces(request.from_id, request.into_id)
+            .await?;
+
+        ctx.response(NamespaceMergeResponse::from_model(merge))
+    }
+
+    /// Moves a tag into a namespace. The tag is merged into the namespaced tag if
+    /// that one already exists
+    #[tracing::instrument(skip_all)]
+    async fn convert_tag_to_namespaced(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<ConvertTagToNamespacedRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await.tags;
+        let namespace = if settings.case_sensitive {
+            request.namespace
+        } else {
+            request.namespace.to_lowercase()
+        };
+        let tag = repo
+            .tag()
+            .convert_to_namespaced(request.tag_id, namespace, &settings)
+            .await?;
+
+        ctx.response(TagResponse::from_model(tag))
+    }
+
+    /// Returns all tags with the same name as the given tag across all namespaces
+    /// including the tag without a namespace
+    #[tracing::instrument(skip_all)]
+    async fn find_tag_variants(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let tag = event.payload::<String>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let case_sensitive = get_settings_from_context(ctx).await.tags.case_sensitive;
+        let (_, name) = parse_namespace_and_tag(tag, case_sensitive);
+        let responses: Vec<TagResponse> = repo
+            .tag()
+            .find_tag_variants(name, case_sensitive)
+            .await?
             .into_iter()
             .map(TagResponse::from_model)
             .collect();
@@ -154,6 +264,152 @@ impl TagsNamespace {
         ctx.response(responses)
     }
 
+    /// Returns groups of tags that only differ in casing, whitespace or underscores
+    #[tracing::instrument(skip_all)]
+    async fn find_similar_tags(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let groups: Vec<Vec<TagResponse>> = repo
+            .tag()
+            .find_similar_tags()
+            .await?
+            .into_iter()
+            .map(|tags| tags.into_iter().map(TagResponse::from_model).collect())
+            .collect();
+
+        ctx.response(groups)
+    }
+
+    /// Merges several tags into one tag by moving their mappings to it
+    #[tracing::instrument(skip_all)]
+    async fn merge_tags(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<MergeTagsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let tag = repo
+            .tag()
+            .merge_tags(request.from_ids, request.into_id)
+            .await?;
+
+        ctx.response(TagResponse::from_model(tag))
+    }
+
+    /// Returns all tag aliases
+    #[tracing::instrument(skip_all)]
+    async fn all_tag_aliases(ctx: &Context, _event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let aliases: Vec<TagAliasResponse> = repo
+            .tag()
+            .all_aliases()
+            .await?
+            .into_iter()
+            .map(TagAliasResponse::from_model)
+            .collect();
+
+        ctx.response(aliases)
+    }
+
+    /// Returns all aliases resolving to a tag
+    #[tracing::instrument(skip_all)]
+    async fn tag_aliases_for_tag(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let tag_id = event.payload::<i64>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let aliases: Vec<TagAliasResponse> = repo
+            .tag()
+            .aliases_for(tag_id)
+            .await?
+            .into_iter()
+            .map(TagAliasResponse::from_model)
+            .collect();
+
+        ctx.response(aliases)
+    }
+
+    /// Adds an alias for a tag. The files of an existing tag with the name
+    /// of the alias are assigned to the aliased tag
+    #[tracing::instrument(skip_all)]
+    async fn add_tag_alias(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<AddTagAliasRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await.tags;
+        let alias = AddTagDto::from_tuple(parse_namespace_and_tag(
+            request.alias,
+            settings.case_sensitive,
+        ));
+        let alias = repo
+            .tag()
+            .add_alias(alias, request.tag_id, &settings)
+            .await?;
+
+        ctx.response(TagAliasResponse::from_model(alias))
+    }
+
+    /// Removes a tag alias
+    #[tracing::instrument(skip_all)]
+    async fn remove_tag_alias(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let alias_id = event.payload::<i64>()?;
+        let repo = get_repo_from_context(ctx).await;
+        repo.tag().remove_alias(alias_id).await?;
+
+        Ok(Response::empty())
+    }
+
+    /// Returns all tags implied by a tag including the tags implied by those tags
+    #[tracing::instrument(skip_all)]
+    async fn tag_implications(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let tag_id = event.payload::<i64>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let tags: Vec<TagResponse> = repo
+            .tag()
+            .implications_for(tag_id)
+            .await?
+            .into_iter()
+            .map(TagResponse::from_model)
+            .collect();
+
+        ctx.response(tags)
+    }
+
+    /// Adds an implication between two tags and assigns the implied tags
+    /// to all files that already have the tag
+    #[tracing::instrument(skip_all)]
+    async fn add_tag_implication(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<TagImplicationRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        repo.tag()
+            .add_implication(request.tag_id, request.implied_tag_id)
+            .await?;
+
+        Ok(Response::empty())
+    }
+
+    /// Removes an implication between two tags together with the implied tags
+    /// that no other tag of a file requires
+    #[tracing::instrument(skip_all)]
+    async fn remove_tag_implication(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<TagImplicationRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        repo.tag()
+            .remove_implication(request.tag_id, request.implied_tag_id)
+            .await?;
+
+        Ok(Response::empty())
+    }
+
+    /// Returns the given tags together with all tags they imply
+    #[tracing::instrument(skip_all)]
+    async fn resolve_implications(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let tag_ids = event.payload::<Vec<i64>>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let tags: Vec<TagResponse> = repo
+            .tag()
+            .resolve_implications(tag_ids)
+            .await?
+            .into_iter()
+            .map(TagResponse::from_model)
+            .collect();
+
+        ctx.response(tags)
+    }
+
     /// Changes tags of a file
     /// it removes the tags from the removed list and adds the one from the add list
     #[tracing::instrument(skip_all)]
@@ -161,17 +417,25 @@ impl TagsNamespace {
         let repo = get_repo_from_context(ctx).await;
         let request = event.payload::<ChangeFileTagsRequest>()?;
         let file = file_by_identifier(request.file_id, &repo).await?;
+        let changes = tag_changes_of_files(
+            &repo,
+            vec![(file.id(), file.cd_id())],
+            &request.added_tags,
+            &request.removed_tags,
+        )
+        .await?;
 
         if !request.added_tags.is_empty() {
             repo.tag()
-                .upsert_mappings(vec![file.cd_id()], request.added_tags)
+                .upsert_mappings(vec![file.cd_id()], request.added_tags.clone())
                 .await?;
         }
         if !request.removed_tags.is_empty() {
             repo.tag()
-                .remove_mappings(vec![file.cd_id()], request.removed_tags)
+                .remove_mappings(vec![file.cd_id()], request.removed_tags.clone())
                 .await?;
         }
+        record_tag_changes(ctx, changes).await;
 
         let responses: Vec<TagResponse> = repo
             .tag()
@@ -183,4 +447,247 @@ impl TagsNamespace {
 
         ctx.response(responses)
     }
+
+    /// Assigns tags to many files at once and returns the number of new mappings
+    #[tracing::instrument(skip_all)]
+    async fn add_tags_to_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<TagFilesRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let files = file_and_cd_ids(&repo, request.file_ids.clone()).await?;
+        let changes = tag_changes_of_files(&repo, files, &request.tag_ids, &[]).await?;
+        let created = repo
+            .add_tags_to_files(request.file_ids, request.tag_ids)
+            .await?;
+        record_tag_changes(ctx, changes).await;
+
+        ctx.response(created)
+    }
+
+    /// Removes tags from many files at once and returns the number of removed mappings
+    #[tracing::instrument(skip_all)]
+    async fn remove_tags_from_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<TagFilesRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let files = file_and_cd_ids(&repo, request.file_ids.clone()).await?;
+        let changes = tag_changes_of_files(&repo, files, &[], &request.tag_ids).await?;
+        let removed = repo
+            .remove_tags_from_files(request.file_ids, request.tag_ids)
+            .await?;
+        record_tag_changes(ctx, changes).await;
+
+        ctx.response(removed)
+    }
+
+    /// Returns the most used tags among the files matching the filters
+    /// together with the number of matching files
+    #[tracing::instrument(skip_all)]
+    async fn tag_facets(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<TagFacetsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let settings = get_settings_from_context(ctx).await;
+        let filters = filters_for_expressions(&repo, request.filters, &settings.tags).await?;
+
+        let facets: Vec<(TagResponse, u64)> = repo
+            .tag()
+            .facets_for_filters(filters, request.limit)
+            .await?
+            .into_iter()
+            .map(|(tag, count)| (TagResponse::from_model(tag), count))
+            .collect();
+
+        ctx.response(facets)
+    }
+
+    /// Returns the tags of a set of files with the number of files they are assigned to
+    #[tracing::instrument(skip_all)]
+    async fn aggregate_tags_for_files(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<AggregateTagsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let (tags, file_count) = repo
+            .tag()
+            .aggregate_tags_for_files(request.file_ids, request.intersection)
+            .await?;
+        let tags = tags
+            .into_iter()
+            .map(|(tag, count)| (TagResponse::from_model(tag), count))
+            .collect();
+
+        ctx.response(TagAggregateResponse { file_count, tags })
+    }
+
+    /// Returns a small combination of the tags of a file that selects as few files as possible.
+    /// Experimental
+    #[tracing::instrument(skip_all)]
+    async fn minimal_identifying_tags(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let request = event.payload::<GetFileTagsRequest>()?;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let (tags, file_count) = repo.minimal_identifying_tags(&file).await?;
+        let tags = tags.into_iter().map(TagResponse::from_model).collect();
+
+        ctx.response(IdentifyingTagsResponse { tags, file_count })
+    }
+
+    /// Returns the tags of the reference files that are missing on a file
+    /// with the number of reference files they are assigned to
+    #[tracing::instrument(skip_all)]
+    async fn missing_tags_for_file(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<MissingTagsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let tags: Vec<(TagResponse, u64)> = repo
+            .tag()
+            .missing_tags_for_file(request.file_id, request.reference_file_ids)
+            .await?
+            .into_iter()
+            .map(|(tag, count)| (TagResponse::from_model(tag), count))
+            .collect();
+
+        ctx.response(tags)
+    }
+
+    /// Returns the tags whose namespace or name starts with the prefix
+    /// with the number of files they are assigned to, most used first
+    #[tracing::instrument(skip_all)]
+    async fn autocomplete_tags(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<AutocompleteTagsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let tags: Vec<(TagResponse, u64)> = repo
+            .tag()
+            .autocomplete(&request.prefix, request.limit)
+            .await?
+            .into_iter()
+            .map(|(tag, count)| (TagResponse::from_model(tag), count))
+            .collect();
+
+        ctx.response(tags)
+    }
+
+    /// Returns the requested tags or all tags with the number of files they are assigned to.
+    /// Tags that aren't assigned to any file are included with a count of 0
+    #[tracing::instrument(skip_all)]
+    async fn get_tag_counts(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let request = event.payload::<GetTagCountsRequest>()?;
+        let repo = get_repo_from_context(ctx).await;
+        let tag_counts = if let Some(tag_ids) = request.tag_ids {
+            let tag_dao = repo.tag();
+            let counts = tag_dao.counts_for_tags(tag_ids.clone()).await?;
+            tag_dao
+                .all_by_ids(tag_ids)
+                .await?
+                .into_iter()
+                .map(|tag| {
+                    let count = counts.get(&tag.id()).copied().unwrap_or(0);
+                    (tag, count)
+                })
+                .collect()
+        } else {
+            repo.tag_counts().await?
+        };
+        let tag_counts: Vec<(TagResponse, u64)> = tag_counts
+            .into_iter()
+            .map(|(tag, count)| (TagResponse::from_model(tag), count))
+            .collect();
+
+        ctx.response(tag_counts)
+    }
+
+    /// Returns the last tag changes of a file with the newest change first
+    #[tracing::instrument(skip_all)]
+    async fn recent_tag_changes_for_file(ctx: &Context, event: Event) -> IPCResult<Response> {
+        let repo = get_repo_from_context(ctx).await;
+        let request = event.payload::<GetFileTagsRequest>()?;
+        let file = file_by_identifier(request.id, &repo).await?;
+        let changes = {
+            let data = ctx.data.read().await;
+            data.get::<TagChangeHistoryKey>()
+                .unwrap()
+                .changes(file.id())
+        };
+        let tag_ids = changes.iter().map(|c| c.tag_id).unique().collect();
+        let tags: HashMap<i64, TagResponse> = repo
+            .tag()
+            .all_by_ids(tag_ids)
+            .await?
+            .into_iter()
+            .map(|t| (t.id(), TagResponse::from_model(t)))
+            .collect();
+
+        let responses: Vec<TagChangeResponse> = changes
+            .into_iter()
+            .filter_map(|c| {
+                Some(TagChangeResponse {
+                    tag: tags.get(&c.tag_id)?.clone(),
+                    added: c.added,
+                    change_time: c.change_time,
+                })
+            })
+            .collect();
+
+        ctx.response(responses)
+    }
+}
+
+/// Returns the (file_id, cd_id) pairs of the files that exist
+async fn file_and_cd_ids(repo: &Repo, file_ids: Vec<i64>) -> RepoResult<Vec<(i64, i64)>> {
+    let files = repo.file().all_by_id(file_ids).await?;
+
+    Ok(files.into_iter().map(|f| (f.id(), f.cd_id())).collect())
+}
+
+/// Returns the tag changes that adding and removing the tags will make to each of the
+/// (file_id, cd_id) pairs. Has to be called before the tags are changed so that
+/// tags the files already have or never had aren't recorded as changes
+async fn tag_changes_of_files(
+    repo: &Repo,
+    files: Vec<(i64, i64)>,
+    added: &[i64],
+    removed: &[i64],
+) -> RepoResult<Vec<(i64, Vec<TagChange>)>> {
+    let cd_ids: Vec<i64> = files.iter().map(|(_, cd_id)| *cd_id).unique().collect();
+    let tag_ids: Vec<i64> = added.iter().chain(removed).copied().unique().collect();
+    let existing: HashSet<(i64, i64)> = repo
+        .tag()
+        .existing_mappings(&cd_ids, &tag_ids)
+        .await?
+        .into_iter()
+        .collect();
+    let change_time = Local::now().naive_local();
+
+    let changes = files
+        .into_iter()
+        .map(|(file_id, cd_id)| {
+            let added = added
+                .iter()
+                .unique()
+                .filter(|tag_id| !existing.contains(&(cd_id, **tag_id)))
+                .map(|tag_id| (*tag_id, true));
+            let removed = removed
+                .iter()
+                .unique()
+                .filter(|tag_id| existing.contains(&(cd_id, **tag_id)))
+                .map(|tag_id| (*tag_id, false));
+            let changes = added
+                .chain(removed)
+                .map(|(tag_id, added)| TagChange {
+                    tag_id,
+                    added,
+                    change_time,
+                })
+                .collect();
+
+            (file_id, changes)
+        })
+        .collect();
+
+    Ok(changes)
+}
+
+/// Remembers the tag changes of the files
+async fn record_tag_changes(ctx: &Context, changes: Vec<(i64, Vec<TagChange>)>) {
+    let mut data = ctx.data.write().await;
+    let history = data.get_mut::<TagChangeHistoryKey>().unwrap();
+
+    for (file_id, changes) in changes {
+        history.record(file_id, changes);
+    }
 }
@@ -0,0 +1,244 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDateTime;
+use mediarepo_core::trait_bound_typemap::TypeMapKey;
+
+/// The maximum number of tag changes remembered per file
+pub const MAX_TAG_CHANGES_PER_FILE: usize = 20;
+
+/// The maximum number of files whose tag changes are remembered
+pub const MAX_TAG_HISTORY_FILES: usize = 1000;
+
+#[derive(Clone, Debug)]
+pub struct TagChange {
+    pub tag_id: i64,
+    pub added: bool,
+    pub change_time: NaiveDateTime,
+}
+
+/// Recent tag changes of the files that were changed last
+pub struct TagChangeHistory {
+    changes: HashMap<i64, VecDeque<TagChange>>,
+    /// The ids of the files with changes with the most recently changed file first
+    order: VecDeque<i64>,
+    max_files: usize,
+}
+
+impl Default for TagChangeHistory {
+    fn default() -> Self {
+        Self::new(MAX_TAG_HISTORY_FILES)
+    }
+}
+
+impl TagChangeHistory {
+    pub fn new(max_files: usize) -> Self {
+        Self {
+            changes: HashMap::new(),
+            order: VecDeque::new(),
+            max_files,
+        }
+    }
+
+    /// Adds the changes of a file keeping at most [MAX_TAG_CHANGES_PER_FILE] entries per file.
+    /// The changes of the least recently changed files are forgotten once there are
+    /// changes of more than the maximum number of files
+    pub fn record(&mut self, file_id: i64, changes: Vec<TagChange>) {
+        if changes.is_empty() {
+            return;
+        }
+        let history = self.changes.entry(file_id).or_default();
+
+        for change in changes {
+            history.push_front(change);
+        }
+        history.truncate(MAX_TAG_CHANGES_PER_FILE);
+
+        if let Some(position) = self.order.iter().position(|id| *id == file_id) {
+            self.order.remove(position);
+        }
+        self.order.push_front(file_id);
+
+        while self.order.len() > self.max_files {
+            if let Some(file_id) = self.order.pop_back() {
+                self.changes.remove(&file_id);
+            }
+        }
+    }
+
+    /// Returns the changes of the file with the newest change first
+    pub fn changes(&self, file_id: i64) -> Vec<TagChange> {
+        self.changes
+            .get(&file_id)
+            .map(|changes| changes.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+pub struct TagChangeHistoryKey;
+
+impl TypeMapKey for TagChangeHistoryKey {
+    type Value = TagChangeHistory;
+}
+
+/// The time after which a delete confirmation token can't be used anymore
+pub const DELETE_TOKEN_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Files that get deleted when the confirmation token of the deletion is presented
+#[derive(Clone, Debug)]
+pub struct PendingDeletion {
+    pub file_ids: Vec<i64>,
+    pub created: Instant,
+}
+
+/// Pending deletions by their confirmation token
+pub struct PendingDeletions {
+    deletions: HashMap<String, PendingDeletion>,
+    lifetime: Duration,
+}
+
+impl Default for PendingDeletions {
+    fn default() -> Self {
+        Self::new(DELETE_TOKEN_LIFETIME)
+    }
+}
+
+impl PendingDeletions {
+    pub fn new(lifetime: Duration) -> Self {
+        Self {
+            deletions: HashMap::new(),
+            lifetime,
+        }
+    }
+
+    /// Stores the deletion of the files and returns the token that confirms it.
+    /// Expired deletions are removed
+    pub fn prepare(&mut self, file_ids: Vec<i64>) -> String {
+        let lifetime = self.lifetime;
+        self.deletions.retain(|_, p| p.created.elapsed() < lifetime);
+        let token = format!("{:016x}", rand::random::<u64>());
+        self.deletions.insert(
+            token.clone(),
+            PendingDeletion {
+                file_ids,
+                created: Instant::now(),
+            },
+        );
+
+        token
+    }
+
+    /// Removes the deletion of the token so that the token can't be used by
+    /// another request while the files are deleted.
+    /// Returns None if the token is unknown or expired
+    pub fn take(&mut self, token: &str) -> Option<PendingDeletion> {
+        self.deletions
+            .remove(token)
+            .filter(|p| p.created.elapsed() < self.lifetime)
+    }
+
+    /// Makes the token of a taken deletion usable again after the deletion failed
+    pub fn restore(&mut self, token: String, deletion: PendingDeletion) {
+        self.deletions.insert(token, deletion);
+    }
+}
+
+pub struct PendingDeletionsKey;
+
+impl TypeMapKey for PendingDeletionsKey {
+    type Value = PendingDeletions;
+}
+
+/// The cancellation flags of running imports and searches by their id
+pub struct CancellationFlagsKey;
+
+impl TypeMapKey for CancellationFlagsKey {
+    type Value = HashMap<String, Arc<AtomicBool>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag_change(tag_id: i64) -> TagChange {
+        TagChange {
+            tag_id,
+            added: true,
+            change_time: chrono::Local::now().naive_local(),
+        }
+    }
+
+    #[test]
+    fn it_keeps_the_newest_tag_changes_of_a_file() {
+        let mut history = TagChangeHistory::default();
+        history.record(1, (0..15).map(tag_change).collect());
+        history.record(1, (15..30).map(tag_change).collect());
+
+        let tag_ids: Vec<i64> = history.changes(1).iter().map(|c| c.tag_id).collect();
+        assert_eq!(tag_ids, (10..30).rev().collect::<Vec<i64>>());
+        assert!(history.changes(2).is_empty());
+    }
+
+    #[test]
+    fn it_forgets_the_least_recently_changed_files() {
+        let mut history = TagChangeHistory::new(2);
+        history.record(1, vec![tag_change(1)]);
+        history.record(2, vec![tag_change(2)]);
+        history.record(1, vec![tag_change(3)]);
+        history.record(3, vec![tag_change(4)]);
+        history.record(4, vec![]);
+
+        assert_eq!(history.changes(1).len(), 2);
+        assert!(history.changes(2).is_empty());
+        assert_eq!(history.changes(3).len(), 1);
+        assert!(history.changes(4).is_empty());
+    }
+
+    #[test]
+    fn it_returns_the_files_of_a_valid_token() {
+        let mut pending = PendingDeletions::default();
+        let token = pending.prepare(vec![1, 2]);
+
+        assert_eq!(pending.take(&token).unwrap().file_ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn it_rejects_expired_tokens() {
+        let mut pending = PendingDeletions::new(Duration::ZERO);
+        let token = pending.prepare(vec![1]);
+
+        assert!(pending.take(&token).is_none());
+    }
+
+    #[test]
+    fn it_rejects_reused_tokens() {
+        let mut pending = PendingDeletions::default();
+        let token = pending.prepare(vec![1]);
+
+        assert!(pending.take(&token).is_some());
+        assert!(pending.take(&token).is_none());
+    }
+
+    #[test]
+    fn it_rejects_mismatched_tokens() {
+        let mut pending = PendingDeletions::default();
+        let token = pending.prepare(vec![1]);
+        let other_token = pending.prepare(vec![2]);
+
+        assert!(pending.take("0123456789abcdef").is_none());
+        assert_eq!(pending.take(&other_token).unwrap().file_ids, vec![2]);
+        assert_eq!(pending.take(&token).unwrap().file_ids, vec![1]);
+    }
+
+    #[test]
+    fn it_accepts_restored_tokens_again() {
+        let mut pending = PendingDeletions::default();
+        let token = pending.prepare(vec![1]);
+
+        let deletion = pending.take(&token).unwrap();
+        pending.restore(token.clone(), deletion);
+        assert_eq!(pending.take(&token).unwrap().file_ids, vec![1]);
+    }
+}
@@ -1,10 +1,14 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use crate::type_keys::CancellationFlagsKey;
 use crate::TypeMap;
 use mediarepo_core::bromine::ipc::context::Context;
 use mediarepo_core::content_descriptor::decode_content_descriptor;
 use mediarepo_core::error::{RepoError, RepoResult};
 use mediarepo_core::mediarepo_api::types::identifier::FileIdentifier;
+use mediarepo_core::settings::{ContentDescriptorEncoding, Settings};
+use mediarepo_core::type_keys::SettingsKey;
 use mediarepo_logic::dao::repo::Repo;
 use mediarepo_logic::dao::DaoProvider;
 use mediarepo_logic::dto::FileDto;
@@ -17,6 +21,71 @@ pub async fn get_repo_from_context(ctx: &Context) -> Arc<Repo> {
     Arc::clone(repo)
 }
 
+pub async fn get_settings_from_context(ctx: &Context) -> Settings {
+    let data = ctx.data.read().await;
+    data.get::<SettingsKey>().unwrap().clone()
+}
+
+/// Returns the configured encoding of content descriptors in responses
+pub async fn get_cd_encoding_from_context(ctx: &Context) -> ContentDescriptorEncoding {
+    let data = ctx.data.read().await;
+    data.get::<SettingsKey>()
+        .unwrap()
+        .server
+        .content_descriptor_encoding
+}
+
+/// Fails with [RepoError::MessageTooLarge] if the size of data sent or received
+/// in a single message exceeds the configured limit
+pub async fn check_message_size(ctx: &Context, size: usize) -> RepoResult<()> {
+    let limit = {
+        let data = ctx.data.read().await;
+        data.get::<SettingsKey>().unwrap().server.max_message_size
+    };
+    let size = size as u64;
+
+    if size > limit {
+        Err(RepoError::MessageTooLarge { size, limit })
+    } else {
+        Ok(())
+    }
+}
+
+/// Registers the cancellation flag of a long running operation under the id.
+/// Fails if an operation with the id is already running
+pub async fn register_cancellation_flag(ctx: &Context, id: &str) -> RepoResult<Arc<AtomicBool>> {
+    let mut data = ctx.data.write().await;
+    let flags = data.get_mut::<CancellationFlagsKey>().unwrap();
+
+    if flags.contains_key(id) {
+        return Err(RepoError::from(
+            "an operation with this id is already running",
+        ));
+    }
+    let flag = Arc::new(AtomicBool::new(false));
+    flags.insert(id.to_string(), flag.clone());
+
+    Ok(flag)
+}
+
+/// Removes the cancellation flag of an operation that has finished
+pub async fn remove_cancellation_flag(ctx: &Context, id: &str) {
+    let mut data = ctx.data.write().await;
+    data.get_mut::<CancellationFlagsKey>().unwrap().remove(id);
+}
+
+/// Cancels the operation with the id and returns if it was running
+pub async fn cancel_operation(ctx: &Context, id: &str) -> bool {
+    let data = ctx.data.read().await;
+
+    if let Some(flag) = data.get::<CancellationFlagsKey>().unwrap().get(id) {
+        flag.store(true, Ordering::SeqCst);
+        true
+    } else {
+        false
+    }
+}
+
 pub async fn get_job_dispatcher_from_context(ctx: &Context) -> JobDispatcher {
     let data = ctx.data.read().await;
     data.get::<DispatcherKey>().unwrap().clone()
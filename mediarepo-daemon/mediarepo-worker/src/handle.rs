@@ -2,11 +2,14 @@ use mediarepo_core::error::{RepoError, RepoResult};
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
 use tokio::sync::broadcast::{Receiver, Sender};
-use tokio::sync::RwLock;
+use tokio::sync::{watch, Notify, RwLock};
 
 pub struct JobHandle<T: Send + Sync, R: Send + Sync> {
     status: Arc<RwLock<T>>,
     state: Arc<RwLock<JobState>>,
+    cancel: Arc<Notify>,
+    paused: Arc<watch::Sender<bool>>,
+    pausable: bool,
     result_receiver: CloneableReceiver<Arc<RwLock<Option<RepoResult<R>>>>>,
 }
 
@@ -15,6 +18,9 @@ impl<T: Send + Sync, R: Send + Sync> Clone for JobHandle<T, R> {
         Self {
             status: self.status.clone(),
             state: self.state.clone(),
+            cancel: self.cancel.clone(),
+            paused: self.paused.clone(),
+            pausable: self.pausable,
             result_receiver: self.result_receiver.clone(),
         }
     }
@@ -24,11 +30,17 @@ impl<T: Send + Sync, R: Send + Sync> JobHandle<T, R> {
     pub fn new(
         status: Arc<RwLock<T>>,
         state: Arc<RwLock<JobState>>,
+        cancel: Arc<Notify>,
+        paused: Arc<watch::Sender<bool>>,
+        pausable: bool,
         result_receiver: CloneableReceiver<Arc<RwLock<Option<RepoResult<R>>>>>,
     ) -> Self {
         Self {
             status,
             state,
+            cancel,
+            paused,
+            pausable,
             result_receiver,
         }
     }
@@ -41,6 +53,44 @@ impl<T: Send + Sync, R: Send + Sync> JobHandle<T, R> {
         &self.status
     }
 
+    /// Stops the current run of the job and saves its state.
+    /// Returns false if the job wasn't running or paused
+    pub async fn cancel(&self) -> bool {
+        if self.state().await.is_active() {
+            self.cancel.notify_waiters();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Pauses the job when it reaches its next checkpoint between two units of work.
+    /// Returns false if the job wasn't running or can't be paused
+    pub async fn pause(&self) -> bool {
+        let mut state = self.state.write().await;
+
+        if self.pausable && *state == JobState::Running {
+            *state = JobState::Paused;
+            self.paused.send_replace(true);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Continues a paused job. Returns false if the job wasn't paused
+    pub async fn resume(&self) -> bool {
+        let mut state = self.state.write().await;
+
+        if *state == JobState::Paused {
+            *state = JobState::Running;
+            self.paused.send_replace(false);
+            true
+        } else {
+            false
+        }
+    }
+
     pub async fn result(&mut self) -> Arc<RwLock<Option<RepoResult<R>>>> {
         match self.result_receiver.recv().await {
             Ok(v) => v,
@@ -60,9 +110,41 @@ pub enum JobState {
     Queued,
     Scheduled,
     Running,
+    Paused,
     Finished,
 }
 
+impl JobState {
+    /// Returns if the job is in the middle of a run
+    pub fn is_active(&self) -> bool {
+        matches!(self, JobState::Running | JobState::Paused)
+    }
+}
+
+/// Passed to running jobs so that they can be paused between units of work
+#[derive(Clone)]
+pub struct JobControl {
+    paused: watch::Receiver<bool>,
+}
+
+impl JobControl {
+    pub(crate) fn new(paused: watch::Receiver<bool>) -> Self {
+        Self { paused }
+    }
+
+    /// Waits until the job is resumed if it has been paused.
+    /// Jobs call this between their units of work
+    pub async fn checkpoint(&self) {
+        let mut paused = self.paused.clone();
+
+        while *paused.borrow_and_update() {
+            if paused.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
 pub struct CloneableReceiver<T: Clone> {
     receiver: Receiver<T>,
     sender: Sender<T>,
@@ -1,4 +1,4 @@
-use crate::handle::{CloneableReceiver, JobHandle, JobState};
+use crate::handle::{CloneableReceiver, JobControl, JobHandle, JobState};
 use crate::jobs::{Job, JobTypeKey};
 use mediarepo_core::error::RepoError;
 use mediarepo_core::tokio_graceful_shutdown::SubsystemHandle;
@@ -8,7 +8,7 @@ use mediarepo_logic::dao::DaoProvider;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::broadcast::channel;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, Notify, RwLock};
 use tokio::time::Instant;
 
 #[derive(Clone)]
@@ -47,6 +47,9 @@ impl JobDispatcher {
     ) -> JobHandle<T::JobStatus, T::Result> {
         let status = job.status();
         let state = Arc::new(RwLock::new(JobState::Queued));
+        let cancel = Arc::new(Notify::new());
+        let (paused, paused_receiver) = watch::channel(false);
+        let paused = Arc::new(paused);
         let (sender, mut receiver) = channel(1);
         self.subsystem
             .start::<RepoError, _, _>("channel-consumer", move |subsystem| async move {
@@ -58,7 +61,14 @@ impl JobDispatcher {
                 Ok(())
             });
         let receiver = CloneableReceiver::new(sender.clone());
-        let handle = JobHandle::new(status.clone(), state.clone(), receiver);
+        let handle = JobHandle::new(
+            status.clone(),
+            state.clone(),
+            cancel.clone(),
+            paused.clone(),
+            job.pausable(),
+            receiver,
+        );
         self.add_handle::<T>(handle.clone()).await;
 
         let repo = self.repo.clone();
@@ -71,7 +81,9 @@ impl JobDispatcher {
                     {
                         let mut state = state.write().await;
                         *state = JobState::Running;
+                        paused.send_replace(false);
                     }
+                    let control = JobControl::new(paused_receiver.clone());
                     if let Err(e) = job.load_state(repo.job()).await {
                         tracing::error!("failed to load the jobs state: {}", e);
                     }
@@ -79,7 +91,13 @@ impl JobDispatcher {
                         _ = subsystem.on_shutdown_requested() => {
                             job_2.save_state(repo.job()).await
                         }
-                        r = job.run(repo.clone()) => {
+                        _ = cancel.notified() => {
+                            tracing::info!("job was cancelled");
+                            let error = RepoError::from("the job was cancelled");
+                            let _ = sender.send(Arc::new(RwLock::new(Some(Err(error)))));
+                            job_2.save_state(repo.job()).await
+                        }
+                        r = job.run(repo.clone(), control) => {
                             match r {
                                 Err(e) => Err(e),
                                 Ok(v) => {
@@ -0,0 +1,88 @@
+use crate::handle::JobControl;
+use crate::jobs::{EmptyStatus, Job};
+use async_trait::async_trait;
+use chrono::Local;
+use mediarepo_core::error::RepoResult;
+use mediarepo_logic::dao::repo::Repo;
+use mediarepo_logic::dao::DaoProvider;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::sync::RwLock;
+
+const BACKUP_PREFIX: &str = "repo-";
+const BACKUP_EXTENSION: &str = ".db";
+
+#[derive(Clone, Debug)]
+pub struct DatabaseBackup {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Copies the database into the backup directory and deletes
+/// the oldest backups so that only the configured number of backups is kept
+#[derive(Clone)]
+pub struct BackupDatabaseJob {
+    directory: PathBuf,
+    keep: usize,
+}
+
+impl BackupDatabaseJob {
+    pub fn new(directory: PathBuf, keep: usize) -> Self {
+        Self { directory, keep }
+    }
+
+    async fn remove_old_backups(&self) -> RepoResult<()> {
+        let mut backups = Vec::new();
+        let mut entries = fs::read_dir(&self.directory).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_EXTENSION) {
+                backups.push(entry.path());
+            }
+        }
+        // the timestamp in the name makes the lexical order the chronological order
+        backups.sort();
+        let obsolete = backups.len().saturating_sub(self.keep.max(1));
+
+        for path in backups.into_iter().take(obsolete) {
+            tracing::debug!("removing old backup {:?}", path);
+            fs::remove_file(path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Job for BackupDatabaseJob {
+    type JobStatus = ();
+    type Result = DatabaseBackup;
+
+    fn status(&self) -> Arc<RwLock<Self::JobStatus>> {
+        EmptyStatus::default()
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn run(&self, repo: Arc<Repo>, _control: JobControl) -> RepoResult<Self::Result> {
+        fs::create_dir_all(&self.directory).await?;
+        let file_name = format!(
+            "{}{}{}",
+            BACKUP_PREFIX,
+            Local::now().format("%Y%m%d-%H%M%S%3f"),
+            BACKUP_EXTENSION
+        );
+        let path = self.directory.join(file_name);
+        repo.job().backup_database(&path).await?;
+        let size = fs::metadata(&path).await?.len();
+        tracing::info!("created database backup at {:?} ({} bytes)", path, size);
+        self.remove_old_backups().await?;
+
+        Ok(DatabaseBackup { path, size })
+    }
+
+    fn pausable(&self) -> bool {
+        false
+    }
+}
@@ -1,3 +1,4 @@
+use crate::handle::JobControl;
 use crate::jobs::Job;
 use crate::status_utils::SimpleProgress;
 use async_trait::async_trait;
@@ -48,7 +49,7 @@ impl Job for CalculateSizesJob {
     }
 
     #[tracing::instrument(level = "debug", skip_all)]
-    async fn run(&self, repo: Arc<Repo>) -> RepoResult<()> {
+    async fn run(&self, repo: Arc<Repo>, control: JobControl) -> RepoResult<()> {
         let size_types = vec![
             SizeType::Total,
             SizeType::FileFolder,
@@ -56,6 +57,7 @@ impl Job for CalculateSizesJob {
             SizeType::DatabaseFile,
         ];
         for size_type in size_types {
+            control.checkpoint().await;
             let size = calculate_size(&size_type, &repo, &self.repo_path, &self.settings).await?;
             let mut state = self.state.write().await;
             state
@@ -1,3 +1,4 @@
+use crate::handle::JobControl;
 use crate::jobs::Job;
 use crate::status_utils::SimpleProgress;
 use async_trait::async_trait;
@@ -21,12 +22,19 @@ impl Job for CheckIntegrityJob {
         self.progress.clone()
     }
 
-    async fn run(&self, repo: Arc<Repo>) -> RepoResult<Self::Result> {
-        repo.job().check_integrity().await?;
+    async fn run(&self, repo: Arc<Repo>, _control: JobControl) -> RepoResult<Self::Result> {
         {
             let mut progress = self.progress.write().await;
-            progress.set_total(100);
+            progress.set_total(1);
+            progress.set_current(0);
         }
+        repo.job().check_integrity().await?;
+        self.progress.write().await.set_current(1);
+
         Ok(())
     }
+
+    fn pausable(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,48 @@
+use crate::handle::JobControl;
+use crate::jobs::Job;
+use crate::status_utils::SimpleProgress;
+use async_trait::async_trait;
+use mediarepo_core::error::RepoResult;
+use mediarepo_logic::dao::repo::Repo;
+use mediarepo_logic::dao::DaoProvider;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Calculates the perceptual hashes of all images that don't have one,
+/// for example because they were imported before the hashes were stored.
+/// Images that already have a hash are skipped, so an interrupted run
+/// continues where it stopped when the job is started again.
+#[derive(Clone, Default)]
+pub struct GeneratePhashesJob {
+    state: Arc<RwLock<SimpleProgress>>,
+}
+
+#[async_trait]
+impl Job for GeneratePhashesJob {
+    type JobStatus = SimpleProgress;
+    type Result = ();
+
+    fn status(&self) -> Arc<RwLock<Self::JobStatus>> {
+        self.state.clone()
+    }
+
+    async fn run(&self, repo: Arc<Repo>, control: JobControl) -> RepoResult<()> {
+        let file_dao = repo.file();
+        let files = file_dao.images_without_phash().await?;
+        {
+            let mut progress = self.state.write().await;
+            progress.set_total(files.len() as u64);
+            progress.set_current(0);
+        }
+
+        for file in files {
+            control.checkpoint().await;
+            if file_dao.generate_phash(&file).await?.is_none() {
+                tracing::debug!("skipped undecodable image {}", file.id());
+            }
+            self.state.write().await.tick();
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::handle::JobControl;
 use crate::jobs::{deserialize_state, serialize_state, Job};
 use crate::status_utils::SimpleProgress;
 use async_trait::async_trait;
@@ -35,7 +36,7 @@ impl Job for MigrateCDsJob {
         Ok(())
     }
 
-    async fn run(&self, repo: Arc<Repo>) -> RepoResult<Self::Result> {
+    async fn run(&self, repo: Arc<Repo>, _control: JobControl) -> RepoResult<Self::Result> {
         if self.migrated.load(Ordering::SeqCst) {
             return Ok(());
         }
@@ -50,6 +51,10 @@ impl Job for MigrateCDsJob {
         Ok(())
     }
 
+    fn pausable(&self) -> bool {
+        false
+    }
+
     async fn save_state(&self, job_dao: JobDao) -> RepoResult<()> {
         if self.migrated.load(Ordering::Relaxed) {
             let state = serialize_state(JobType::MigrateCDs, &MigrationStatus { migrated: true })?;
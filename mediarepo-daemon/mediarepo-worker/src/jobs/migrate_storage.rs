@@ -0,0 +1,131 @@
+use crate::handle::JobControl;
+use crate::jobs::{deserialize_state, serialize_state, Job};
+use crate::status_utils::SimpleProgress;
+use async_trait::async_trait;
+use mediarepo_core::content_descriptor::HashAlgorithm;
+use mediarepo_core::error::{RepoError, RepoResult};
+use mediarepo_database::entities::job_state::JobType;
+use mediarepo_logic::dao::job::JobDao;
+use mediarepo_logic::dao::repo::Repo;
+use mediarepo_logic::dao::DaoProvider;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Stores all files again under content descriptors created with a different hashing algorithm.
+/// Files are migrated one by one so that a cancelled or failed migration continues
+/// with the remaining files when the job is started again.
+/// Without an algorithm the job resumes the last migration that didn't finish
+#[derive(Clone, Default)]
+pub struct MigrateStorageJob {
+    progress: Arc<RwLock<SimpleProgress>>,
+    algorithm: Arc<RwLock<Option<HashAlgorithm>>>,
+}
+
+impl MigrateStorageJob {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self {
+            progress: Default::default(),
+            algorithm: Arc::new(RwLock::new(Some(algorithm))),
+        }
+    }
+
+    /// Returns the algorithm the files are migrated to
+    pub async fn algorithm(&self) -> Option<HashAlgorithm> {
+        *self.algorithm.read().await
+    }
+}
+
+#[async_trait]
+impl Job for MigrateStorageJob {
+    type JobStatus = SimpleProgress;
+    type Result = ();
+
+    fn status(&self) -> Arc<RwLock<Self::JobStatus>> {
+        self.progress.clone()
+    }
+
+    async fn load_state(&self, job_dao: JobDao) -> RepoResult<()> {
+        let mut algorithm = self.algorithm.write().await;
+
+        if algorithm.is_none() {
+            if let Some(state) = job_dao.state_for_job_type(JobType::MigrateStorage).await? {
+                let state = deserialize_state::<MigrationState>(state)?;
+                if !state.finished {
+                    *algorithm = Some(state.algorithm);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run(&self, repo: Arc<Repo>, control: JobControl) -> RepoResult<Self::Result> {
+        let algorithm = if let Some(algorithm) = self.algorithm().await {
+            algorithm
+        } else {
+            return Ok(());
+        };
+        let job_dao = repo.job();
+        save_migration_state(&job_dao, algorithm, false).await?;
+
+        let ids = job_dao.descriptors_to_migrate(algorithm).await?;
+        {
+            let mut progress = self.progress.write().await;
+            progress.set_total(ids.len() as u64);
+            progress.set_current(0);
+        }
+        let mut failed = 0;
+
+        for id in ids {
+            control.checkpoint().await;
+            let repo = repo.clone();
+
+            // migrating a file runs in its own task so that cancelling the job
+            // doesn't interrupt it between moving the files and committing the new descriptor
+            let result =
+                tokio::spawn(async move { repo.job().migrate_descriptor(id, algorithm).await })
+                    .await
+                    .map_err(|e| RepoError::from(&*e.to_string()))
+                    .and_then(|r| r);
+
+            if let Err(e) = result {
+                tracing::warn!("failed to migrate content descriptor {}: {}", id, e);
+                failed += 1;
+            }
+            self.progress.write().await.tick();
+        }
+        save_migration_state(&job_dao, algorithm, failed == 0).await?;
+
+        if failed > 0 {
+            Err(RepoError::from(&*format!(
+                "{} files could not be migrated",
+                failed
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+async fn save_migration_state(
+    job_dao: &JobDao,
+    algorithm: HashAlgorithm,
+    finished: bool,
+) -> RepoResult<()> {
+    let state = serialize_state(
+        JobType::MigrateStorage,
+        &MigrationState {
+            algorithm,
+            finished,
+        },
+    )?;
+
+    job_dao.upsert_state(state).await
+}
+
+#[derive(Serialize, Deserialize)]
+struct MigrationState {
+    pub algorithm: HashAlgorithm,
+    pub finished: bool,
+}
@@ -1,18 +1,26 @@
+mod backup_database;
 mod calculate_sizes;
 mod check_integrity;
 mod generate_missing_thumbnails;
+mod generate_phashes;
 mod migrate_content_descriptors;
+mod migrate_storage;
+mod resniff_mime_types;
 mod vacuum;
 
+pub use backup_database::*;
 pub use calculate_sizes::*;
 pub use check_integrity::*;
 pub use generate_missing_thumbnails::*;
+pub use generate_phashes::*;
 pub use migrate_content_descriptors::*;
+pub use migrate_storage::*;
+pub use resniff_mime_types::*;
 use std::marker::PhantomData;
 use std::sync::Arc;
 pub use vacuum::*;
 
-use crate::handle::JobHandle;
+use crate::handle::{JobControl, JobHandle};
 use async_trait::async_trait;
 use mediarepo_core::bincode;
 use mediarepo_core::error::{RepoError, RepoResult};
@@ -38,7 +46,15 @@ pub trait Job: Clone + Send + Sync {
         Ok(())
     }
 
-    async fn run(&self, repo: Arc<Repo>) -> RepoResult<Self::Result>;
+    /// Runs the job. Jobs that consist of multiple units of work wait at
+    /// [JobControl::checkpoint] between them so that they can be paused
+    async fn run(&self, repo: Arc<Repo>, control: JobControl) -> RepoResult<Self::Result>;
+
+    /// Returns if the job reaches checkpoints while running. Jobs that run a single
+    /// database operation can't be paused
+    fn pausable(&self) -> bool {
+        true
+    }
 
     async fn save_state(&self, _job_dao: JobDao) -> RepoResult<()> {
         Ok(())
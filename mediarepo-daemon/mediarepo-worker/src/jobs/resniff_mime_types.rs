@@ -0,0 +1,51 @@
+use crate::handle::JobControl;
+use crate::jobs::Job;
+use crate::status_utils::SimpleProgress;
+use async_trait::async_trait;
+use mediarepo_core::error::RepoResult;
+use mediarepo_logic::dao::repo::Repo;
+use mediarepo_logic::dao::DaoProvider;
+use mediarepo_logic::dto::MetadataField;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Detects the types of files that were stored without a known mime type
+/// from the magic bytes of their contents. Files whose type is recognized
+/// get the detected mime type and new thumbnails, so they aren't part of
+/// the next run when the job is started again.
+#[derive(Clone, Default)]
+pub struct ResniffMimeTypesJob {
+    state: Arc<RwLock<SimpleProgress>>,
+}
+
+#[async_trait]
+impl Job for ResniffMimeTypesJob {
+    type JobStatus = SimpleProgress;
+    type Result = ();
+
+    fn status(&self) -> Arc<RwLock<Self::JobStatus>> {
+        self.state.clone()
+    }
+
+    async fn run(&self, repo: Arc<Repo>, control: JobControl) -> RepoResult<()> {
+        let files = repo
+            .file()
+            .missing_metadata(vec![MetadataField::MimeType])
+            .await?;
+        {
+            let mut progress = self.state.write().await;
+            progress.set_total(files.len() as u64);
+            progress.set_current(0);
+        }
+
+        for file in files {
+            control.checkpoint().await;
+            if let Some(file) = repo.resniff_file(&file).await? {
+                tracing::debug!("detected {} for file {}", file.mime_type(), file.id());
+            }
+            self.state.write().await.tick();
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::handle::JobControl;
 use crate::jobs::{EmptyStatus, Job};
 use async_trait::async_trait;
 use mediarepo_core::error::RepoResult;
@@ -19,9 +20,13 @@ impl Job for VacuumJob {
     }
 
     #[tracing::instrument(level = "debug", skip_all)]
-    async fn run(&self, repo: Arc<Repo>) -> RepoResult<()> {
+    async fn run(&self, repo: Arc<Repo>, _control: JobControl) -> RepoResult<()> {
         repo.job().vacuum().await?;
 
         Ok(())
     }
+
+    fn pausable(&self) -> bool {
+        false
+    }
 }
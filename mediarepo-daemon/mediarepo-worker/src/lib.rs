@@ -1,8 +1,10 @@
 use crate::job_dispatcher::JobDispatcher;
-use crate::jobs::{CheckIntegrityJob, MigrateCDsJob};
+use crate::jobs::{BackupDatabaseJob, CheckIntegrityJob, MigrateCDsJob};
 use mediarepo_core::error::RepoError;
+use mediarepo_core::settings::Settings;
 use mediarepo_core::tokio_graceful_shutdown::Toplevel;
 use mediarepo_logic::dao::repo::Repo;
+use std::path::Path;
 use std::time::Duration;
 use tokio::sync::oneshot::channel;
 
@@ -11,8 +13,15 @@ pub mod job_dispatcher;
 pub mod jobs;
 pub mod status_utils;
 
-pub async fn start(top_level: Toplevel, repo: Repo) -> (Toplevel, JobDispatcher) {
+pub async fn start(
+    top_level: Toplevel,
+    repo: Repo,
+    settings: &Settings,
+    repo_path: &Path,
+) -> (Toplevel, JobDispatcher) {
     let (tx, rx) = channel();
+    let backup = settings.backup.clone();
+    let backup_dir = backup.backup_dir(repo_path);
 
     let top_level =
         top_level.start::<RepoError, _, _>("mediarepo-worker", |subsystem| async move {
@@ -27,6 +36,15 @@ pub async fn start(top_level: Toplevel, repo: Repo) -> (Toplevel, JobDispatcher)
                 .await;
             dispatcher.dispatch(MigrateCDsJob::default()).await;
 
+            if backup.enabled {
+                dispatcher
+                    .dispatch_periodically(
+                        BackupDatabaseJob::new(backup_dir, backup.keep),
+                        backup.interval(),
+                    )
+                    .await;
+            }
+
             Ok(())
         });
     let receiver = rx
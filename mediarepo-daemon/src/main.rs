@@ -8,9 +8,10 @@ use structopt::StructOpt;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
-use mediarepo_core::error::RepoResult;
+use mediarepo_core::error::{RepoError, RepoResult};
 use mediarepo_core::fs::drop_file::DropFile;
-use mediarepo_core::settings::{PathSettings, Settings};
+use mediarepo_core::fs::file_hash_store::FileHashStore;
+use mediarepo_core::settings::Settings;
 use mediarepo_core::tokio_graceful_shutdown::{SubsystemHandle, Toplevel};
 use mediarepo_core::trait_bound_typemap::{CloneSendSyncTypeMap, SendSyncTypeMap, TypeMap};
 use mediarepo_core::type_keys::{RepoPathKey, SettingsKey};
@@ -51,6 +52,14 @@ enum SubCommand {
 
     /// Starts the event server for the selected repository
     Start,
+
+    /// Moves the stored files into another folder and uses it as the file storage
+    /// of the repository. The daemon must not be running while the files are moved
+    MigrateStorage {
+        /// The folder the files are moved to
+        #[structopt(parse(from_os_str))]
+        target: PathBuf,
+    },
 }
 
 #[tokio::main]
@@ -84,6 +93,7 @@ async fn main() -> RepoResult<()> {
     let result = match opt.cmd.clone() {
         SubCommand::Init { force } => init(opt, force).await,
         SubCommand::Start => start_server(opt, settings).await,
+        SubCommand::MigrateStorage { target } => migrate_storage(opt, settings, target).await,
     };
 
     opentelemetry::global::shutdown_tracer_provider();
@@ -97,16 +107,17 @@ async fn main() -> RepoResult<()> {
     }
 }
 
-async fn init_repo(opt: &Opt, paths: &PathSettings) -> RepoResult<Repo> {
-    let repo = get_repo(&opt.repo, paths).await?;
+async fn init_repo(opt: &Opt, settings: &Settings) -> RepoResult<Repo> {
+    let repo = get_repo(&opt.repo, settings).await?;
 
     Ok(repo)
 }
 
 /// Starts the server
 async fn start_server(opt: Opt, settings: Settings) -> RepoResult<()> {
-    let repo = init_repo(&opt, &settings.paths).await?;
-    let (mut top_level, dispatcher) = mediarepo_worker::start(Toplevel::new(), repo.clone()).await;
+    let repo = init_repo(&opt, &settings).await?;
+    let (mut top_level, dispatcher) =
+        mediarepo_worker::start(Toplevel::new(), repo.clone(), &settings, &opt.repo).await;
 
     let mut shared_data = CloneSendSyncTypeMap::new();
     shared_data.insert::<RepoKey>(Arc::new(repo));
@@ -233,7 +244,7 @@ async fn init(opt: Opt, force: bool) -> RepoResult<()> {
         panic!("Database already exists in location. Use --force with init to delete everything and start a new repository");
     }
     log::debug!("Creating repo");
-    let _repo = get_repo(&opt.repo, &settings.paths).await?;
+    let _repo = get_repo(&opt.repo, &settings).await?;
 
     log::debug!("Writing settings");
     settings.save(&opt.repo)?;
@@ -243,6 +254,44 @@ async fn init(opt: Opt, force: bool) -> RepoResult<()> {
     Ok(())
 }
 
+/// Moves the file storage of the repository into another folder. Running it again
+/// after an interruption continues with the files that haven't been moved yet
+async fn migrate_storage(opt: Opt, mut settings: Settings, target: PathBuf) -> RepoResult<()> {
+    fs::create_dir_all(&target).await?;
+    let target = target.canonicalize()?;
+    let source = settings.paths.files_dir(&opt.repo).canonicalize()?;
+
+    if source == target {
+        return Err(RepoError::from(
+            "the files are already stored in the folder",
+        ));
+    }
+    let repo = init_repo(&opt, &settings).await?;
+    tracing::info!("Moving files from {:?} to {:?}", source, target);
+
+    let (migrated, skipped) = repo
+        .migrate_storage(
+            &FileHashStore::new(source),
+            &FileHashStore::new(target.clone()),
+            |progress| {
+                if progress.processed % 100 == 0 || progress.processed == progress.total {
+                    tracing::info!("Moved {} of {} files", progress.processed, progress.total);
+                }
+            },
+        )
+        .await?;
+    settings.paths.set_files_dir(target.to_string_lossy());
+    settings.save(&opt.repo)?;
+
+    tracing::info!(
+        "Storage migrated. Moved {} files, {} had already been moved",
+        migrated,
+        skipped
+    );
+
+    Ok(())
+}
+
 async fn clean_old_connection_files(root: &Path) -> RepoResult<()> {
     let paths = ["repo.tcp", "repo.sock"];
 
@@ -21,16 +21,23 @@ pub fn load_settings(root_path: &Path) -> RepoResult<Settings> {
     }
 }
 
-pub async fn get_repo(root_path: &Path, path_settings: &PathSettings) -> RepoResult<Repo> {
-    Repo::connect(
+pub async fn get_repo(root_path: &Path, settings: &Settings) -> RepoResult<Repo> {
+    let path_settings = &settings.paths;
+
+    let repo = Repo::connect(
         format!(
             "sqlite://{}",
             path_settings.db_file_path(root_path).to_string_lossy()
         ),
+        &settings.database,
         path_settings.files_dir(root_path),
         path_settings.thumbs_dir(root_path),
     )
-    .await
+    .await?;
+
+    Ok(repo
+        .with_hash_algorithm(settings.import.hash_algorithm)
+        .with_thumbnail_sizes(settings.thumbnails.sizes.iter().map(|s| (*s).into())))
 }
 
 pub async fn create_paths_for_repo(root: &Path, settings: &PathSettings) -> RepoResult<()> {
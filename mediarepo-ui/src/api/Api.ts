@@ -1,23 +1,39 @@
-import {FileBasicData, FileMetadata, FileOsMetadata} from "./api-types/files";
+import {
+    FileBasicData,
+    FileDeletion,
+    FileMetadata,
+    FindFilesResponse,
+    ImportDirectoryResponse,
+    ImportFilesResponse,
+    ResolvedFiles
+} from "./api-types/files";
 import {invoke} from "@tauri-apps/api/tauri";
 import {ApiFunction} from "./api-types/functions";
 import {
     AddLocalFileREquest,
     AddRepositoryRequest,
     AddSortingPresetRequest,
+    AutocompleteTagsRequest,
+    CancelImportRequest,
+    ChangeFilesStatusRequest,
     ChangeFileTagsRequest,
     CheckDaemonRunningRequest,
+    CheckIntegrityRequest,
     CheckLocalRepositoryExistsRequest,
     CreateTagsRequest,
     DeleteFileRequest,
     DeleteRepositoryRequest,
     DeleteSortingPresetRequest,
     DeleteThumbnailsRequest,
+    ExportFilesRequest,
     FindFilesRequest,
+    FindFilesWithStatusesRequest,
     GetFileMetadataRequest,
     GetFileTagMapRequest,
     GetSizeRequest,
     GetTagsForFilesRequest,
+    ImportDirectoryRequest,
+    ImportFilesRequest,
     InitRepositoryRequest,
     IsJobRunningRequest,
     ReadFileRequest,
@@ -25,14 +41,20 @@ import {
     ResolvePathsToFilesRequest,
     RunJobRequest,
     SaveFileRequest,
+    SearchFilesByTextRequest,
     SelectRepositoryRequest,
+    SetFileIndexedRequest,
     SetFrontendStateRequest,
     StartDaemonRequest,
+    StopSearchRequest,
+    StreamFilesRequest,
+    UpdateFileCommentRequest,
+    UpdateFileMetadataRequest,
     UpdateFileNameRequest,
     UpdateFileStatusRequest
 } from "./api-types/requests";
-import {RepositoryData, RepositoryMetadata, SizeMetadata} from "./api-types/repo";
-import {CdTagMappings, NamespaceData, TagData} from "./api-types/tags";
+import {IntegrityReport, RepositoryData, RepositoryMetadata, SizeMetadata} from "./api-types/repo";
+import {CdTagMappings, CreateTagResult, NamespaceData, TagData} from "./api-types/tags";
 import {ShortCache} from "./ShortCache";
 import {SortingPresetData} from "./api-types/presets";
 
@@ -98,14 +120,34 @@ export class MediarepoApi {
         return this.invokePlugin(ApiFunction.GetActiveRepository);
     }
 
+    public static async checkIntegrity(request: CheckIntegrityRequest): Promise<IntegrityReport> {
+        return this.invokePlugin(ApiFunction.CheckIntegrity, request);
+    }
+
     public static async getAllFiles(): Promise<FileBasicData[]> {
         return this.invokePlugin(ApiFunction.GetAllFiles);
     }
 
-    public static async findFiles(request: FindFilesRequest): Promise<FileBasicData[]> {
+    public static async findFiles(request: FindFilesRequest): Promise<FindFilesResponse> {
         return ShortCache.cached(request, () => this.invokePlugin(ApiFunction.FindFiles, request), 5000, "findFiles");
     }
 
+    public static async findFilesWithStatuses(request: FindFilesWithStatusesRequest): Promise<FindFilesResponse> {
+        return this.invokePlugin(ApiFunction.FindFilesWithStatuses, request);
+    }
+
+    public static async searchFilesByText(request: SearchFilesByTextRequest): Promise<FindFilesResponse> {
+        return this.invokePlugin(ApiFunction.SearchFilesByText, request);
+    }
+
+    public static async streamFiles(request: StreamFilesRequest): Promise<number> {
+        return this.invokePlugin(ApiFunction.StreamFiles, request);
+    }
+
+    public static async stopSearch(request: StopSearchRequest): Promise<boolean> {
+        return this.invokePlugin(ApiFunction.StopSearch, request);
+    }
+
     public static async getFileMetadata(request: GetFileMetadataRequest): Promise<FileMetadata> {
         return this.invokePlugin(ApiFunction.GetFileMetadata, request);
     }
@@ -114,10 +156,34 @@ export class MediarepoApi {
         return this.invokePlugin(ApiFunction.UpdateFileName, request);
     }
 
+    public static async updateFileComment(request: UpdateFileCommentRequest): Promise<FileMetadata> {
+        return this.invokePlugin(ApiFunction.UpdateFileComment, request);
+    }
+
+    public static async updateFileMetadata(request: UpdateFileMetadataRequest): Promise<FileMetadata> {
+        return this.invokePlugin(ApiFunction.UpdateFileMetadata, request);
+    }
+
     public static async updateFileStatus(request: UpdateFileStatusRequest): Promise<FileBasicData> {
         return this.invokePlugin(ApiFunction.UpdateFileStatus, request);
     }
 
+    public static async archiveFiles(request: ChangeFilesStatusRequest): Promise<FileBasicData[]> {
+        return this.invokePlugin(ApiFunction.ArchiveFiles, request);
+    }
+
+    public static async unarchiveFiles(request: ChangeFilesStatusRequest): Promise<FileBasicData[]> {
+        return this.invokePlugin(ApiFunction.UnarchiveFiles, request);
+    }
+
+    public static async restoreFiles(request: ChangeFilesStatusRequest): Promise<FileBasicData[]> {
+        return this.invokePlugin(ApiFunction.RestoreFiles, request);
+    }
+
+    public static async setFileIndexed(request: SetFileIndexedRequest): Promise<FileBasicData> {
+        return this.invokePlugin(ApiFunction.SetFileIndexed, request);
+    }
+
     public static async saveFileLocally(request: SaveFileRequest): Promise<void> {
         return this.invokePlugin(ApiFunction.SaveFileLocally, request);
     }
@@ -130,7 +196,7 @@ export class MediarepoApi {
         return this.invokePlugin(ApiFunction.ReadFile, request);
     }
 
-    public static async deleteFile(request: DeleteFileRequest): Promise<void> {
+    public static async deleteFile(request: DeleteFileRequest): Promise<FileDeletion> {
         return this.invokePlugin(ApiFunction.DeleteFile, request);
     }
 
@@ -160,15 +226,19 @@ export class MediarepoApi {
         );
     }
 
-    public static async createTags(request: CreateTagsRequest): Promise<TagData[]> {
+    public static async createTags(request: CreateTagsRequest): Promise<CreateTagResult[]> {
         return this.invokePlugin(ApiFunction.CreateTags, request);
     }
 
+    public static async autocompleteTags(request: AutocompleteTagsRequest): Promise<[TagData, number][]> {
+        return this.invokePlugin(ApiFunction.AutocompleteTags, request);
+    }
+
     public static async changeFileTags(request: ChangeFileTagsRequest): Promise<TagData[]> {
         return this.invokePlugin(ApiFunction.ChangeFileTags, request);
     }
 
-    public static async resolvePathsToFiles(request: ResolvePathsToFilesRequest): Promise<FileOsMetadata[]> {
+    public static async resolvePathsToFiles(request: ResolvePathsToFilesRequest): Promise<ResolvedFiles> {
         return this.invokePlugin(ApiFunction.ResolvePathsToFiles, request);
     }
 
@@ -176,6 +246,22 @@ export class MediarepoApi {
         return this.invokePlugin(ApiFunction.AddLocalFile, request);
     }
 
+    public static async importFiles(request: ImportFilesRequest): Promise<ImportFilesResponse> {
+        return this.invokePlugin(ApiFunction.ImportFiles, request);
+    }
+
+    public static async importDirectory(request: ImportDirectoryRequest): Promise<ImportDirectoryResponse> {
+        return this.invokePlugin(ApiFunction.ImportDirectory, request);
+    }
+
+    public static async cancelImport(request: CancelImportRequest): Promise<boolean> {
+        return this.invokePlugin(ApiFunction.CancelImport, request);
+    }
+
+    public static async exportFiles(request: ExportFilesRequest): Promise<string[]> {
+        return this.invokePlugin(ApiFunction.ExportFiles, request);
+    }
+
     public static async getFrontendState(): Promise<string> {
         return ShortCache.cached("frontend-state", () => this.invokePlugin(ApiFunction.GetFrontendState), 1000);
     }
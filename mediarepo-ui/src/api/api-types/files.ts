@@ -24,7 +24,8 @@ export type PropertyQuery = PropertyQueryStatus
     | PropertyQueryCreatedTime
     | PropertyQueryTagCount
     | PropertyQueryCd
-    | PropertyQueryId;
+    | PropertyQueryId
+    | PropertyQueryText;
 
 export type PropertyQueryStatus = { Status: FileStatus };
 export type PropertyQueryFileSize = { FileSize: ValueComparator<number> };
@@ -34,6 +35,7 @@ export type PropertyQueryCreatedTime = { CreatedTime: ValueComparator<string> };
 export type PropertyQueryTagCount = { TagCount: ValueComparator<number> };
 export type PropertyQueryCd = { Cd: string };
 export type PropertyQueryId = { Id: number };
+export type PropertyQueryText = { Text: string };
 
 export type ValueComparator<T> =
     { Less: T }
@@ -60,6 +62,19 @@ export type FileBasicData = {
     status: FileStatus,
     cd: string,
     mime_type: string,
+    indexed: boolean,
+};
+
+export type FilesPageEvent = {
+    search_id: string,
+    files: FileBasicData[],
+    total?: number,
+};
+
+export type FindFilesResponse = {
+    files: FileBasicData[],
+    truncated: boolean,
+    total?: number,
 };
 
 export type FileStatus = "Imported" | "Archived" | "Deleted";
@@ -74,6 +89,77 @@ export type FileMetadata = {
     size: number,
 };
 
+export type FileDeletion = {
+    file_id: number,
+    content_removed: boolean,
+    tag_mappings: number,
+    thumbnails: number,
+    freed_bytes: number,
+};
+
+export type ResolvedFiles = {
+    files: FileOsMetadata[],
+    skipped_count: number,
+    skipped_symlinks: string[],
+};
+
+export type SymlinkHandling = "Follow" | "Skip" | "ImportAsIs";
+
+export type ImportFileEntry = {
+    metadata: FileOsMetadata,
+    tags: string[],
+};
+
+export type ImportProgress = {
+    processed: number,
+    total: number,
+    current_file: string,
+    failed: number,
+    error?: string,
+};
+
+export type ImportFailure = {
+    path: string,
+    error: string,
+};
+
+export type ImportFilesResponse = {
+    files: FileBasicData[],
+    failures: ImportFailure[],
+    cancelled: boolean,
+};
+
+export type PathTags = "Tags" | { Namespace: string };
+
+export type ImportDirectoryOptions = {
+    pattern?: string,
+    extensions: string[],
+    path_tags?: PathTags,
+    follow_symlinks: boolean,
+    include_hidden: boolean,
+};
+
+export type DirectoryImportProgress = {
+    path: string,
+    imported: number,
+    skipped: number,
+    failed: number,
+    error?: string,
+};
+
+export type ImportDirectoryResponse = {
+    imported: number,
+    skipped: number,
+    failures: ImportFailure[],
+    cancelled: boolean,
+};
+
+export type ExportProgress = {
+    exported: number,
+    total: number,
+    path: string,
+};
+
 export type FileOsMetadata = {
     name: string,
     path: string,
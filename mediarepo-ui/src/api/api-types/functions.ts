@@ -15,12 +15,23 @@ export enum ApiFunction {
     GetRepoMetadata = "get_repo_metadata",
     GetSize = "get_size",
     GetActiveRepository = "get_active_repository",
+    CheckIntegrity = "check_integrity",
     // files
     GetAllFiles = "get_all_files",
     FindFiles = "find_files",
+    FindFilesWithStatuses = "find_files_with_statuses",
+    SearchFilesByText = "search_files_by_text",
+    StreamFiles = "stream_files",
+    StopSearch = "stop_search",
     GetFileMetadata = "get_file_metadata",
     UpdateFileName = "update_file_name",
+    UpdateFileComment = "update_file_comment",
+    UpdateFileMetadata = "update_file_metadata",
     UpdateFileStatus = "update_file_status",
+    ArchiveFiles = "archive_files",
+    UnarchiveFiles = "unarchive_files",
+    RestoreFiles = "restore_files",
+    SetFileIndexed = "set_file_indexed",
     SaveFileLocally = "save_file_locally",
     DeleteThumbnails = "delete_thumbnails",
     ReadFile = "read_file",
@@ -32,9 +43,15 @@ export enum ApiFunction {
     GetFileTagMap = "get_file_tag_map",
     CreateTags = "create_tags",
     ChangeFileTags = "change_file_tags",
+    AutocompleteTags = "autocomplete_tags",
     // import
     ResolvePathsToFiles = "resolve_paths_to_files",
     AddLocalFile = "add_local_file",
+    ImportFiles = "import_files",
+    ImportDirectory = "import_directory",
+    CancelImport = "cancel_import",
+    // export
+    ExportFiles = "export_files",
     // state
     GetFrontendState = "get_frontend_state",
     SetFrontendState = "set_frontend_state",
@@ -2,4 +2,7 @@ export type JobType = "MigrateContentDescriptors"
     | "CalculateSizes"
     | "CheckIntegrity"
     | "Vacuum"
-    | "GenerateThumbnails";
+    | "GenerateThumbnails"
+    | "MigrateStorage"
+    | "GeneratePhashes"
+    | "ResniffMimeTypes";
@@ -1,3 +1,5 @@
+import {FileBasicData} from "./files";
+
 export type RepositoryMetadata = {
     version: string,
     file_count: number,
@@ -12,6 +14,24 @@ export type SizeMetadata = {
     size: number,
 };
 
+export type IntegrityProgress = {
+    checked: number,
+    total: number,
+    corrupt: number,
+    missing: number,
+};
+
+export type DamagedContent = {
+    cd: string,
+    files: FileBasicData[],
+};
+
+export type IntegrityReport = {
+    checked: number,
+    corrupt: DamagedContent[],
+    missing: DamagedContent[],
+};
+
 export type SizeType = "Total" | "FileFolder" | "ThumbFolder" | "DatabaseFile";
 
 export type RepositoryData = {
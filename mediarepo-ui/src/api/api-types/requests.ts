@@ -1,4 +1,12 @@
-import {FileOsMetadata, FileStatus, FilterExpression, SortKeyData} from "./files";
+import {
+    FileOsMetadata,
+    FileStatus,
+    FilterExpression,
+    ImportDirectoryOptions,
+    ImportFileEntry,
+    SortKeyData,
+    SymlinkHandling
+} from "./files";
 import {RepositoryData, SizeType} from "./repo";
 import {JobType} from "./job";
 
@@ -40,7 +48,39 @@ export type GetSizeRequest = {
 
 export type FindFilesRequest = {
     filters: FilterExpression[],
-    sortBy: SortKeyData[]
+    sortBy: SortKeyData[],
+    maxResults?: number,
+    offset?: number,
+    limit?: number,
+    includeUnindexed?: boolean,
+};
+
+export type FindFilesWithStatusesRequest = {
+    filters: FilterExpression[],
+    sortBy: SortKeyData[],
+    offset?: number,
+    limit?: number,
+    includeUnindexed?: boolean,
+    statuses: FileStatus[],
+};
+
+export type StreamFilesRequest = {
+    searchId: string,
+    filters: FilterExpression[],
+    sortBy: SortKeyData[],
+    pageSize: number,
+    maxResults?: number,
+};
+
+export type StopSearchRequest = {
+    searchId: string,
+};
+
+export type SearchFilesByTextRequest = {
+    query: string,
+    sortBy: SortKeyData[],
+    offset?: number,
+    limit?: number,
 };
 
 export type UpdateFileNameRequest = {
@@ -60,15 +100,39 @@ export type ReadFileRequest = {
     mimeType: string,
 };
 
-export type DeleteFileRequest = IdIdentifierRequest;
+export type DeleteFileRequest = IdIdentifierRequest & {
+    dryRun?: boolean,
+};
 
 export type GetFileMetadataRequest = IdIdentifierRequest;
 
+export type UpdateFileCommentRequest = {
+    id: number,
+    comment?: string,
+};
+
+export type UpdateFileMetadataRequest = {
+    id: number,
+    name?: string,
+    comment?: string,
+    status?: FileStatus,
+    undelete?: boolean,
+};
+
 export type UpdateFileStatusRequest = {
     id: number,
     status: FileStatus
 };
 
+export type ChangeFilesStatusRequest = {
+    ids: number[],
+};
+
+export type SetFileIndexedRequest = {
+    id: number,
+    indexed: boolean,
+};
+
 export type GetTagsForFilesRequest = {
     cds: string[]
 };
@@ -81,6 +145,11 @@ export type CreateTagsRequest = {
     tags: string[]
 };
 
+export type AutocompleteTagsRequest = {
+    prefix: string,
+    limit: number,
+};
+
 export type ChangeFileTagsRequest = {
     id: number,
     addedTags: number[],
@@ -89,6 +158,8 @@ export type ChangeFileTagsRequest = {
 
 export type ResolvePathsToFilesRequest = {
     paths: string[],
+    skipHidden?: boolean,
+    symlinks?: SymlinkHandling,
 };
 
 export type AddLocalFileREquest = {
@@ -96,11 +167,37 @@ export type AddLocalFileREquest = {
     options: AddFileOptions,
 }
 
+export type ImportFilesRequest = {
+    importId: string,
+    files: ImportFileEntry[],
+};
+
+export type ImportDirectoryRequest = {
+    importId: string,
+    path: string,
+    options: ImportDirectoryOptions,
+};
+
+export type CancelImportRequest = {
+    importId: string,
+};
+
+export type ExportFilesRequest = {
+    ids: number[],
+    targetDir: string,
+    writeTagSidecars: boolean,
+    groupByNamespace?: string,
+};
+
 type AddFileOptions = {
     read_tags_from_txt: boolean,
     delete_after_import: boolean,
 };
 
+export type CheckIntegrityRequest = {
+    tagIds: number[],
+};
+
 export type SetFrontendStateRequest = {
     state: string
 };
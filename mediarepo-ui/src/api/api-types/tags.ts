@@ -4,6 +4,8 @@ export type TagData = {
     name: string,
 };
 
+export type CreateTagResult = { Ok: TagData } | { Err: string };
+
 export type NamespaceData = {
     id: number,
     name: string,
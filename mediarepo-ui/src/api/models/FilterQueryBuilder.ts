@@ -10,7 +10,8 @@ export type PropertyType =
     | "CreatedTime"
     | "TagCount"
     | "Cd"
-    | "Id";
+    | "Id"
+    | "Text";
 
 export class FilterQueryBuilder {
 
@@ -61,6 +62,10 @@ export class FilterQueryBuilder {
         return filterQuery({ Id: id });
     }
 
+    public static text(text: string): FilterQuery {
+        return filterQuery({ Text: text });
+    }
+
     public static buildFilterExpressionsFromString(expressionStr: string): FilterExpression | undefined {
         const parts = expressionStr.split(/\s+or\s+/gi);
         const queries = parts.map(part => this.buildFilterFromString(part)).filter(f => f != undefined) as FilterQuery[];
@@ -187,6 +192,11 @@ export class FilterQueryBuilder {
                         return this.fileId(value);
                     }
 
+                    break;
+                case "Text":
+                    if (comparator === "Equal" && compareValue.trim()) {
+                        return this.text(compareValue.trim());
+                    }
                     break;
             }
         }
@@ -234,6 +244,8 @@ export class FilterQueryBuilder {
             case "id":
             case "fileid":
                 return "Id";
+            case "text":
+                return "Text";
             default:
                 return;
         }
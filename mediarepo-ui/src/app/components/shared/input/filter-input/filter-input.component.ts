@@ -6,7 +6,7 @@ import {FilterExpression, FilterQuery} from "../../../../../api/api-types/files"
 import {debounceTime, map, startWith} from "rxjs/operators";
 import {compareSearchResults} from "../../../../utils/compare-utils";
 import {FilterQueryBuilder} from "../../../../../api/models/FilterQueryBuilder";
-import {normalizeTag} from "../../../../utils/tag-utils";
+import {normalizeTag, tagMatchesQuery} from "../../../../utils/tag-utils";
 
 type AutocompleteEntry = {
     value: string,
@@ -128,7 +128,7 @@ export class FilterInputComponent implements OnChanges {
         const trimmedValue = latestQuery.trim();
         let isNegation = trimmedValue.startsWith("-");
         const cleanValue = normalizeTag(trimmedValue.replace(/^-/, ""));
-        const autosuggestTags = this.tagsForAutocomplete.filter(t => t.includes(cleanValue)).map(t => isNegation ? "-" + t : t);
+        const autosuggestTags = this.tagsForAutocomplete.filter(t => tagMatchesQuery(t, cleanValue)).map(t => isNegation ? "-" + t : t);
         let propertyQuerySuggestions: string[] = [];
 
         if (trimmedValue.startsWith(".")) {
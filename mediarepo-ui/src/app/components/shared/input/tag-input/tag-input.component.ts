@@ -5,7 +5,7 @@ import {MatAutocompleteSelectedEvent} from "@angular/material/autocomplete";
 import {Observable} from "rxjs";
 import {debounceTime, map, startWith} from "rxjs/operators";
 import {compareSearchResults} from "../../../../utils/compare-utils";
-import {normalizeTag} from "../../../../utils/tag-utils";
+import {normalizeTag, tagMatchesQuery} from "../../../../utils/tag-utils";
 
 @Component({
     selector: "app-tag-input",
@@ -77,7 +77,7 @@ export class TagInputComponent implements OnChanges {
         ) : normalizedTag;
 
         const autocompleteTags = this.tagsForAutocomplete.filter(
-            t => t.includes(normalizedTag))
+            t => tagMatchesQuery(t, normalizedTag))
             .map(t => negated ? "-" + t : t)
             .sort((l, r) => compareSearchResults(normalizedTag, l, r))
             .slice(0, 50);
@@ -6,7 +6,7 @@ import {DialogFilter} from "@tauri-apps/api/dialog";
 import {FileOsMetadata} from "../../../../../../api/api-types/files";
 import {ImportTabState} from "../../../../../models/state/ImportTabState";
 
-const IMAGE_EXTENSIONS = ["png", "jpg", "jpeg", "webp", "bmp", "gif"];
+const IMAGE_EXTENSIONS = ["png", "jpg", "jpeg", "webp", "avif", "bmp", "gif"];
 const VIDEO_EXTENSIONS = ["mp4", "mkv", "wmv", "avi", "webm"];
 const AUDIO_EXTENSIONS = ["mp3", "ogg", "wav", "flac", "aac"];
 const DOCUMENT_EXTENSIONS = ["pdf", "doc", "docx", "odf"];
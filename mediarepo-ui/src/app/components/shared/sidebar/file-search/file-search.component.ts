@@ -229,7 +229,10 @@ export class FileSearchComponent implements AfterViewChecked, OnInit {
     private updateStatusFilters() {
         this.deleteAllStatusFilters();
         const filter = this.buildFilterForDisplayProperty();
-        this.filters.addFilter(filter, 0);
+        // empty groups are rejected by the daemon
+        if (this.displayImported || this.displayArchived || this.displayDeleted) {
+            this.filters.addFilter(filter, 0);
+        }
         this.state.setTagFilters(this.filters);
     }
 
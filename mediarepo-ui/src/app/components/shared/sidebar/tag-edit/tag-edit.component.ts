@@ -63,7 +63,13 @@ export class TagEditComponent implements AfterViewInit, OnChanges {
                 t => t.getNormalizedOutput() === tag);
 
             if (!tagInstance) {
-                tagInstance = (await this.tagService.createTags([tag]))[0];
+                const result = (await this.tagService.createTags([tag]))[0];
+
+                if (typeof result === "string") {
+                    this.logger.error(new Error(`Failed to create tag "${tag}": ${result}`));
+                    return;
+                }
+                tagInstance = result;
                 this.tagService.tags.next([...this.tagService.tags.value, tagInstance]);
             }
             this.changeDetector.markForCheck();
@@ -28,6 +28,7 @@ export class FileService {
                 filters: filters.getFilters(),
                 sortBy: sortBy.map(k => k.rawData)
             })
+            .then(response => response.files)
             .then(mapMany(mapNew(File)));
     }
 
@@ -66,7 +67,7 @@ export class FileService {
      * @returns {Promise<void>}
      */
     public async deleteFile(id: number): Promise<void> {
-        return MediarepoApi.deleteFile({ id });
+        await MediarepoApi.deleteFile({ id });
     }
 
     /**
@@ -3,23 +3,40 @@ import {AddFileOptions} from "../../models/AddFileOptions";
 import {File} from "../../../api/models/File";
 import {MediarepoApi} from "../../../api/Api";
 import {mapNew,} from "../../../api/models/adaptors";
-import {FileOsMetadata} from "../../../api/api-types/files";
+import {FileOsMetadata, SymlinkHandling} from "../../../api/api-types/files";
+import {LoggingService} from "../logging/logging.service";
 
 @Injectable({
     providedIn: "root"
 })
 export class ImportService {
 
-    constructor() {
+    constructor(private logger: LoggingService) {
     }
 
     /**
-     * Resolves paths from the local file system into a list of files that can be imported
+     * Resolves paths from the local file system into a list of files that can be imported.
+     * Hidden and system files are skipped. Symlinks are skipped unless another handling is given.
+     * A warning with the number of skipped entries is shown if any were skipped.
      * @param {string[]} paths
+     * @param {SymlinkHandling} symlinks
      * @returns {Promise<FileOsMetadata[]>}
      */
-    public async resolvePathsToFiles(paths: string[]): Promise<FileOsMetadata[]> {
-        return MediarepoApi.resolvePathsToFiles({ paths });
+    public async resolvePathsToFiles(paths: string[], symlinks?: SymlinkHandling): Promise<FileOsMetadata[]> {
+        const resolved = await MediarepoApi.resolvePathsToFiles({ paths, symlinks });
+        const skipped: string[] = [];
+
+        if (resolved.skipped_count > 0) {
+            skipped.push(`${resolved.skipped_count} hidden or system files`);
+        }
+        if (resolved.skipped_symlinks.length > 0) {
+            console.debug("skipped symlinks", resolved.skipped_symlinks);
+            skipped.push(`${resolved.skipped_symlinks.length} symlinks`);
+        }
+        if (skipped.length > 0) {
+            this.logger.warn(`Skipped ${skipped.join(" and ")}`);
+        }
+        return resolved.files;
     }
 
     /**
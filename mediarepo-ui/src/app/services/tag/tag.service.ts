@@ -49,8 +49,13 @@ export class TagService {
         }
     }
 
-    public async createTags(tags: string[]): Promise<Tag[]> {
-        return MediarepoApi.createTags({ tags }).then(mapMany(mapNew(Tag)));
+    /**
+     * Creates the given tags and returns either the tag or the reason
+     * why it couldn't be created for each tag in the input order
+     */
+    public async createTags(tags: string[]): Promise<(Tag | string)[]> {
+        const results = await MediarepoApi.createTags({ tags });
+        return results.map(result => "Ok" in result ? new Tag(result.Ok) : result.Err);
     }
 
     public async changeFileTags(fileId: number, addedTags: number[], removedTags: number[]): Promise<Tag[]> {
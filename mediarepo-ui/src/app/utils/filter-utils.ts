@@ -64,6 +64,8 @@ export function propertyQueryToStringParts(propertyQuery: PropertyQuery): [strin
         return ["ContentDescriptor", "=", propertyQuery.Cd];
     } else if ("Id" in propertyQuery) {
         return ["FileId", "=", propertyQuery.Id.toString()];
+    } else if ("Text" in propertyQuery) {
+        return ["Text", "=", propertyQuery.Text];
     } else {
         return ["Invalid Expression", "", ""];
     }
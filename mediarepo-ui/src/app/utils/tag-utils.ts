@@ -16,3 +16,23 @@ export function normalizeTag(tag: string): string {
         return normalizedTag;
     }
 }
+
+/**
+ * Checks if a tag should be suggested for the autocomplete query.
+ * Queries with a namespace only match the names of tags in that namespace
+ * while queries without a namespace match tags of all namespaces
+ * @param {string} tag - the normalized tag
+ * @param {string} query - the normalized query
+ * @returns {boolean}
+ */
+export function tagMatchesQuery(tag: string, query: string): boolean {
+    const separatorIndex = query.indexOf(":");
+
+    if (separatorIndex < 0) {
+        return tag.includes(query);
+    }
+    const namespacePrefix = query.substring(0, separatorIndex + 1);
+    const name = query.substring(separatorIndex + 1);
+
+    return tag.startsWith(namespacePrefix) && tag.substring(namespacePrefix.length).includes(name);
+}